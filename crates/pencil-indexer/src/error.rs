@@ -0,0 +1,43 @@
+//! 索引器错误
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Error {
+    /// SQLite 读写失败
+    Sqlite(rusqlite::Error),
+    /// 复用链上计算逻辑（如拆分已领取利息）时失败
+    Program(anchor_lang::error::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Sqlite(error) => write!(f, "sqlite: {}", error),
+            Error::Program(error) => write!(f, "program: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Sqlite(error) => Some(error),
+            Error::Program(error) => Some(error),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Error::Sqlite(error)
+    }
+}
+
+impl From<anchor_lang::error::Error> for Error {
+    fn from(error: anchor_lang::error::Error) -> Self {
+        Error::Program(error)
+    }
+}
@@ -0,0 +1,729 @@
+//! 事件折叠
+//! 按事件顺序把每个事件应用到 pools / positions / junior_nfts / repayments，
+//! 与链上指令对账户的修改保持一致。
+
+use crate::error::Result;
+use pencil_solana::constants::{asset_pool_status, listing_kind, subscription_status};
+use pencil_solana::instructions::utils::split_claimed_interest;
+use pencil_solana_client::PencilEvent;
+use rusqlite::{params, OptionalExtension, Transaction};
+
+/// 认购类型：0 = Senior，1 = Junior
+const SENIOR: u8 = 0;
+const JUNIOR: u8 = 1;
+
+fn tranche_column(subscription_type: u8) -> &'static str {
+    if subscription_type == SENIOR {
+        "senior_amount"
+    } else {
+        "junior_amount"
+    }
+}
+
+fn set_pool_status(tx: &Transaction, asset_pool: &str, status: u8, slot: u64) -> Result<()> {
+    tx.execute(
+        "UPDATE pools SET status = ?2, updated_slot = ?3 WHERE asset_pool = ?1",
+        params![asset_pool, status, slot],
+    )?;
+    Ok(())
+}
+
+/// pools 表金额列增减（column 仅为内部常量）
+fn add_pool(
+    tx: &Transaction,
+    asset_pool: &str,
+    column: &str,
+    delta: i128,
+    slot: u64,
+) -> Result<()> {
+    tx.execute(
+        &format!(
+            "UPDATE pools SET {column} = {column} + ?2, updated_slot = ?3 WHERE asset_pool = ?1"
+        ),
+        params![asset_pool, delta as i64, slot],
+    )?;
+    Ok(())
+}
+
+/// 认购 / 撤资事件携带的资产池认购总额（链上写入后的值）
+fn set_pool_subscriptions(
+    tx: &Transaction,
+    asset_pool: &str,
+    senior_amount: u64,
+    junior_amount: u64,
+    slot: u64,
+) -> Result<()> {
+    tx.execute(
+        "UPDATE pools SET senior_amount = ?2, junior_amount = ?3, updated_slot = ?4
+         WHERE asset_pool = ?1",
+        params![asset_pool, senior_amount, junior_amount, slot],
+    )?;
+    Ok(())
+}
+
+fn ensure_position(
+    tx: &Transaction,
+    asset_pool: &str,
+    investor: &str,
+    tranche: u8,
+    slot: u64,
+) -> Result<()> {
+    tx.execute(
+        "INSERT OR IGNORE INTO positions (asset_pool, investor, tranche, updated_slot)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![asset_pool, investor, tranche, slot],
+    )?;
+    Ok(())
+}
+
+/// positions 表金额列增减（column 仅为内部常量），不存在时先创建
+fn add_position(
+    tx: &Transaction,
+    asset_pool: &str,
+    investor: &str,
+    tranche: u8,
+    column: &str,
+    delta: i128,
+    slot: u64,
+) -> Result<()> {
+    ensure_position(tx, asset_pool, investor, tranche, slot)?;
+    tx.execute(
+        &format!(
+            "UPDATE positions SET {column} = {column} + ?4, updated_slot = ?5
+             WHERE asset_pool = ?1 AND investor = ?2 AND tranche = ?3"
+        ),
+        params![asset_pool, investor, tranche, delta as i64, slot],
+    )?;
+    Ok(())
+}
+
+fn set_position_status(
+    tx: &Transaction,
+    asset_pool: &str,
+    investor: &str,
+    tranche: u8,
+    status: u8,
+    slot: u64,
+) -> Result<()> {
+    ensure_position(tx, asset_pool, investor, tranche, slot)?;
+    tx.execute(
+        "UPDATE positions SET status = ?4, updated_slot = ?5
+         WHERE asset_pool = ?1 AND investor = ?2 AND tranche = ?3",
+        params![asset_pool, investor, tranche, status, slot],
+    )?;
+    Ok(())
+}
+
+fn insert_nft(
+    tx: &Transaction,
+    asset_pool: &str,
+    nft_id: u64,
+    owner: &str,
+    principal: u64,
+    claimed_interest: u64,
+    slot: u64,
+) -> Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO junior_nfts
+         (asset_pool, nft_id, owner, principal, claimed_interest, principal_withdrawn, updated_slot)
+         VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6)",
+        params![asset_pool, nft_id, owner, principal, claimed_interest, slot],
+    )?;
+    Ok(())
+}
+
+/// 删除 NFT 并返回其 (principal, claimed_interest)；拆分 / 合并 / 早退时源 NFT 元数据在链上被关闭
+fn take_nft(tx: &Transaction, asset_pool: &str, nft_id: u64) -> Result<Option<(u64, u64)>> {
+    let nft = tx
+        .query_row(
+            "SELECT principal, claimed_interest FROM junior_nfts
+             WHERE asset_pool = ?1 AND nft_id = ?2",
+            params![asset_pool, nft_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    tx.execute(
+        "DELETE FROM junior_nfts WHERE asset_pool = ?1 AND nft_id = ?2",
+        params![asset_pool, nft_id],
+    )?;
+    Ok(nft)
+}
+
+/// 将单个事件应用到账本
+pub(crate) fn apply(
+    tx: &Transaction,
+    signature: &str,
+    slot: u64,
+    event: &PencilEvent,
+) -> Result<()> {
+    match event {
+        PencilEvent::AssetPoolCreated(e) => {
+            tx.execute(
+                "INSERT OR REPLACE INTO pools
+                 (asset_pool, creator, asset_mint, name, status, total_amount, min_amount,
+                  repayment_count, funding_start_time, funding_end_time, updated_slot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    e.asset_pool.to_string(),
+                    e.creator.to_string(),
+                    e.asset_mint.to_string(),
+                    e.name,
+                    asset_pool_status::CREATED,
+                    e.total_amount,
+                    e.min_amount,
+                    e.repayment_count,
+                    e.funding_start_time,
+                    e.funding_end_time,
+                    slot,
+                ],
+            )?;
+        }
+        PencilEvent::PoolApproved(e) => {
+            set_pool_status(
+                tx,
+                &e.asset_pool.to_string(),
+                asset_pool_status::APPROVED,
+                slot,
+            )?;
+        }
+        PencilEvent::AssetPoolAmended(e) => {
+            tx.execute(
+                "UPDATE pools SET total_amount = ?2, min_amount = ?3, repayment_count = ?4,
+                 funding_start_time = ?5, funding_end_time = ?6, updated_slot = ?7
+                 WHERE asset_pool = ?1",
+                params![
+                    e.asset_pool.to_string(),
+                    e.terms.total_amount,
+                    e.terms.min_amount,
+                    e.terms.repayment_count,
+                    e.terms.funding_start_time,
+                    e.terms.funding_end_time,
+                    slot,
+                ],
+            )?;
+        }
+        PencilEvent::FundingClosedEarly(e) => {
+            tx.execute(
+                "UPDATE pools SET funding_end_time = ?2, updated_slot = ?3 WHERE asset_pool = ?1",
+                params![e.asset_pool.to_string(), e.funding_end_time, slot],
+            )?;
+        }
+        PencilEvent::FundingExtended(e) => {
+            tx.execute(
+                "UPDATE pools SET funding_end_time = ?2, updated_slot = ?3 WHERE asset_pool = ?1",
+                params![e.asset_pool.to_string(), e.new_funding_end_time, slot],
+            )?;
+        }
+        PencilEvent::AssetPoolPauseUpdated(e) => {
+            tx.execute(
+                "UPDATE pools SET paused = ?2, updated_slot = ?3 WHERE asset_pool = ?1",
+                params![e.asset_pool.to_string(), e.paused, slot],
+            )?;
+        }
+        PencilEvent::Subscribed(e) => {
+            let pool = e.asset_pool.to_string();
+            set_pool_subscriptions(tx, &pool, e.pool_senior_amount, e.pool_junior_amount, slot)?;
+            add_position(
+                tx,
+                &pool,
+                &e.user.to_string(),
+                e.subscription_type,
+                "subscribed",
+                e.amount as i128,
+                slot,
+            )?;
+        }
+        PencilEvent::SubscriptionWithdrawn(e) => {
+            let pool = e.asset_pool.to_string();
+            let user = e.user.to_string();
+            set_pool_subscriptions(tx, &pool, e.pool_senior_amount, e.pool_junior_amount, slot)?;
+            add_position(
+                tx,
+                &pool,
+                &user,
+                e.subscription_type,
+                "withdrawn",
+                e.amount as i128,
+                slot,
+            )?;
+            add_position(
+                tx,
+                &pool,
+                &user,
+                e.subscription_type,
+                "withdrawal_fee",
+                e.fee as i128,
+                slot,
+            )?;
+        }
+        PencilEvent::SubscriptionRefunded(e) => {
+            set_position_status(
+                tx,
+                &e.asset_pool.to_string(),
+                &e.user.to_string(),
+                e.subscription_type,
+                subscription_status::REFUNDED,
+                slot,
+            )?;
+        }
+        PencilEvent::RefundProcessed(e) => {
+            let pool = e.asset_pool.to_string();
+            let user = e.user.to_string();
+            add_pool(
+                tx,
+                &pool,
+                tranche_column(e.subscription_type),
+                -(e.amount as i128),
+                slot,
+            )?;
+            add_position(
+                tx,
+                &pool,
+                &user,
+                e.subscription_type,
+                "refunded",
+                e.amount as i128,
+                slot,
+            )?;
+            set_position_status(
+                tx,
+                &pool,
+                &user,
+                e.subscription_type,
+                subscription_status::REFUNDED,
+                slot,
+            )?;
+        }
+        PencilEvent::FundingCompleted(e) => {
+            tx.execute(
+                "UPDATE pools SET status = ?2, total_amount = ?3, senior_amount = ?4,
+                 junior_amount = ?5, updated_slot = ?6 WHERE asset_pool = ?1",
+                params![
+                    e.asset_pool.to_string(),
+                    asset_pool_status::FUNDED,
+                    e.total_amount,
+                    e.senior_amount,
+                    e.junior_amount,
+                    slot,
+                ],
+            )?;
+        }
+        // 账户已关闭，账本保留最终状态
+        PencilEvent::AssetPoolRejected(e) => {
+            set_pool_status(tx, &e.asset_pool.to_string(), e.status, slot)?;
+        }
+        PencilEvent::AssetPoolWithdrawn(e) => {
+            set_pool_status(tx, &e.asset_pool.to_string(), e.status, slot)?;
+        }
+        PencilEvent::PoolCancelled(e) => {
+            set_pool_status(
+                tx,
+                &e.asset_pool.to_string(),
+                asset_pool_status::CANCELLED,
+                slot,
+            )?;
+        }
+        PencilEvent::SeniorTokenDistributed(e) => {
+            let pool = e.asset_pool.to_string();
+            let user = e.user.to_string();
+            set_position_status(
+                tx,
+                &pool,
+                &user,
+                SENIOR,
+                subscription_status::CONFIRMED,
+                slot,
+            )?;
+            add_position(
+                tx,
+                &pool,
+                &user,
+                SENIOR,
+                "grow_balance",
+                e.amount as i128,
+                slot,
+            )?;
+        }
+        PencilEvent::JuniorNftDistributed(e) => {
+            let pool = e.asset_pool.to_string();
+            let user = e.user.to_string();
+            set_position_status(
+                tx,
+                &pool,
+                &user,
+                JUNIOR,
+                subscription_status::CONFIRMED,
+                slot,
+            )?;
+            insert_nft(tx, &pool, e.nft_id, &user, e.principal, 0, slot)?;
+        }
+        PencilEvent::GrowTokenMinted(e) => {
+            add_position(
+                tx,
+                &e.asset_pool.to_string(),
+                &e.recipient.to_string(),
+                SENIOR,
+                "grow_balance",
+                e.amount as i128,
+                slot,
+            )?;
+        }
+        PencilEvent::GrowTokenBurned(e) => {
+            add_position(
+                tx,
+                &e.asset_pool.to_string(),
+                &e.user.to_string(),
+                SENIOR,
+                "grow_balance",
+                -(e.amount as i128),
+                slot,
+            )?;
+        }
+        PencilEvent::JuniorNftMinted(e) => {
+            insert_nft(
+                tx,
+                &e.asset_pool.to_string(),
+                e.nft_id,
+                &e.recipient.to_string(),
+                e.principal,
+                0,
+                slot,
+            )?;
+        }
+        PencilEvent::RepaymentDistributed(e) => {
+            let pool = e.asset_pool.to_string();
+            tx.execute(
+                "INSERT OR REPLACE INTO repayments
+                 (asset_pool, period, total_amount, platform_fee, senior_amount, junior_interest,
+                  payer, third_party, timestamp, signature)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    pool,
+                    e.period,
+                    e.total_amount,
+                    e.platform_fee,
+                    e.senior_amount,
+                    e.junior_interest,
+                    e.payer.to_string(),
+                    e.third_party,
+                    e.timestamp,
+                    signature,
+                ],
+            )?;
+            // 与 repay 一致：首次还款 FUNDED -> REPAYING，最后一期 -> COMPLETED
+            tx.execute(
+                "UPDATE pools SET
+                    repaid_amount = repaid_amount + ?3,
+                    platform_fee = platform_fee + ?4,
+                    senior_repaid = senior_repaid + ?5,
+                    junior_interest = junior_interest + ?6,
+                    last_period = MAX(last_period, ?2),
+                    status = CASE
+                        WHEN ?2 = repayment_count THEN ?8
+                        WHEN status = ?9 THEN ?10
+                        ELSE status
+                    END,
+                    updated_slot = ?7
+                 WHERE asset_pool = ?1",
+                params![
+                    pool,
+                    e.period,
+                    e.total_amount,
+                    e.platform_fee,
+                    e.senior_amount,
+                    e.junior_interest,
+                    slot,
+                    asset_pool_status::COMPLETED,
+                    asset_pool_status::FUNDED,
+                    asset_pool_status::REPAYING,
+                ],
+            )?;
+        }
+        PencilEvent::PrepaymentProcessed(e) => {
+            // 提前还清剩余期数，资产池直接完成
+            tx.execute(
+                "UPDATE pools SET
+                    repaid_amount = repaid_amount + ?2,
+                    platform_fee = platform_fee + ?3,
+                    senior_repaid = senior_repaid + ?4,
+                    junior_interest = junior_interest + ?5,
+                    last_period = repayment_count,
+                    status = ?6,
+                    updated_slot = ?7
+                 WHERE asset_pool = ?1",
+                params![
+                    e.asset_pool.to_string(),
+                    e.principal + e.interest + e.penalty,
+                    e.platform_fee,
+                    e.senior_amount,
+                    e.junior_interest,
+                    asset_pool_status::COMPLETED,
+                    slot,
+                ],
+            )?;
+        }
+        PencilEvent::PoolReserveReleased(e) => {
+            // 储备金截留的部分在释放时才计入 Junior 利息与平台费
+            tx.execute(
+                "UPDATE pools SET
+                    junior_interest = junior_interest + ?2,
+                    platform_fee = platform_fee + ?3,
+                    updated_slot = ?4
+                 WHERE asset_pool = ?1",
+                params![
+                    e.asset_pool.to_string(),
+                    e.to_junior_interest,
+                    e.to_treasury,
+                    slot,
+                ],
+            )?;
+        }
+        PencilEvent::InsuranceDrawExecuted(e) => {
+            // 保险基金补足违约资产池的 Senior 缺口，按 Senior 还款入账
+            tx.execute(
+                "UPDATE pools SET senior_repaid = senior_repaid + ?2, updated_slot = ?3
+                 WHERE asset_pool = ?1",
+                params![e.asset_pool.to_string(), e.amount, slot],
+            )?;
+        }
+        PencilEvent::EarlyExitProcessed(e) => {
+            let pool = e.asset_pool.to_string();
+            let user = e.user.to_string();
+            add_position(
+                tx,
+                &pool,
+                &user,
+                SENIOR,
+                "early_exit_amount",
+                e.amount as i128,
+                slot,
+            )?;
+            add_position(
+                tx,
+                &pool,
+                &user,
+                SENIOR,
+                "early_exit_fee",
+                e.fee as i128,
+                slot,
+            )?;
+            add_position(
+                tx,
+                &pool,
+                &user,
+                SENIOR,
+                "grow_balance",
+                -(e.amount as i128),
+                slot,
+            )?;
+        }
+        PencilEvent::SeniorExitFilled(e) => {
+            // 排队中的 GROW 仍计入持仓，成交时扣除
+            let pool = e.asset_pool.to_string();
+            let holder = e.holder.to_string();
+            add_position(
+                tx,
+                &pool,
+                &holder,
+                SENIOR,
+                "early_exit_amount",
+                e.payout as i128,
+                slot,
+            )?;
+            add_position(
+                tx,
+                &pool,
+                &holder,
+                SENIOR,
+                "early_exit_fee",
+                e.exit_fee as i128,
+                slot,
+            )?;
+            add_position(
+                tx,
+                &pool,
+                &holder,
+                SENIOR,
+                "grow_balance",
+                -(e.grow_filled as i128),
+                slot,
+            )?;
+        }
+        PencilEvent::SeniorWithdrawn(e) => {
+            let pool = e.asset_pool.to_string();
+            let user = e.user.to_string();
+            add_position(
+                tx,
+                &pool,
+                &user,
+                SENIOR,
+                "senior_payout",
+                e.amount as i128,
+                slot,
+            )?;
+            add_position(
+                tx,
+                &pool,
+                &user,
+                SENIOR,
+                "grow_balance",
+                -(e.grow_burned as i128),
+                slot,
+            )?;
+        }
+        PencilEvent::SeniorDistributionClaimed(e) => {
+            // 锁定的 GROW 仍计入持仓，赎回时扣除
+            let pool = e.asset_pool.to_string();
+            let holder = e.holder.to_string();
+            add_position(
+                tx,
+                &pool,
+                &holder,
+                SENIOR,
+                "senior_payout",
+                e.amount as i128,
+                slot,
+            )?;
+            if e.grow_redeemed > 0 {
+                add_position(
+                    tx,
+                    &pool,
+                    &holder,
+                    SENIOR,
+                    "grow_balance",
+                    -(e.grow_redeemed as i128),
+                    slot,
+                )?;
+            }
+        }
+        PencilEvent::SeniorDistributionUnlocked(e) => {
+            // 解锁的 GROW 仍计入持仓；补足的金额冲减已领取分配
+            add_position(
+                tx,
+                &e.asset_pool.to_string(),
+                &e.holder.to_string(),
+                SENIOR,
+                "senior_payout",
+                e.payout as i128 - e.repaid as i128,
+                slot,
+            )?;
+        }
+        PencilEvent::InterestClaimed(e) => {
+            tx.execute(
+                "UPDATE junior_nfts SET claimed_interest = claimed_interest + ?3, updated_slot = ?4
+                 WHERE asset_pool = ?1 AND nft_id = ?2",
+                params![e.asset_pool.to_string(), e.nft_id, e.amount, slot],
+            )?;
+        }
+        PencilEvent::PrincipalWithdrawn(e) => {
+            tx.execute(
+                "UPDATE junior_nfts SET principal_withdrawn = 1, updated_slot = ?3
+                 WHERE asset_pool = ?1 AND nft_id = ?2",
+                params![e.asset_pool.to_string(), e.nft_id, slot],
+            )?;
+        }
+        PencilEvent::JuniorNFTSplit(e) => {
+            let pool = e.asset_pool.to_string();
+            let user = e.user.to_string();
+            let principal: u64 = e.amounts.iter().sum();
+            let claimed_interest = take_nft(tx, &pool, e.nft_id)?
+                .map(|(_, claimed_interest)| claimed_interest)
+                .unwrap_or_default();
+            // 与链上拆分相同的已领取利息分配
+            let shares = split_claimed_interest(claimed_interest, principal, &e.amounts)?;
+            for ((nft_id, amount), share) in e.new_nft_ids.iter().zip(&e.amounts).zip(shares) {
+                insert_nft(tx, &pool, *nft_id, &user, *amount, share, slot)?;
+            }
+        }
+        PencilEvent::JuniorEarlyExitProcessed(e) => {
+            let pool = e.asset_pool.to_string();
+            let user = e.user.to_string();
+            let claimed_interest = take_nft(tx, &pool, e.nft_id)?
+                .map(|(_, claimed_interest)| claimed_interest)
+                .unwrap_or_default();
+            add_position(
+                tx,
+                &pool,
+                &user,
+                JUNIOR,
+                "early_exit_amount",
+                e.net_amount as i128,
+                slot,
+            )?;
+            add_position(
+                tx,
+                &pool,
+                &user,
+                JUNIOR,
+                "early_exit_fee",
+                e.fee as i128,
+                slot,
+            )?;
+            // 接盘方承接本金与已领取利息（含早退时结清的利息）
+            if let (Some(buyer), Some(new_nft_id)) = (e.buyer, e.replacement_nft_id) {
+                insert_nft(
+                    tx,
+                    &pool,
+                    new_nft_id,
+                    &buyer.to_string(),
+                    e.principal,
+                    claimed_interest + e.interest_claimed,
+                    slot,
+                )?;
+            }
+        }
+        PencilEvent::ListingFilled(e) => {
+            // 挂单中的持仓仍计入卖方，成交时转给买方
+            let pool = e.asset_pool.to_string();
+            if e.kind == listing_kind::JUNIOR_NFT {
+                tx.execute(
+                    "UPDATE junior_nfts SET owner = ?3, updated_slot = ?4
+                     WHERE asset_pool = ?1 AND nft_id = ?2",
+                    params![pool, e.nft_id, e.buyer.to_string(), slot],
+                )?;
+            } else {
+                add_position(
+                    tx,
+                    &pool,
+                    &e.seller.to_string(),
+                    SENIOR,
+                    "grow_balance",
+                    -(e.amount as i128),
+                    slot,
+                )?;
+                add_position(
+                    tx,
+                    &pool,
+                    &e.buyer.to_string(),
+                    SENIOR,
+                    "grow_balance",
+                    e.amount as i128,
+                    slot,
+                )?;
+            }
+        }
+        PencilEvent::JuniorNFTsMerged(e) => {
+            let pool = e.asset_pool.to_string();
+            let mut merged_claimed_interest = 0u64;
+            for source_nft_id in &e.source_nft_ids {
+                if let Some((_, claimed_interest)) = take_nft(tx, &pool, *source_nft_id)? {
+                    merged_claimed_interest += claimed_interest;
+                }
+            }
+            tx.execute(
+                "UPDATE junior_nfts SET principal = ?3, claimed_interest = claimed_interest + ?4,
+                 updated_slot = ?5 WHERE asset_pool = ?1 AND nft_id = ?2",
+                params![
+                    pool,
+                    e.target_nft_id,
+                    e.principal,
+                    merged_claimed_interest,
+                    slot
+                ],
+            )?;
+        }
+        // 系统级事件只保存在 events 表中
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,556 @@
+//! Pencil Solana 事件索引器
+//! Parses transaction logs into a typed event stream (via
+//! `pencil_solana_client::parse_logs`) and folds it into a per-pool /
+//! per-investor ledger stored in SQLite.
+//!
+//! - `events`: 原始事件（签名 + 序号去重，可重放）
+//! - `pools`: 资产池状态、认购总额、还款与费用累计
+//! - `positions`: 投资者在每个资产池每个层级的持仓
+//! - `junior_nfts`: Junior NFT 本金与已领取利息
+//! - `repayments`: 每期还款分配
+//!
+//! ```ignore
+//! let mut indexer = Indexer::open("pencil.db")?;
+//! indexer.ingest_logs(&signature, slot, &log_messages)?;
+//! let pool = indexer.pool(&asset_pool)?;
+//! ```
+
+mod error;
+mod ledger;
+mod query;
+mod schema;
+
+pub use error::{Error, Result};
+pub use query::{JuniorNftRecord, PoolRecord, PositionRecord, RepaymentRecord};
+
+use pencil_solana_client::{parse_logs, PencilEvent};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+pub struct Indexer {
+    conn: Connection,
+}
+
+impl Indexer {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(schema::SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// 底层连接，用于自定义 SQL 查询
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// 解析一笔交易的日志并写入账本，返回新写入的事件数
+    pub fn ingest_logs<S: AsRef<str>>(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        logs: &[S],
+    ) -> Result<usize> {
+        let events = parse_logs(logs);
+        self.ingest_events(signature, slot, &events)
+    }
+
+    /// 按顺序写入一笔交易的事件
+    ///
+    /// 同一签名只会被处理一次，重复写入返回 0；交易内任一事件失败则整体回滚
+    pub fn ingest_events(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        events: &[PencilEvent],
+    ) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let seen: bool = tx.query_row(
+            "SELECT EXISTS (SELECT 1 FROM events WHERE signature = ?1)",
+            params![signature],
+            |row| row.get(0),
+        )?;
+        if seen || events.is_empty() {
+            return Ok(0);
+        }
+
+        for (idx, event) in events.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO events (signature, idx, slot, name, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![signature, idx, slot, event.name(), event.data()],
+            )?;
+            ledger::apply(&tx, signature, slot, event)?;
+        }
+        tx.commit()?;
+        Ok(events.len())
+    }
+
+    /// 已索引的原始事件数
+    pub fn event_count(&self) -> Result<u64> {
+        Ok(self
+            .conn
+            .query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Event;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use pencil_solana::constants::{asset_pool_status, listing_kind, subscription_status};
+    use pencil_solana::{
+        AssetPoolCreated, FundingCompleted, InsuranceDrawExecuted, InterestClaimed, JuniorNFTSplit,
+        JuniorNFTsMerged, JuniorNftDistributed, ListingFilled, PoolApproved, PoolReserveReleased,
+        PrepaymentProcessed, RepaymentDistributed, SeniorDistributionClaimed,
+        SeniorTokenDistributed, Subscribed, SubscriptionWithdrawn,
+    };
+
+    struct Fixture {
+        pool: Pubkey,
+        senior: Pubkey,
+        junior: Pubkey,
+    }
+
+    /// 模拟一次程序调用的日志
+    fn logs(events: &[Vec<u8>]) -> Vec<String> {
+        let program = pencil_solana::ID.to_string();
+        let mut logs = vec![format!("Program {} invoke [1]", program)];
+        for data in events {
+            logs.push(format!("Program data: {}", STANDARD.encode(data)));
+        }
+        logs.push(format!("Program {} success", program));
+        logs
+    }
+
+    fn subscribed(
+        pool: Pubkey,
+        user: Pubkey,
+        subscription_type: u8,
+        amount: u64,
+        (pool_senior_amount, pool_junior_amount): (u64, u64),
+    ) -> PencilEvent {
+        PencilEvent::Subscribed(Subscribed {
+            asset_pool: pool,
+            user,
+            subscription_type,
+            amount,
+            subscription_amount: amount,
+            pool_senior_amount,
+            pool_junior_amount,
+            timestamp: 2,
+        })
+    }
+
+    /// 创建 -> 审批 -> 认购 -> 撤资 -> 募资完成 -> 分发
+    fn funded_pool(indexer: &mut Indexer) -> Fixture {
+        let fixture = Fixture {
+            pool: Pubkey::new_unique(),
+            senior: Pubkey::new_unique(),
+            junior: Pubkey::new_unique(),
+        };
+        let pool = fixture.pool;
+        let created = AssetPoolCreated {
+            asset_pool: pool,
+            creator: Pubkey::new_unique(),
+            asset_mint: Pubkey::new_unique(),
+            name: "pool".to_string(),
+            total_amount: 1_000,
+            min_amount: 500,
+            min_junior_ratio: 2000,
+            inherited_params: 0,
+            repayment_count: 2,
+            funding_start_time: 1,
+            funding_end_time: 10,
+            timestamp: 1,
+        };
+        let approved = PoolApproved {
+            asset_pool: pool,
+            admin: Pubkey::new_unique(),
+            status: asset_pool_status::APPROVED,
+            total_amount: 1_000,
+            funding_start_time: 1,
+            funding_end_time: 10,
+            timestamp: 1,
+        };
+        indexer
+            .ingest_logs("create", 1, &logs(&[created.data(), approved.data()]))
+            .unwrap();
+
+        indexer
+            .ingest_events(
+                "subscribe",
+                2,
+                &[
+                    subscribed(pool, fixture.senior, 0, 800, (800, 0)),
+                    subscribed(pool, fixture.junior, 1, 300, (800, 300)),
+                    PencilEvent::SubscriptionWithdrawn(SubscriptionWithdrawn {
+                        asset_pool: pool,
+                        user: fixture.junior,
+                        subscription_type: 1,
+                        amount: 100,
+                        fee: 1,
+                        net_amount: 99,
+                        subscription_amount: 200,
+                        pool_senior_amount: 800,
+                        pool_junior_amount: 200,
+                        timestamp: 3,
+                    }),
+                ],
+            )
+            .unwrap();
+
+        indexer
+            .ingest_events(
+                "funded",
+                3,
+                &[
+                    PencilEvent::FundingCompleted(FundingCompleted {
+                        asset_pool: pool,
+                        status: asset_pool_status::FUNDED,
+                        total_amount: 1_000,
+                        min_amount: 500,
+                        senior_amount: 800,
+                        junior_amount: 200,
+                        junior_ratio: 2000,
+                        timestamp: 11,
+                    }),
+                    PencilEvent::SeniorTokenDistributed(SeniorTokenDistributed {
+                        asset_pool: pool,
+                        user: fixture.senior,
+                        amount: 800,
+                        grow_supply: 800,
+                        timestamp: 11,
+                    }),
+                    PencilEvent::JuniorNftDistributed(JuniorNftDistributed {
+                        asset_pool: pool,
+                        user: fixture.junior,
+                        nft_id: 1,
+                        junior_nft_mint: Pubkey::new_unique(),
+                        principal: 200,
+                        timestamp: 11,
+                    }),
+                ],
+            )
+            .unwrap();
+        fixture
+    }
+
+    fn repayment(pool: Pubkey, period: u64) -> PencilEvent {
+        PencilEvent::RepaymentDistributed(RepaymentDistributed {
+            asset_pool: pool,
+            period,
+            total_amount: 550,
+            platform_fee: 27,
+            senior_amount: 420,
+            junior_interest: 103,
+            payer: Pubkey::new_unique(),
+            third_party: period > 1,
+            timestamp: 20 + period as i64,
+        })
+    }
+
+    #[test]
+    fn test_folds_pool_lifecycle() {
+        let mut indexer = Indexer::open_in_memory().unwrap();
+        let Fixture {
+            pool,
+            senior,
+            junior,
+        } = funded_pool(&mut indexer);
+
+        let record = indexer.pool(&pool).unwrap().unwrap();
+        assert_eq!(record.status, asset_pool_status::FUNDED);
+        assert_eq!((record.senior_amount, record.junior_amount), (800, 200));
+
+        let junior_position = indexer.position(&pool, &junior, 1).unwrap().unwrap();
+        assert_eq!(junior_position.status, subscription_status::CONFIRMED);
+        assert_eq!(junior_position.subscribed, 300);
+        assert_eq!(junior_position.withdrawn, 100);
+        assert_eq!(junior_position.withdrawal_fee, 1);
+        assert_eq!(
+            indexer.investor_positions(&senior).unwrap()[0].grow_balance,
+            800
+        );
+
+        indexer
+            .ingest_events("repay-1", 4, &[repayment(pool, 1)])
+            .unwrap();
+        assert_eq!(
+            indexer.pool(&pool).unwrap().unwrap().status,
+            asset_pool_status::REPAYING
+        );
+        indexer
+            .ingest_events(
+                "repay-2",
+                5,
+                &[
+                    repayment(pool, 2),
+                    PencilEvent::InterestClaimed(InterestClaimed {
+                        asset_pool: pool,
+                        user: junior,
+                        nft_id: 1,
+                        amount: 206,
+                        timestamp: 23,
+                    }),
+                ],
+            )
+            .unwrap();
+
+        let record = indexer.pool(&pool).unwrap().unwrap();
+        assert_eq!(record.status, asset_pool_status::COMPLETED);
+        assert_eq!(record.repaid_amount, 1_100);
+        assert_eq!(record.platform_fee, 54);
+        assert_eq!(record.last_period, 2);
+        let repayments = indexer.repayments(&pool).unwrap();
+        assert_eq!(repayments.len(), 2);
+        assert!(!repayments[0].third_party && repayments[1].third_party);
+        assert_eq!(indexer.junior_nfts(&pool).unwrap()[0].claimed_interest, 206);
+    }
+
+    #[test]
+    fn test_prepayment_completes_pool() {
+        let mut indexer = Indexer::open_in_memory().unwrap();
+        let Fixture { pool, senior, .. } = funded_pool(&mut indexer);
+        let claimed = |amount, locked_amount, grow_redeemed| {
+            PencilEvent::SeniorDistributionClaimed(SeniorDistributionClaimed {
+                asset_pool: pool,
+                holder: senior,
+                amount,
+                locked_amount,
+                grow_redeemed,
+                distribution_index: 0,
+                timestamp: 21,
+            })
+        };
+        indexer
+            .ingest_events("repay-1", 4, &[repayment(pool, 1), claimed(0, 800, 0)])
+            .unwrap();
+        indexer
+            .ingest_events(
+                "prepay",
+                5,
+                &[PencilEvent::PrepaymentProcessed(PrepaymentProcessed {
+                    asset_pool: pool,
+                    payer: Pubkey::new_unique(),
+                    third_party: false,
+                    remaining_periods: 1,
+                    principal: 500,
+                    interest: 20,
+                    penalty: 10,
+                    platform_fee: 26,
+                    senior_amount: 410,
+                    junior_interest: 94,
+                    timestamp: 22,
+                })],
+            )
+            .unwrap();
+
+        let record = indexer.pool(&pool).unwrap().unwrap();
+        assert_eq!(record.status, asset_pool_status::COMPLETED);
+        assert_eq!(record.repaid_amount, 550 + 530);
+        assert_eq!(record.platform_fee, 27 + 26);
+        assert_eq!(record.last_period, 2);
+        assert_eq!(indexer.repayments(&pool).unwrap().len(), 1);
+
+        indexer
+            .ingest_events("redeem", 6, &[claimed(830, 0, 800)])
+            .unwrap();
+        let position = indexer.position(&pool, &senior, 0).unwrap().unwrap();
+        assert_eq!((position.senior_payout, position.grow_balance), (830, 0));
+    }
+
+    #[test]
+    fn test_pool_reserve_release_credits_interest_and_fees() {
+        let mut indexer = Indexer::open_in_memory().unwrap();
+        let Fixture { pool, .. } = funded_pool(&mut indexer);
+        indexer
+            .ingest_events(
+                "repay-2",
+                4,
+                &[
+                    repayment(pool, 2),
+                    PencilEvent::PoolReserveReleased(PoolReserveReleased {
+                        asset_pool: pool,
+                        to_first_loss: 0,
+                        to_junior_interest: 40,
+                        to_treasury: 12,
+                        timestamp: 23,
+                    }),
+                ],
+            )
+            .unwrap();
+
+        let record = indexer.pool(&pool).unwrap().unwrap();
+        assert_eq!(record.status, asset_pool_status::COMPLETED);
+        assert_eq!(record.junior_interest, 103 + 40);
+        assert_eq!(record.platform_fee, 27 + 12);
+    }
+
+    #[test]
+    fn test_insurance_draw_counts_as_senior_repayment() {
+        let mut indexer = Indexer::open_in_memory().unwrap();
+        let Fixture { pool, .. } = funded_pool(&mut indexer);
+        indexer
+            .ingest_events("repay-1", 4, &[repayment(pool, 1)])
+            .unwrap();
+        indexer
+            .ingest_events(
+                "insurance-draw",
+                5,
+                &[PencilEvent::InsuranceDrawExecuted(InsuranceDrawExecuted {
+                    insurance_fund: Pubkey::new_unique(),
+                    asset_pool: pool,
+                    approver: Pubkey::new_unique(),
+                    amount: 150,
+                    pool_drawn: 150,
+                    fund_balance: 850,
+                    timestamp: 22,
+                })],
+            )
+            .unwrap();
+
+        let record = indexer.pool(&pool).unwrap().unwrap();
+        assert_eq!(record.senior_repaid, 420 + 150);
+        assert_eq!(record.repaid_amount, 550);
+        assert_eq!(record.status, asset_pool_status::REPAYING);
+    }
+
+    #[test]
+    fn test_ingest_is_idempotent_per_signature() {
+        let mut indexer = Indexer::open_in_memory().unwrap();
+        let Fixture { pool, senior, .. } = funded_pool(&mut indexer);
+        let count = indexer.event_count().unwrap();
+
+        assert_eq!(
+            indexer
+                .ingest_events(
+                    "subscribe",
+                    2,
+                    &[subscribed(pool, senior, 0, 800, (1_600, 200))]
+                )
+                .unwrap(),
+            0
+        );
+        assert_eq!(indexer.event_count().unwrap(), count);
+        assert_eq!(indexer.pool(&pool).unwrap().unwrap().senior_amount, 800);
+    }
+
+    #[test]
+    fn test_split_and_merge_follow_on_chain_interest_split() {
+        let mut indexer = Indexer::open_in_memory().unwrap();
+        let Fixture { pool, junior, .. } = funded_pool(&mut indexer);
+        indexer
+            .ingest_events(
+                "claim",
+                4,
+                &[PencilEvent::InterestClaimed(InterestClaimed {
+                    asset_pool: pool,
+                    user: junior,
+                    nft_id: 1,
+                    amount: 10,
+                    timestamp: 21,
+                })],
+            )
+            .unwrap();
+
+        indexer
+            .ingest_events(
+                "split",
+                5,
+                &[PencilEvent::JuniorNFTSplit(JuniorNFTSplit {
+                    asset_pool: pool,
+                    user: junior,
+                    nft_id: 1,
+                    new_nft_ids: vec![2, 3, 4],
+                    amounts: vec![50, 50, 100],
+                    timestamp: 22,
+                })],
+            )
+            .unwrap();
+        let nfts = indexer.junior_nfts(&pool).unwrap();
+        assert_eq!(
+            nfts.iter()
+                .map(|nft| (nft.nft_id, nft.principal, nft.claimed_interest))
+                .collect::<Vec<_>>(),
+            vec![(2, 50, 3), (3, 50, 2), (4, 100, 5)]
+        );
+
+        indexer
+            .ingest_events(
+                "merge",
+                6,
+                &[PencilEvent::JuniorNFTsMerged(JuniorNFTsMerged {
+                    asset_pool: pool,
+                    user: junior,
+                    target_nft_id: 4,
+                    source_nft_ids: vec![2, 3],
+                    principal: 200,
+                    timestamp: 23,
+                })],
+            )
+            .unwrap();
+        let nfts = indexer.investor_junior_nfts(&junior).unwrap();
+        assert_eq!(nfts.len(), 1);
+        assert_eq!((nfts[0].nft_id, nfts[0].principal), (4, 200));
+        assert_eq!(nfts[0].claimed_interest, 10);
+    }
+
+    #[test]
+    fn test_listing_fills_move_positions_to_buyer() {
+        let mut indexer = Indexer::open_in_memory().unwrap();
+        let Fixture {
+            pool,
+            senior,
+            junior,
+        } = funded_pool(&mut indexer);
+        let buyer = Pubkey::new_unique();
+        let fill = |kind, seller, nft_id, amount| {
+            PencilEvent::ListingFilled(ListingFilled {
+                asset_pool: pool,
+                listing: Pubkey::new_unique(),
+                seller,
+                buyer,
+                listing_id: 0,
+                kind,
+                nft_id,
+                amount,
+                cost: amount,
+                fee: 0,
+                remaining: 0,
+                timestamp: 30,
+            })
+        };
+        indexer
+            .ingest_events(
+                "fills",
+                4,
+                &[
+                    fill(listing_kind::GROW, senior, 0, 300),
+                    fill(listing_kind::JUNIOR_NFT, junior, 1, 1),
+                ],
+            )
+            .unwrap();
+
+        let grow_balance = |investor| {
+            indexer
+                .position(&pool, &investor, 0)
+                .unwrap()
+                .unwrap()
+                .grow_balance
+        };
+        assert_eq!(grow_balance(senior), 500);
+        assert_eq!(grow_balance(buyer), 300);
+        assert!(indexer.investor_junior_nfts(&junior).unwrap().is_empty());
+        let nfts = indexer.investor_junior_nfts(&buyer).unwrap();
+        assert_eq!((nfts[0].nft_id, nfts[0].principal), (1, 200));
+    }
+}
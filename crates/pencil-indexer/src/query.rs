@@ -0,0 +1,267 @@
+//! 账本查询
+
+use crate::error::Result;
+use crate::Indexer;
+use anchor_lang::prelude::Pubkey;
+use rusqlite::types::Type;
+use rusqlite::{params, OptionalExtension, Row};
+use std::str::FromStr;
+
+fn pubkey(row: &Row, index: usize) -> rusqlite::Result<Pubkey> {
+    let text: String = row.get(index)?;
+    Pubkey::from_str(&text).map_err(|error| {
+        rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(error))
+    })
+}
+
+/// 资产池账本
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolRecord {
+    pub asset_pool: Pubkey,
+    pub creator: Pubkey,
+    pub asset_mint: Pubkey,
+    pub name: String,
+    pub status: u8,
+    pub paused: bool,
+    pub total_amount: u64,
+    pub min_amount: u64,
+    pub repayment_count: u64,
+    pub funding_start_time: i64,
+    pub funding_end_time: i64,
+    /// 当前 Senior / Junior 认购总额（已扣除撤资与退款）
+    pub senior_amount: u64,
+    pub junior_amount: u64,
+    pub repaid_amount: u64,
+    pub platform_fee: u64,
+    pub senior_repaid: u64,
+    pub junior_interest: u64,
+    pub last_period: u64,
+    pub updated_slot: u64,
+}
+
+const POOL_COLUMNS: &str = "asset_pool, creator, asset_mint, name, status, paused, total_amount,
+    min_amount, repayment_count, funding_start_time, funding_end_time, senior_amount,
+    junior_amount, repaid_amount, platform_fee, senior_repaid, junior_interest, last_period,
+    updated_slot";
+
+impl PoolRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            asset_pool: pubkey(row, 0)?,
+            creator: pubkey(row, 1)?,
+            asset_mint: pubkey(row, 2)?,
+            name: row.get(3)?,
+            status: row.get(4)?,
+            paused: row.get(5)?,
+            total_amount: row.get(6)?,
+            min_amount: row.get(7)?,
+            repayment_count: row.get(8)?,
+            funding_start_time: row.get(9)?,
+            funding_end_time: row.get(10)?,
+            senior_amount: row.get(11)?,
+            junior_amount: row.get(12)?,
+            repaid_amount: row.get(13)?,
+            platform_fee: row.get(14)?,
+            senior_repaid: row.get(15)?,
+            junior_interest: row.get(16)?,
+            last_period: row.get(17)?,
+            updated_slot: row.get(18)?,
+        })
+    }
+}
+
+/// 投资者在某个资产池某一层级的持仓
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionRecord {
+    pub asset_pool: Pubkey,
+    pub investor: Pubkey,
+    /// 0: Senior, 1: Junior
+    pub tranche: u8,
+    /// 认购状态 (subscription_status)
+    pub status: u8,
+    pub subscribed: u64,
+    /// 募资期内撤回的本金（含手续费）
+    pub withdrawn: u64,
+    pub withdrawal_fee: u64,
+    pub refunded: u64,
+    /// 持有的 GROW Token (Senior)
+    pub grow_balance: u64,
+    pub early_exit_amount: u64,
+    pub early_exit_fee: u64,
+    /// 资产池完成后按比例提取的金额 (Senior)
+    pub senior_payout: u64,
+    pub updated_slot: u64,
+}
+
+const POSITION_COLUMNS: &str = "asset_pool, investor, tranche, status, subscribed, withdrawn,
+    withdrawal_fee, refunded, grow_balance, early_exit_amount, early_exit_fee, senior_payout,
+    updated_slot";
+
+impl PositionRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            asset_pool: pubkey(row, 0)?,
+            investor: pubkey(row, 1)?,
+            tranche: row.get(2)?,
+            status: row.get(3)?,
+            subscribed: row.get(4)?,
+            withdrawn: row.get(5)?,
+            withdrawal_fee: row.get(6)?,
+            refunded: row.get(7)?,
+            grow_balance: row.get(8)?,
+            early_exit_amount: row.get(9)?,
+            early_exit_fee: row.get(10)?,
+            senior_payout: row.get(11)?,
+            updated_slot: row.get(12)?,
+        })
+    }
+}
+
+/// Junior NFT 持仓
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JuniorNftRecord {
+    pub asset_pool: Pubkey,
+    pub nft_id: u64,
+    pub owner: Pubkey,
+    pub principal: u64,
+    pub claimed_interest: u64,
+    pub principal_withdrawn: bool,
+    pub updated_slot: u64,
+}
+
+const NFT_COLUMNS: &str =
+    "asset_pool, nft_id, owner, principal, claimed_interest, principal_withdrawn, updated_slot";
+
+impl JuniorNftRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            asset_pool: pubkey(row, 0)?,
+            nft_id: row.get(1)?,
+            owner: pubkey(row, 2)?,
+            principal: row.get(3)?,
+            claimed_interest: row.get(4)?,
+            principal_withdrawn: row.get(5)?,
+            updated_slot: row.get(6)?,
+        })
+    }
+}
+
+/// 单期还款记录
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RepaymentRecord {
+    pub asset_pool: Pubkey,
+    pub period: u64,
+    pub total_amount: u64,
+    pub platform_fee: u64,
+    pub senior_amount: u64,
+    pub junior_interest: u64,
+    pub payer: Pubkey,
+    /// 付款人既非还款义务人也非受托服务方
+    pub third_party: bool,
+    pub timestamp: i64,
+    pub signature: String,
+}
+
+impl RepaymentRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            asset_pool: pubkey(row, 0)?,
+            period: row.get(1)?,
+            total_amount: row.get(2)?,
+            platform_fee: row.get(3)?,
+            senior_amount: row.get(4)?,
+            junior_interest: row.get(5)?,
+            payer: pubkey(row, 6)?,
+            third_party: row.get(7)?,
+            timestamp: row.get(8)?,
+            signature: row.get(9)?,
+        })
+    }
+}
+
+impl Indexer {
+    pub fn pool(&self, asset_pool: &Pubkey) -> Result<Option<PoolRecord>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {POOL_COLUMNS} FROM pools WHERE asset_pool = ?1"),
+                params![asset_pool.to_string()],
+                PoolRecord::from_row,
+            )
+            .optional()?)
+    }
+
+    pub fn pools(&self) -> Result<Vec<PoolRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {POOL_COLUMNS} FROM pools ORDER BY asset_pool"
+        ))?;
+        let rows = stmt.query_map([], PoolRecord::from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn position(
+        &self,
+        asset_pool: &Pubkey,
+        investor: &Pubkey,
+        tranche: u8,
+    ) -> Result<Option<PositionRecord>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {POSITION_COLUMNS} FROM positions
+                     WHERE asset_pool = ?1 AND investor = ?2 AND tranche = ?3"
+                ),
+                params![asset_pool.to_string(), investor.to_string(), tranche],
+                PositionRecord::from_row,
+            )
+            .optional()?)
+    }
+
+    /// 某个资产池的所有持仓
+    pub fn positions(&self, asset_pool: &Pubkey) -> Result<Vec<PositionRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {POSITION_COLUMNS} FROM positions WHERE asset_pool = ?1
+             ORDER BY tranche, investor"
+        ))?;
+        let rows = stmt.query_map(params![asset_pool.to_string()], PositionRecord::from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// 某个投资者在所有资产池中的持仓
+    pub fn investor_positions(&self, investor: &Pubkey) -> Result<Vec<PositionRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {POSITION_COLUMNS} FROM positions WHERE investor = ?1
+             ORDER BY asset_pool, tranche"
+        ))?;
+        let rows = stmt.query_map(params![investor.to_string()], PositionRecord::from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn junior_nfts(&self, asset_pool: &Pubkey) -> Result<Vec<JuniorNftRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {NFT_COLUMNS} FROM junior_nfts WHERE asset_pool = ?1 ORDER BY nft_id"
+        ))?;
+        let rows = stmt.query_map(params![asset_pool.to_string()], JuniorNftRecord::from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// 某个投资者持有的所有 Junior NFT
+    pub fn investor_junior_nfts(&self, owner: &Pubkey) -> Result<Vec<JuniorNftRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {NFT_COLUMNS} FROM junior_nfts WHERE owner = ?1 ORDER BY asset_pool, nft_id"
+        ))?;
+        let rows = stmt.query_map(params![owner.to_string()], JuniorNftRecord::from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn repayments(&self, asset_pool: &Pubkey) -> Result<Vec<RepaymentRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT asset_pool, period, total_amount, platform_fee, senior_amount,
+             junior_interest, payer, third_party, timestamp, signature
+             FROM repayments WHERE asset_pool = ?1 ORDER BY period",
+        )?;
+        let rows = stmt.query_map(params![asset_pool.to_string()], RepaymentRecord::from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
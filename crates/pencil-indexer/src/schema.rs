@@ -0,0 +1,79 @@
+//! SQLite 表结构
+//! 所有 Pubkey 以 base58 文本存储，金额以 INTEGER 存储。
+
+pub(crate) const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    data BLOB NOT NULL,
+    PRIMARY KEY (signature, idx)
+);
+
+CREATE TABLE IF NOT EXISTS pools (
+    asset_pool TEXT PRIMARY KEY,
+    creator TEXT NOT NULL,
+    asset_mint TEXT NOT NULL,
+    name TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    paused INTEGER NOT NULL DEFAULT 0,
+    total_amount INTEGER NOT NULL,
+    min_amount INTEGER NOT NULL,
+    repayment_count INTEGER NOT NULL,
+    funding_start_time INTEGER NOT NULL,
+    funding_end_time INTEGER NOT NULL,
+    senior_amount INTEGER NOT NULL DEFAULT 0,
+    junior_amount INTEGER NOT NULL DEFAULT 0,
+    repaid_amount INTEGER NOT NULL DEFAULT 0,
+    platform_fee INTEGER NOT NULL DEFAULT 0,
+    senior_repaid INTEGER NOT NULL DEFAULT 0,
+    junior_interest INTEGER NOT NULL DEFAULT 0,
+    last_period INTEGER NOT NULL DEFAULT 0,
+    updated_slot INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS positions (
+    asset_pool TEXT NOT NULL,
+    investor TEXT NOT NULL,
+    tranche INTEGER NOT NULL,
+    status INTEGER NOT NULL DEFAULT 0,
+    subscribed INTEGER NOT NULL DEFAULT 0,
+    withdrawn INTEGER NOT NULL DEFAULT 0,
+    withdrawal_fee INTEGER NOT NULL DEFAULT 0,
+    refunded INTEGER NOT NULL DEFAULT 0,
+    grow_balance INTEGER NOT NULL DEFAULT 0,
+    early_exit_amount INTEGER NOT NULL DEFAULT 0,
+    early_exit_fee INTEGER NOT NULL DEFAULT 0,
+    senior_payout INTEGER NOT NULL DEFAULT 0,
+    updated_slot INTEGER NOT NULL,
+    PRIMARY KEY (asset_pool, investor, tranche)
+);
+CREATE INDEX IF NOT EXISTS positions_investor ON positions (investor);
+
+CREATE TABLE IF NOT EXISTS junior_nfts (
+    asset_pool TEXT NOT NULL,
+    nft_id INTEGER NOT NULL,
+    owner TEXT NOT NULL,
+    principal INTEGER NOT NULL,
+    claimed_interest INTEGER NOT NULL DEFAULT 0,
+    principal_withdrawn INTEGER NOT NULL DEFAULT 0,
+    updated_slot INTEGER NOT NULL,
+    PRIMARY KEY (asset_pool, nft_id)
+);
+CREATE INDEX IF NOT EXISTS junior_nfts_owner ON junior_nfts (owner);
+
+CREATE TABLE IF NOT EXISTS repayments (
+    asset_pool TEXT NOT NULL,
+    period INTEGER NOT NULL,
+    total_amount INTEGER NOT NULL,
+    platform_fee INTEGER NOT NULL,
+    senior_amount INTEGER NOT NULL,
+    junior_interest INTEGER NOT NULL,
+    payer TEXT NOT NULL,
+    third_party INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    signature TEXT NOT NULL,
+    PRIMARY KEY (asset_pool, period)
+);
+";
@@ -0,0 +1,58 @@
+//! 内部收益率
+//! Periodic IRR by bisection on NPV, plus annualization.
+
+/// 每期现金流的净现值，flows[0] 为期初
+fn npv(rate: f64, flows: &[f64]) -> f64 {
+    flows
+        .iter()
+        .enumerate()
+        .map(|(period, flow)| flow / (1.0 + rate).powi(period as i32))
+        .sum()
+}
+
+/// 每期 IRR；现金流不变号或无解时返回 None
+pub fn irr(flows: &[f64]) -> Option<f64> {
+    let mut low = -0.9999;
+    let mut high = 10.0;
+    let mut npv_low = npv(low, flows);
+    if npv_low.is_nan() || npv_low * npv(high, flows) > 0.0 {
+        return None;
+    }
+
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        let npv_mid = npv(mid, flows);
+        if npv_mid.abs() < 1e-9 || (high - low) < 1e-12 {
+            return Some(mid);
+        }
+        if npv_low * npv_mid < 0.0 {
+            high = mid;
+        } else {
+            low = mid;
+            npv_low = npv_mid;
+        }
+    }
+    Some((low + high) / 2.0)
+}
+
+/// 将每期收益率年化（复利）
+pub fn annualize(periodic_rate: f64, periods_per_year: f64) -> f64 {
+    (1.0 + periodic_rate).powf(periods_per_year) - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_irr_matches_known_rate() {
+        // 投入 100，一期后收回 110
+        assert!((irr(&[-100.0, 110.0]).unwrap() - 0.10).abs() < 1e-6);
+        // 等额三期年金，每期 10%
+        let payment = 100.0 * 0.1 / (1.0 - 1.1f64.powi(-3));
+        assert!((irr(&[-100.0, payment, payment, payment]).unwrap() - 0.10).abs() < 1e-6);
+        // 全部损失无解
+        assert!(irr(&[-100.0, 0.0, 0.0]).is_none());
+        assert!((annualize(0.01, 12.0) - 0.126825).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,293 @@
+//! 资产池现金流推演
+//! Period-by-period projection built on `pencil_solana::waterfall`, the
+//! same math the on-chain `repay` instruction uses.
+//!
+//! 现金流视角与链上一致：Senior 本金放款给借款人，Junior 本金留在首损池作为准备金；
+//! 借款人按期偿还 `calculate_per_period_amount` 的金额，Senior 每期获得本息（不足由首损补足），
+//! Junior 获得剩余部分，到期后取回首损池剩余本金。
+
+use crate::irr;
+use anchor_lang::prelude::*;
+use pencil_solana::constants::{
+    BASIS_POINTS, MAX_ANNUAL_RATE, MAX_JUNIOR_RATIO, MAX_PLATFORM_FEE, MAX_REPAYMENT_COUNT,
+    MAX_REPAYMENT_PERIOD, MIN_JUNIOR_RATIO, MIN_REPAYMENT_PERIOD,
+};
+use pencil_solana::errors::PencilError;
+use pencil_solana::waterfall::{
+    allocate_repayment, calculate_per_period_amount, calculate_senior_amount,
+};
+use serde::Serialize;
+
+/// 推演参数
+#[derive(Clone, Debug, Serialize)]
+pub struct Scenario {
+    /// 募资总额
+    pub total_amount: u64,
+    /// Junior 占比 (基点)
+    pub junior_ratio: u16,
+    /// 每期还款利率 (基点)
+    pub repayment_rate: u16,
+    /// Senior 每期固定利率 (基点)
+    pub senior_fixed_rate: u16,
+    /// 平台手续费率 (基点)
+    pub platform_fee: u16,
+    /// 还款期数
+    pub repayment_count: u64,
+    /// 还款周期 (天)，用于年化 IRR
+    pub repayment_period_days: u64,
+    /// 从第几期开始违约 (None 表示正常还款)
+    pub default_from_period: Option<u64>,
+    /// 违约期回收率 (基点，占应还金额)
+    pub recovery_rate: u16,
+}
+
+impl Scenario {
+    /// 与 `create_asset_pool` 相同的参数范围校验
+    pub fn validate(&self) -> Result<()> {
+        require!(self.total_amount > 0, PencilError::InvalidFundingParams);
+        require!(
+            (MIN_JUNIOR_RATIO..=MAX_JUNIOR_RATIO).contains(&self.junior_ratio),
+            PencilError::InvalidJuniorRatio
+        );
+        require!(
+            self.repayment_rate > 0 && self.repayment_rate <= MAX_ANNUAL_RATE,
+            PencilError::InvalidRepaymentRate
+        );
+        require!(
+            self.senior_fixed_rate > 0 && self.senior_fixed_rate <= MAX_ANNUAL_RATE,
+            PencilError::InvalidSeniorFixedRate
+        );
+        require!(
+            self.platform_fee <= MAX_PLATFORM_FEE,
+            PencilError::InvalidPlatformFee
+        );
+        require!(
+            self.repayment_count > 0 && self.repayment_count <= MAX_REPAYMENT_COUNT,
+            PencilError::InvalidRepaymentCount
+        );
+        require!(
+            (MIN_REPAYMENT_PERIOD..=MAX_REPAYMENT_PERIOD).contains(&self.repayment_period_days),
+            PencilError::InvalidRepaymentPeriod
+        );
+        Ok(())
+    }
+
+    pub fn junior_amount(&self) -> u64 {
+        (self.total_amount as u128 * self.junior_ratio as u128 / BASIS_POINTS as u128) as u64
+    }
+
+    pub fn senior_amount(&self) -> u64 {
+        self.total_amount - self.junior_amount()
+    }
+
+    fn is_defaulted(&self, period: u64) -> bool {
+        matches!(self.default_from_period, Some(from) if period >= from)
+    }
+}
+
+/// 单期现金流
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct PeriodFlow {
+    pub period: u64,
+    /// 应还金额
+    pub scheduled: u64,
+    /// 实际还款
+    pub paid: u64,
+    pub platform_fee: u64,
+    /// Senior 应得本息
+    pub senior_due: u64,
+    /// 还款直接支付给 Senior 的金额
+    pub senior_from_repayment: u64,
+    /// 首损补足 Senior 的金额
+    pub first_loss_used: u64,
+    /// Senior 未获足额部分
+    pub senior_shortfall: u64,
+    /// Junior 获得的剩余收益
+    pub junior_interest: u64,
+    /// 本期结束后首损池剩余
+    pub first_loss_remaining: u64,
+    /// 首损覆盖率 = 首损剩余 / 剩余 Senior 应得 (无剩余应得时为空)
+    pub first_loss_coverage: Option<f64>,
+}
+
+/// 推演汇总
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub senior_amount: u64,
+    pub junior_amount: u64,
+    pub per_period_total: u64,
+    pub total_paid: u64,
+    pub total_platform_fee: u64,
+    pub total_senior_received: u64,
+    pub total_senior_shortfall: u64,
+    pub total_junior_interest: u64,
+    pub total_first_loss_used: u64,
+    /// Senior 年化 IRR
+    pub senior_irr: Option<f64>,
+    /// Junior 年化 IRR（含到期取回的首损池剩余）
+    pub junior_irr: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Projection {
+    pub scenario: Scenario,
+    pub periods: Vec<PeriodFlow>,
+    pub summary: Summary,
+}
+
+/// 按期推演整个资产池
+pub fn project(scenario: &Scenario) -> Result<Projection> {
+    scenario.validate()?;
+
+    let senior_amount = scenario.senior_amount();
+    let junior_amount = scenario.junior_amount();
+    let count = scenario.repayment_count;
+    let per_period_total =
+        calculate_per_period_amount(scenario.total_amount, count, scenario.repayment_rate)?;
+    let senior_due = calculate_senior_amount(senior_amount, count, scenario.senior_fixed_rate)?;
+
+    let mut first_loss_remaining = junior_amount;
+    let mut periods = Vec::with_capacity(count as usize);
+    let mut summary = Summary {
+        senior_amount,
+        junior_amount,
+        per_period_total,
+        ..Summary::default()
+    };
+    let mut senior_flows = vec![-(senior_amount as f64)];
+    let mut junior_flows = vec![-(junior_amount as f64)];
+
+    for period in 1..=count {
+        let paid = if scenario.is_defaulted(period) {
+            (per_period_total as u128 * scenario.recovery_rate as u128 / BASIS_POINTS as u128)
+                as u64
+        } else {
+            per_period_total
+        };
+
+        let distribution = allocate_repayment(
+            paid,
+            per_period_total,
+            scenario.platform_fee,
+            senior_due,
+            first_loss_remaining,
+        )?;
+        let senior_received = distribution.senior_total()?;
+        first_loss_remaining -= distribution.first_loss_used;
+
+        let remaining_senior_due = senior_due * (count - period);
+        let flow = PeriodFlow {
+            period,
+            scheduled: per_period_total,
+            paid,
+            platform_fee: distribution.platform_fee,
+            senior_due,
+            senior_from_repayment: distribution.senior_from_repayment,
+            first_loss_used: distribution.first_loss_used,
+            senior_shortfall: senior_due - senior_received,
+            junior_interest: distribution.junior_interest,
+            first_loss_remaining,
+            first_loss_coverage: (remaining_senior_due > 0)
+                .then(|| first_loss_remaining as f64 / remaining_senior_due as f64),
+        };
+
+        summary.total_paid += flow.paid;
+        summary.total_platform_fee += flow.platform_fee;
+        summary.total_senior_received += senior_received;
+        summary.total_senior_shortfall += flow.senior_shortfall;
+        summary.total_junior_interest += flow.junior_interest;
+        summary.total_first_loss_used += flow.first_loss_used;
+
+        senior_flows.push(senior_received as f64);
+        junior_flows.push(flow.junior_interest as f64);
+        periods.push(flow);
+    }
+    // 到期后 Junior 取回首损池剩余本金
+    if let Some(last) = junior_flows.last_mut() {
+        *last += first_loss_remaining as f64;
+    }
+
+    let periods_per_year = 365.0 / scenario.repayment_period_days as f64;
+    summary.senior_irr = irr::irr(&senior_flows).map(|rate| irr::annualize(rate, periods_per_year));
+    summary.junior_irr = irr::irr(&junior_flows).map(|rate| irr::annualize(rate, periods_per_year));
+
+    Ok(Projection {
+        scenario: scenario.clone(),
+        periods,
+        summary,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario() -> Scenario {
+        Scenario {
+            total_amount: 1_000_000_000,
+            junior_ratio: 2000,
+            repayment_rate: 100,
+            senior_fixed_rate: 50,
+            platform_fee: 500,
+            repayment_count: 12,
+            repayment_period_days: 30,
+            default_from_period: None,
+            recovery_rate: 0,
+        }
+    }
+
+    #[test]
+    fn test_performing_pool_conserves_repayments() {
+        let projection = project(&scenario()).unwrap();
+        let summary = &projection.summary;
+
+        assert_eq!(projection.periods.len(), 12);
+        assert_eq!(summary.total_first_loss_used, 0);
+        assert_eq!(summary.total_senior_shortfall, 0);
+        // 借款人还款 = 平台费 + Senior + Junior
+        assert_eq!(
+            summary.total_paid,
+            summary.total_platform_fee
+                + summary.total_senior_received
+                + summary.total_junior_interest
+        );
+        assert!(summary.senior_irr.unwrap() > 0.0);
+        assert!(summary.junior_irr.unwrap() > summary.senior_irr.unwrap());
+    }
+
+    #[test]
+    fn test_default_draws_first_loss_before_senior_loses() {
+        let projection = project(&Scenario {
+            default_from_period: Some(7),
+            ..scenario()
+        })
+        .unwrap();
+
+        // 违约前 Senior 全额，违约后首损先补足，耗尽后 Senior 才出现缺口
+        assert!(projection.periods[..6]
+            .iter()
+            .all(|flow| flow.first_loss_used == 0));
+        assert!(projection.periods[6].first_loss_used > 0);
+        for flow in &projection.periods {
+            if flow.senior_shortfall > 0 {
+                assert_eq!(flow.first_loss_remaining, 0);
+            }
+        }
+        assert!(projection.summary.total_first_loss_used <= projection.summary.junior_amount);
+        assert!(projection.summary.junior_irr.unwrap_or(-1.0) < 0.0);
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_terms() {
+        assert!(project(&Scenario {
+            junior_ratio: 100,
+            ..scenario()
+        })
+        .is_err());
+        assert!(project(&Scenario {
+            repayment_count: 0,
+            ..scenario()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! 输出格式
+//! CSV (one row per period) and JSON (scenario, periods and summary).
+
+use crate::projection::{PeriodFlow, Projection, Summary};
+use std::io::{self, Write};
+
+const CSV_HEADER: &str = "period,scheduled,paid,platform_fee,senior_due,senior_from_repayment,\
+first_loss_used,senior_shortfall,junior_interest,first_loss_remaining,first_loss_coverage";
+
+fn csv_row(flow: &PeriodFlow) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{}",
+        flow.period,
+        flow.scheduled,
+        flow.paid,
+        flow.platform_fee,
+        flow.senior_due,
+        flow.senior_from_repayment,
+        flow.first_loss_used,
+        flow.senior_shortfall,
+        flow.junior_interest,
+        flow.first_loss_remaining,
+        flow.first_loss_coverage
+            .map(|coverage| format!("{:.6}", coverage))
+            .unwrap_or_default(),
+    )
+}
+
+pub fn write_csv(projection: &Projection, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "{}", CSV_HEADER)?;
+    for flow in &projection.periods {
+        writeln!(out, "{}", csv_row(flow))?;
+    }
+    Ok(())
+}
+
+pub fn write_json(projection: &Projection, out: &mut impl Write) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, projection)?;
+    writeln!(out)
+}
+
+fn percent(rate: Option<f64>) -> String {
+    rate.map(|rate| format!("{:.2}%", rate * 100.0))
+        .unwrap_or_else(|| "n/a".to_string())
+}
+
+/// 汇总信息（CSV 模式输出到 stderr）
+pub fn write_summary(summary: &Summary, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "senior amount:         {}", summary.senior_amount)?;
+    writeln!(out, "junior amount:         {}", summary.junior_amount)?;
+    writeln!(out, "per-period repayment:  {}", summary.per_period_total)?;
+    writeln!(out, "total paid:            {}", summary.total_paid)?;
+    writeln!(out, "platform fees:         {}", summary.total_platform_fee)?;
+    writeln!(
+        out,
+        "senior received:       {}",
+        summary.total_senior_received
+    )?;
+    writeln!(
+        out,
+        "senior shortfall:      {}",
+        summary.total_senior_shortfall
+    )?;
+    writeln!(
+        out,
+        "junior interest:       {}",
+        summary.total_junior_interest
+    )?;
+    writeln!(
+        out,
+        "first-loss used:       {}",
+        summary.total_first_loss_used
+    )?;
+    writeln!(
+        out,
+        "senior IRR (annual):   {}",
+        percent(summary.senior_irr)
+    )?;
+    writeln!(
+        out,
+        "junior IRR (annual):   {}",
+        percent(summary.junior_irr)
+    )?;
+    Ok(())
+}
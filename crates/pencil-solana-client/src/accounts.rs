@@ -0,0 +1,101 @@
+//! 账户反序列化
+//! Decoders for every account type in `pencil_solana::state`.
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::{AccountDeserialize, Discriminator, Result};
+use pencil_solana::state::*;
+
+/// 按指定类型反序列化账户数据（校验 8 字节 discriminator）
+pub fn decode_account<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+macro_rules! pencil_accounts {
+    ($($name:ident),* $(,)?) => {
+        /// 程序拥有的任意账户
+        #[allow(clippy::large_enum_variant)]
+        pub enum PencilAccount {
+            $($name($name),)*
+        }
+
+        impl PencilAccount {
+            /// 根据 discriminator 识别账户类型并反序列化
+            pub fn decode(data: &[u8]) -> Result<Self> {
+                if data.len() < 8 {
+                    return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+                }
+                $(
+                    if data[..8] == *$name::DISCRIMINATOR {
+                        return decode_account::<$name>(data).map(PencilAccount::$name);
+                    }
+                )*
+                Err(ErrorCode::AccountDiscriminatorMismatch.into())
+            }
+
+            /// 账户类型名称
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(PencilAccount::$name(_) => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+pencil_accounts!(
+    SystemConfig,
+    AssetPool,
+    Funding,
+    Subscription,
+    SeniorPool,
+    FirstLossPool,
+    JuniorInterestPool,
+    RepaymentRecord,
+    JuniorNFTMetadata,
+    AssetWhitelist,
+    Originator,
+    SeniorCheckpoint,
+    SeniorExitQueue,
+    SeniorExitRequest,
+    Listing,
+    PoolReserve,
+    InsuranceFund,
+    InsuranceDraw,
+    FeeSplit,
+    ReferralProgram,
+    ReferralRecord,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::AccountSerialize;
+
+    #[test]
+    fn test_decode_by_discriminator() {
+        let record = RepaymentRecord {
+            asset_pool: Pubkey::new_unique(),
+            period: 2,
+            amount: 1_000,
+            repaid_at: 1_700_000_000,
+            status: 1,
+            payer: Pubkey::new_unique(),
+            third_party: false,
+            _reserved: [0; 95],
+        };
+        let mut data = Vec::new();
+        record.try_serialize(&mut data).unwrap();
+
+        match PencilAccount::decode(&data).unwrap() {
+            PencilAccount::RepaymentRecord(decoded) => {
+                assert_eq!(decoded.asset_pool, record.asset_pool);
+                assert_eq!(decoded.period, 2);
+                assert_eq!(decoded.amount, 1_000);
+            }
+            other => panic!("unexpected account {}", other.name()),
+        }
+        assert!(decode_account::<Funding>(&data).is_err());
+        assert!(PencilAccount::decode(&data[..4]).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+//! 事件解码
+//! Decoders for every event emitted by the program.
+//!
+//! `emit!` 通过 `sol_log_data` 输出 `Program data: <base64>` 日志，内容为
+//! 8 字节 discriminator + Borsh 序列化的事件结构体。
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use pencil_solana::{
+    AdminUpdated, AssetPoolAmended, AssetPoolCreated, AssetPoolPauseUpdated, AssetPoolRejected,
+    AssetPoolWithdrawn, AssetSupportUpdated, EarlyExitProcessed, FeeCollectorInitialized,
+    FeeRateUpdated, FeeSplitUpdated, FeesDistributed, FundingClosedEarly, FundingCompleted,
+    FundingExtended, GrowTokenBurned, GrowTokenMinted, InsuranceDrawCancelled,
+    InsuranceDrawExecuted, InsuranceDrawProposed, InsuranceFeeCollected, InsuranceFundInitialized,
+    InsuranceFundUpdated, InterestClaimed, JuniorEarlyExitProcessed, JuniorNFTSplit,
+    JuniorNFTsMerged, JuniorNftDistributed, JuniorNftMinted, ListingCancelled, ListingCreated,
+    ListingFilled, MaxVaultDriftUpdated, OriginatorDefaultRecorded, OriginatorExposureUpdated,
+    OriginatorRegistered, OriginatorUpdated, PoolApproved, PoolCancelled, PoolInvariantsChecked,
+    PoolParamBoundsUpdated, PoolParamsSynced, PoolReserveInitialized, PoolReserveReleased,
+    PoolReserveUpdated, PoolTreasuryMigrated, PrepaymentProcessed, PrepaymentTermsUpdated,
+    PrincipalWithdrawn, ReferralRecorded, ReferralRewardPaid, ReferralTermsUpdated,
+    RefundProcessed, RelatedAccountsInitialized, RepaymentAuthorityUpdated, RepaymentDistributed,
+    SeniorDistributionClaimed, SeniorDistributionUnlocked, SeniorExitCancelled, SeniorExitFilled,
+    SeniorExitRequested, SeniorTokenDistributed, SeniorWithdrawn, ServicerDelegated, Subscribed,
+    SubscriptionRefunded, SubscriptionWithdrawn, SystemConfigInitialized, SystemPaused,
+    SystemUnpaused, TokensDistributed, TreasuryUpdated,
+};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+macro_rules! pencil_events {
+    ($($name:ident),* $(,)?) => {
+        /// 程序发出的任意事件
+        #[allow(clippy::large_enum_variant)]
+        pub enum PencilEvent {
+            $($name($name),)*
+        }
+
+        /// 解码单个事件（discriminator + Borsh 数据）；无法识别时返回 None
+        pub fn decode_event(data: &[u8]) -> Option<PencilEvent> {
+            if data.len() < 8 {
+                return None;
+            }
+            let (discriminator, mut payload) = data.split_at(8);
+            $(
+                if discriminator == $name::DISCRIMINATOR {
+                    return $name::deserialize(&mut payload).ok().map(PencilEvent::$name);
+                }
+            )*
+            None
+        }
+
+        impl PencilEvent {
+            /// 事件名称
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(PencilEvent::$name(_) => stringify!($name),)*
+                }
+            }
+
+            /// 重新编码为 discriminator + Borsh 数据（与 `Program data:` 日志内容一致）
+            pub fn data(&self) -> Vec<u8> {
+                match self {
+                    $(PencilEvent::$name(event) => anchor_lang::Event::data(event),)*
+                }
+            }
+        }
+    };
+}
+
+pencil_events!(
+    AdminUpdated,
+    SystemPaused,
+    SystemUnpaused,
+    FeeRateUpdated,
+    AssetSupportUpdated,
+    RelatedAccountsInitialized,
+    TokensDistributed,
+    RefundProcessed,
+    RepaymentDistributed,
+    EarlyExitProcessed,
+    SeniorWithdrawn,
+    InterestClaimed,
+    PrincipalWithdrawn,
+    JuniorNFTSplit,
+    JuniorNFTsMerged,
+    PoolInvariantsChecked,
+    AssetPoolPauseUpdated,
+    SystemConfigInitialized,
+    TreasuryUpdated,
+    MaxVaultDriftUpdated,
+    AssetPoolCreated,
+    PoolApproved,
+    Subscribed,
+    SubscriptionWithdrawn,
+    SubscriptionRefunded,
+    FundingCompleted,
+    PoolCancelled,
+    SeniorTokenDistributed,
+    JuniorNftDistributed,
+    GrowTokenMinted,
+    GrowTokenBurned,
+    JuniorNftMinted,
+    AssetPoolAmended,
+    FundingClosedEarly,
+    FundingExtended,
+    AssetPoolRejected,
+    AssetPoolWithdrawn,
+    OriginatorRegistered,
+    OriginatorUpdated,
+    OriginatorExposureUpdated,
+    OriginatorDefaultRecorded,
+    RepaymentAuthorityUpdated,
+    ServicerDelegated,
+    PrepaymentProcessed,
+    PrepaymentTermsUpdated,
+    SeniorDistributionClaimed,
+    SeniorDistributionUnlocked,
+    SeniorExitRequested,
+    SeniorExitFilled,
+    SeniorExitCancelled,
+    JuniorEarlyExitProcessed,
+    ListingCreated,
+    ListingFilled,
+    ListingCancelled,
+    PoolReserveInitialized,
+    PoolReserveUpdated,
+    PoolReserveReleased,
+    InsuranceFundInitialized,
+    InsuranceFundUpdated,
+    InsuranceFeeCollected,
+    InsuranceDrawProposed,
+    InsuranceDrawExecuted,
+    InsuranceDrawCancelled,
+    FeeSplitUpdated,
+    FeeCollectorInitialized,
+    FeesDistributed,
+    ReferralTermsUpdated,
+    ReferralRecorded,
+    ReferralRewardPaid,
+    PoolTreasuryMigrated,
+    PoolParamBoundsUpdated,
+    PoolParamsSynced,
+);
+
+/// 从交易日志中解析本程序发出的事件
+///
+/// 根据 `Program <id> invoke` / `success` / `failed` 维护调用栈，
+/// 只解码本程序处于栈顶时输出的 `Program data:` 日志
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<PencilEvent> {
+    let program_id = pencil_solana::ID.to_string();
+    let mut stack: Vec<String> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let log = log.as_ref();
+        if let Some(data) = log.strip_prefix(PROGRAM_DATA) {
+            if stack.last() != Some(&program_id) {
+                continue;
+            }
+            if let Some(event) = STANDARD
+                .decode(data.trim())
+                .ok()
+                .and_then(|bytes| decode_event(&bytes))
+            {
+                events.push(event);
+            }
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut parts = rest.split_whitespace();
+            let (Some(id), Some(action)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            if action == "invoke" {
+                stack.push(id.to_string());
+            } else if (action == "success" || action.starts_with("failed"))
+                && id.parse::<Pubkey>().is_ok()
+            {
+                stack.pop();
+            }
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+
+    fn log_line(event: &impl Event) -> String {
+        format!("{}{}", PROGRAM_DATA, STANDARD.encode(event.data()))
+    }
+
+    #[test]
+    fn test_parse_logs_round_trip() {
+        let claimed = InterestClaimed {
+            asset_pool: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            nft_id: 7,
+            amount: 1_234,
+            timestamp: 1_700_000_000,
+        };
+        let program = pencil_solana::ID.to_string();
+        let other = Pubkey::new_unique().to_string();
+        let logs = vec![
+            format!("Program {} invoke [1]", program),
+            "Program log: Instruction: ClaimJuniorInterest".to_string(),
+            format!("Program {} invoke [2]", other),
+            // 其他程序的日志不应被解码
+            log_line(&SystemPaused { timestamp: 1 }),
+            format!("Program {} success", other),
+            log_line(&claimed),
+            format!("Program {} consumed 12345 of 200000 compute units", program),
+            format!("Program {} success", program),
+        ];
+
+        let events = parse_logs(&logs);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            PencilEvent::InterestClaimed(event) => {
+                assert_eq!(event.asset_pool, claimed.asset_pool);
+                assert_eq!(event.nft_id, 7);
+                assert_eq!(event.amount, 1_234);
+            }
+            other => panic!("unexpected event {}", other.name()),
+        }
+    }
+
+    #[test]
+    fn test_decode_event_rejects_unknown_data() {
+        assert!(decode_event(&[0u8; 4]).is_none());
+        assert!(decode_event(&[0u8; 16]).is_none());
+    }
+}
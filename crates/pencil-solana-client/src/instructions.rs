@@ -0,0 +1,1954 @@
+//! 指令构造器
+//! One builder per `#[program]` entry point. Builders only assemble the
+//! `Instruction`; signing and sending is left to the caller.
+
+use crate::pda;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{get_associated_token_address, spl_associated_token_account};
+use anchor_spl::token::spl_token;
+use pencil_solana::instructions::{issued_nft_id, AdminRole, AssetPoolTerms, FeeType};
+use pencil_solana::state::{AssetPool, FeeRecipient, PoolParamBounds};
+use pencil_solana::{accounts, instruction};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: pencil_solana::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// 资产池及其所有关联账户地址
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolKeys {
+    pub asset_pool: Pubkey,
+    pub asset_mint: Pubkey,
+    pub system_config: Pubkey,
+    pub asset_whitelist: Pubkey,
+    pub funding: Pubkey,
+    pub senior_pool: Pubkey,
+    pub first_loss_pool: Pubkey,
+    pub junior_interest_pool: Pubkey,
+    pub grow_token_mint: Pubkey,
+    pub junior_nft_mint: Pubkey,
+    pub senior_pool_vault: Pubkey,
+    pub first_loss_pool_vault: Pubkey,
+    pub junior_interest_pool_vault: Pubkey,
+    /// 募资 Vault（普通 TokenAccount，非 PDA）
+    pub asset_pool_vault: Pubkey,
+    pub treasury: Pubkey,
+    pub treasury_ata: Pubkey,
+    /// 创建者对应的发起方 PDA
+    pub originator: Pubkey,
+}
+
+impl PoolKeys {
+    /// 由创建者与名称推导所有 PDA
+    ///
+    /// `asset_pool_vault` 为 `initialize_related_accounts` 时生成的密钥对地址，
+    /// `treasury` 为 SystemConfig 中的金库地址
+    pub fn derive(
+        creator: &Pubkey,
+        name: &str,
+        asset_mint: &Pubkey,
+        asset_pool_vault: &Pubkey,
+        treasury: &Pubkey,
+    ) -> Self {
+        let asset_pool = pda::asset_pool(creator, name).0;
+        Self {
+            asset_pool,
+            asset_mint: *asset_mint,
+            system_config: pda::system_config().0,
+            asset_whitelist: pda::asset_whitelist().0,
+            funding: pda::funding(&asset_pool).0,
+            senior_pool: pda::senior_pool(&asset_pool).0,
+            first_loss_pool: pda::first_loss_pool(&asset_pool).0,
+            junior_interest_pool: pda::junior_interest_pool(&asset_pool).0,
+            grow_token_mint: pda::grow_token_mint(&asset_pool).0,
+            junior_nft_mint: pda::junior_nft_collection_mint(&asset_pool).0,
+            senior_pool_vault: pda::senior_pool_vault(&asset_pool).0,
+            first_loss_pool_vault: pda::first_loss_pool_vault(&asset_pool).0,
+            junior_interest_pool_vault: pda::junior_interest_pool_vault(&asset_pool).0,
+            asset_pool_vault: *asset_pool_vault,
+            treasury: *treasury,
+            treasury_ata: get_associated_token_address(treasury, asset_mint),
+            originator: pda::originator(creator).0,
+        }
+    }
+
+    /// 从链上 AssetPool 账户读取地址（需已执行 `initialize_related_accounts`）
+    pub fn from_state(asset_pool: &Pubkey, state: &AssetPool) -> Self {
+        let senior_pool_vault = pda::senior_pool_vault(asset_pool).0;
+        let first_loss_pool_vault = pda::first_loss_pool_vault(asset_pool).0;
+        let junior_interest_pool_vault = pda::junior_interest_pool_vault(asset_pool).0;
+        Self {
+            asset_pool: *asset_pool,
+            asset_mint: state.asset_address,
+            system_config: state.system_config,
+            asset_whitelist: pda::asset_whitelist().0,
+            funding: state.funding,
+            senior_pool: state.senior_pool,
+            first_loss_pool: state.first_loss_pool,
+            junior_interest_pool: state.junior_interest_pool,
+            grow_token_mint: state.grow_token,
+            junior_nft_mint: state.junior_nft,
+            senior_pool_vault,
+            first_loss_pool_vault,
+            junior_interest_pool_vault,
+            asset_pool_vault: state.asset_pool_vault,
+            treasury: state.treasury,
+            treasury_ata: state.treasury_ata,
+            originator: pda::originator(&state.creator).0,
+        }
+    }
+
+    pub fn subscription(&self, user: &Pubkey, senior: bool) -> Pubkey {
+        pda::subscription(&self.asset_pool, user, senior).0
+    }
+
+    pub fn junior_nft_mint(&self, nft_id: u64) -> Pubkey {
+        pda::junior_nft_mint(&self.asset_pool, nft_id).0
+    }
+
+    pub fn junior_nft_metadata(&self, nft_id: u64) -> Pubkey {
+        pda::junior_nft_metadata(&self.asset_pool, nft_id).0
+    }
+
+    pub fn repayment_record(&self, period: u64) -> Pubkey {
+        pda::repayment_record(&self.asset_pool, period).0
+    }
+
+    pub fn senior_checkpoint(&self, holder: &Pubkey) -> Pubkey {
+        pda::senior_checkpoint(&self.asset_pool, holder).0
+    }
+
+    pub fn senior_exit_request(&self, request_id: u64) -> Pubkey {
+        pda::senior_exit_request(&self.asset_pool, request_id).0
+    }
+
+    pub fn listing(&self, seller: &Pubkey, listing_id: u64) -> Pubkey {
+        pda::listing(&self.asset_pool, seller, listing_id).0
+    }
+
+    /// 挂单持仓的 Mint：`nft_id` 为 None 时为 GROW
+    pub fn listing_position_mint(&self, nft_id: Option<u64>) -> Pubkey {
+        nft_id.map_or(self.grow_token_mint, |nft_id| self.junior_nft_mint(nft_id))
+    }
+
+    pub fn pool_reserve(&self) -> Pubkey {
+        pda::pool_reserve(&self.asset_pool).0
+    }
+
+    pub fn pool_reserve_vault(&self) -> Pubkey {
+        pda::pool_reserve_vault(&self.asset_pool).0
+    }
+
+    /// 资产代币对应的保险基金
+    pub fn insurance_fund(&self) -> Pubkey {
+        pda::insurance_fund(&self.asset_mint).0
+    }
+
+    pub fn insurance_fund_vault(&self) -> Pubkey {
+        pda::insurance_fund_vault(&self.asset_mint).0
+    }
+
+    pub fn insurance_draw(&self) -> Pubkey {
+        pda::insurance_draw(&self.asset_pool).0
+    }
+
+    /// 资产池级手续费分账配置
+    pub fn fee_split(&self) -> Pubkey {
+        pda::fee_split(&self.asset_pool).0
+    }
+
+    pub fn fee_collector(&self) -> Pubkey {
+        pda::fee_collector(&self.asset_pool).0
+    }
+
+    pub fn referral_program(&self) -> Pubkey {
+        pda::referral_program(&self.asset_pool).0
+    }
+
+    pub fn referral(&self, referrer: &Pubkey) -> Pubkey {
+        pda::referral(&self.asset_pool, referrer).0
+    }
+
+    /// 还款类指令附带的储备金账户；资产池未启用储备金时链上忽略
+    fn reserve_account_metas(&self) -> [AccountMeta; 2] {
+        [
+            AccountMeta::new(self.pool_reserve(), false),
+            AccountMeta::new(self.pool_reserve_vault(), false),
+        ]
+    }
+
+    /// 用户的资产代币 ATA
+    pub fn asset_ata(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address(owner, &self.asset_mint)
+    }
+
+    /// 用户的 GROW Token ATA
+    pub fn grow_token_ata(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address(owner, &self.grow_token_mint)
+    }
+
+    /// 用户的 Junior NFT ATA
+    pub fn junior_nft_ata(&self, owner: &Pubkey, nft_id: u64) -> Pubkey {
+        get_associated_token_address(owner, &self.junior_nft_mint(nft_id))
+    }
+}
+
+// ==================== System Configuration ====================
+
+/// 初始化系统配置；`payer` 成为所有管理员，`treasury` 需签名
+pub fn initialize_system_config(
+    payer: &Pubkey,
+    treasury: &Pubkey,
+    args: instruction::InitializeSystemConfig,
+) -> Instruction {
+    build(
+        accounts::InitializeSystemConfig {
+            payer: *payer,
+            system_config: pda::system_config().0,
+            treasury: *treasury,
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+pub fn update_admin(super_admin: &Pubkey, role: AdminRole, new_admin: &Pubkey) -> Instruction {
+    build(
+        accounts::UpdateAdmin {
+            super_admin: *super_admin,
+            system_config: pda::system_config().0,
+        },
+        instruction::UpdateAdmin {
+            role,
+            new_admin: *new_admin,
+        },
+    )
+}
+
+pub fn pause_system(super_admin: &Pubkey) -> Instruction {
+    build(
+        accounts::PauseSystem {
+            super_admin: *super_admin,
+            system_config: pda::system_config().0,
+        },
+        instruction::PauseSystem {},
+    )
+}
+
+pub fn unpause_system(super_admin: &Pubkey) -> Instruction {
+    build(
+        accounts::UnpauseSystem {
+            super_admin: *super_admin,
+            system_config: pda::system_config().0,
+        },
+        instruction::UnpauseSystem {},
+    )
+}
+
+pub fn update_fee_rate(system_admin: &Pubkey, fee_type: FeeType, new_rate: u16) -> Instruction {
+    build(
+        accounts::UpdateFeeRate {
+            system_admin: *system_admin,
+            system_config: pda::system_config().0,
+        },
+        instruction::UpdateFeeRate { fee_type, new_rate },
+    )
+}
+
+pub fn set_treasury(system_admin: &Pubkey, treasury: &Pubkey) -> Instruction {
+    build(
+        accounts::SetTreasury {
+            system_admin: *system_admin,
+            system_config: pda::system_config().0,
+        },
+        instruction::SetTreasury {
+            treasury: *treasury,
+        },
+    )
+}
+
+/// 金库变更后为 `asset_mint` 创建当前金库 ATA 并迁移 `asset_pools` 的金库记录（系统管理员）
+pub fn migrate_pool_treasury(
+    system_admin: &Pubkey,
+    treasury: &Pubkey,
+    asset_mint: &Pubkey,
+    asset_pools: &[Pubkey],
+) -> Instruction {
+    let mut ix = build(
+        accounts::MigratePoolTreasury {
+            system_admin: *system_admin,
+            system_config: pda::system_config().0,
+            treasury: *treasury,
+            treasury_ata: get_associated_token_address(treasury, asset_mint),
+            asset_mint: *asset_mint,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        instruction::MigratePoolTreasury {},
+    );
+    ix.accounts.extend(
+        asset_pools
+            .iter()
+            .map(|asset_pool| AccountMeta::new(*asset_pool, false)),
+    );
+    ix
+}
+
+pub fn set_asset_supported(
+    operation_admin: &Pubkey,
+    asset: &Pubkey,
+    supported: bool,
+) -> Instruction {
+    build(
+        accounts::SetAssetSupported {
+            operation_admin: *operation_admin,
+            system_config: pda::system_config().0,
+            asset_whitelist: pda::asset_whitelist().0,
+            system_program: system_program::ID,
+        },
+        instruction::SetAssetSupported {
+            asset: *asset,
+            supported,
+        },
+    )
+}
+
+pub fn set_max_vault_drift(system_admin: &Pubkey, max_vault_drift: u64) -> Instruction {
+    build(
+        accounts::SetMaxVaultDrift {
+            system_admin: *system_admin,
+            system_config: pda::system_config().0,
+        },
+        instruction::SetMaxVaultDrift { max_vault_drift },
+    )
+}
+
+/// 设置创建者覆盖资产池参数 (`pool_param`) 时的取值范围；`None` 清除
+pub fn set_pool_param_bounds(
+    system_admin: &Pubkey,
+    param: u8,
+    bounds: Option<PoolParamBounds>,
+) -> Instruction {
+    build(
+        accounts::SetPoolParamBounds {
+            system_admin: *system_admin,
+            system_config: pda::system_config().0,
+        },
+        instruction::SetPoolParamBounds { param, bounds },
+    )
+}
+
+// ==================== Asset Pool ====================
+
+/// 创建资产池；资产池地址由 `payer` 与 `args.name` 推导
+pub fn create_asset_pool(
+    payer: &Pubkey,
+    asset_mint: &Pubkey,
+    args: instruction::CreateAssetPool,
+) -> Instruction {
+    build(
+        accounts::CreateAssetPool {
+            payer: *payer,
+            system_config: pda::system_config().0,
+            asset_whitelist: pda::asset_whitelist().0,
+            asset_pool: pda::asset_pool(payer, &args.name).0,
+            originator: pda::originator(payer).0,
+            asset_address: *asset_mint,
+            system_program: system_program::ID,
+        },
+        args,
+    )
+}
+
+pub fn approve_asset_pool(admin: &Pubkey, creator: &Pubkey, name: &str) -> Instruction {
+    build(
+        accounts::ApproveAssetPool {
+            admin: *admin,
+            system_config: pda::system_config().0,
+            asset_pool: pda::asset_pool(creator, name).0,
+        },
+        instruction::ApproveAssetPool {
+            creator: *creator,
+            name: name.to_string(),
+        },
+    )
+}
+
+/// 初始化资产池关联账户；`pool.asset_pool_vault` 对应的密钥对需签名
+pub fn register_originator(
+    operation_admin: &Pubkey,
+    authority: &Pubkey,
+    legal_name_hash: [u8; 32],
+    max_outstanding: u64,
+    allowed_assets: Vec<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::RegisterOriginator {
+            operation_admin: *operation_admin,
+            system_config: pda::system_config().0,
+            originator: pda::originator(authority).0,
+            system_program: system_program::ID,
+        },
+        instruction::RegisterOriginator {
+            authority: *authority,
+            legal_name_hash,
+            max_outstanding,
+            allowed_assets,
+        },
+    )
+}
+
+pub fn update_originator(
+    operation_admin: &Pubkey,
+    authority: &Pubkey,
+    max_outstanding: u64,
+    allowed_assets: Vec<Pubkey>,
+    active: bool,
+) -> Instruction {
+    build(
+        accounts::UpdateOriginator {
+            operation_admin: *operation_admin,
+            system_config: pda::system_config().0,
+            originator: pda::originator(authority).0,
+        },
+        instruction::UpdateOriginator {
+            max_outstanding,
+            allowed_assets,
+            active,
+        },
+    )
+}
+
+/// 资产池完成或取消后释放发起方敞口（任何人可调用）
+pub fn sync_originator_exposure(asset_pool: &Pubkey, creator: &Pubkey) -> Instruction {
+    build(
+        accounts::SyncOriginatorExposure {
+            asset_pool: *asset_pool,
+            originator: pda::originator(creator).0,
+        },
+        instruction::SyncOriginatorExposure {},
+    )
+}
+
+pub fn record_originator_default(
+    operation_admin: &Pubkey,
+    asset_pool: &Pubkey,
+    creator: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::RecordOriginatorDefault {
+            operation_admin: *operation_admin,
+            system_config: pda::system_config().0,
+            asset_pool: *asset_pool,
+            originator: pda::originator(creator).0,
+        },
+        instruction::RecordOriginatorDefault {},
+    )
+}
+
+/// 发起方委托还款服务方；`Pubkey::default()` 撤销委托
+pub fn delegate_servicer(authority: &Pubkey, servicer: &Pubkey) -> Instruction {
+    build(
+        accounts::DelegateServicer {
+            authority: *authority,
+            originator: pda::originator(authority).0,
+        },
+        instruction::DelegateServicer {
+            servicer: *servicer,
+        },
+    )
+}
+
+/// 审批前由创建者修订条款
+pub fn amend_asset_pool(
+    creator: &Pubkey,
+    asset_pool: &Pubkey,
+    terms: AssetPoolTerms,
+) -> Instruction {
+    build(
+        accounts::AmendAssetPool {
+            creator: *creator,
+            system_config: pda::system_config().0,
+            asset_pool: *asset_pool,
+            originator: pda::originator(creator).0,
+        },
+        instruction::AmendAssetPool { terms },
+    )
+}
+
+/// 审批后由创建者和超级管理员共同签名修订条款
+pub fn amend_approved_asset_pool(
+    creator: &Pubkey,
+    admin: &Pubkey,
+    asset_pool: &Pubkey,
+    terms: AssetPoolTerms,
+) -> Instruction {
+    build(
+        accounts::AmendApprovedAssetPool {
+            creator: *creator,
+            admin: *admin,
+            system_config: pda::system_config().0,
+            asset_pool: *asset_pool,
+            originator: pda::originator(creator).0,
+        },
+        instruction::AmendApprovedAssetPool { terms },
+    )
+}
+
+/// 将继承的资产池参数刷新为 SystemConfig 当前默认值（任何人可调用）
+pub fn sync_pool_params(caller: &Pubkey, asset_pool: &Pubkey) -> Instruction {
+    build(
+        accounts::SyncPoolParams {
+            caller: *caller,
+            system_config: pda::system_config().0,
+            asset_pool: *asset_pool,
+        },
+        instruction::SyncPoolParams {},
+    )
+}
+
+/// 驳回资产池，租金退还创建者
+pub fn reject_asset_pool(
+    admin: &Pubkey,
+    asset_pool: &Pubkey,
+    creator: &Pubkey,
+    reason_code: u16,
+) -> Instruction {
+    build(
+        accounts::RejectAssetPool {
+            admin: *admin,
+            system_config: pda::system_config().0,
+            asset_pool: *asset_pool,
+            creator: *creator,
+            originator: pda::originator(creator).0,
+        },
+        instruction::RejectAssetPool { reason_code },
+    )
+}
+
+pub fn withdraw_asset_pool(creator: &Pubkey, asset_pool: &Pubkey) -> Instruction {
+    build(
+        accounts::WithdrawAssetPool {
+            creator: *creator,
+            asset_pool: *asset_pool,
+            originator: pda::originator(creator).0,
+        },
+        instruction::WithdrawAssetPool {},
+    )
+}
+
+/// 创建者变更资产池的还款义务人
+pub fn set_repayment_authority(
+    creator: &Pubkey,
+    asset_pool: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::SetRepaymentAuthority {
+            creator: *creator,
+            asset_pool: *asset_pool,
+        },
+        instruction::SetRepaymentAuthority {
+            new_authority: *new_authority,
+        },
+    )
+}
+
+/// 审批前由创建者设置提前还款计息方式与违约金费率
+pub fn set_prepayment_terms(
+    creator: &Pubkey,
+    asset_pool: &Pubkey,
+    policy: u8,
+    penalty_rate: u16,
+) -> Instruction {
+    build(
+        accounts::SetPrepaymentTerms {
+            creator: *creator,
+            asset_pool: *asset_pool,
+        },
+        instruction::SetPrepaymentTerms {
+            policy,
+            penalty_rate,
+        },
+    )
+}
+
+pub fn initialize_related_accounts(payer: &Pubkey, pool: &PoolKeys) -> Instruction {
+    build(
+        accounts::InitializeRelatedAccounts {
+            payer: *payer,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            asset_mint: pool.asset_mint,
+            funding: pool.funding,
+            senior_pool: pool.senior_pool,
+            first_loss_pool: pool.first_loss_pool,
+            junior_interest_pool: pool.junior_interest_pool,
+            grow_token_mint: pool.grow_token_mint,
+            junior_nft_mint: pool.junior_nft_mint,
+            asset_pool_vault: pool.asset_pool_vault,
+            senior_pool_vault: pool.senior_pool_vault,
+            first_loss_pool_vault: pool.first_loss_pool_vault,
+            junior_interest_pool_vault: pool.junior_interest_pool_vault,
+            treasury: pool.treasury,
+            treasury_ata: pool.treasury_ata,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::InitializeRelatedAccounts {},
+    )
+}
+
+// ==================== Funding ====================
+
+/// 认购 Senior；经 `referrer` 推荐时追加 [referrer, referral_record, referral_program]
+pub fn subscribe_senior(
+    user: &Pubkey,
+    pool: &PoolKeys,
+    amount: u64,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    let mut ix = build(
+        accounts::SubscribeSenior {
+            user: *user,
+            system_config: pool.system_config,
+            asset_whitelist: pool.asset_whitelist,
+            asset_pool: pool.asset_pool,
+            user_token_account: pool.asset_ata(user),
+            pool_token_account: pool.asset_pool_vault,
+            asset_mint: pool.asset_mint,
+            subscription: pool.subscription(user, true),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::SubscribeSenior { amount },
+    );
+    if let Some(referrer) = referrer {
+        ix.accounts.extend([
+            AccountMeta::new_readonly(*referrer, false),
+            AccountMeta::new(pool.referral(referrer), false),
+            AccountMeta::new(pool.referral_program(), false),
+        ]);
+    }
+    ix
+}
+
+/// 认购 Junior；经 `referrer` 推荐时追加 [referrer, referral_record, referral_program]
+pub fn subscribe_junior(
+    user: &Pubkey,
+    pool: &PoolKeys,
+    amount: u64,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    let mut ix = build(
+        accounts::SubscribeJunior {
+            user: *user,
+            system_config: pool.system_config,
+            asset_whitelist: pool.asset_whitelist,
+            asset_pool: pool.asset_pool,
+            user_token_account: pool.asset_ata(user),
+            pool_token_account: pool.asset_pool_vault,
+            asset_mint: pool.asset_mint,
+            subscription: pool.subscription(user, false),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::SubscribeJunior { amount },
+    );
+    if let Some(referrer) = referrer {
+        ix.accounts.extend([
+            AccountMeta::new_readonly(*referrer, false),
+            AccountMeta::new(pool.referral(referrer), false),
+            AccountMeta::new(pool.referral_program(), false),
+        ]);
+    }
+    ix
+}
+
+fn complete_funding_accounts(payer: &Pubkey, pool: &PoolKeys) -> accounts::CompleteFunding {
+    accounts::CompleteFunding {
+        payer: *payer,
+        asset_pool: pool.asset_pool,
+        senior_pool: pool.senior_pool,
+        first_loss_pool: pool.first_loss_pool,
+        asset_pool_vault: pool.asset_pool_vault,
+        senior_pool_vault: pool.senior_pool_vault,
+        first_loss_pool_vault: pool.first_loss_pool_vault,
+        originator: pool.originator,
+        token_program: spl_token::ID,
+    }
+}
+
+pub fn complete_funding(payer: &Pubkey, pool: &PoolKeys) -> Instruction {
+    build(
+        complete_funding_accounts(payer, pool),
+        instruction::CompleteFunding {},
+    )
+}
+
+/// 募资总额已满时任何人可调用；否则 `payer` 须为 operation_admin
+pub fn close_funding_early(payer: &Pubkey, pool: &PoolKeys) -> Instruction {
+    build(
+        accounts::CloseFundingEarly {
+            system_config: pda::system_config().0,
+            complete_funding: complete_funding_accounts(payer, pool),
+        },
+        instruction::CloseFundingEarly {},
+    )
+}
+
+pub fn extend_funding(operation_admin: &Pubkey, asset_pool: &Pubkey, new_end: i64) -> Instruction {
+    build(
+        accounts::ExtendFunding {
+            operation_admin: *operation_admin,
+            system_config: pda::system_config().0,
+            asset_pool: *asset_pool,
+        },
+        instruction::ExtendFunding { new_end },
+    )
+}
+
+pub fn distribute_senior_token(payer: &Pubkey, pool: &PoolKeys, user: &Pubkey) -> Instruction {
+    build(
+        accounts::DistributeSeniorToken {
+            payer: *payer,
+            asset_pool: pool.asset_pool,
+            subscription: pool.subscription(user, true),
+            grow_token_mint: pool.grow_token_mint,
+            user: *user,
+            user_token_account: pool.grow_token_ata(user),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        instruction::DistributeSeniorToken {},
+    )
+}
+
+pub fn distribute_junior_nft(
+    payer: &Pubkey,
+    pool: &PoolKeys,
+    user: &Pubkey,
+    nft_id: u64,
+) -> Instruction {
+    build(
+        accounts::DistributeJuniorNFT {
+            payer: *payer,
+            asset_pool: pool.asset_pool,
+            subscription: pool.subscription(user, false),
+            junior_nft_mint: pool.junior_nft_mint(nft_id),
+            user: *user,
+            user_token_account: pool.junior_nft_ata(user, nft_id),
+            nft_metadata: pool.junior_nft_metadata(nft_id),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::DistributeJuniorNft { nft_id },
+    )
+}
+
+pub fn finalize_token_distribution(
+    payer: &Pubkey,
+    pool: &PoolKeys,
+    senior_count: u64,
+    junior_count: u64,
+) -> Instruction {
+    build(
+        accounts::FinalizeTokenDistribution {
+            payer: *payer,
+            asset_pool: pool.asset_pool,
+        },
+        instruction::FinalizeTokenDistribution {
+            senior_count,
+            junior_count,
+        },
+    )
+}
+
+pub fn refund_subscription(user: &Pubkey, pool: &PoolKeys, senior: bool) -> Instruction {
+    build(
+        accounts::RefundSubscription {
+            user: *user,
+            asset_pool: pool.asset_pool,
+            subscription: pool.subscription(user, senior),
+        },
+        instruction::RefundSubscription {
+            subscription_type: if senior { 0 } else { 1 },
+        },
+    )
+}
+
+pub fn process_refund(user: &Pubkey, pool: &PoolKeys, senior: bool) -> Instruction {
+    build(
+        accounts::ProcessRefund {
+            user: *user,
+            asset_pool: pool.asset_pool,
+            subscription: pool.subscription(user, senior),
+            pool_vault: pool.asset_pool_vault,
+            user_token_account: pool.asset_ata(user),
+            asset_mint: pool.asset_mint,
+            originator: pool.originator,
+            token_program: spl_token::ID,
+        },
+        instruction::ProcessRefund {},
+    )
+}
+
+pub fn cancel_asset_pool(authority: &Pubkey, pool: &PoolKeys) -> Instruction {
+    build(
+        accounts::CancelAssetPool {
+            authority: *authority,
+            asset_pool: pool.asset_pool,
+            pool_vault: pool.asset_pool_vault,
+            asset_mint: pool.asset_mint,
+            originator: pool.originator,
+        },
+        instruction::CancelAssetPool {},
+    )
+}
+
+/// 撤回 Senior 认购；经推荐的订阅须传入订阅记录中的 `referrer`
+pub fn withdraw_senior_subscription(
+    user: &Pubkey,
+    pool: &PoolKeys,
+    amount: u64,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    let mut ix = build(
+        accounts::WithdrawSeniorSubscription {
+            user: *user,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            subscription: pool.subscription(user, true),
+            pool_token_account: pool.asset_pool_vault,
+            user_token_account: pool.asset_ata(user),
+            treasury_ata: pool.treasury_ata,
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+        },
+        instruction::WithdrawSeniorSubscription { amount },
+    );
+    if let Some(referrer) = referrer {
+        ix.accounts.extend([
+            AccountMeta::new(pool.referral(referrer), false),
+            AccountMeta::new(pool.referral_program(), false),
+        ]);
+    }
+    ix
+}
+
+/// 撤回 Junior 认购；经推荐的订阅须传入订阅记录中的 `referrer`
+pub fn withdraw_junior_subscription(
+    user: &Pubkey,
+    pool: &PoolKeys,
+    amount: u64,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    let mut ix = build(
+        accounts::WithdrawJuniorSubscription {
+            user: *user,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            subscription: pool.subscription(user, false),
+            pool_token_account: pool.asset_pool_vault,
+            user_token_account: pool.asset_ata(user),
+            treasury_ata: pool.treasury_ata,
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+        },
+        instruction::WithdrawJuniorSubscription { amount },
+    );
+    if let Some(referrer) = referrer {
+        ix.accounts.extend([
+            AccountMeta::new(pool.referral(referrer), false),
+            AccountMeta::new(pool.referral_program(), false),
+        ]);
+    }
+    ix
+}
+
+// ==================== Repayment ====================
+
+/// 按期还款；始终附带储备金账户（未启用储备金的资产池不会读取）
+pub fn repay(payer: &Pubkey, pool: &PoolKeys, amount: u64, period: u64) -> Instruction {
+    let mut ix = build(
+        accounts::Repay {
+            payer: *payer,
+            system_config: pool.system_config,
+            asset_whitelist: pool.asset_whitelist,
+            asset_pool: pool.asset_pool,
+            originator: pool.originator,
+            senior_pool: pool.senior_pool,
+            first_loss_pool: pool.first_loss_pool,
+            junior_interest_pool: pool.junior_interest_pool,
+            payer_token_account: pool.asset_ata(payer),
+            senior_pool_vault: pool.senior_pool_vault,
+            first_loss_pool_vault: pool.first_loss_pool_vault,
+            junior_interest_pool_vault: pool.junior_interest_pool_vault,
+            treasury_ata: pool.treasury_ata,
+            asset_mint: pool.asset_mint,
+            repayment_record: pool.repayment_record(period),
+            grow_token_mint: pool.grow_token_mint,
+            insurance_fund: pool.insurance_fund(),
+            insurance_fund_vault: pool.insurance_fund_vault(),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::Repay { amount, period },
+    );
+    ix.accounts.extend(pool.reserve_account_metas());
+    ix
+}
+
+/// 一次性提前还清剩余期数；`max_amount` 为付款人接受的最高应付金额
+pub fn prepay_remaining(payer: &Pubkey, pool: &PoolKeys, max_amount: u64) -> Instruction {
+    let mut ix = build(
+        accounts::PrepayRemaining {
+            payer: *payer,
+            system_config: pool.system_config,
+            asset_whitelist: pool.asset_whitelist,
+            asset_pool: pool.asset_pool,
+            originator: pool.originator,
+            senior_pool: pool.senior_pool,
+            first_loss_pool: pool.first_loss_pool,
+            junior_interest_pool: pool.junior_interest_pool,
+            payer_token_account: pool.asset_ata(payer),
+            senior_pool_vault: pool.senior_pool_vault,
+            first_loss_pool_vault: pool.first_loss_pool_vault,
+            junior_interest_pool_vault: pool.junior_interest_pool_vault,
+            treasury_ata: pool.treasury_ata,
+            asset_mint: pool.asset_mint,
+            grow_token_mint: pool.grow_token_mint,
+            insurance_fund: pool.insurance_fund(),
+            insurance_fund_vault: pool.insurance_fund_vault(),
+            token_program: spl_token::ID,
+        },
+        instruction::PrepayRemaining { max_amount },
+    );
+    ix.accounts.extend(pool.reserve_account_metas());
+    ix
+}
+
+pub fn claim_junior_interest(user: &Pubkey, pool: &PoolKeys, nft_id: u64) -> Instruction {
+    build(
+        accounts::ClaimJuniorInterest {
+            user: *user,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            first_loss_pool: pool.first_loss_pool,
+            junior_interest_pool: pool.junior_interest_pool,
+            nft_metadata: pool.junior_nft_metadata(nft_id),
+            user_nft_account: pool.junior_nft_ata(user, nft_id),
+            junior_nft_mint: pool.junior_nft_mint(nft_id),
+            user_asset_account: pool.asset_ata(user),
+            junior_interest_pool_vault: pool.junior_interest_pool_vault,
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+        },
+        instruction::ClaimJuniorInterest { nft_id },
+    )
+}
+
+pub fn withdraw_principal(user: &Pubkey, pool: &PoolKeys, nft_id: u64) -> Instruction {
+    build(
+        accounts::WithdrawPrincipal {
+            user: *user,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            first_loss_pool: pool.first_loss_pool,
+            nft_metadata: pool.junior_nft_metadata(nft_id),
+            user_nft_account: pool.junior_nft_ata(user, nft_id),
+            junior_nft_mint: pool.junior_nft_mint(nft_id),
+            user_asset_account: pool.asset_ata(user),
+            first_loss_pool_vault: pool.first_loss_pool_vault,
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+        },
+        instruction::WithdrawPrincipal { nft_id },
+    )
+}
+
+pub fn early_exit_senior(user: &Pubkey, pool: &PoolKeys, amount: u64) -> Instruction {
+    build(
+        accounts::EarlyExitSenior {
+            user: *user,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            senior_pool: pool.senior_pool,
+            exit_queue: pda::senior_exit_queue(&pool.asset_pool).0,
+            grow_token_mint: pool.grow_token_mint,
+            user_grow_token_account: pool.grow_token_ata(user),
+            user_asset_account: pool.asset_ata(user),
+            senior_pool_vault: pool.senior_pool_vault,
+            treasury_ata: pool.treasury_ata,
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::EarlyExitSenior { amount },
+    )
+}
+
+/// Junior 早退；指定 `replacement` (接盘方, AssetPool.issued_nft_count) 时追加
+/// [buyer, buyer_asset_account, new_nft_mint, new_nft_metadata, buyer_nft_account]
+pub fn early_exit_junior(
+    user: &Pubkey,
+    pool: &PoolKeys,
+    nft_id: u64,
+    replacement: Option<(Pubkey, u32)>,
+) -> Instruction {
+    let mut ix = build(
+        accounts::EarlyExitJunior {
+            user: *user,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            senior_pool: pool.senior_pool,
+            first_loss_pool: pool.first_loss_pool,
+            junior_interest_pool: pool.junior_interest_pool,
+            nft_metadata: pool.junior_nft_metadata(nft_id),
+            user_nft_account: pool.junior_nft_ata(user, nft_id),
+            junior_nft_mint: pool.junior_nft_mint(nft_id),
+            user_asset_account: pool.asset_ata(user),
+            first_loss_pool_vault: pool.first_loss_pool_vault,
+            junior_interest_pool_vault: pool.junior_interest_pool_vault,
+            treasury_ata: pool.treasury_ata,
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        instruction::EarlyExitJunior {
+            nft_id,
+            with_replacement: replacement.is_some(),
+        },
+    );
+    if let Some((buyer, issued_nft_count)) = replacement {
+        let new_nft_id = issued_nft_id(issued_nft_count);
+        ix.accounts.extend([
+            AccountMeta::new(buyer, true),
+            AccountMeta::new(pool.asset_ata(&buyer), false),
+            AccountMeta::new(pool.junior_nft_mint(new_nft_id), false),
+            AccountMeta::new(pool.junior_nft_metadata(new_nft_id), false),
+            AccountMeta::new(pool.junior_nft_ata(&buyer, new_nft_id), false),
+        ]);
+    }
+    ix
+}
+
+/// 领取 Senior 分配并可追加锁定 `lock_amount` 个 GROW；资产池完成后赎回锁定的 GROW
+pub fn claim_senior_distribution(
+    holder: &Pubkey,
+    pool: &PoolKeys,
+    lock_amount: u64,
+) -> Instruction {
+    build(
+        accounts::ClaimSeniorDistribution {
+            holder: *holder,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            senior_pool: pool.senior_pool,
+            grow_token_mint: pool.grow_token_mint,
+            checkpoint: pool.senior_checkpoint(holder),
+            grow_escrow: pda::grow_escrow(&pool.asset_pool).0,
+            holder_grow_account: pool.grow_token_ata(holder),
+            holder_asset_account: pool.asset_ata(holder),
+            senior_pool_vault: pool.senior_pool_vault,
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::ClaimSeniorDistribution { lock_amount },
+    )
+}
+
+/// 完成前解锁 GROW；结算待领取分配后按比例扣回已领取部分
+pub fn unlock_senior_distribution(holder: &Pubkey, pool: &PoolKeys, amount: u64) -> Instruction {
+    build(
+        accounts::UnlockSeniorDistribution {
+            holder: *holder,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            senior_pool: pool.senior_pool,
+            checkpoint: pool.senior_checkpoint(holder),
+            grow_escrow: pda::grow_escrow(&pool.asset_pool).0,
+            holder_grow_account: pool.grow_token_ata(holder),
+            holder_asset_account: pool.asset_ata(holder),
+            senior_pool_vault: pool.senior_pool_vault,
+            token_program: spl_token::ID,
+        },
+        instruction::UnlockSeniorDistribution { amount },
+    )
+}
+
+/// 提交早退请求；`request_id` 为当前 SeniorExitQueue.tail（队列不存在时为 0）
+pub fn request_senior_exit(
+    holder: &Pubkey,
+    pool: &PoolKeys,
+    request_id: u64,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::RequestSeniorExit {
+            holder: *holder,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            exit_queue: pda::senior_exit_queue(&pool.asset_pool).0,
+            exit_request: pool.senior_exit_request(request_id),
+            grow_token_mint: pool.grow_token_mint,
+            exit_escrow: pda::senior_exit_escrow(&pool.asset_pool).0,
+            holder_grow_account: pool.grow_token_ata(holder),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::RequestSeniorExit { amount },
+    )
+}
+
+pub fn cancel_senior_exit(holder: &Pubkey, pool: &PoolKeys, request_id: u64) -> Instruction {
+    build(
+        accounts::CancelSeniorExit {
+            holder: *holder,
+            asset_pool: pool.asset_pool,
+            exit_queue: pda::senior_exit_queue(&pool.asset_pool).0,
+            exit_request: pool.senior_exit_request(request_id),
+            exit_escrow: pda::senior_exit_escrow(&pool.asset_pool).0,
+            holder_grow_account: pool.grow_token_ata(holder),
+            token_program: spl_token::ID,
+        },
+        instruction::CancelSeniorExit { request_id },
+    )
+}
+
+/// 撮合早退队列；`requests` 为从队首开始的 (request_id, holder)，
+/// 已撤销的请求也需按顺序传入（holder 可为任意地址）
+pub fn process_senior_exits(
+    caller: &Pubkey,
+    pool: &PoolKeys,
+    requests: &[(u64, Pubkey)],
+) -> Instruction {
+    let mut ix = build(
+        accounts::ProcessSeniorExits {
+            caller: *caller,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            senior_pool: pool.senior_pool,
+            exit_queue: pda::senior_exit_queue(&pool.asset_pool).0,
+            grow_token_mint: pool.grow_token_mint,
+            exit_escrow: pda::senior_exit_escrow(&pool.asset_pool).0,
+            senior_pool_vault: pool.senior_pool_vault,
+            treasury_ata: pool.treasury_ata,
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+        },
+        instruction::ProcessSeniorExits {},
+    );
+    for (request_id, holder) in requests {
+        ix.accounts.extend([
+            AccountMeta::new(pool.senior_exit_request(*request_id), false),
+            AccountMeta::new(pool.asset_ata(holder), false),
+            AccountMeta::new(*holder, false),
+        ]);
+    }
+    ix
+}
+
+// ==================== Marketplace ====================
+
+pub fn create_grow_listing(
+    seller: &Pubkey,
+    pool: &PoolKeys,
+    listing_id: u64,
+    amount: u64,
+    unit_price: u64,
+) -> Instruction {
+    let listing = pool.listing(seller, listing_id);
+    build(
+        accounts::CreateGrowListing {
+            seller: *seller,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            listing,
+            grow_token_mint: pool.grow_token_mint,
+            listing_escrow: pda::listing_escrow(&listing).0,
+            seller_grow_account: pool.grow_token_ata(seller),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateGrowListing {
+            listing_id,
+            amount,
+            unit_price,
+        },
+    )
+}
+
+pub fn create_junior_nft_listing(
+    seller: &Pubkey,
+    pool: &PoolKeys,
+    listing_id: u64,
+    nft_id: u64,
+    price: u64,
+) -> Instruction {
+    let listing = pool.listing(seller, listing_id);
+    build(
+        accounts::CreateJuniorNftListing {
+            seller: *seller,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            listing,
+            nft_metadata: pool.junior_nft_metadata(nft_id),
+            junior_nft_mint: pool.junior_nft_mint(nft_id),
+            listing_escrow: pda::listing_escrow(&listing).0,
+            seller_nft_account: pool.junior_nft_ata(seller, nft_id),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::CreateJuniorNftListing {
+            listing_id,
+            nft_id,
+            price,
+        },
+    )
+}
+
+/// 成交挂单；`nft_id` 为 None 时为 GROW 挂单，否则追加 [nft_metadata]
+pub fn fill_listing(
+    buyer: &Pubkey,
+    pool: &PoolKeys,
+    seller: &Pubkey,
+    listing_id: u64,
+    nft_id: Option<u64>,
+    amount: u64,
+) -> Instruction {
+    let listing = pool.listing(seller, listing_id);
+    let position_mint = pool.listing_position_mint(nft_id);
+    build(
+        accounts::FillListing {
+            buyer: *buyer,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            listing,
+            seller: *seller,
+            position_mint,
+            nft_metadata: nft_id.map(|nft_id| pool.junior_nft_metadata(nft_id)),
+            listing_escrow: pda::listing_escrow(&listing).0,
+            buyer_position_account: get_associated_token_address(buyer, &position_mint),
+            buyer_asset_account: pool.asset_ata(buyer),
+            seller_asset_account: pool.asset_ata(seller),
+            treasury_ata: pool.treasury_ata,
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        instruction::FillListing { amount },
+    )
+}
+
+/// 撤单；`nft_id` 为 None 时为 GROW 挂单
+pub fn cancel_listing(
+    seller: &Pubkey,
+    pool: &PoolKeys,
+    listing_id: u64,
+    nft_id: Option<u64>,
+) -> Instruction {
+    let listing = pool.listing(seller, listing_id);
+    build(
+        accounts::CancelListing {
+            seller: *seller,
+            asset_pool: pool.asset_pool,
+            listing,
+            listing_escrow: pda::listing_escrow(&listing).0,
+            seller_position_account: get_associated_token_address(
+                seller,
+                &pool.listing_position_mint(nft_id),
+            ),
+            token_program: spl_token::ID,
+        },
+        instruction::CancelListing {},
+    )
+}
+
+// ==================== Pool Reserve ====================
+
+/// 为资产池开启储备金（超级管理员，募资完成前）
+pub fn initialize_pool_reserve(
+    admin: &Pubkey,
+    pool: &PoolKeys,
+    fee_share: u16,
+    interest_share: u16,
+) -> Instruction {
+    build(
+        accounts::InitializePoolReserve {
+            admin: *admin,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            pool_reserve: pool.pool_reserve(),
+            reserve_vault: pool.pool_reserve_vault(),
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitializePoolReserve {
+            fee_share,
+            interest_share,
+        },
+    )
+}
+
+// ==================== Insurance Fund ====================
+
+/// 为资产代币创建保险基金（超级管理员）
+pub fn initialize_insurance_fund(
+    admin: &Pubkey,
+    asset_mint: &Pubkey,
+    fee_share: u16,
+    max_draw_per_pool: u64,
+) -> Instruction {
+    build(
+        accounts::InitializeInsuranceFund {
+            admin: *admin,
+            system_config: pda::system_config().0,
+            asset_whitelist: pda::asset_whitelist().0,
+            insurance_fund: pda::insurance_fund(asset_mint).0,
+            insurance_fund_vault: pda::insurance_fund_vault(asset_mint).0,
+            asset_mint: *asset_mint,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeInsuranceFund {
+            fee_share,
+            max_draw_per_pool,
+        },
+    )
+}
+
+pub fn update_insurance_fund(
+    admin: &Pubkey,
+    asset_mint: &Pubkey,
+    fee_share: u16,
+    max_draw_per_pool: u64,
+) -> Instruction {
+    build(
+        accounts::UpdateInsuranceFund {
+            admin: *admin,
+            system_config: pda::system_config().0,
+            insurance_fund: pda::insurance_fund(asset_mint).0,
+        },
+        instruction::UpdateInsuranceFund {
+            fee_share,
+            max_draw_per_pool,
+        },
+    )
+}
+
+/// 为违约资产池提出保险基金提取提案（运营管理员）
+pub fn propose_insurance_draw(
+    operation_admin: &Pubkey,
+    pool: &PoolKeys,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::ProposeInsuranceDraw {
+            operation_admin: *operation_admin,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            senior_pool: pool.senior_pool,
+            insurance_fund: pool.insurance_fund(),
+            insurance_fund_vault: pool.insurance_fund_vault(),
+            insurance_draw: pool.insurance_draw(),
+            system_program: system_program::ID,
+        },
+        instruction::ProposeInsuranceDraw { amount },
+    )
+}
+
+/// 执行待定的保险基金提取提案（超级管理员）
+pub fn execute_insurance_draw(super_admin: &Pubkey, pool: &PoolKeys) -> Instruction {
+    build(
+        accounts::ExecuteInsuranceDraw {
+            super_admin: *super_admin,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            senior_pool: pool.senior_pool,
+            senior_pool_vault: pool.senior_pool_vault,
+            insurance_fund: pool.insurance_fund(),
+            insurance_fund_vault: pool.insurance_fund_vault(),
+            insurance_draw: pool.insurance_draw(),
+            grow_token_mint: pool.grow_token_mint,
+            token_program: spl_token::ID,
+        },
+        instruction::ExecuteInsuranceDraw {},
+    )
+}
+
+/// 撤销待定的保险基金提取提案（超级管理员或运营管理员）
+pub fn cancel_insurance_draw(admin: &Pubkey, pool: &PoolKeys) -> Instruction {
+    build(
+        accounts::CancelInsuranceDraw {
+            admin: *admin,
+            system_config: pool.system_config,
+            insurance_draw: pool.insurance_draw(),
+        },
+        instruction::CancelInsuranceDraw {},
+    )
+}
+
+// ==================== Fee Split ====================
+
+/// 设置系统级手续费分账（金库管理员）
+pub fn set_system_fee_split(treasury_admin: &Pubkey, recipients: Vec<FeeRecipient>) -> Instruction {
+    let system_config = pda::system_config().0;
+    build(
+        accounts::SetSystemFeeSplit {
+            treasury_admin: *treasury_admin,
+            system_config,
+            fee_split: pda::fee_split(&system_config).0,
+            system_program: system_program::ID,
+        },
+        instruction::SetSystemFeeSplit { recipients },
+    )
+}
+
+/// 设置资产池级手续费分账（金库管理员）
+pub fn set_pool_fee_split(
+    treasury_admin: &Pubkey,
+    pool: &PoolKeys,
+    recipients: Vec<FeeRecipient>,
+) -> Instruction {
+    build(
+        accounts::SetPoolFeeSplit {
+            treasury_admin: *treasury_admin,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            fee_split: pool.fee_split(),
+            system_program: system_program::ID,
+        },
+        instruction::SetPoolFeeSplit { recipients },
+    )
+}
+
+/// 开启资产池手续费归集（金库管理员）；之后需以 `PoolKeys::from_state` 重新读取 `treasury_ata`
+pub fn initialize_fee_collector(treasury_admin: &Pubkey, pool: &PoolKeys) -> Instruction {
+    build(
+        accounts::InitializeFeeCollector {
+            treasury_admin: *treasury_admin,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            fee_collector: pool.fee_collector(),
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeFeeCollector {},
+    )
+}
+
+/// 分发归集的手续费；`recipients` 为生效分账配置中的收款方，按配置顺序传入
+pub fn distribute_fees(caller: &Pubkey, pool: &PoolKeys, recipients: &[Pubkey]) -> Instruction {
+    let mut ix = build(
+        accounts::DistributeFees {
+            caller: *caller,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            fee_collector: pool.fee_collector(),
+            pool_fee_split: pool.fee_split(),
+            system_fee_split: pda::fee_split(&pool.system_config).0,
+            referral_program: pool.referral_program(),
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+        },
+        instruction::DistributeFees {},
+    );
+    ix.accounts.extend(
+        recipients
+            .iter()
+            .map(|recipient| AccountMeta::new(pool.asset_ata(recipient), false)),
+    );
+    ix
+}
+
+// ==================== Referrals ====================
+
+/// 设置资产池推荐奖励条款（金库管理员），`reward_mode` 取 `referral_reward_mode`
+pub fn set_referral_terms(
+    treasury_admin: &Pubkey,
+    pool: &PoolKeys,
+    reward_mode: u8,
+    reward_rate: u16,
+) -> Instruction {
+    build(
+        accounts::SetReferralTerms {
+            treasury_admin: *treasury_admin,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            referral_program: pool.referral_program(),
+            system_program: system_program::ID,
+        },
+        instruction::SetReferralTerms {
+            reward_mode,
+            reward_rate,
+        },
+    )
+}
+
+/// 结算并支付 `referrer` 的推荐奖励（任何人可调用）
+pub fn claim_referral_reward(caller: &Pubkey, pool: &PoolKeys, referrer: &Pubkey) -> Instruction {
+    build(
+        accounts::ClaimReferralReward {
+            caller: *caller,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            referral_program: pool.referral_program(),
+            referral_record: pool.referral(referrer),
+            fee_collector: pool.fee_collector(),
+            referrer_token_account: pool.asset_ata(referrer),
+            asset_mint: pool.asset_mint,
+            token_program: spl_token::ID,
+        },
+        instruction::ClaimReferralReward {},
+    )
+}
+
+// ==================== Invariants ====================
+
+pub fn check_pool_invariants(caller: &Pubkey, pool: &PoolKeys) -> Instruction {
+    build(
+        accounts::CheckPoolInvariants {
+            caller: *caller,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            senior_pool: pool.senior_pool,
+            first_loss_pool: pool.first_loss_pool,
+            junior_interest_pool: pool.junior_interest_pool,
+            asset_pool_vault: pool.asset_pool_vault,
+            senior_pool_vault: pool.senior_pool_vault,
+            first_loss_pool_vault: pool.first_loss_pool_vault,
+            junior_interest_pool_vault: pool.junior_interest_pool_vault,
+        },
+        instruction::CheckPoolInvariants {},
+    )
+}
+
+pub fn set_asset_pool_paused(
+    super_admin: &Pubkey,
+    asset_pool: &Pubkey,
+    paused: bool,
+) -> Instruction {
+    build(
+        accounts::SetAssetPoolPaused {
+            super_admin: *super_admin,
+            system_config: pda::system_config().0,
+            asset_pool: *asset_pool,
+        },
+        instruction::SetAssetPoolPaused { paused },
+    )
+}
+
+// ==================== Token Management ====================
+
+pub fn mint_grow_token(
+    payer: &Pubkey,
+    pool: &PoolKeys,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::MintGrowToken {
+            payer: *payer,
+            asset_pool: pool.asset_pool,
+            grow_token_mint: pool.grow_token_mint,
+            recipient: *recipient,
+            recipient_token_account: pool.grow_token_ata(recipient),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        instruction::MintGrowToken { amount },
+    )
+}
+
+pub fn burn_grow_token(user: &Pubkey, pool: &PoolKeys, amount: u64) -> Instruction {
+    build(
+        accounts::BurnGrowToken {
+            user: *user,
+            grow_token_mint: pool.grow_token_mint,
+            asset_pool: pool.asset_pool,
+            user_token_account: pool.grow_token_ata(user),
+            token_program: spl_token::ID,
+        },
+        instruction::BurnGrowToken { amount },
+    )
+}
+
+pub fn mint_junior_nft(
+    payer: &Pubkey,
+    pool: &PoolKeys,
+    recipient: &Pubkey,
+    nft_id: u64,
+    principal: u64,
+) -> Instruction {
+    build(
+        accounts::MintJuniorNFT {
+            payer: *payer,
+            asset_pool: pool.asset_pool,
+            junior_nft_mint: pool.junior_nft_mint(nft_id),
+            recipient: *recipient,
+            recipient_token_account: pool.junior_nft_ata(recipient, nft_id),
+            nft_metadata: pool.junior_nft_metadata(nft_id),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        instruction::MintJuniorNft { nft_id, principal },
+    )
+}
+
+/// 拆分 Junior NFT；为每个新 NFT 追加 [junior_nft_mint, nft_metadata, user_token_account]
+/// issued_nft_count 取自 AssetPool，新 NFT 编号由其依次分配
+pub fn split_junior_nft(
+    user: &Pubkey,
+    pool: &PoolKeys,
+    nft_id: u64,
+    amounts: Vec<u64>,
+    issued_nft_count: u32,
+) -> Instruction {
+    let new_nft_ids = (issued_nft_count..)
+        .take(amounts.len())
+        .map(issued_nft_id)
+        .collect::<Vec<u64>>();
+    let mut ix = build(
+        accounts::SplitJuniorNFT {
+            user: *user,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            nft_metadata: pool.junior_nft_metadata(nft_id),
+            junior_nft_mint: pool.junior_nft_mint(nft_id),
+            user_nft_account: pool.junior_nft_ata(user, nft_id),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        instruction::SplitJuniorNft { nft_id, amounts },
+    );
+    for new_nft_id in new_nft_ids {
+        ix.accounts.extend([
+            AccountMeta::new(pool.junior_nft_mint(new_nft_id), false),
+            AccountMeta::new(pool.junior_nft_metadata(new_nft_id), false),
+            AccountMeta::new(pool.junior_nft_ata(user, new_nft_id), false),
+        ]);
+    }
+    ix
+}
+
+/// 合并 Junior NFT；为每个源 NFT 追加 [nft_metadata, junior_nft_mint, user_nft_account]
+pub fn merge_junior_nfts(
+    user: &Pubkey,
+    pool: &PoolKeys,
+    target_nft_id: u64,
+    source_nft_ids: Vec<u64>,
+) -> Instruction {
+    let mut ix = build(
+        accounts::MergeJuniorNFTs {
+            user: *user,
+            system_config: pool.system_config,
+            asset_pool: pool.asset_pool,
+            target_metadata: pool.junior_nft_metadata(target_nft_id),
+            target_nft_mint: pool.junior_nft_mint(target_nft_id),
+            target_nft_account: pool.junior_nft_ata(user, target_nft_id),
+            token_program: spl_token::ID,
+        },
+        instruction::MergeJuniorNfts {
+            target_nft_id,
+            source_nft_ids: source_nft_ids.clone(),
+        },
+    );
+    for source_nft_id in source_nft_ids {
+        ix.accounts.extend([
+            AccountMeta::new(pool.junior_nft_metadata(source_nft_id), false),
+            AccountMeta::new(pool.junior_nft_mint(source_nft_id), false),
+            AccountMeta::new(pool.junior_nft_ata(user, source_nft_id), false),
+        ]);
+    }
+    ix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+    use pencil_solana::constants::JUNIOR_NFT_ISSUED_ID_BASE;
+
+    fn pool() -> PoolKeys {
+        PoolKeys::derive(
+            &Pubkey::new_unique(),
+            "pool",
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        )
+    }
+
+    #[test]
+    fn test_repay_accounts_and_data() {
+        let pool = pool();
+        let payer = Pubkey::new_unique();
+        let ix = repay(&payer, &pool, 500, 2);
+
+        assert_eq!(ix.program_id, pencil_solana::ID);
+        assert_eq!(ix.accounts[0].pubkey, payer);
+        assert!(ix.accounts[0].is_signer);
+        assert_eq!(ix.accounts[4].pubkey, pool.originator);
+        assert!(ix.accounts[4].is_writable);
+        assert_eq!(ix.accounts[8].pubkey, pool.asset_ata(&payer));
+        assert_eq!(ix.accounts[14].pubkey, pool.repayment_record(2));
+        assert_eq!(&ix.data[..8], instruction::Repay::DISCRIMINATOR);
+        assert_eq!(&ix.data[8..16], &500u64.to_le_bytes());
+        assert_eq!(&ix.data[16..24], &2u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_split_appends_remaining_accounts() {
+        let pool = pool();
+        let user = Pubkey::new_unique();
+        let ix = split_junior_nft(&user, &pool, 1, vec![40, 60], 2);
+
+        let first = JUNIOR_NFT_ISSUED_ID_BASE + 2;
+        assert_eq!(ix.accounts.len(), 9 + 2 * 3);
+        assert_eq!(ix.accounts[9].pubkey, pool.junior_nft_mint(first));
+        assert_eq!(ix.accounts[10].pubkey, pool.junior_nft_metadata(first));
+        assert_eq!(
+            ix.accounts[14].pubkey,
+            pool.junior_nft_ata(&user, first + 1)
+        );
+        assert!(ix.accounts[9..]
+            .iter()
+            .all(|meta| meta.is_writable && !meta.is_signer));
+    }
+
+    #[test]
+    fn test_early_exit_junior_appends_replacement_accounts() {
+        let pool = pool();
+        let user = Pubkey::new_unique();
+        let buyer = Pubkey::new_unique();
+
+        let ix = early_exit_junior(&user, &pool, 1, None);
+        assert_eq!(ix.accounts.len(), 17);
+
+        let ix = early_exit_junior(&user, &pool, 1, Some((buyer, 7)));
+        let new_nft_id = JUNIOR_NFT_ISSUED_ID_BASE + 7;
+        assert_eq!(ix.accounts.len(), 17 + 5);
+        assert_eq!(ix.accounts[17].pubkey, buyer);
+        assert!(ix.accounts[17].is_signer);
+        assert_eq!(ix.accounts[18].pubkey, pool.asset_ata(&buyer));
+        assert_eq!(
+            ix.accounts[21].pubkey,
+            pool.junior_nft_ata(&buyer, new_nft_id)
+        );
+        assert_eq!(&ix.data[..8], instruction::EarlyExitJunior::DISCRIMINATOR);
+        assert_eq!(&ix.data[8..16], &1u64.to_le_bytes());
+        assert_eq!(ix.data[16..], [1]);
+    }
+
+    #[test]
+    fn test_close_funding_early_flattens_complete_funding_accounts() {
+        let pool = pool();
+        let payer = Pubkey::new_unique();
+        let ix = close_funding_early(&payer, &pool);
+        let complete = complete_funding(&payer, &pool);
+
+        assert_eq!(ix.accounts[0].pubkey, pda::system_config().0);
+        assert_eq!(ix.accounts[1..], complete.accounts[..]);
+        assert_eq!(&ix.data[..], instruction::CloseFundingEarly::DISCRIMINATOR);
+    }
+
+    #[test]
+    fn test_process_senior_exits_appends_request_triples() {
+        let pool = pool();
+        let holder = Pubkey::new_unique();
+        let ix = process_senior_exits(&Pubkey::new_unique(), &pool, &[(3, holder), (4, holder)]);
+
+        assert_eq!(ix.accounts.len(), 11 + 2 * 3);
+        assert_eq!(ix.accounts[11].pubkey, pool.senior_exit_request(3));
+        assert_eq!(ix.accounts[12].pubkey, pool.asset_ata(&holder));
+        assert_eq!(ix.accounts[13].pubkey, holder);
+        assert_eq!(ix.accounts[14].pubkey, pool.senior_exit_request(4));
+        assert!(ix.accounts[11..].iter().all(|meta| meta.is_writable));
+    }
+
+    #[test]
+    fn test_fill_listing_passes_optional_nft_metadata() {
+        let pool = pool();
+        let buyer = Pubkey::new_unique();
+        let seller = Pubkey::new_unique();
+
+        let ix = fill_listing(&buyer, &pool, &seller, 4, None, 10);
+        assert_eq!(ix.accounts.len(), 16);
+        assert_eq!(ix.accounts[3].pubkey, pool.listing(&seller, 4));
+        assert_eq!(ix.accounts[5].pubkey, pool.grow_token_mint);
+        assert_eq!(ix.accounts[6].pubkey, pencil_solana::ID);
+        assert_eq!(
+            ix.accounts[7].pubkey,
+            pda::listing_escrow(&pool.listing(&seller, 4)).0
+        );
+
+        let ix = fill_listing(&buyer, &pool, &seller, 4, Some(9), 1);
+        assert_eq!(ix.accounts.len(), 16);
+        assert_eq!(ix.accounts[5].pubkey, pool.junior_nft_mint(9));
+        assert_eq!(ix.accounts[6].pubkey, pool.junior_nft_metadata(9));
+        assert!(ix.accounts[6].is_writable);
+        assert_eq!(ix.accounts[8].pubkey, pool.junior_nft_ata(&buyer, 9));
+    }
+
+    #[test]
+    fn test_insurance_fund_is_shared_per_asset_mint() {
+        let pool = pool();
+        let other = PoolKeys::derive(
+            &Pubkey::new_unique(),
+            "other",
+            &pool.asset_mint,
+            &Pubkey::new_unique(),
+            &pool.treasury,
+        );
+        assert_eq!(pool.insurance_fund(), other.insurance_fund());
+        assert_ne!(pool.insurance_draw(), other.insurance_draw());
+
+        let ix = repay(&Pubkey::new_unique(), &pool, 500, 2);
+        assert_eq!(ix.accounts[16].pubkey, pool.insurance_fund());
+        assert_eq!(ix.accounts[17].pubkey, pool.insurance_fund_vault());
+        assert!(ix.accounts[16].is_writable && ix.accounts[17].is_writable);
+    }
+
+    #[test]
+    fn test_repayments_append_pool_reserve_accounts() {
+        let pool = pool();
+        let payer = Pubkey::new_unique();
+
+        let ix = repay(&payer, &pool, 500, 2);
+        assert_eq!(ix.accounts.len(), 20 + 2);
+        assert_eq!(ix.accounts[20].pubkey, pool.pool_reserve());
+        assert_eq!(ix.accounts[21].pubkey, pool.pool_reserve_vault());
+        assert!(ix.accounts[20..].iter().all(|meta| meta.is_writable));
+
+        let ix = prepay_remaining(&payer, &pool, 1_000);
+        assert_eq!(ix.accounts.len(), 18 + 2);
+        assert_eq!(ix.accounts[18].pubkey, pool.pool_reserve());
+    }
+
+    #[test]
+    fn test_distribute_fees_passes_both_splits_and_recipient_atas() {
+        let pool = pool();
+        let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let ix = distribute_fees(&Pubkey::new_unique(), &pool, &recipients);
+
+        assert_eq!(ix.accounts.len(), 9 + 2);
+        assert_eq!(ix.accounts[3].pubkey, pool.fee_collector());
+        assert_eq!(ix.accounts[4].pubkey, pool.fee_split());
+        assert_eq!(ix.accounts[5].pubkey, pda::fee_split(&pool.system_config).0);
+        assert_eq!(ix.accounts[6].pubkey, pool.referral_program());
+        assert_eq!(ix.accounts[9].pubkey, pool.asset_ata(&recipients[0]));
+        assert_eq!(ix.accounts[10].pubkey, pool.asset_ata(&recipients[1]));
+        assert!(ix.accounts[9..].iter().all(|meta| meta.is_writable));
+    }
+
+    #[test]
+    fn test_migrate_pool_treasury_creates_current_treasury_ata() {
+        let treasury = Pubkey::new_unique();
+        let asset_mint = Pubkey::new_unique();
+        let pools = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let ix = migrate_pool_treasury(&Pubkey::new_unique(), &treasury, &asset_mint, &pools);
+
+        assert_eq!(ix.accounts.len(), 8 + 2);
+        assert_eq!(ix.accounts[2].pubkey, treasury);
+        assert_eq!(
+            ix.accounts[3].pubkey,
+            get_associated_token_address(&treasury, &asset_mint)
+        );
+        assert_eq!(ix.accounts[8].pubkey, pools[0]);
+        assert!(ix.accounts[8..].iter().all(|meta| meta.is_writable));
+    }
+
+    #[test]
+    fn test_subscribe_appends_referral_accounts() {
+        let pool = pool();
+        let user = Pubkey::new_unique();
+        let referrer = Pubkey::new_unique();
+
+        let ix = subscribe_junior(&user, &pool, 100, None);
+        assert_eq!(ix.accounts.len(), 10);
+
+        let ix = subscribe_junior(&user, &pool, 100, Some(&referrer));
+        assert_eq!(ix.accounts.len(), 10 + 3);
+        assert_eq!(ix.accounts[10].pubkey, referrer);
+        assert!(!ix.accounts[10].is_writable && !ix.accounts[10].is_signer);
+        assert_eq!(ix.accounts[11].pubkey, pool.referral(&referrer));
+        assert_eq!(ix.accounts[12].pubkey, pool.referral_program());
+        assert!(ix.accounts[11..].iter().all(|meta| meta.is_writable));
+
+        let ix = withdraw_junior_subscription(&user, &pool, 100, Some(&referrer));
+        assert_eq!(ix.accounts.len(), 9 + 2);
+        assert_eq!(ix.accounts[9].pubkey, pool.referral(&referrer));
+        assert_eq!(ix.accounts[10].pubkey, pool.referral_program());
+    }
+
+    #[test]
+    fn test_create_asset_pool_encodes_inherited_params() {
+        let payer = Pubkey::new_unique();
+        let ix = create_asset_pool(
+            &payer,
+            &Pubkey::new_unique(),
+            instruction::CreateAssetPool {
+                name: "pool".to_string(),
+                platform_fee: None,
+                senior_early_before_exit_fee: Some(150),
+                senior_early_after_exit_fee: None,
+                junior_early_before_exit_fee: None,
+                min_junior_ratio: Some(2000),
+                repayment_rate: 300,
+                senior_fixed_rate: 100,
+                repayment_period: 30,
+                repayment_count: 3,
+                total_amount: 1_000,
+                min_amount: 500,
+                funding_start_time: 1,
+                funding_end_time: 3_601,
+            },
+        );
+
+        assert_eq!(ix.accounts[3].pubkey, pda::asset_pool(&payer, "pool").0);
+        // 名称之后依次为五个可继承参数，None 仅占 1 字节
+        let params = &ix.data[8 + 4 + 4..];
+        assert_eq!(params[0], 0);
+        assert_eq!(&params[1..4], &[1, 150, 0]);
+        assert_eq!(&params[4..6], &[0, 0]);
+        assert_eq!(&params[6..9], &[1, 0xd0, 0x07]);
+    }
+
+    #[test]
+    fn test_pool_param_instructions_target_system_config() {
+        let caller = Pubkey::new_unique();
+        let asset_pool = Pubkey::new_unique();
+        let ix = sync_pool_params(&caller, &asset_pool);
+        assert_eq!(ix.accounts[1].pubkey, pda::system_config().0);
+        assert!(!ix.accounts[1].is_writable);
+        assert_eq!(ix.accounts[2].pubkey, asset_pool);
+        assert!(ix.accounts[2].is_writable);
+
+        let bounds = PoolParamBounds { min: 100, max: 800 };
+        let ix = set_pool_param_bounds(&caller, 0, Some(bounds));
+        assert!(ix.accounts[1].is_writable);
+        assert_eq!(&ix.data[8..], &[0, 1, 100, 0, 0x20, 0x03]);
+    }
+}
@@ -0,0 +1,18 @@
+//! Pencil Solana Rust 客户端
+//! Typed client SDK for the pencil-solana program.
+//!
+//! - [`pda`]: `constants::seeds` 中每个种子的 PDA 推导
+//! - [`instructions`]: 每个 `#[program]` 入口的指令构造器
+//! - [`accounts`]: `state.rs` 中所有账户的反序列化
+//! - [`events`]: `lib.rs` 中所有事件的解码（含交易日志解析）
+
+pub mod accounts;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+
+pub use accounts::{decode_account, PencilAccount};
+pub use events::{decode_event, parse_logs, PencilEvent};
+pub use instructions::PoolKeys;
+pub use pencil_solana;
+pub use pencil_solana::ID as PROGRAM_ID;
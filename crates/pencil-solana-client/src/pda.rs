@@ -0,0 +1,243 @@
+//! PDA 地址推导
+//! One helper per seed in `pencil_solana::constants::seeds`, with the exact
+//! seed layout used by the program. All helpers return `(address, bump)`.
+
+use anchor_lang::prelude::Pubkey;
+use pencil_solana::constants::seeds;
+
+fn find(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &pencil_solana::ID)
+}
+
+/// 系统配置: [SYSTEM_CONFIG]
+pub fn system_config() -> (Pubkey, u8) {
+    find(&[seeds::SYSTEM_CONFIG])
+}
+
+/// 资产白名单: [ASSET_WHITELIST]
+pub fn asset_whitelist() -> (Pubkey, u8) {
+    find(&[seeds::ASSET_WHITELIST])
+}
+
+/// 发起方: [ORIGINATOR, authority]
+pub fn originator(authority: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::ORIGINATOR, authority.as_ref()])
+}
+
+/// 资产池: [ASSET_POOL, creator, name]
+pub fn asset_pool(creator: &Pubkey, name: &str) -> (Pubkey, u8) {
+    find(&[seeds::ASSET_POOL, creator.as_ref(), name.as_bytes()])
+}
+
+/// 募资账户: [FUNDING, asset_pool]
+pub fn funding(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::FUNDING, asset_pool.as_ref()])
+}
+
+/// 优先池: [SENIOR_POOL, asset_pool]
+pub fn senior_pool(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::SENIOR_POOL, asset_pool.as_ref()])
+}
+
+/// 首损池: [FIRST_LOSS_POOL, asset_pool]
+pub fn first_loss_pool(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::FIRST_LOSS_POOL, asset_pool.as_ref()])
+}
+
+/// 利息池: [JUNIOR_INTEREST_POOL, asset_pool]
+pub fn junior_interest_pool(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::JUNIOR_INTEREST_POOL, asset_pool.as_ref()])
+}
+
+/// 资产池金库: [TREASURY, asset_pool]
+///
+/// 程序当前使用 SystemConfig.treasury 收取手续费，未推导该 PDA；保留以便与种子表一致
+pub fn treasury(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::TREASURY, asset_pool.as_ref()])
+}
+
+/// GROW Token Mint: [GROW_TOKEN_MINT, asset_pool]
+pub fn grow_token_mint(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::GROW_TOKEN_MINT, asset_pool.as_ref()])
+}
+
+/// Junior NFT 基础 Mint: [JUNIOR_NFT_MINT, asset_pool]
+pub fn junior_nft_collection_mint(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::JUNIOR_NFT_MINT, asset_pool.as_ref()])
+}
+
+/// 单个 Junior NFT Mint: [JUNIOR_NFT_MINT, asset_pool, nft_id (LE)]
+pub fn junior_nft_mint(asset_pool: &Pubkey, nft_id: u64) -> (Pubkey, u8) {
+    find(&[
+        seeds::JUNIOR_NFT_MINT,
+        asset_pool.as_ref(),
+        &nft_id.to_le_bytes(),
+    ])
+}
+
+/// Junior NFT 元数据: [JUNIOR_NFT_METADATA, asset_pool, nft_id (LE)]
+pub fn junior_nft_metadata(asset_pool: &Pubkey, nft_id: u64) -> (Pubkey, u8) {
+    find(&[
+        seeds::JUNIOR_NFT_METADATA,
+        asset_pool.as_ref(),
+        &nft_id.to_le_bytes(),
+    ])
+}
+
+/// 订阅记录: [SUBSCRIPTION, asset_pool, user, "senior" | "junior"]
+pub fn subscription(asset_pool: &Pubkey, user: &Pubkey, senior: bool) -> (Pubkey, u8) {
+    let tranche: &[u8] = if senior { b"senior" } else { b"junior" };
+    find(&[
+        seeds::SUBSCRIPTION,
+        asset_pool.as_ref(),
+        user.as_ref(),
+        tranche,
+    ])
+}
+
+/// 还款记录: [REPAYMENT_RECORD, asset_pool, period (LE)]
+pub fn repayment_record(asset_pool: &Pubkey, period: u64) -> (Pubkey, u8) {
+    find(&[
+        seeds::REPAYMENT_RECORD,
+        asset_pool.as_ref(),
+        &period.to_le_bytes(),
+    ])
+}
+
+/// Senior 分配检查点: [SENIOR_CHECKPOINT, asset_pool, holder]
+pub fn senior_checkpoint(asset_pool: &Pubkey, holder: &Pubkey) -> (Pubkey, u8) {
+    find(&[
+        seeds::SENIOR_CHECKPOINT,
+        asset_pool.as_ref(),
+        holder.as_ref(),
+    ])
+}
+
+/// 锁定 GROW 托管账户: [GROW_ESCROW, asset_pool]
+pub fn grow_escrow(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::GROW_ESCROW, asset_pool.as_ref()])
+}
+
+/// Senior 早退队列: [SENIOR_EXIT_QUEUE, asset_pool]
+pub fn senior_exit_queue(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::SENIOR_EXIT_QUEUE, asset_pool.as_ref()])
+}
+
+/// Senior 早退请求: [SENIOR_EXIT_REQUEST, asset_pool, request_id (LE)]
+pub fn senior_exit_request(asset_pool: &Pubkey, request_id: u64) -> (Pubkey, u8) {
+    find(&[
+        seeds::SENIOR_EXIT_REQUEST,
+        asset_pool.as_ref(),
+        &request_id.to_le_bytes(),
+    ])
+}
+
+/// 排队 GROW 托管账户: [SENIOR_EXIT_ESCROW, asset_pool]
+pub fn senior_exit_escrow(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::SENIOR_EXIT_ESCROW, asset_pool.as_ref()])
+}
+
+/// 二级市场挂单: [LISTING, asset_pool, seller, listing_id (LE)]
+pub fn listing(asset_pool: &Pubkey, seller: &Pubkey, listing_id: u64) -> (Pubkey, u8) {
+    find(&[
+        seeds::LISTING,
+        asset_pool.as_ref(),
+        seller.as_ref(),
+        &listing_id.to_le_bytes(),
+    ])
+}
+
+/// 挂单托管账户: [LISTING_ESCROW, listing]
+pub fn listing_escrow(listing: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::LISTING_ESCROW, listing.as_ref()])
+}
+
+/// 资产池储备金: [POOL_RESERVE, asset_pool]
+pub fn pool_reserve(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::POOL_RESERVE, asset_pool.as_ref()])
+}
+
+/// 储备金 Token Vault: [POOL_RESERVE_VAULT, asset_pool]
+pub fn pool_reserve_vault(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::POOL_RESERVE_VAULT, asset_pool.as_ref()])
+}
+
+/// 保险基金: [INSURANCE_FUND, asset_mint]
+pub fn insurance_fund(asset_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::INSURANCE_FUND, asset_mint.as_ref()])
+}
+
+/// 保险基金 Token Vault: [INSURANCE_FUND_VAULT, asset_mint]
+pub fn insurance_fund_vault(asset_mint: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::INSURANCE_FUND_VAULT, asset_mint.as_ref()])
+}
+
+/// 资产池的保险基金提取记录: [INSURANCE_DRAW, asset_pool]
+pub fn insurance_draw(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::INSURANCE_DRAW, asset_pool.as_ref()])
+}
+
+/// 手续费分账配置: [FEE_SPLIT, owner]，owner 为 SystemConfig（系统级）或 AssetPool（资产池级）
+pub fn fee_split(owner: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::FEE_SPLIT, owner.as_ref()])
+}
+
+/// 手续费归集账户: [FEE_COLLECTOR, asset_pool]
+pub fn fee_collector(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::FEE_COLLECTOR, asset_pool.as_ref()])
+}
+
+/// 资产池推荐奖励条款: [REFERRAL_PROGRAM, asset_pool]
+pub fn referral_program(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::REFERRAL_PROGRAM, asset_pool.as_ref()])
+}
+
+/// 推荐人在资产池内的推荐记录: [REFERRAL, asset_pool, referrer]
+pub fn referral(asset_pool: &Pubkey, referrer: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::REFERRAL, asset_pool.as_ref(), referrer.as_ref()])
+}
+
+/// 优先池 Token Vault: [SENIOR_POOL_VAULT, asset_pool]
+pub fn senior_pool_vault(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::SENIOR_POOL_VAULT, asset_pool.as_ref()])
+}
+
+/// 首损池 Token Vault: [FIRST_LOSS_POOL_VAULT, asset_pool]
+pub fn first_loss_pool_vault(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::FIRST_LOSS_POOL_VAULT, asset_pool.as_ref()])
+}
+
+/// 利息池 Token Vault: [JUNIOR_INTEREST_POOL_VAULT, asset_pool]
+pub fn junior_interest_pool_vault(asset_pool: &Pubkey) -> (Pubkey, u8) {
+    find(&[seeds::JUNIOR_INTEREST_POOL_VAULT, asset_pool.as_ref()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nft_seeds_use_little_endian_id() {
+        let pool = Pubkey::new_unique();
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"junior_nft_mint", pool.as_ref(), &[1, 0, 0, 0, 0, 0, 0, 0]],
+            &pencil_solana::ID,
+        );
+        assert_eq!(junior_nft_mint(&pool, 1).0, expected);
+        assert_ne!(
+            junior_nft_mint(&pool, 1).0,
+            junior_nft_collection_mint(&pool).0
+        );
+        assert_ne!(junior_nft_mint(&pool, 1).0, junior_nft_metadata(&pool, 1).0);
+    }
+
+    #[test]
+    fn test_subscription_is_per_tranche() {
+        let pool = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        assert_ne!(
+            subscription(&pool, &user, true).0,
+            subscription(&pool, &user, false).0
+        );
+    }
+}
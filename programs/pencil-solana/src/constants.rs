@@ -1,5 +1,5 @@
-/// 常量定义
-/// Constants for the Pencil Solana program
+//! 常量定义
+//! Constants for the Pencil Solana program
 
 /// 基点单位 (10000 = 100%)
 pub const BASIS_POINTS: u16 = 10000;
@@ -25,6 +25,12 @@ pub const MIN_FUNDING_PERIOD: i64 = 10;
 /// 最大募资期限 (365 天)
 pub const MAX_FUNDING_PERIOD: i64 = 31536000;
 
+/// 有认购后修订经济条款或延长募资期时，投资者免手续费撤资的窗口 (3 天)
+pub const AMENDMENT_WITHDRAW_WINDOW: i64 = 259200;
+
+/// 发起方允许发行的资产数量上限
+pub const MAX_ORIGINATOR_ASSETS: usize = 10;
+
 /// 最小还款期限 (1 天)
 pub const MIN_REPAYMENT_PERIOD: u64 = 1;
 
@@ -34,6 +40,27 @@ pub const MAX_REPAYMENT_PERIOD: u64 = 365;
 /// 最大还款期数 (120 期)
 pub const MAX_REPAYMENT_COUNT: u64 = 120;
 
+/// 最大提前还款违约金 (按剩余本金计，10%)
+pub const MAX_PREPAYMENT_PENALTY: u16 = 1000;
+
+/// 最大二级市场协议费率 (5%)
+pub const MAX_MARKETPLACE_FEE: u16 = 500;
+
+/// Senior 分配累计指数的放大倍数
+pub const DISTRIBUTION_INDEX_SCALE: u128 = 1_000_000_000_000;
+
+/// Junior NFT 单次拆分/合并的最大数量
+pub const MAX_NFT_SPLIT_COUNT: usize = 10;
+
+/// 程序新铸 Junior NFT 的编号起点，分发时指定的编号须小于该值
+pub const JUNIOR_NFT_ISSUED_ID_BASE: u64 = 1 << 32;
+
+/// 手续费分账收款方数量上限
+pub const MAX_FEE_RECIPIENTS: usize = 5;
+
+/// 可继承 SystemConfig 默认值的资产池参数数量
+pub const POOL_PARAM_COUNT: usize = 5;
+
 /// 小数精度 (6 位，与 USDC 一致)
 pub const DECIMALS: u8 = 6;
 
@@ -49,6 +76,8 @@ pub mod asset_pool_status {
     pub const REPAYING: u8 = 4;
     pub const COMPLETED: u8 = 5; // ENDED - 项目已结束，可以提取本金
     pub const CANCELLED: u8 = 6;
+    pub const REJECTED: u8 = 7; // 管理员驳回，账户已关闭
+    pub const WITHDRAWN: u8 = 8; // 创建者撤回，账户已关闭
 }
 
 /// 订阅状态
@@ -58,6 +87,33 @@ pub mod subscription_status {
     pub const REFUNDED: u8 = 2;
 }
 
+/// 提前还款计息方式
+pub mod prepayment_policy {
+    pub const ACCRUED_INTEREST: u8 = 0; // 按实际占用时间计息
+    pub const FULL_INTEREST: u8 = 1; // 剩余各期利息全额支付
+}
+
+/// 二级市场挂单类型
+pub mod listing_kind {
+    pub const GROW: u8 = 0;
+    pub const JUNIOR_NFT: u8 = 1;
+}
+
+/// 推荐奖励模式
+pub mod referral_reward_mode {
+    pub const UPFRONT: u8 = 0; // 按推荐认购额预付
+    pub const FEE_SHARE: u8 = 1; // 按推荐认购额分摊的平台费分成
+}
+
+/// 可继承 SystemConfig 默认值的资产池参数（费率类型的对应关系见 FeeType::pool_param）
+pub mod pool_param {
+    pub const PLATFORM_FEE: u8 = 0;
+    pub const SENIOR_EARLY_BEFORE_EXIT_FEE: u8 = 1;
+    pub const SENIOR_EARLY_AFTER_EXIT_FEE: u8 = 2;
+    pub const JUNIOR_EARLY_BEFORE_EXIT_FEE: u8 = 3;
+    pub const MIN_JUNIOR_RATIO: u8 = 4;
+}
+
 /// 还款状态
 pub mod repayment_status {
     pub const PENDING: u8 = 0;
@@ -80,4 +136,24 @@ pub mod seeds {
     pub const REPAYMENT_RECORD: &[u8] = b"repayment_record";
     pub const JUNIOR_NFT_METADATA: &[u8] = b"junior_nft_metadata";
     pub const ASSET_WHITELIST: &[u8] = b"asset_whitelist";
+    pub const ORIGINATOR: &[u8] = b"originator";
+    pub const SENIOR_CHECKPOINT: &[u8] = b"senior_checkpoint";
+    pub const GROW_ESCROW: &[u8] = b"grow_escrow";
+    pub const SENIOR_EXIT_QUEUE: &[u8] = b"senior_exit_queue";
+    pub const SENIOR_EXIT_REQUEST: &[u8] = b"senior_exit_request";
+    pub const SENIOR_EXIT_ESCROW: &[u8] = b"senior_exit_escrow";
+    pub const LISTING: &[u8] = b"listing";
+    pub const LISTING_ESCROW: &[u8] = b"listing_escrow";
+    pub const POOL_RESERVE: &[u8] = b"pool_reserve";
+    pub const POOL_RESERVE_VAULT: &[u8] = b"pool_reserve_vault";
+    pub const INSURANCE_FUND: &[u8] = b"insurance_fund";
+    pub const INSURANCE_FUND_VAULT: &[u8] = b"insurance_fund_vault";
+    pub const INSURANCE_DRAW: &[u8] = b"insurance_draw";
+    pub const FEE_SPLIT: &[u8] = b"fee_split";
+    pub const FEE_COLLECTOR: &[u8] = b"fee_collector";
+    pub const REFERRAL_PROGRAM: &[u8] = b"referral_program";
+    pub const REFERRAL: &[u8] = b"referral";
+    pub const SENIOR_POOL_VAULT: &[u8] = b"senior_pool_vault";
+    pub const FIRST_LOSS_POOL_VAULT: &[u8] = b"first_loss_pool_vault";
+    pub const JUNIOR_INTEREST_POOL_VAULT: &[u8] = b"junior_interest_pool_vault";
 }
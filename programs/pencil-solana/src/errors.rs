@@ -205,4 +205,100 @@ pub enum PencilError {
 
     #[msg("Invalid period calculation")]
     InvalidPeriodCalculation,
+
+    #[msg("Invalid split amounts")]
+    InvalidSplitAmounts,
+
+    #[msg("Invalid merge parameters")]
+    InvalidMergeParams,
+
+    #[msg("Asset pool is paused")]
+    AssetPoolPaused,
+
+    #[msg("Asset pool amendment not allowed")]
+    AmendmentNotAllowed,
+
+    #[msg("Funding must stay open through the amendment withdraw window")]
+    AmendmentWithdrawWindowTooShort,
+
+    #[msg("Investor withdraw window is still open")]
+    WithdrawWindowOpen,
+
+    #[msg("Originator is not active")]
+    OriginatorNotActive,
+
+    #[msg("Originator outstanding exposure limit exceeded")]
+    OriginatorExposureExceeded,
+
+    #[msg("Too many originator assets")]
+    TooManyOriginatorAssets,
+
+    #[msg("Asset pool already recorded as defaulted")]
+    OriginatorDefaultAlreadyRecorded,
+
+    #[msg("Invalid prepayment terms")]
+    InvalidPrepaymentTerms,
+
+    #[msg("Prepayment amount exceeds the maximum accepted")]
+    PrepaymentAmountExceeded,
+
+    #[msg("No senior distribution to claim")]
+    NoSeniorDistribution,
+
+    #[msg("Invalid senior exit request")]
+    InvalidExitRequest,
+
+    #[msg("No senior exit could be filled")]
+    NoExitFilled,
+
+    #[msg("Invalid marketplace fee rate")]
+    InvalidMarketplaceFee,
+
+    #[msg("Invalid listing")]
+    InvalidListing,
+
+    #[msg("Listing fill amount exceeds the remaining amount")]
+    ListingAmountExceeded,
+
+    #[msg("Invalid pool reserve configuration")]
+    InvalidPoolReserve,
+
+    #[msg("Pool reserve accounts are required")]
+    PoolReserveRequired,
+
+    #[msg("Invalid insurance fund configuration")]
+    InvalidInsuranceFund,
+
+    #[msg("Invalid insurance draw")]
+    InvalidInsuranceDraw,
+
+    #[msg("Insurance draw exceeds the pool's shortfall, cap or fund balance")]
+    InsuranceDrawExceeded,
+
+    #[msg("Invalid fee split configuration")]
+    InvalidFeeSplit,
+
+    #[msg("Fee recipient account does not match the fee split")]
+    InvalidFeeRecipient,
+
+    #[msg("Invalid referral")]
+    InvalidReferral,
+
+    #[msg("Referral accounts are required for a referred subscription")]
+    ReferralRequired,
+
+    #[msg("Invalid pool parameter")]
+    InvalidPoolParam,
+
+    #[msg("Pool parameter is outside the system bounds")]
+    PoolParamOutOfBounds,
+
+    #[msg("Asset pool has subscriptions")]
+    PoolHasSubscriptions,
+
+    #[msg("Early exit exceeds the available senior liquidity")]
+    ExitLiquidityExceeded,
+
+    #[msg("Referral terms are not set for this pool")]
+    ReferralTermsNotSet,
 }
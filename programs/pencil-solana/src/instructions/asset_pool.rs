@@ -1,12 +1,137 @@
 use crate::constants::*;
 use crate::errors::PencilError;
+use crate::instructions::originator::{set_originator_account_exposure, set_pool_exposure};
+use crate::instructions::pool_params::{self, PoolParams};
 use crate::state::{
-    AssetPool, AssetWhitelist, FirstLossPool, Funding, JuniorInterestPool, SeniorPool, SystemConfig,
+    AssetPool, AssetWhitelist, FirstLossPool, Funding, JuniorInterestPool, Originator, SeniorPool,
+    SystemConfig,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
+/// 资产池条款（create_asset_pool 的参数，名称与资产除外）
+/// 费率与最低 Junior 占比为 None 时继承 SystemConfig 默认值
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AssetPoolTerms {
+    pub platform_fee: Option<u16>,
+    pub senior_early_before_exit_fee: Option<u16>,
+    pub senior_early_after_exit_fee: Option<u16>,
+    pub junior_early_before_exit_fee: Option<u16>,
+    pub min_junior_ratio: Option<u16>,
+    pub repayment_rate: u16,
+    pub senior_fixed_rate: u16,
+    pub repayment_period: u64,
+    pub repayment_count: u64,
+    pub total_amount: u64,
+    pub min_amount: u64,
+    pub funding_start_time: i64,
+    pub funding_end_time: i64,
+}
+
+impl AssetPoolTerms {
+    pub fn of(asset_pool: &AssetPool) -> Self {
+        let [
+            platform_fee,
+            senior_early_before_exit_fee,
+            senior_early_after_exit_fee,
+            junior_early_before_exit_fee,
+            min_junior_ratio,
+        ] = pool_params::params_of(asset_pool);
+        Self {
+            platform_fee,
+            senior_early_before_exit_fee,
+            senior_early_after_exit_fee,
+            junior_early_before_exit_fee,
+            min_junior_ratio,
+            repayment_rate: asset_pool.repayment_rate,
+            senior_fixed_rate: asset_pool.senior_fixed_rate,
+            repayment_period: asset_pool.repayment_period,
+            repayment_count: asset_pool.repayment_count,
+            total_amount: asset_pool.total_amount,
+            min_amount: asset_pool.min_amount,
+            funding_start_time: asset_pool.funding_start_time,
+            funding_end_time: asset_pool.funding_end_time,
+        }
+    }
+
+    /// 按 pool_param 编号排列的可继承参数
+    pub fn params(&self) -> PoolParams {
+        [
+            self.platform_fee,
+            self.senior_early_before_exit_fee,
+            self.senior_early_after_exit_fee,
+            self.junior_early_before_exit_fee,
+            self.min_junior_ratio,
+        ]
+    }
+
+    /// 写入资产池，继承的参数取 SystemConfig 当前默认值
+    pub fn apply(&self, asset_pool: &mut AssetPool, system_config: &SystemConfig) -> Result<()> {
+        pool_params::apply(asset_pool, system_config, &self.params())?;
+        asset_pool.repayment_rate = self.repayment_rate;
+        asset_pool.senior_fixed_rate = self.senior_fixed_rate;
+        asset_pool.repayment_period = self.repayment_period;
+        asset_pool.repayment_count = self.repayment_count;
+        asset_pool.total_amount = self.total_amount;
+        asset_pool.min_amount = self.min_amount;
+        asset_pool.funding_start_time = self.funding_start_time;
+        asset_pool.funding_end_time = self.funding_end_time;
+        Ok(())
+    }
+
+    /// 经济条款：费率、利率、Junior 占比、还款安排与最低募资额。
+    /// 募资总额与募资结束时间不在其中；参数在继承与覆盖之间切换也视为变化
+    pub fn economic_terms_differ(&self, other: &Self) -> bool {
+        self.platform_fee != other.platform_fee
+            || self.senior_early_before_exit_fee != other.senior_early_before_exit_fee
+            || self.senior_early_after_exit_fee != other.senior_early_after_exit_fee
+            || self.junior_early_before_exit_fee != other.junior_early_before_exit_fee
+            || self.min_junior_ratio != other.min_junior_ratio
+            || self.repayment_rate != other.repayment_rate
+            || self.senior_fixed_rate != other.senior_fixed_rate
+            || self.repayment_period != other.repayment_period
+            || self.repayment_count != other.repayment_count
+            || self.min_amount != other.min_amount
+    }
+
+    /// 参数范围校验，与 create_asset_pool 一致；覆盖值须在系统设置的范围内
+    pub fn validate(&self, system_config: &SystemConfig) -> Result<()> {
+        pool_params::resolve(system_config, &self.params())?;
+        require!(
+            self.repayment_rate > 0 && self.repayment_rate <= MAX_ANNUAL_RATE,
+            PencilError::InvalidRepaymentRate
+        );
+        require!(
+            self.senior_fixed_rate > 0 && self.senior_fixed_rate <= MAX_ANNUAL_RATE,
+            PencilError::InvalidSeniorFixedRate
+        );
+        require!(
+            (MIN_REPAYMENT_PERIOD..=MAX_REPAYMENT_PERIOD).contains(&self.repayment_period),
+            PencilError::InvalidRepaymentPeriod
+        );
+        require!(
+            self.repayment_count > 0 && self.repayment_count <= MAX_REPAYMENT_COUNT,
+            PencilError::InvalidRepaymentCount
+        );
+        require!(self.total_amount > 0, PencilError::InvalidFundingParams);
+        require!(
+            self.min_amount > 0 && self.min_amount <= self.total_amount,
+            PencilError::InvalidFundingParams
+        );
+        require!(
+            self.funding_start_time > 0 && self.funding_end_time > self.funding_start_time,
+            PencilError::InvalidTimeParameters
+        );
+        require!(
+            self.funding_end_time - self.funding_start_time >= MIN_FUNDING_PERIOD
+                && self.funding_end_time - self.funding_start_time <= MAX_FUNDING_PERIOD,
+            PencilError::InvalidTimeParameters
+        );
+        Ok(())
+    }
+}
+
 #[derive(Accounts)]
 #[instruction(name: String)]
 pub struct CreateAssetPool<'info> {
@@ -34,20 +159,30 @@ pub struct CreateAssetPool<'info> {
     )]
     pub asset_pool: Account<'info, AssetPool>,
 
+    /// 创建者须为已注册且启用的发起方
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, payer.key().as_ref()],
+        bump,
+        constraint = originator.active @ PencilError::OriginatorNotActive
+    )]
+    pub originator: Account<'info, Originator>,
+
     /// 资产代币地址
     pub asset_address: Account<'info, Mint>,
 
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_asset_pool(
     ctx: Context<CreateAssetPool>,
     name: String,
-    platform_fee: u16,
-    senior_early_before_exit_fee: u16,
-    senior_early_after_exit_fee: u16,
-    junior_early_before_exit_fee: u16,
-    min_junior_ratio: u16,
+    platform_fee: Option<u16>,
+    senior_early_before_exit_fee: Option<u16>,
+    senior_early_after_exit_fee: Option<u16>,
+    junior_early_before_exit_fee: Option<u16>,
+    min_junior_ratio: Option<u16>,
     repayment_rate: u16,
     senior_fixed_rate: u16,
     repayment_period: u64,
@@ -69,89 +204,79 @@ pub fn create_asset_pool(
         ctx.accounts.asset_whitelist.assets.contains(&asset_address),
         PencilError::AssetNotSupported
     );
+    require!(
+        ctx.accounts.originator.allowed_assets.contains(&asset_address),
+        PencilError::AssetNotSupported
+    );
 
     // 验证参数
     require!(
         !name.is_empty() && name.len() <= 64,
         PencilError::InvalidStringLength
     );
-    require!(
-        platform_fee <= MAX_PLATFORM_FEE,
-        PencilError::InvalidPlatformFee
-    );
-    require!(
-        senior_early_before_exit_fee <= MAX_EARLY_EXIT_FEE,
-        PencilError::InvalidEarlyExitFee
-    );
-    require!(
-        senior_early_after_exit_fee <= MAX_EARLY_EXIT_FEE,
-        PencilError::InvalidEarlyExitFee
-    );
-    require!(
-        junior_early_before_exit_fee <= MAX_EARLY_EXIT_FEE,
-        PencilError::InvalidEarlyExitFee
-    );
-    require!(
-        min_junior_ratio >= MIN_JUNIOR_RATIO && min_junior_ratio <= MAX_JUNIOR_RATIO,
-        PencilError::InvalidMinJuniorRatio
-    );
-    require!(
-        repayment_rate > 0 && repayment_rate <= MAX_ANNUAL_RATE,
-        PencilError::InvalidRepaymentRate
-    );
-    require!(
-        senior_fixed_rate > 0 && senior_fixed_rate <= MAX_ANNUAL_RATE,
-        PencilError::InvalidSeniorFixedRate
-    );
-    require!(
-        repayment_period >= MIN_REPAYMENT_PERIOD && repayment_period <= MAX_REPAYMENT_PERIOD,
-        PencilError::InvalidRepaymentPeriod
-    );
-    require!(
-        repayment_count > 0 && repayment_count <= MAX_REPAYMENT_COUNT,
-        PencilError::InvalidRepaymentCount
-    );
-    require!(total_amount > 0, PencilError::InvalidFundingParams);
-    require!(
-        min_amount > 0 && min_amount <= total_amount,
-        PencilError::InvalidFundingParams
-    );
-    require!(
-        funding_start_time > 0 && funding_end_time > funding_start_time,
-        PencilError::InvalidTimeParameters
-    );
-    require!(
-        funding_end_time - funding_start_time >= MIN_FUNDING_PERIOD
-            && funding_end_time - funding_start_time <= MAX_FUNDING_PERIOD,
-        PencilError::InvalidTimeParameters
-    );
+    let terms = AssetPoolTerms {
+        platform_fee,
+        senior_early_before_exit_fee,
+        senior_early_after_exit_fee,
+        junior_early_before_exit_fee,
+        min_junior_ratio,
+        repayment_rate,
+        senior_fixed_rate,
+        repayment_period,
+        repayment_count,
+        total_amount,
+        min_amount,
+        funding_start_time,
+        funding_end_time,
+    };
+    terms.validate(&ctx.accounts.system_config)?;
 
     let asset_pool = &mut ctx.accounts.asset_pool;
     asset_pool.name = name.as_bytes().to_vec();
     asset_pool.status = asset_pool_status::CREATED;
     asset_pool.asset_address = ctx.accounts.asset_address.key();
     asset_pool.system_config = ctx.accounts.system_config.key();
-    asset_pool.platform_fee = platform_fee;
-    asset_pool.senior_early_before_exit_fee = senior_early_before_exit_fee;
-    asset_pool.senior_early_after_exit_fee = senior_early_after_exit_fee;
-    asset_pool.junior_early_before_exit_fee = junior_early_before_exit_fee;
-    asset_pool.min_junior_ratio = min_junior_ratio;
-    asset_pool.repayment_rate = repayment_rate;
-    asset_pool.senior_fixed_rate = senior_fixed_rate;
-    asset_pool.repayment_period = repayment_period;
-    asset_pool.repayment_count = repayment_count;
-    asset_pool.total_amount = total_amount;
-    asset_pool.min_amount = min_amount;
-    asset_pool.funding_start_time = funding_start_time;
-    asset_pool.funding_end_time = funding_end_time;
+    terms.apply(asset_pool, &ctx.accounts.system_config)?;
     asset_pool.creator = ctx.accounts.payer.key();
     asset_pool.created_at = Clock::get()?.unix_timestamp;
+    asset_pool.repayment_authority = ctx.accounts.payer.key();
+
+    // 募资目标计入发起方未偿本金
+    let asset_pool_key = asset_pool.key();
+    let created_at = asset_pool.created_at;
+    let originator = &mut ctx.accounts.originator;
+    originator.pool_count = originator
+        .pool_count
+        .checked_add(1)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    set_pool_exposure(
+        originator,
+        asset_pool_key,
+        asset_pool,
+        total_amount,
+        created_at,
+    )?;
 
     let name_str = String::from_utf8_lossy(&asset_pool.name);
     msg!("Asset pool created: {}", name_str);
     msg!("Total amount: {}", total_amount);
     msg!("Min amount: {}", min_amount);
 
+    emit!(crate::AssetPoolCreated {
+        asset_pool: asset_pool.key(),
+        creator: asset_pool.creator,
+        asset_mint: asset_pool.asset_address,
+        name,
+        total_amount,
+        min_amount,
+        min_junior_ratio: asset_pool.min_junior_ratio,
+        inherited_params: asset_pool.inherited_params,
+        repayment_count,
+        funding_start_time,
+        funding_end_time,
+        timestamp: asset_pool.created_at,
+    });
+
     Ok(())
 }
 
@@ -190,12 +315,448 @@ pub fn approve_asset_pool(
 
     asset_pool.status = asset_pool_status::APPROVED;
 
+    emit!(crate::PoolApproved {
+        asset_pool: asset_pool.key(),
+        admin: ctx.accounts.admin.key(),
+        status: asset_pool.status,
+        total_amount: asset_pool.total_amount,
+        funding_start_time: asset_pool.funding_start_time,
+        funding_end_time: asset_pool.funding_end_time,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     let name_str = String::from_utf8_lossy(&asset_pool.name);
     msg!("Asset pool approved: {}", name_str);
 
     Ok(())
 }
 
+#[derive(Accounts)]
+pub struct AmendAssetPool<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.creator == creator.key() @ PencilError::Unauthorized,
+        constraint = asset_pool.status == asset_pool_status::CREATED @ PencilError::InvalidAssetPoolStatus
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    /// CHECK: 发起方 PDA，用于调整敞口；发起方登记前创建的资产池，其创建者可能没有发起方账户
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: UncheckedAccount<'info>,
+}
+
+/// 审批前由创建者修订资产池条款
+pub fn amend_asset_pool(ctx: Context<AmendAssetPool>, terms: AssetPoolTerms) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let clock = Clock::get()?;
+
+    let (economic_terms_changed, withdraw_deadline) = apply_amendment(
+        asset_pool,
+        &terms,
+        &ctx.accounts.system_config,
+        clock.unix_timestamp,
+    )?;
+    let asset_pool_key = asset_pool.key();
+    set_originator_account_exposure(
+        &ctx.accounts.originator,
+        asset_pool_key,
+        asset_pool,
+        terms.total_amount,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(crate::AssetPoolAmended {
+        asset_pool: asset_pool.key(),
+        creator: asset_pool.creator,
+        admin: None,
+        status: asset_pool.status,
+        terms,
+        economic_terms_changed,
+        withdraw_deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    let name_str = String::from_utf8_lossy(&asset_pool.name);
+    msg!("Asset pool amended: {}", name_str);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AmendApprovedAssetPool<'info> {
+    pub creator: Signer<'info>,
+
+    /// 审批后的修订需超级管理员共同签名
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused,
+        constraint = system_config.super_admin == admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.creator == creator.key() @ PencilError::Unauthorized,
+        constraint = asset_pool.status == asset_pool_status::APPROVED @ PencilError::InvalidAssetPoolStatus
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    /// CHECK: 发起方 PDA，用于调整敞口；发起方登记前创建的资产池，其创建者可能没有发起方账户
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: UncheckedAccount<'info>,
+}
+
+/// 审批后经超级管理员同意修订资产池条款（如延长募资期、提高募资总额）
+pub fn amend_approved_asset_pool(
+    ctx: Context<AmendApprovedAssetPool>,
+    terms: AssetPoolTerms,
+) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let clock = Clock::get()?;
+
+    // 募资结束后不能再修订
+    require!(
+        clock.unix_timestamp <= asset_pool.funding_end_time,
+        PencilError::AmendmentNotAllowed
+    );
+    // 募资已开始时不能修改开始时间
+    require!(
+        clock.unix_timestamp < asset_pool.funding_start_time
+            || terms.funding_start_time == asset_pool.funding_start_time,
+        PencilError::AmendmentNotAllowed
+    );
+    // 募资结束时间只能延后
+    require!(
+        terms.funding_end_time >= asset_pool.funding_end_time,
+        PencilError::AmendmentNotAllowed
+    );
+    // 募资总额不能低于已认购金额
+    let subscribed = asset_pool
+        .senior_amount
+        .checked_add(asset_pool.junior_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    require!(
+        terms.total_amount >= subscribed,
+        PencilError::AmendmentNotAllowed
+    );
+
+    let (economic_terms_changed, withdraw_deadline) = apply_amendment(
+        asset_pool,
+        &terms,
+        &ctx.accounts.system_config,
+        clock.unix_timestamp,
+    )?;
+    let asset_pool_key = asset_pool.key();
+    set_originator_account_exposure(
+        &ctx.accounts.originator,
+        asset_pool_key,
+        asset_pool,
+        terms.total_amount,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(crate::AssetPoolAmended {
+        asset_pool: asset_pool.key(),
+        creator: asset_pool.creator,
+        admin: Some(ctx.accounts.admin.key()),
+        status: asset_pool.status,
+        terms,
+        economic_terms_changed,
+        withdraw_deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    let name_str = String::from_utf8_lossy(&asset_pool.name);
+    msg!("Approved asset pool amended: {}", name_str);
+
+    Ok(())
+}
+
+/// 校验并写入新条款，返回 (经济条款是否变化, 免手续费撤资截止时间)
+///
+/// 已有认购时修改经济条款，必须给投资者留出免手续费撤资窗口，
+/// 且募资期需覆盖整个窗口
+fn apply_amendment(
+    asset_pool: &mut AssetPool,
+    terms: &AssetPoolTerms,
+    system_config: &SystemConfig,
+    now: i64,
+) -> Result<(bool, i64)> {
+    terms.validate(system_config)?;
+
+    // 继承的参数按当前系统默认值重新解析，生效值变化同样属于经济条款变化
+    let previous_terms = AssetPoolTerms::of(asset_pool);
+    let previous_values = pool_params::values_of(asset_pool);
+    terms.apply(asset_pool, system_config)?;
+    let economic_terms_changed = terms.economic_terms_differ(&previous_terms)
+        || pool_params::values_of(asset_pool) != previous_values;
+
+    let has_subscriptions = asset_pool.senior_amount > 0 || asset_pool.junior_amount > 0;
+    if economic_terms_changed && has_subscriptions {
+        open_amendment_withdraw_window(asset_pool, terms.funding_end_time, now)?;
+    }
+
+    Ok((economic_terms_changed, asset_pool.amendment_withdraw_deadline))
+}
+
+/// 开放免手续费撤资窗口，募资期需覆盖整个窗口
+pub(crate) fn open_amendment_withdraw_window(
+    asset_pool: &mut AssetPool,
+    funding_end_time: i64,
+    now: i64,
+) -> Result<()> {
+    let deadline = now
+        .checked_add(AMENDMENT_WITHDRAW_WINDOW)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    require!(
+        funding_end_time >= deadline,
+        PencilError::AmendmentWithdrawWindowTooShort
+    );
+    asset_pool.amendment_withdraw_deadline = deadline;
+    msg!("Investor withdraw window until: {}", deadline);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RejectAssetPool<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.super_admin == admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.status == asset_pool_status::CREATED
+            || asset_pool.status == asset_pool_status::APPROVED @ PencilError::InvalidAssetPoolStatus,
+        constraint = !asset_pool.related_accounts_initialized @ PencilError::RelatedAccountsAlreadyInitialized,
+        constraint = asset_pool.senior_amount == 0
+            && asset_pool.junior_amount == 0 @ PencilError::PoolHasSubscriptions
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    /// CHECK: 资产池创建者，接收退还的租金
+    #[account(mut, address = asset_pool.creator @ PencilError::InvalidAccount)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: 发起方 PDA，用于释放敞口；发起方登记前创建的资产池，其创建者可能没有发起方账户
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: UncheckedAccount<'info>,
+}
+
+/// 管理员驳回尚无认购的资产池，关闭账户并将租金退还创建者。
+/// 关联账户已初始化时拒绝：其中的 Mint 无法关闭，关闭 AssetPool 会遗留无法回收的账户，
+/// 且同名资产池无法重建；此类资产池在募资期结束后通过 cancel_asset_pool 取消
+pub fn reject_asset_pool(ctx: Context<RejectAssetPool>, reason_code: u16) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    asset_pool.status = asset_pool_status::REJECTED;
+
+    // 释放计入发起方的敞口
+    let asset_pool_key = asset_pool.key();
+    set_originator_account_exposure(
+        &ctx.accounts.originator,
+        asset_pool_key,
+        asset_pool,
+        0,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    emit!(crate::AssetPoolRejected {
+        asset_pool: asset_pool.key(),
+        admin: ctx.accounts.admin.key(),
+        creator: asset_pool.creator,
+        status: asset_pool.status,
+        reason_code,
+        rent_refunded: asset_pool.to_account_info().lamports(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    let name_str = String::from_utf8_lossy(&asset_pool.name);
+    msg!("Asset pool rejected: {} (reason {})", name_str, reason_code);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawAssetPool<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.creator == creator.key() @ PencilError::Unauthorized,
+        constraint = asset_pool.status == asset_pool_status::CREATED
+            || asset_pool.status == asset_pool_status::APPROVED @ PencilError::InvalidAssetPoolStatus,
+        constraint = !asset_pool.related_accounts_initialized @ PencilError::RelatedAccountsAlreadyInitialized,
+        constraint = asset_pool.senior_amount == 0
+            && asset_pool.junior_amount == 0 @ PencilError::PoolHasSubscriptions
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    /// CHECK: 发起方 PDA，用于释放敞口；发起方登记前创建的资产池，其创建者可能没有发起方账户
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: UncheckedAccount<'info>,
+}
+
+/// 创建者撤回尚无认购的资产池，关闭账户并退还租金；关联账户已初始化时拒绝（同 reject_asset_pool）
+pub fn withdraw_asset_pool(ctx: Context<WithdrawAssetPool>) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    asset_pool.status = asset_pool_status::WITHDRAWN;
+
+    // 释放计入发起方的敞口
+    let asset_pool_key = asset_pool.key();
+    set_originator_account_exposure(
+        &ctx.accounts.originator,
+        asset_pool_key,
+        asset_pool,
+        0,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    emit!(crate::AssetPoolWithdrawn {
+        asset_pool: asset_pool.key(),
+        creator: asset_pool.creator,
+        status: asset_pool.status,
+        rent_refunded: asset_pool.to_account_info().lamports(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    let name_str = String::from_utf8_lossy(&asset_pool.name);
+    msg!("Asset pool withdrawn by creator: {}", name_str);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRepaymentAuthority<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.creator == creator.key() @ PencilError::Unauthorized
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+}
+
+/// 创建者变更资产池的还款义务人
+pub fn set_repayment_authority(
+    ctx: Context<SetRepaymentAuthority>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    require!(
+        new_authority != Pubkey::default(),
+        PencilError::InvalidAccount
+    );
+
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let old_authority = asset_pool.repayment_authority;
+    asset_pool.repayment_authority = new_authority;
+
+    emit!(crate::RepaymentAuthorityUpdated {
+        asset_pool: asset_pool.key(),
+        creator: asset_pool.creator,
+        old_authority,
+        new_authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Repayment authority updated: {}", new_authority);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPrepaymentTerms<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.creator == creator.key() @ PencilError::Unauthorized,
+        constraint = asset_pool.status == asset_pool_status::CREATED @ PencilError::InvalidAssetPoolStatus
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+}
+
+/// 审批前由创建者设置提前还款计息方式与违约金费率
+pub fn set_prepayment_terms(
+    ctx: Context<SetPrepaymentTerms>,
+    policy: u8,
+    penalty_rate: u16,
+) -> Result<()> {
+    require!(
+        policy == prepayment_policy::ACCRUED_INTEREST || policy == prepayment_policy::FULL_INTEREST,
+        PencilError::InvalidPrepaymentTerms
+    );
+    require!(
+        penalty_rate <= MAX_PREPAYMENT_PENALTY,
+        PencilError::InvalidPrepaymentTerms
+    );
+
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    asset_pool.prepayment_policy = policy;
+    asset_pool.prepayment_penalty_rate = penalty_rate;
+
+    emit!(crate::PrepaymentTermsUpdated {
+        asset_pool: asset_pool.key(),
+        policy,
+        penalty_rate,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Prepayment terms updated: policy {}, penalty {}",
+        policy,
+        penalty_rate
+    );
+
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct InitializeRelatedAccounts<'info> {
     #[account(mut)]
@@ -288,6 +849,39 @@ pub struct InitializeRelatedAccounts<'info> {
     )]
     pub asset_pool_vault: Box<Account<'info, TokenAccount>>,
 
+    /// 优先池 Token Vault PDA（Senior 本金与 Senior 还款）
+    #[account(
+        init,
+        payer = payer,
+        token::mint = asset_mint,
+        token::authority = asset_pool,
+        seeds = [seeds::SENIOR_POOL_VAULT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub senior_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    /// 首损池 Token Vault PDA（Junior 本金）
+    #[account(
+        init,
+        payer = payer,
+        token::mint = asset_mint,
+        token::authority = asset_pool,
+        seeds = [seeds::FIRST_LOSS_POOL_VAULT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub first_loss_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    /// 利息池 Token Vault PDA（未分配的 Junior 利息）
+    #[account(
+        init,
+        payer = payer,
+        token::mint = asset_mint,
+        token::authority = asset_pool,
+        seeds = [seeds::JUNIOR_INTEREST_POOL_VAULT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub junior_interest_pool_vault: Box<Account<'info, TokenAccount>>,
+
     /// 金库账户 (从 SystemConfig 读取)
     /// CHECK: This is the treasury account from SystemConfig
     #[account(
@@ -329,18 +923,21 @@ pub fn initialize_related_accounts(ctx: Context<InitializeRelatedAccounts>) -> R
     senior_pool.grow_token = ctx.accounts.grow_token_mint.key();
     senior_pool.total_deposits = 0;
     senior_pool.repaid_amount = 0;
+    senior_pool.vault = ctx.accounts.senior_pool_vault.key();
 
     // 初始化 FirstLossPool 账户
     first_loss_pool.asset_pool = asset_pool.key();
     first_loss_pool.junior_nft = ctx.accounts.junior_nft_mint.key();
     first_loss_pool.total_deposits = 0;
     first_loss_pool.repaid_amount = 0;
+    first_loss_pool.vault = ctx.accounts.first_loss_pool_vault.key();
 
     // 初始化 JuniorInterestPool 账户
     junior_interest_pool.asset_pool = asset_pool.key();
     junior_interest_pool.junior_nft = ctx.accounts.junior_nft_mint.key();
     junior_interest_pool.total_interest = 0;
     junior_interest_pool.distributed_interest = 0;
+    junior_interest_pool.vault = ctx.accounts.junior_interest_pool_vault.key();
 
     // 将所有账户地址写入 AssetPool
     asset_pool.funding = ctx.accounts.funding.key();
@@ -365,6 +962,9 @@ pub fn initialize_related_accounts(ctx: Context<InitializeRelatedAccounts>) -> R
         junior_interest_pool: ctx.accounts.junior_interest_pool.key(),
         grow_token: ctx.accounts.grow_token_mint.key(),
         asset_pool_vault: ctx.accounts.asset_pool_vault.key(),
+        senior_pool_vault: ctx.accounts.senior_pool_vault.key(),
+        first_loss_pool_vault: ctx.accounts.first_loss_pool_vault.key(),
+        junior_interest_pool_vault: ctx.accounts.junior_interest_pool_vault.key(),
         treasury_ata: ctx.accounts.treasury_ata.key(),
         timestamp: clock.unix_timestamp,
     });
@@ -384,6 +984,15 @@ pub fn initialize_related_accounts(ctx: Context<InitializeRelatedAccounts>) -> R
     msg!("GROW Token: {}", ctx.accounts.grow_token_mint.key());
     msg!("Junior NFT: {}", ctx.accounts.junior_nft_mint.key());
     msg!("Asset Pool Vault: {}", ctx.accounts.asset_pool_vault.key());
+    msg!("Senior Pool Vault: {}", ctx.accounts.senior_pool_vault.key());
+    msg!(
+        "First Loss Pool Vault: {}",
+        ctx.accounts.first_loss_pool_vault.key()
+    );
+    msg!(
+        "Junior Interest Pool Vault: {}",
+        ctx.accounts.junior_interest_pool_vault.key()
+    );
     msg!("Treasury ATA: {}", ctx.accounts.treasury_ata.key());
 
     Ok(())
@@ -0,0 +1,527 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::state::{AssetPool, SeniorCheckpoint, SeniorPool, SystemConfig};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+
+/// 每期 Senior 还款到账时累加每个 GROW 的分配指数
+/// GROW 总量为 0 时不累加（还款留在优先池 Vault，完成后按比例提取）
+pub fn accrue_senior_distribution(
+    senior_pool: &mut SeniorPool,
+    grow_supply: u64,
+    amount: u64,
+) -> Result<()> {
+    if grow_supply == 0 || amount == 0 {
+        return Ok(());
+    }
+
+    let delta = (amount as u128)
+        .checked_mul(DISTRIBUTION_INDEX_SCALE)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_div(grow_supply as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    senior_pool.distribution_index = senior_pool
+        .distribution_index
+        .checked_add(delta)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+/// 按指数差计算锁定 GROW 的待领取分配
+fn calculate_pending_distribution(
+    locked_amount: u64,
+    current_index: u128,
+    checkpoint_index: u128,
+) -> Result<u64> {
+    let pending = (locked_amount as u128)
+        .checked_mul(current_index.saturating_sub(checkpoint_index))
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_div(DISTRIBUTION_INDEX_SCALE)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    u64::try_from(pending).map_err(|_| error!(PencilError::ArithmeticOverflow))
+}
+
+/// 解锁 GROW 对应的已领取分配（按锁定比例，向上取整）
+/// 解锁后的 GROW 赎回时按全额计算，这部分需先退回优先池
+fn calculate_unlocked_claim(
+    claimed_amount: u64,
+    unlock_amount: u64,
+    locked_amount: u64,
+) -> Result<u64> {
+    let claim = (claimed_amount as u128)
+        .checked_mul(unlock_amount as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .div_ceil(locked_amount as u128);
+    u64::try_from(claim).map_err(|_| error!(PencilError::ArithmeticOverflow))
+}
+
+// SeniorDistributionClaimed event is defined in lib.rs
+
+#[derive(Accounts)]
+pub struct ClaimSeniorDistribution<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub senior_pool: Box<Account<'info, SeniorPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::GROW_TOKEN_MINT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub grow_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + std::mem::size_of::<SeniorCheckpoint>(),
+        seeds = [seeds::SENIOR_CHECKPOINT, asset_pool.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub checkpoint: Box<Account<'info, SeniorCheckpoint>>,
+
+    /// 锁定 GROW 的托管账户（资产池为权限人）
+    #[account(
+        init_if_needed,
+        payer = holder,
+        seeds = [seeds::GROW_ESCROW, asset_pool.key().as_ref()],
+        bump,
+        token::mint = grow_token_mint,
+        token::authority = asset_pool
+    )]
+    pub grow_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = grow_token_mint,
+        token::authority = holder
+    )]
+    pub holder_grow_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = asset_mint,
+        token::authority = holder
+    )]
+    pub holder_asset_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = senior_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub senior_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = asset_pool.asset_address @ PencilError::InvalidAccount)]
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 领取 Senior 分配（免手续费）
+/// 还款期间：按累计指数领取锁定 GROW 的应得还款，可同时追加锁定 GROW
+/// 资产池完成后：按比例赎回锁定的 GROW（扣除已领取部分）并重置检查点
+/// 锁定的 GROW 托管在资产池名下，完成前需经 unlock_senior_distribution 解锁后才能转让或早退
+pub fn claim_senior_distribution(
+    ctx: Context<ClaimSeniorDistribution>,
+    lock_amount: u64,
+) -> Result<()> {
+    let asset_pool = &ctx.accounts.asset_pool;
+    let clock = Clock::get()?;
+
+    require!(
+        asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING
+            || asset_pool.status == asset_pool_status::COMPLETED,
+        PencilError::InvalidAssetPoolStatus
+    );
+    require!(
+        asset_pool.related_accounts_initialized,
+        PencilError::RelatedAccountsNotInitialized
+    );
+
+    let asset_pool_key = asset_pool.key();
+    let holder_key = ctx.accounts.holder.key();
+    let current_index = ctx.accounts.senior_pool.distribution_index;
+
+    // 新检查点从当前指数开始计算
+    let checkpoint = &mut ctx.accounts.checkpoint;
+    if checkpoint.asset_pool == Pubkey::default() {
+        checkpoint.asset_pool = asset_pool_key;
+        checkpoint.holder = holder_key;
+        checkpoint.locked_amount = 0;
+        checkpoint.claimed_amount = 0;
+        checkpoint.index = current_index;
+    }
+
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[ctx.bumps.asset_pool],
+    ];
+    let signer_seeds = &[&asset_pool_seeds[..]];
+
+    let completed = asset_pool.status == asset_pool_status::COMPLETED;
+    let locked_amount = checkpoint.locked_amount;
+    let mut grow_redeemed = 0u64;
+
+    let payout = if completed {
+        // 资产池完成：锁定 GROW 按比例赎回，已领取部分从应得金额中扣除
+        require!(lock_amount == 0, PencilError::InvalidAssetPoolStatus);
+        require!(locked_amount > 0, PencilError::NoSeniorDistribution);
+
+        let grow_total_supply = ctx.accounts.grow_token_mint.supply;
+        require!(
+            grow_total_supply > 0,
+            PencilError::InvalidPrincipalCalculation
+        );
+        let senior_pool = &ctx.accounts.senior_pool;
+        let redeemable = (ctx.accounts.senior_pool_vault.amount as u128)
+            .checked_add(senior_pool.distributed_amount as u128)
+            .ok_or(PencilError::ArithmeticOverflow)?
+            .checked_mul(locked_amount as u128)
+            .ok_or(PencilError::ArithmeticOverflow)?
+            .checked_div(grow_total_supply as u128)
+            .ok_or(PencilError::ArithmeticOverflow)? as u64;
+        let claimed_amount = checkpoint.claimed_amount;
+        let payout = redeemable.saturating_sub(claimed_amount);
+
+        let burn_cpi_accounts = Burn {
+            mint: ctx.accounts.grow_token_mint.to_account_info(),
+            from: ctx.accounts.grow_escrow.to_account_info(),
+            authority: ctx.accounts.asset_pool.to_account_info(),
+        };
+        let burn_cpi_program = ctx.accounts.token_program.to_account_info();
+        let burn_cpi_ctx =
+            CpiContext::new_with_signer(burn_cpi_program, burn_cpi_accounts, signer_seeds);
+        token::burn(burn_cpi_ctx, locked_amount)?;
+
+        // GROW 与 Senior 本金 1:1，超出本金的部分来自已还款金额
+        let senior_pool = &mut ctx.accounts.senior_pool;
+        let principal_part = locked_amount.min(senior_pool.total_deposits);
+        let repaid_part = payout
+            .checked_add(claimed_amount)
+            .ok_or(PencilError::ArithmeticOverflow)?
+            .saturating_sub(principal_part)
+            .min(senior_pool.repaid_amount);
+        senior_pool.total_deposits = senior_pool
+            .total_deposits
+            .checked_sub(principal_part)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        senior_pool.repaid_amount = senior_pool
+            .repaid_amount
+            .checked_sub(repaid_part)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        senior_pool.distributed_amount = senior_pool
+            .distributed_amount
+            .saturating_sub(claimed_amount);
+
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        checkpoint.locked_amount = 0;
+        checkpoint.claimed_amount = 0;
+        checkpoint.index = current_index;
+        grow_redeemed = locked_amount;
+
+        payout
+    } else {
+        // 还款期间：先结算已锁定 GROW 的待领取分配，再追加锁定
+        let pending =
+            calculate_pending_distribution(locked_amount, current_index, checkpoint.index)?;
+        require!(
+            pending > 0 || lock_amount > 0,
+            PencilError::NoSeniorDistribution
+        );
+
+        checkpoint.claimed_amount = checkpoint
+            .claimed_amount
+            .checked_add(pending)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        checkpoint.index = current_index;
+
+        if lock_amount > 0 {
+            require!(
+                ctx.accounts.holder_grow_account.amount >= lock_amount,
+                PencilError::InsufficientBalance
+            );
+            let lock_cpi_accounts = Transfer {
+                from: ctx.accounts.holder_grow_account.to_account_info(),
+                to: ctx.accounts.grow_escrow.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            };
+            let lock_cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(
+                CpiContext::new(lock_cpi_program, lock_cpi_accounts),
+                lock_amount,
+            )?;
+
+            let checkpoint = &mut ctx.accounts.checkpoint;
+            checkpoint.locked_amount = checkpoint
+                .locked_amount
+                .checked_add(lock_amount)
+                .ok_or(PencilError::ArithmeticOverflow)?;
+        }
+
+        let senior_pool = &mut ctx.accounts.senior_pool;
+        senior_pool.distributed_amount = senior_pool
+            .distributed_amount
+            .checked_add(pending)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+
+        pending
+    };
+
+    if payout > 0 {
+        let transfer_cpi_accounts = Transfer {
+            from: ctx.accounts.senior_pool_vault.to_account_info(),
+            to: ctx.accounts.holder_asset_account.to_account_info(),
+            authority: ctx.accounts.asset_pool.to_account_info(),
+        };
+        let transfer_cpi_program = ctx.accounts.token_program.to_account_info();
+        let transfer_cpi_ctx =
+            CpiContext::new_with_signer(transfer_cpi_program, transfer_cpi_accounts, signer_seeds);
+        token::transfer(transfer_cpi_ctx, payout)?;
+
+        // 领取的 Senior 还款不再可用于撮合早退
+        let senior_pool = &mut ctx.accounts.senior_pool;
+        senior_pool.exit_liquidity = senior_pool.exit_liquidity.saturating_sub(payout);
+    }
+
+    emit!(crate::SeniorDistributionClaimed {
+        asset_pool: asset_pool_key,
+        holder: holder_key,
+        amount: payout,
+        locked_amount: ctx.accounts.checkpoint.locked_amount,
+        grow_redeemed,
+        distribution_index: current_index,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Senior 分配领取完成 - 持有人: {}, 金额: {}, 锁定 GROW: {}, 赎回 GROW: {}",
+        holder_key,
+        payout,
+        ctx.accounts.checkpoint.locked_amount,
+        grow_redeemed
+    );
+
+    Ok(())
+}
+
+// SeniorDistributionUnlocked event is defined in lib.rs
+
+#[derive(Accounts)]
+pub struct UnlockSeniorDistribution<'info> {
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused,
+        constraint = asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING @ PencilError::InvalidAssetPoolStatus
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub senior_pool: Box<Account<'info, SeniorPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_CHECKPOINT, asset_pool.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub checkpoint: Box<Account<'info, SeniorCheckpoint>>,
+
+    /// 锁定 GROW 的托管账户（资产池为权限人）
+    #[account(
+        mut,
+        seeds = [seeds::GROW_ESCROW, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub grow_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = grow_escrow.mint,
+        token::authority = holder
+    )]
+    pub holder_grow_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = asset_pool.asset_address,
+        token::authority = holder
+    )]
+    pub holder_asset_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = senior_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub senior_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 资产池完成前解锁部分或全部 GROW（免手续费）
+/// 先按当前指数结算待领取分配，再扣回解锁 GROW 按比例对应的已领取分配：
+/// 结算额大于扣回额时向持有人支付差额，否则由持有人补足差额；随后从托管账户退回 GROW
+pub fn unlock_senior_distribution(
+    ctx: Context<UnlockSeniorDistribution>,
+    amount: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let asset_pool = &ctx.accounts.asset_pool;
+    let asset_pool_key = asset_pool.key();
+    let holder_key = ctx.accounts.holder.key();
+    let current_index = ctx.accounts.senior_pool.distribution_index;
+
+    let checkpoint = &mut ctx.accounts.checkpoint;
+    let locked_amount = checkpoint.locked_amount;
+    require!(
+        amount > 0 && amount <= locked_amount,
+        PencilError::InsufficientBalance
+    );
+
+    // 结算已锁定 GROW 的待领取分配，并扣回解锁部分对应的已领取分配
+    let pending = calculate_pending_distribution(locked_amount, current_index, checkpoint.index)?;
+    let claimed_amount = checkpoint
+        .claimed_amount
+        .checked_add(pending)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let returned = calculate_unlocked_claim(claimed_amount, amount, locked_amount)?;
+    checkpoint.claimed_amount = claimed_amount
+        .checked_sub(returned)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    checkpoint.locked_amount = locked_amount
+        .checked_sub(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    checkpoint.index = current_index;
+
+    let senior_pool = &mut ctx.accounts.senior_pool;
+    senior_pool.distributed_amount = senior_pool
+        .distributed_amount
+        .checked_add(pending)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_sub(returned)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[ctx.bumps.asset_pool],
+    ];
+    let signer_seeds = &[&asset_pool_seeds[..]];
+
+    // 净额结算：领取的分配不再可用于撮合早退，退回的部分重新计入
+    let payout = pending.saturating_sub(returned);
+    let repaid = returned.saturating_sub(pending);
+    if payout > 0 {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.senior_pool_vault.to_account_info(),
+            to: ctx.accounts.holder_asset_account.to_account_info(),
+            authority: ctx.accounts.asset_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds),
+            payout,
+        )?;
+        senior_pool.exit_liquidity = senior_pool.exit_liquidity.saturating_sub(payout);
+    } else if repaid > 0 {
+        require!(
+            ctx.accounts.holder_asset_account.amount >= repaid,
+            PencilError::InsufficientBalance
+        );
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.holder_asset_account.to_account_info(),
+            to: ctx.accounts.senior_pool_vault.to_account_info(),
+            authority: ctx.accounts.holder.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), repaid)?;
+        senior_pool.exit_liquidity = senior_pool
+            .exit_liquidity
+            .checked_add(repaid)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+
+    // 退回 GROW
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.grow_escrow.to_account_info(),
+        to: ctx.accounts.holder_grow_account.to_account_info(),
+        authority: ctx.accounts.asset_pool.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds),
+        amount,
+    )?;
+
+    emit!(crate::SeniorDistributionUnlocked {
+        asset_pool: asset_pool_key,
+        holder: holder_key,
+        grow_unlocked: amount,
+        payout,
+        repaid,
+        locked_amount: ctx.accounts.checkpoint.locked_amount,
+        distribution_index: current_index,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Senior GROW 解锁完成 - 持有人: {}, 解锁: {}, 支付: {}, 补足: {}",
+        holder_key,
+        amount,
+        payout,
+        repaid
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlocked_claim_rounds_up() {
+        assert_eq!(calculate_unlocked_claim(1_000, 250, 500).unwrap(), 500);
+        assert_eq!(calculate_unlocked_claim(1_001, 250, 500).unwrap(), 501);
+        assert_eq!(calculate_unlocked_claim(1_001, 500, 500).unwrap(), 1_001);
+        assert_eq!(calculate_unlocked_claim(0, 250, 500).unwrap(), 0);
+    }
+}
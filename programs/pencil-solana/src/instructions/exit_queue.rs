@@ -0,0 +1,535 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::instructions::utils::pool_fee_destination;
+use crate::state::{AssetPool, SeniorExitQueue, SeniorExitRequest, SeniorPool, SystemConfig};
+use crate::waterfall::calculate_exit_fill;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount, Transfer};
+
+// SeniorExitRequested / SeniorExitFilled / SeniorExitCancelled events are defined in lib.rs
+
+/// 早退队列中待撮合的 GROW 数量，队列尚未创建时为 0（地址由指令按种子校验）
+pub(crate) fn queued_exit_amount(exit_queue: &AccountInfo) -> Result<u64> {
+    if exit_queue.owner != &crate::ID || exit_queue.data_is_empty() {
+        return Ok(0);
+    }
+    let exit_queue = SeniorExitQueue::try_deserialize(&mut &exit_queue.try_borrow_data()?[..])?;
+    Ok(exit_queue.queued_amount)
+}
+
+#[derive(Accounts)]
+pub struct RequestSeniorExit<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + std::mem::size_of::<SeniorExitQueue>(),
+        seeds = [seeds::SENIOR_EXIT_QUEUE, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub exit_queue: Box<Account<'info, SeniorExitQueue>>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + std::mem::size_of::<SeniorExitRequest>(),
+        seeds = [
+            seeds::SENIOR_EXIT_REQUEST,
+            asset_pool.key().as_ref(),
+            &exit_queue.tail.to_le_bytes()
+        ],
+        bump
+    )]
+    pub exit_request: Box<Account<'info, SeniorExitRequest>>,
+
+    #[account(
+        seeds = [seeds::GROW_TOKEN_MINT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub grow_token_mint: Box<Account<'info, Mint>>,
+
+    /// 排队 GROW 的托管账户（资产池为权限人）
+    #[account(
+        init_if_needed,
+        payer = holder,
+        seeds = [seeds::SENIOR_EXIT_ESCROW, asset_pool.key().as_ref()],
+        bump,
+        token::mint = grow_token_mint,
+        token::authority = asset_pool
+    )]
+    pub exit_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = grow_token_mint,
+        token::authority = holder
+    )]
+    pub holder_grow_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 提交 Senior 早退请求：GROW 托管进队列，随后由 process_senior_exits 按先进先出撮合
+pub fn request_senior_exit(ctx: Context<RequestSeniorExit>, amount: u64) -> Result<()> {
+    require!(amount > 0, PencilError::InvalidEarlyExitAmount);
+
+    let asset_pool = &ctx.accounts.asset_pool;
+    require!(
+        asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING,
+        PencilError::InvalidAssetPoolStatus
+    );
+    require!(
+        asset_pool.related_accounts_initialized,
+        PencilError::RelatedAccountsNotInitialized
+    );
+    require!(
+        ctx.accounts.holder_grow_account.amount >= amount,
+        PencilError::InsufficientBalance
+    );
+
+    let transfer_cpi_accounts = Transfer {
+        from: ctx.accounts.holder_grow_account.to_account_info(),
+        to: ctx.accounts.exit_escrow.to_account_info(),
+        authority: ctx.accounts.holder.to_account_info(),
+    };
+    let transfer_cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(
+        CpiContext::new(transfer_cpi_program, transfer_cpi_accounts),
+        amount,
+    )?;
+
+    let asset_pool_key = asset_pool.key();
+    let clock = Clock::get()?;
+
+    let exit_queue = &mut ctx.accounts.exit_queue;
+    exit_queue.asset_pool = asset_pool_key;
+    let request_id = exit_queue.tail;
+    exit_queue.tail = exit_queue
+        .tail
+        .checked_add(1)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    exit_queue.queued_amount = exit_queue
+        .queued_amount
+        .checked_add(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    let exit_request = &mut ctx.accounts.exit_request;
+    exit_request.asset_pool = asset_pool_key;
+    exit_request.holder = ctx.accounts.holder.key();
+    exit_request.request_id = request_id;
+    exit_request.amount = amount;
+    exit_request.filled_amount = 0;
+    exit_request.paid_amount = 0;
+    exit_request.requested_at = clock.unix_timestamp;
+
+    emit!(crate::SeniorExitRequested {
+        asset_pool: asset_pool_key,
+        holder: ctx.accounts.holder.key(),
+        request_id,
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Senior 早退请求已排队 - 请求 ID: {}, 持有人: {}, GROW: {}",
+        request_id,
+        ctx.accounts.holder.key(),
+        amount
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(request_id: u64)]
+pub struct CancelSeniorExit<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_EXIT_QUEUE, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub exit_queue: Box<Account<'info, SeniorExitQueue>>,
+
+    #[account(
+        mut,
+        close = holder,
+        seeds = [
+            seeds::SENIOR_EXIT_REQUEST,
+            asset_pool.key().as_ref(),
+            &request_id.to_le_bytes()
+        ],
+        bump,
+        has_one = holder @ PencilError::Unauthorized
+    )]
+    pub exit_request: Box<Account<'info, SeniorExitRequest>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_EXIT_ESCROW, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub exit_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = exit_escrow.mint,
+        token::authority = holder
+    )]
+    pub holder_grow_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 撤销早退请求：退还未成交的 GROW 并关闭请求账户（任何状态均可撤销）
+pub fn cancel_senior_exit(ctx: Context<CancelSeniorExit>, request_id: u64) -> Result<()> {
+    let remaining = ctx.accounts.exit_request.amount;
+
+    if remaining > 0 {
+        let asset_pool = &ctx.accounts.asset_pool;
+        let asset_pool_seeds = &[
+            seeds::ASSET_POOL,
+            asset_pool.creator.as_ref(),
+            &asset_pool.name,
+            &[ctx.bumps.asset_pool],
+        ];
+        let signer_seeds = &[&asset_pool_seeds[..]];
+        let transfer_cpi_accounts = Transfer {
+            from: ctx.accounts.exit_escrow.to_account_info(),
+            to: ctx.accounts.holder_grow_account.to_account_info(),
+            authority: ctx.accounts.asset_pool.to_account_info(),
+        };
+        let transfer_cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(transfer_cpi_program, transfer_cpi_accounts, signer_seeds),
+            remaining,
+        )?;
+    }
+
+    let exit_queue = &mut ctx.accounts.exit_queue;
+    exit_queue.queued_amount = exit_queue.queued_amount.saturating_sub(remaining);
+
+    emit!(crate::SeniorExitCancelled {
+        asset_pool: ctx.accounts.asset_pool.key(),
+        holder: ctx.accounts.holder.key(),
+        request_id,
+        returned_amount: remaining,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Senior 早退请求已撤销 - 请求 ID: {}, 退还 GROW: {}",
+        request_id,
+        remaining
+    );
+
+    Ok(())
+}
+
+/// 撮合早退队列（任何人可调用，通常紧随 repay 执行）
+///
+/// remaining_accounts: 从 head 开始按请求 ID 依次传入
+/// [exit_request, holder_asset_account, holder]
+/// 已撤销的请求账户为空，直接跳过
+#[derive(Accounts)]
+pub struct ProcessSeniorExits<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub senior_pool: Box<Account<'info, SeniorPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_EXIT_QUEUE, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub exit_queue: Box<Account<'info, SeniorExitQueue>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::GROW_TOKEN_MINT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub grow_token_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_EXIT_ESCROW, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub exit_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = senior_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub senior_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = pool_fee_destination(&asset_pool, &system_config) @ PencilError::InvalidAccount
+    )]
+    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = asset_pool.asset_address @ PencilError::InvalidAccount)]
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 按先进先出用可用的 Senior 还款撮合早退请求
+/// 成交价为 GROW 净值（与完成后按比例提取一致）扣除早退费用，不动用 FirstLossPool
+pub fn process_senior_exits<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProcessSeniorExits<'info>>,
+) -> Result<()> {
+    let asset_pool = &ctx.accounts.asset_pool;
+    require!(
+        asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING,
+        PencilError::InvalidAssetPoolStatus
+    );
+    require!(
+        ctx.remaining_accounts.len().is_multiple_of(3),
+        PencilError::InvalidAccount
+    );
+
+    let clock = Clock::get()?;
+    let exit_fee_rate = if clock.unix_timestamp < asset_pool.funding_end_time {
+        asset_pool.senior_early_before_exit_fee
+    } else {
+        asset_pool.senior_early_after_exit_fee
+    };
+
+    let asset_pool_key = asset_pool.key();
+    let asset_mint_key = ctx.accounts.asset_mint.key();
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[ctx.bumps.asset_pool],
+    ];
+    let signer_seeds = &[&asset_pool_seeds[..]];
+
+    // 撮合过程中的净值基数与流动性
+    let mut vault_balance = ctx.accounts.senior_pool_vault.amount;
+    let mut grow_supply = ctx.accounts.grow_token_mint.supply;
+    let distributed_amount = ctx.accounts.senior_pool.distributed_amount;
+    let mut liquidity = ctx.accounts.senior_pool.exit_liquidity.min(vault_balance);
+    let mut head = ctx.accounts.exit_queue.head;
+    let tail = ctx.accounts.exit_queue.tail;
+    let mut processed = 0u64;
+
+    for accounts in ctx.remaining_accounts.chunks(3) {
+        if head >= tail {
+            break;
+        }
+        let request_info = &accounts[0];
+        let holder_asset_info = &accounts[1];
+        let holder_info = &accounts[2];
+
+        let (expected_request, _) = Pubkey::find_program_address(
+            &[
+                seeds::SENIOR_EXIT_REQUEST,
+                asset_pool_key.as_ref(),
+                &head.to_le_bytes(),
+            ],
+            ctx.program_id,
+        );
+        require_keys_eq!(
+            request_info.key(),
+            expected_request,
+            PencilError::InvalidExitRequest
+        );
+
+        // 已撤销的请求
+        if request_info.data_is_empty() {
+            head += 1;
+            processed += 1;
+            continue;
+        }
+
+        let mut exit_request = Account::<SeniorExitRequest>::try_from(request_info)?;
+        require_keys_eq!(
+            holder_info.key(),
+            exit_request.holder,
+            PencilError::InvalidExitRequest
+        );
+        let holder_asset_account = Account::<TokenAccount>::try_from(holder_asset_info)?;
+        require!(
+            holder_asset_account.mint == asset_mint_key
+                && holder_asset_account.owner == exit_request.holder,
+            PencilError::InvalidAccount
+        );
+
+        let senior_value = vault_balance
+            .checked_add(distributed_amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        let fill = calculate_exit_fill(
+            exit_request.amount,
+            senior_value,
+            grow_supply,
+            liquidity,
+            exit_fee_rate,
+        )?;
+        if fill.grow_filled == 0 {
+            break;
+        }
+        let net_amount = fill.net()?;
+
+        let burn_cpi_accounts = Burn {
+            mint: ctx.accounts.grow_token_mint.to_account_info(),
+            from: ctx.accounts.exit_escrow.to_account_info(),
+            authority: ctx.accounts.asset_pool.to_account_info(),
+        };
+        let burn_cpi_program = ctx.accounts.token_program.to_account_info();
+        token::burn(
+            CpiContext::new_with_signer(burn_cpi_program, burn_cpi_accounts, signer_seeds),
+            fill.grow_filled,
+        )?;
+
+        if fill.exit_fee > 0 {
+            let fee_cpi_accounts = Transfer {
+                from: ctx.accounts.senior_pool_vault.to_account_info(),
+                to: ctx.accounts.treasury_ata.to_account_info(),
+                authority: ctx.accounts.asset_pool.to_account_info(),
+            };
+            let fee_cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(
+                CpiContext::new_with_signer(fee_cpi_program, fee_cpi_accounts, signer_seeds),
+                fill.exit_fee,
+            )?;
+        }
+
+        if net_amount > 0 {
+            let payout_cpi_accounts = Transfer {
+                from: ctx.accounts.senior_pool_vault.to_account_info(),
+                to: holder_asset_info.clone(),
+                authority: ctx.accounts.asset_pool.to_account_info(),
+            };
+            let payout_cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(
+                CpiContext::new_with_signer(payout_cpi_program, payout_cpi_accounts, signer_seeds),
+                net_amount,
+            )?;
+        }
+
+        // 更新 SeniorPool 账面：GROW 与 Senior 本金 1:1，超出本金的部分来自已还款金额
+        let senior_pool = &mut ctx.accounts.senior_pool;
+        let principal_part = fill.grow_filled.min(senior_pool.total_deposits);
+        let repaid_part = fill
+            .payout
+            .saturating_sub(principal_part)
+            .min(senior_pool.repaid_amount);
+        senior_pool.total_deposits = senior_pool
+            .total_deposits
+            .checked_sub(principal_part)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        senior_pool.repaid_amount = senior_pool
+            .repaid_amount
+            .checked_sub(repaid_part)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        senior_pool.exit_liquidity = senior_pool.exit_liquidity.saturating_sub(fill.payout);
+
+        let exit_queue = &mut ctx.accounts.exit_queue;
+        exit_queue.queued_amount = exit_queue.queued_amount.saturating_sub(fill.grow_filled);
+
+        vault_balance = vault_balance
+            .checked_sub(fill.payout)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        grow_supply = grow_supply
+            .checked_sub(fill.grow_filled)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        liquidity = liquidity.saturating_sub(fill.payout);
+        processed += 1;
+
+        exit_request.amount = exit_request
+            .amount
+            .checked_sub(fill.grow_filled)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        exit_request.filled_amount = exit_request
+            .filled_amount
+            .checked_add(fill.grow_filled)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        exit_request.paid_amount = exit_request
+            .paid_amount
+            .checked_add(net_amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        let remaining = exit_request.amount;
+
+        emit!(crate::SeniorExitFilled {
+            asset_pool: asset_pool_key,
+            holder: exit_request.holder,
+            request_id: head,
+            grow_filled: fill.grow_filled,
+            payout: net_amount,
+            exit_fee: fill.exit_fee,
+            remaining,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if remaining == 0 {
+            // 全部成交：关闭请求账户，租金退还请求人
+            exit_request.close(holder_info.clone())?;
+            head += 1;
+        } else {
+            // 部分成交：流动性已用完
+            exit_request.exit(ctx.program_id)?;
+            break;
+        }
+    }
+
+    require!(processed > 0, PencilError::NoExitFilled);
+    ctx.accounts.exit_queue.head = head;
+
+    msg!(
+        "Senior 早退队列撮合完成 - 队首: {}, 剩余流动性: {}",
+        head,
+        ctx.accounts.senior_pool.exit_liquidity
+    );
+
+    Ok(())
+}
@@ -0,0 +1,401 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::instructions::referral::referral_holdback;
+use crate::state::{AssetPool, FeeRecipient, FeeSplit, SystemConfig};
+use crate::waterfall::bps_of;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+// FeeSplitUpdated / FeeCollectorInitialized / FeesDistributed events are defined in lib.rs
+
+/// 校验并写入分账配置
+fn write_fee_split(
+    fee_split: &mut Account<FeeSplit>,
+    owner: Pubkey,
+    recipients: &[FeeRecipient],
+) -> Result<()> {
+    require!(
+        !recipients.is_empty() && recipients.len() <= MAX_FEE_RECIPIENTS,
+        PencilError::InvalidFeeSplit
+    );
+    for (index, recipient) in recipients.iter().enumerate() {
+        require!(
+            recipient.share > 0 && recipient.recipient != Pubkey::default(),
+            PencilError::InvalidFeeSplit
+        );
+        require!(
+            recipients[..index]
+                .iter()
+                .all(|other| other.recipient != recipient.recipient),
+            PencilError::InvalidFeeSplit
+        );
+    }
+    let total_shares = recipients
+        .iter()
+        .map(|recipient| recipient.share as u32)
+        .sum::<u32>();
+    require!(
+        total_shares == BASIS_POINTS as u32,
+        PencilError::InvalidFeeSplit
+    );
+
+    fee_split.owner = owner;
+    fee_split.recipient_count = recipients.len() as u8;
+    fee_split.recipients = [FeeRecipient::default(); MAX_FEE_RECIPIENTS];
+    fee_split.recipients[..recipients.len()].copy_from_slice(recipients);
+
+    emit!(crate::FeeSplitUpdated {
+        fee_split: fee_split.key(),
+        owner,
+        recipients: recipients.iter().map(|r| r.recipient).collect(),
+        shares: recipients.iter().map(|r| r.share).collect(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "手续费分账已更新 - 所属: {}, 收款方数量: {}",
+        owner,
+        recipients.len()
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetSystemFeeSplit<'info> {
+    #[account(mut)]
+    pub treasury_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.treasury_admin == treasury_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = treasury_admin,
+        space = 8 + std::mem::size_of::<FeeSplit>(),
+        seeds = [seeds::FEE_SPLIT, system_config.key().as_ref()],
+        bump
+    )]
+    pub fee_split: Box<Account<'info, FeeSplit>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 设置系统级手续费分账（未单独配置的资产池使用）。
+/// 仅对已通过 initialize_fee_collector 开启归集的资产池生效，未开启的资产池手续费仍直接转入 treasury_ata
+pub fn set_system_fee_split(
+    ctx: Context<SetSystemFeeSplit>,
+    recipients: Vec<FeeRecipient>,
+) -> Result<()> {
+    let owner = ctx.accounts.system_config.key();
+    write_fee_split(&mut ctx.accounts.fee_split, owner, &recipients)
+}
+
+#[derive(Accounts)]
+pub struct SetPoolFeeSplit<'info> {
+    #[account(mut)]
+    pub treasury_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.treasury_admin == treasury_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = treasury_admin,
+        space = 8 + std::mem::size_of::<FeeSplit>(),
+        seeds = [seeds::FEE_SPLIT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub fee_split: Box<Account<'info, FeeSplit>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 设置资产池级手续费分账（覆盖系统级配置，如与发起方的收入分成）
+pub fn set_pool_fee_split(
+    ctx: Context<SetPoolFeeSplit>,
+    recipients: Vec<FeeRecipient>,
+) -> Result<()> {
+    let owner = ctx.accounts.asset_pool.key();
+    write_fee_split(&mut ctx.accounts.fee_split, owner, &recipients)
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeCollector<'info> {
+    #[account(mut)]
+    pub treasury_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.treasury_admin == treasury_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    /// 手续费归集 Token Account PDA（由 AssetPool PDA 持有）
+    #[account(
+        init,
+        payer = treasury_admin,
+        token::mint = asset_mint,
+        token::authority = asset_pool,
+        seeds = [seeds::FEE_COLLECTOR, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub fee_collector: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = asset_pool.asset_address @ PencilError::InvalidAccount)]
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 为资产池开启手续费分账：此后平台费与提前退出费转入归集账户，再由 distribute_fees 分发。
+/// 仅限仍会产生手续费的资产池（已结束或已取消的资产池不可再改写 treasury_ata）
+pub fn initialize_fee_collector(ctx: Context<InitializeFeeCollector>) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    require!(
+        asset_pool.related_accounts_initialized,
+        PencilError::RelatedAccountsNotInitialized
+    );
+    require!(
+        matches!(
+            asset_pool.status,
+            asset_pool_status::CREATED
+                | asset_pool_status::APPROVED
+                | asset_pool_status::FUNDED
+                | asset_pool_status::REPAYING
+        ),
+        PencilError::InvalidAssetPoolStatus
+    );
+
+    let previous_treasury_ata = asset_pool.treasury_ata;
+    asset_pool.treasury_ata = ctx.accounts.fee_collector.key();
+    asset_pool.fee_split_enabled = true;
+
+    emit!(crate::FeeCollectorInitialized {
+        asset_pool: asset_pool.key(),
+        fee_collector: asset_pool.treasury_ata,
+        previous_treasury_ata,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "手续费归集账户已开启 - 资产池: {}, 归集账户: {}",
+        asset_pool.key(),
+        asset_pool.treasury_ata
+    );
+
+    Ok(())
+}
+
+/// 分发资产池归集的手续费（任何人可调用）
+///
+/// remaining_accounts: 按分账配置中的收款方顺序传入其资产代币 ATA
+#[derive(Accounts)]
+pub struct DistributeFees<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.fee_split_enabled @ PencilError::InvalidFeeSplit
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::FEE_COLLECTOR, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub fee_collector: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: 资产池级分账配置，可能尚未创建；地址按种子校验
+    #[account(
+        seeds = [seeds::FEE_SPLIT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub pool_fee_split: UncheckedAccount<'info>,
+
+    /// CHECK: 系统级分账配置，可能尚未创建；地址按种子校验
+    #[account(
+        seeds = [seeds::FEE_SPLIT, system_config.key().as_ref()],
+        bump
+    )]
+    pub system_fee_split: UncheckedAccount<'info>,
+
+    /// CHECK: 推荐奖励条款，可能尚未创建；地址按种子校验
+    #[account(
+        seeds = [seeds::REFERRAL_PROGRAM, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub referral_program: UncheckedAccount<'info>,
+
+    #[account(address = asset_pool.asset_address @ PencilError::InvalidAccount)]
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 读取已创建的分账配置
+fn load_fee_split(fee_split: &AccountInfo) -> Result<Option<FeeSplit>> {
+    if fee_split.owner != &crate::ID || fee_split.data_is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(FeeSplit::try_deserialize(
+        &mut &fee_split.try_borrow_data()?[..],
+    )?))
+}
+
+/// 按分账比例拆分手续费，整除余数计入最后一个收款方
+/// shares 合计须为 10000
+pub fn calculate_fee_split(amount: u64, shares: &[u16]) -> Result<Vec<u64>> {
+    let total_shares = shares.iter().map(|share| *share as u32).sum::<u32>();
+    require!(
+        !shares.is_empty() && total_shares == BASIS_POINTS as u32,
+        PencilError::InvalidFeeSplit
+    );
+
+    let mut amounts = shares
+        .iter()
+        .map(|share| bps_of(amount, *share))
+        .collect::<Result<Vec<u64>>>()?;
+    let distributed = amounts
+        .iter()
+        .try_fold(0u64, |sum, share_amount| sum.checked_add(*share_amount))
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let remainder = amount
+        .checked_sub(distributed)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    if let Some(last) = amounts.last_mut() {
+        *last = last
+            .checked_add(remainder)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+    Ok(amounts)
+}
+
+/// 将归集账户余额（扣除应保留的推荐奖励）按分账配置转入各收款方 ATA
+/// 资产池级配置优先，否则使用系统级配置
+pub fn distribute_fees<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DistributeFees<'info>>,
+) -> Result<()> {
+    let (fee_split_key, fee_split) =
+        match load_fee_split(&ctx.accounts.pool_fee_split.to_account_info())? {
+            Some(fee_split) => (ctx.accounts.pool_fee_split.key(), fee_split),
+            None => match load_fee_split(&ctx.accounts.system_fee_split.to_account_info())? {
+                Some(fee_split) => (ctx.accounts.system_fee_split.key(), fee_split),
+                None => return err!(PencilError::InvalidFeeSplit),
+            },
+        };
+    let recipients = &fee_split.recipients[..fee_split.recipient_count as usize];
+    require!(
+        ctx.remaining_accounts.len() == recipients.len(),
+        PencilError::InvalidFeeRecipient
+    );
+
+    // 为推荐奖励保留余额
+    let holdback = referral_holdback(
+        &ctx.accounts.referral_program.to_account_info(),
+        &ctx.accounts.asset_pool,
+    )?;
+    let amount = ctx.accounts.fee_collector.amount.saturating_sub(holdback);
+    let shares = recipients.iter().map(|r| r.share).collect::<Vec<u16>>();
+    let amounts = calculate_fee_split(amount, &shares)?;
+
+    let asset_pool = &ctx.accounts.asset_pool;
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[ctx.bumps.asset_pool],
+    ];
+    let signer_seeds = &[&asset_pool_seeds[..]];
+    let asset_mint = ctx.accounts.asset_mint.key();
+
+    for ((recipient, recipient_ata), amount) in recipients
+        .iter()
+        .zip(ctx.remaining_accounts.iter())
+        .zip(amounts.iter())
+    {
+        require!(
+            recipient_ata.key() == get_associated_token_address(&recipient.recipient, &asset_mint),
+            PencilError::InvalidFeeRecipient
+        );
+        if *amount == 0 {
+            continue;
+        }
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.fee_collector.to_account_info(),
+            to: recipient_ata.clone(),
+            authority: asset_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, *amount)?;
+    }
+
+    emit!(crate::FeesDistributed {
+        asset_pool: asset_pool.key(),
+        fee_split: fee_split_key,
+        amount,
+        recipients: recipients.iter().map(|r| r.recipient).collect(),
+        amounts,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "手续费已分账 - 资产池: {}, 金额: {}, 收款方数量: {}",
+        asset_pool.key(),
+        amount,
+        recipients.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_split_assigns_rounding_to_last_recipient() {
+        assert_eq!(
+            calculate_fee_split(1_001, &[6000, 2500, 1500]).unwrap(),
+            vec![600, 250, 151]
+        );
+        assert_eq!(calculate_fee_split(7, &[10_000]).unwrap(), vec![7]);
+        assert_eq!(calculate_fee_split(0, &[5000, 5000]).unwrap(), vec![0, 0]);
+        assert!(calculate_fee_split(100, &[5000, 4000]).is_err());
+        assert!(calculate_fee_split(100, &[]).is_err());
+    }
+}
@@ -1,5 +1,10 @@
 use crate::constants::*;
 use crate::errors::PencilError;
+use crate::instructions::originator::{
+    load_originator, record_pool_cancellation, sync_originator_account,
+};
+use crate::instructions::referral::{record_referral, release_referral};
+use crate::instructions::utils::pool_fee_destination;
 use crate::state::{AssetPool, Subscription};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
@@ -54,7 +59,11 @@ pub struct SubscribeSenior<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn subscribe_senior(ctx: Context<SubscribeSenior>, amount: u64) -> Result<()> {
+/// remaining_accounts: 经推荐的订阅传入 [referrer, referral_record, referral_program]
+pub fn subscribe_senior<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SubscribeSenior<'info>>,
+    amount: u64,
+) -> Result<()> {
     require!(amount > 0, PencilError::InvalidSubscriptionAmount);
 
     // 检查系统是否暂停
@@ -96,8 +105,10 @@ pub fn subscribe_senior(ctx: Context<SubscribeSenior>, amount: u64) -> Result<()
     // 记录订阅
     let subscription = &mut ctx.accounts.subscription;
 
+    let first_subscription = subscription.amount == 0;
+
     // 判断是否是第一次投资（通过检查 amount 是否为 0）
-    if subscription.amount == 0 {
+    if first_subscription {
         // 第一次投资，初始化所有字段
         subscription.asset_pool = asset_pool.key();
         subscription.user = ctx.accounts.user.key();
@@ -113,12 +124,34 @@ pub fn subscribe_senior(ctx: Context<SubscribeSenior>, amount: u64) -> Result<()
             .ok_or(PencilError::ArithmeticOverflow)?;
     }
 
+    // 记录推荐关系
+    record_referral(
+        ctx.remaining_accounts,
+        asset_pool.key(),
+        subscription,
+        first_subscription,
+        &ctx.accounts.user,
+        &ctx.accounts.system_program,
+        amount,
+    )?;
+
     // 更新资产池
     asset_pool.senior_amount = asset_pool
         .senior_amount
         .checked_add(amount)
         .ok_or(PencilError::ArithmeticOverflow)?;
 
+    emit!(crate::Subscribed {
+        asset_pool: asset_pool.key(),
+        user: ctx.accounts.user.key(),
+        subscription_type: 0,
+        amount,
+        subscription_amount: subscription.amount,
+        pool_senior_amount: asset_pool.senior_amount,
+        pool_junior_amount: asset_pool.junior_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
     msg!("Senior subscription: {} tokens", amount);
 
     Ok(())
@@ -174,7 +207,11 @@ pub struct SubscribeJunior<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn subscribe_junior(ctx: Context<SubscribeJunior>, amount: u64) -> Result<()> {
+/// remaining_accounts: 经推荐的订阅传入 [referrer, referral_record, referral_program]
+pub fn subscribe_junior<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SubscribeJunior<'info>>,
+    amount: u64,
+) -> Result<()> {
     require!(amount > 0, PencilError::InvalidSubscriptionAmount);
 
     // 检查系统是否暂停
@@ -216,8 +253,10 @@ pub fn subscribe_junior(ctx: Context<SubscribeJunior>, amount: u64) -> Result<()
     // 记录订阅
     let subscription = &mut ctx.accounts.subscription;
 
+    let first_subscription = subscription.amount == 0;
+
     // 判断是否是第一次投资（通过检查 amount 是否为 0）
-    if subscription.amount == 0 {
+    if first_subscription {
         // 第一次投资，初始化所有字段
         subscription.asset_pool = asset_pool.key();
         subscription.user = ctx.accounts.user.key();
@@ -233,12 +272,34 @@ pub fn subscribe_junior(ctx: Context<SubscribeJunior>, amount: u64) -> Result<()
             .ok_or(PencilError::ArithmeticOverflow)?;
     }
 
+    // 记录推荐关系
+    record_referral(
+        ctx.remaining_accounts,
+        asset_pool.key(),
+        subscription,
+        first_subscription,
+        &ctx.accounts.user,
+        &ctx.accounts.system_program,
+        amount,
+    )?;
+
     // 更新资产池
     asset_pool.junior_amount = asset_pool
         .junior_amount
         .checked_add(amount)
         .ok_or(PencilError::ArithmeticOverflow)?;
 
+    emit!(crate::Subscribed {
+        asset_pool: asset_pool.key(),
+        user: ctx.accounts.user.key(),
+        subscription_type: 1,
+        amount,
+        subscription_amount: subscription.amount,
+        pool_senior_amount: asset_pool.senior_amount,
+        pool_junior_amount: asset_pool.junior_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
     msg!("Junior subscription: {} tokens", amount);
 
     Ok(())
@@ -249,7 +310,12 @@ pub struct CompleteFunding<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.status == asset_pool_status::APPROVED @ PencilError::InvalidAssetPoolStatus
+    )]
     pub asset_pool: Account<'info, AssetPool>,
 
     #[account(
@@ -265,18 +331,58 @@ pub struct CompleteFunding<'info> {
         bump
     )]
     pub first_loss_pool: Account<'info, crate::state::FirstLossPool>,
+
+    /// 募资 Vault（认购资金暂存于此）
+    #[account(
+        mut,
+        address = asset_pool.asset_pool_vault @ PencilError::InvalidAccount
+    )]
+    pub asset_pool_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = senior_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub senior_pool_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = first_loss_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub first_loss_pool_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: 发起方 PDA，用于同步敞口；发起方登记前创建的资产池，其创建者可能没有发起方账户
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
 }
 
 pub fn complete_funding(ctx: Context<CompleteFunding>) -> Result<()> {
-    let asset_pool = &mut ctx.accounts.asset_pool;
     let clock = Clock::get()?;
 
     // 检查募资是否已结束
     require!(
-        clock.unix_timestamp > asset_pool.funding_end_time,
+        clock.unix_timestamp > ctx.accounts.asset_pool.funding_end_time,
         PencilError::FundingNotCompleted
     );
 
+    let asset_pool_bump = ctx.bumps.asset_pool;
+    settle_funding(ctx.accounts, asset_pool_bump, clock.unix_timestamp)
+}
+
+/// 校验募资结果并将认购资金划转至各子池，资产池进入 FUNDED
+fn settle_funding(
+    accounts: &mut CompleteFunding,
+    asset_pool_bump: u8,
+    timestamp: i64,
+) -> Result<()> {
+    let asset_pool = &mut accounts.asset_pool;
+
     // 检查募资目标是否达成
     let total = asset_pool
         .senior_amount
@@ -294,12 +400,45 @@ pub fn complete_funding(ctx: Context<CompleteFunding>) -> Result<()> {
         PencilError::InvalidJuniorRatio
     );
 
+    // 将认购资金从募资 Vault 划转至各子池 Vault
+    let senior_amount = asset_pool.senior_amount;
+    let junior_amount = asset_pool.junior_amount;
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[asset_pool_bump],
+    ];
+    let asset_pool_signer = &[&asset_pool_seeds[..]];
+
+    if senior_amount > 0 {
+        let cpi_accounts = Transfer {
+            from: accounts.asset_pool_vault.to_account_info(),
+            to: accounts.senior_pool_vault.to_account_info(),
+            authority: asset_pool.to_account_info(),
+        };
+        let cpi_program = accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, asset_pool_signer);
+        token::transfer(cpi_ctx, senior_amount)?;
+    }
+
+    if junior_amount > 0 {
+        let cpi_accounts = Transfer {
+            from: accounts.asset_pool_vault.to_account_info(),
+            to: accounts.first_loss_pool_vault.to_account_info(),
+            authority: asset_pool.to_account_info(),
+        };
+        let cpi_program = accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, asset_pool_signer);
+        token::transfer(cpi_ctx, junior_amount)?;
+    }
+
     // 更新 SeniorPool 和 FirstLossPool
-    let senior_pool = &mut ctx.accounts.senior_pool;
-    senior_pool.total_deposits = asset_pool.senior_amount;
+    let senior_pool = &mut accounts.senior_pool;
+    senior_pool.total_deposits = senior_amount;
 
-    let first_loss_pool = &mut ctx.accounts.first_loss_pool;
-    first_loss_pool.total_deposits = asset_pool.junior_amount;
+    let first_loss_pool = &mut accounts.first_loss_pool;
+    first_loss_pool.total_deposits = junior_amount;
 
     // 更新 asset_pool.total_amount 为实际募资金额
     asset_pool.total_amount = total;
@@ -307,15 +446,183 @@ pub fn complete_funding(ctx: Context<CompleteFunding>) -> Result<()> {
     // 更新资产池状态
     asset_pool.status = asset_pool_status::FUNDED;
 
+    // 敞口由募资目标改为实际募资额
+    if let Some(mut originator) = load_originator(&accounts.originator)? {
+        let asset_pool_key = asset_pool.key();
+        sync_originator_account(
+            &accounts.originator,
+            &mut originator,
+            asset_pool_key,
+            asset_pool,
+            timestamp,
+        )?;
+    }
+
+    emit!(crate::FundingCompleted {
+        asset_pool: asset_pool.key(),
+        status: asset_pool.status,
+        total_amount: total,
+        min_amount: asset_pool.min_amount,
+        senior_amount,
+        junior_amount,
+        junior_ratio,
+        timestamp,
+    });
+
     msg!("Funding completed - ready for token distribution");
     msg!("Total amount: {}", total);
-    msg!("Senior amount: {}", asset_pool.senior_amount);
-    msg!("Junior amount: {}", asset_pool.junior_amount);
+    msg!("Senior amount: {}", senior_amount);
+    msg!("Junior amount: {}", junior_amount);
     msg!("Junior ratio: {}%", junior_ratio / 100);
 
     Ok(())
 }
 
+// 募资期内提前结束募资
+#[derive(Accounts)]
+pub struct CloseFundingEarly<'info> {
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Account<'info, crate::state::SystemConfig>,
+
+    /// payer 即发起人；未达募资总额时须为 operation_admin
+    pub complete_funding: CompleteFunding<'info>,
+}
+
+pub fn close_funding_early(ctx: Context<CloseFundingEarly>) -> Result<()> {
+    let clock = Clock::get()?;
+    let closed_by = ctx.accounts.complete_funding.payer.key();
+    let asset_pool = &mut ctx.accounts.complete_funding.asset_pool;
+
+    require!(
+        clock.unix_timestamp >= asset_pool.funding_start_time,
+        PencilError::FundingNotStarted
+    );
+    require!(
+        clock.unix_timestamp <= asset_pool.funding_end_time,
+        PencilError::FundingEnded
+    );
+    // 条款修订后的免手续费撤资窗口结束前不能提前结束募资
+    require!(
+        clock.unix_timestamp > asset_pool.amendment_withdraw_deadline,
+        PencilError::WithdrawWindowOpen
+    );
+
+    // 募资总额已满任何人可提前结束；否则由 operation_admin 在达到最低募资额后决定
+    // （最低募资额与 Junior 占比在 settle_funding 中校验）
+    let total = asset_pool
+        .senior_amount
+        .checked_add(asset_pool.junior_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    if total < asset_pool.total_amount {
+        require!(
+            ctx.accounts.system_config.operation_admin == closed_by,
+            PencilError::Unauthorized
+        );
+    }
+
+    // 以实际结束时间作为还款计划起点
+    let original_funding_end_time = asset_pool.funding_end_time;
+    asset_pool.funding_end_time = clock.unix_timestamp;
+
+    emit!(crate::FundingClosedEarly {
+        asset_pool: asset_pool.key(),
+        closed_by,
+        original_funding_end_time,
+        funding_end_time: asset_pool.funding_end_time,
+        subscribed_amount: total,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Funding closed early at {} (originally {})",
+        clock.unix_timestamp,
+        original_funding_end_time
+    );
+
+    let asset_pool_bump = ctx.bumps.complete_funding.asset_pool;
+    settle_funding(
+        &mut ctx.accounts.complete_funding,
+        asset_pool_bump,
+        clock.unix_timestamp,
+    )
+}
+
+// 延长募资期
+#[derive(Accounts)]
+pub struct ExtendFunding<'info> {
+    pub operation_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused,
+        constraint = system_config.operation_admin == operation_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Account<'info, crate::state::SystemConfig>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.status == asset_pool_status::APPROVED @ PencilError::InvalidAssetPoolStatus
+    )]
+    pub asset_pool: Account<'info, AssetPool>,
+}
+
+pub fn extend_funding(ctx: Context<ExtendFunding>, new_end: i64) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let clock = Clock::get()?;
+
+    // 只能在募资结束前延长，结束后走 complete_funding / 退款流程
+    require!(
+        clock.unix_timestamp <= asset_pool.funding_end_time,
+        PencilError::FundingEnded
+    );
+    require!(
+        new_end > asset_pool.funding_end_time,
+        PencilError::InvalidTimeParameters
+    );
+    require!(
+        new_end - asset_pool.funding_start_time <= MAX_FUNDING_PERIOD,
+        PencilError::InvalidTimeParameters
+    );
+
+    // 已有认购的投资者可在延长后免手续费撤资（窗口不超过新的募资结束时间）
+    if asset_pool.senior_amount > 0 || asset_pool.junior_amount > 0 {
+        let deadline = clock
+            .unix_timestamp
+            .checked_add(AMENDMENT_WITHDRAW_WINDOW)
+            .ok_or(PencilError::ArithmeticOverflow)?
+            .min(new_end);
+        asset_pool.amendment_withdraw_deadline =
+            asset_pool.amendment_withdraw_deadline.max(deadline);
+    }
+
+    let old_funding_end_time = asset_pool.funding_end_time;
+    asset_pool.funding_end_time = new_end;
+
+    emit!(crate::FundingExtended {
+        asset_pool: asset_pool.key(),
+        operator: ctx.accounts.operation_admin.key(),
+        old_funding_end_time,
+        new_funding_end_time: new_end,
+        withdraw_deadline: asset_pool.amendment_withdraw_deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Funding extended from {} to {}",
+        old_funding_end_time,
+        new_end
+    );
+
+    Ok(())
+}
+
 // 为 Senior 投资者分发 GROW Token
 #[derive(Accounts)]
 pub struct DistributeSeniorToken<'info> {
@@ -383,10 +690,19 @@ pub fn distribute_senior_token(ctx: Context<DistributeSeniorToken>) -> Result<()
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, asset_pool_signer);
     anchor_spl::token::mint_to(cpi_ctx, subscription.amount)?;
+    ctx.accounts.grow_token_mint.reload()?;
 
     // 更新订阅状态
     subscription.status = subscription_status::CONFIRMED;
 
+    emit!(crate::SeniorTokenDistributed {
+        asset_pool: asset_pool.key(),
+        user: subscription.user,
+        amount: subscription.amount,
+        grow_supply: ctx.accounts.grow_token_mint.supply,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     msg!(
         "Distributed {} GROW tokens to {}",
         subscription.amount,
@@ -458,6 +774,9 @@ pub struct DistributeJuniorNFT<'info> {
 }
 
 pub fn distribute_junior_nft(ctx: Context<DistributeJuniorNFT>, nft_id: u64) -> Result<()> {
+    // 编号起点以上留给程序新铸的 NFT
+    require!(nft_id < JUNIOR_NFT_ISSUED_ID_BASE, PencilError::InvalidNFT);
+
     let subscription = &mut ctx.accounts.subscription;
     let asset_pool = &ctx.accounts.asset_pool;
     let clock = Clock::get()?;
@@ -494,6 +813,15 @@ pub fn distribute_junior_nft(ctx: Context<DistributeJuniorNFT>, nft_id: u64) ->
     // 更新订阅状态
     subscription.status = subscription_status::CONFIRMED;
 
+    emit!(crate::JuniorNftDistributed {
+        asset_pool: asset_pool.key(),
+        user: subscription.user,
+        nft_id,
+        junior_nft_mint: ctx.accounts.junior_nft_mint.key(),
+        principal: subscription.amount,
+        timestamp: clock.unix_timestamp,
+    });
+
     msg!(
         "Distributed Junior NFT {} to {} with principal {}",
         nft_id,
@@ -561,6 +889,14 @@ pub fn refund_subscription(ctx: Context<RefundSubscription>, _subscription_type:
 
     subscription.status = subscription_status::REFUNDED;
 
+    emit!(crate::SubscriptionRefunded {
+        asset_pool: subscription.asset_pool,
+        user: subscription.user,
+        subscription_type: subscription.subscription_type,
+        amount: subscription.amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     msg!("Subscription refunded: {} tokens", subscription.amount);
 
     Ok(())
@@ -603,6 +939,14 @@ pub struct ProcessRefund<'info> {
 
     pub asset_mint: Account<'info, anchor_spl::token::Mint>,
 
+    /// CHECK: 发起方 PDA，用于同步敞口；发起方登记前创建的资产池，其创建者可能没有发起方账户
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
 }
 
@@ -617,26 +961,10 @@ pub fn process_refund(ctx: Context<ProcessRefund>) -> Result<()> {
     );
 
     // 2. 检查是否达到最低募资目标或 Junior 占比不足
-    let total = ctx
-        .accounts
-        .asset_pool
-        .senior_amount
-        .checked_add(ctx.accounts.asset_pool.junior_amount)
-        .ok_or(PencilError::ArithmeticOverflow)?;
-
-    let funding_failed = if total < ctx.accounts.asset_pool.min_amount {
-        // 未达到最低募资目标
-        true
-    } else if ctx.accounts.asset_pool.junior_amount > 0 {
-        // 检查 Junior 占比
-        let junior_ratio = (ctx.accounts.asset_pool.junior_amount * 10000) / total;
-        junior_ratio < ctx.accounts.asset_pool.min_junior_ratio as u64
-    } else {
-        // 没有 Junior 投资
-        true
-    };
-
-    require!(funding_failed, PencilError::FundingTargetNotMet);
+    require!(
+        funding_failed(&ctx.accounts.asset_pool)?,
+        PencilError::FundingTargetNotMet
+    );
 
     // 验证退款金额
     let refund_amount = ctx.accounts.subscription.amount;
@@ -693,6 +1021,19 @@ pub fn process_refund(ctx: Context<ProcessRefund>) -> Result<()> {
             .ok_or(PencilError::ArithmeticOverflow)?;
     }
 
+    // 募资失败后释放计入发起方的敞口（首笔退款时）
+    if ctx.accounts.asset_pool.originator_exposure != 0 {
+        if let Some(mut originator) = load_originator(&ctx.accounts.originator)? {
+            sync_originator_account(
+                &ctx.accounts.originator,
+                &mut originator,
+                asset_pool_key,
+                &mut ctx.accounts.asset_pool,
+                clock.unix_timestamp,
+            )?;
+        }
+    }
+
     // 发出 RefundProcessed 事件
     emit!(crate::RefundProcessed {
         asset_pool: asset_pool_key,
@@ -707,6 +1048,20 @@ pub fn process_refund(ctx: Context<ProcessRefund>) -> Result<()> {
     Ok(())
 }
 
+/// 募资是否失败：未达到最低募资额，或 Junior 占比不足（含没有 Junior 认购）
+pub(crate) fn funding_failed(asset_pool: &AssetPool) -> Result<bool> {
+    let total = asset_pool
+        .senior_amount
+        .checked_add(asset_pool.junior_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    if total < asset_pool.min_amount || asset_pool.junior_amount == 0 {
+        return Ok(true);
+    }
+    let junior_ratio = (asset_pool.junior_amount * 10000) / total;
+    Ok(junior_ratio < asset_pool.min_junior_ratio as u64)
+}
+
 // 取消资产池（在所有认购都已退款后）
 #[derive(Accounts)]
 pub struct CancelAssetPool<'info> {
@@ -718,7 +1073,8 @@ pub struct CancelAssetPool<'info> {
         seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
         bump,
         constraint = asset_pool.creator == authority.key() ||
-                     asset_pool.system_config == authority.key() @ PencilError::Unauthorized
+                     asset_pool.system_config == authority.key() @ PencilError::Unauthorized,
+        constraint = asset_pool.status == asset_pool_status::APPROVED @ PencilError::InvalidAssetPoolStatus
     )]
     pub asset_pool: Account<'info, AssetPool>,
 
@@ -730,6 +1086,14 @@ pub struct CancelAssetPool<'info> {
     pub pool_vault: Account<'info, TokenAccount>,
 
     pub asset_mint: Account<'info, anchor_spl::token::Mint>,
+
+    /// CHECK: 发起方 PDA，用于释放敞口并记录取消；发起方登记前创建的资产池，其创建者可能没有发起方账户
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: UncheckedAccount<'info>,
 }
 
 pub fn cancel_asset_pool(ctx: Context<CancelAssetPool>) -> Result<()> {
@@ -743,21 +1107,10 @@ pub fn cancel_asset_pool(ctx: Context<CancelAssetPool>) -> Result<()> {
     );
 
     // 验证募资失败条件
-    let total = asset_pool
-        .senior_amount
-        .checked_add(asset_pool.junior_amount)
-        .ok_or(PencilError::ArithmeticOverflow)?;
-
-    let funding_failed = if total < asset_pool.min_amount {
-        true
-    } else if asset_pool.junior_amount > 0 {
-        let junior_ratio = (asset_pool.junior_amount * 10000) / total;
-        junior_ratio < asset_pool.min_junior_ratio as u64
-    } else {
-        true
-    };
-
-    require!(funding_failed, PencilError::FundingTargetNotMet);
+    require!(
+        funding_failed(asset_pool)?,
+        PencilError::FundingTargetNotMet
+    );
 
     // 验证所有认购都已退款（检查 vault 余额应该为 0 或接近 0）
     // 允许有微小的余额差异（由于精度问题）
@@ -769,6 +1122,24 @@ pub fn cancel_asset_pool(ctx: Context<CancelAssetPool>) -> Result<()> {
     // 更新资产池状态为 CANCELLED
     asset_pool.status = asset_pool_status::CANCELLED;
 
+    let asset_pool_key = asset_pool.key();
+    record_pool_cancellation(
+        &ctx.accounts.originator,
+        asset_pool_key,
+        asset_pool,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(crate::PoolCancelled {
+        asset_pool: asset_pool.key(),
+        authority: ctx.accounts.authority.key(),
+        status: asset_pool.status,
+        senior_amount: asset_pool.senior_amount,
+        junior_amount: asset_pool.junior_amount,
+        min_amount: asset_pool.min_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
     let name_str = String::from_utf8_lossy(&asset_pool.name);
     msg!("Asset pool cancelled: {}", name_str);
     msg!("All refunds have been processed");
@@ -820,21 +1191,18 @@ pub struct WithdrawSeniorSubscription<'info> {
 
     #[account(
         mut,
-        token::mint = asset_mint,
-        token::authority = treasury
+        address = pool_fee_destination(&asset_pool, &system_config) @ PencilError::InvalidAccount
     )]
     pub treasury_ata: Account<'info, TokenAccount>,
 
     pub asset_mint: Account<'info, anchor_spl::token::Mint>,
 
-    /// CHECK: Treasury account from SystemConfig
-    pub treasury: AccountInfo<'info>,
-
     pub token_program: Program<'info, Token>,
 }
 
-pub fn withdraw_senior_subscription(
-    ctx: Context<WithdrawSeniorSubscription>,
+/// remaining_accounts: 经推荐的订阅传入 [referral_record, referral_program]
+pub fn withdraw_senior_subscription<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawSeniorSubscription<'info>>,
     amount: u64,
 ) -> Result<()> {
     require!(amount > 0, PencilError::InvalidSubscriptionAmount);
@@ -857,7 +1225,12 @@ pub fn withdraw_senior_subscription(
     );
 
     // 计算手续费
-    let fee_rate = ctx.accounts.system_config.senior_early_before_exit_fee_rate;
+    // 经济条款修订后的撤资窗口内免手续费
+    let fee_rate = if clock.unix_timestamp <= ctx.accounts.asset_pool.amendment_withdraw_deadline {
+        0
+    } else {
+        ctx.accounts.asset_pool.senior_early_before_exit_fee
+    };
     let fee = amount
         .checked_mul(fee_rate as u64)
         .ok_or(PencilError::ArithmeticOverflow)?
@@ -875,6 +1248,14 @@ pub fn withdraw_senior_subscription(
         .checked_sub(amount)
         .ok_or(PencilError::ArithmeticOverflow)?;
 
+    // 扣减推荐人的推荐额
+    release_referral(
+        ctx.remaining_accounts,
+        ctx.accounts.asset_pool.key(),
+        &ctx.accounts.subscription,
+        amount,
+    )?;
+
     // 更新资产池 Senior 金额
     ctx.accounts.asset_pool.senior_amount = ctx
         .accounts
@@ -917,6 +1298,19 @@ pub fn withdraw_senior_subscription(
         token::transfer(cpi_ctx, actual_amount)?;
     }
 
+    emit!(crate::SubscriptionWithdrawn {
+        asset_pool: ctx.accounts.asset_pool.key(),
+        user: ctx.accounts.user.key(),
+        subscription_type: 0,
+        amount,
+        fee,
+        net_amount: actual_amount,
+        subscription_amount: ctx.accounts.subscription.amount,
+        pool_senior_amount: asset_pool.senior_amount,
+        pool_junior_amount: asset_pool.junior_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
     msg!(
         "Senior subscription withdrawn: {} tokens, fee: {}",
         amount,
@@ -970,21 +1364,18 @@ pub struct WithdrawJuniorSubscription<'info> {
 
     #[account(
         mut,
-        token::mint = asset_mint,
-        token::authority = treasury
+        address = pool_fee_destination(&asset_pool, &system_config) @ PencilError::InvalidAccount
     )]
     pub treasury_ata: Account<'info, TokenAccount>,
 
     pub asset_mint: Account<'info, anchor_spl::token::Mint>,
 
-    /// CHECK: Treasury account from SystemConfig
-    pub treasury: AccountInfo<'info>,
-
     pub token_program: Program<'info, Token>,
 }
 
-pub fn withdraw_junior_subscription(
-    ctx: Context<WithdrawJuniorSubscription>,
+/// remaining_accounts: 经推荐的订阅传入 [referral_record, referral_program]
+pub fn withdraw_junior_subscription<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawJuniorSubscription<'info>>,
     amount: u64,
 ) -> Result<()> {
     require!(amount > 0, PencilError::InvalidSubscriptionAmount);
@@ -1007,7 +1398,12 @@ pub fn withdraw_junior_subscription(
     );
 
     // 计算手续费
-    let fee_rate = ctx.accounts.system_config.junior_early_before_exit_fee_rate;
+    // 经济条款修订后的撤资窗口内免手续费
+    let fee_rate = if clock.unix_timestamp <= ctx.accounts.asset_pool.amendment_withdraw_deadline {
+        0
+    } else {
+        ctx.accounts.asset_pool.junior_early_before_exit_fee
+    };
     let fee = amount
         .checked_mul(fee_rate as u64)
         .ok_or(PencilError::ArithmeticOverflow)?
@@ -1025,6 +1421,14 @@ pub fn withdraw_junior_subscription(
         .checked_sub(amount)
         .ok_or(PencilError::ArithmeticOverflow)?;
 
+    // 扣减推荐人的推荐额
+    release_referral(
+        ctx.remaining_accounts,
+        ctx.accounts.asset_pool.key(),
+        &ctx.accounts.subscription,
+        amount,
+    )?;
+
     // 更新资产池 Junior 金额
     ctx.accounts.asset_pool.junior_amount = ctx
         .accounts
@@ -1067,6 +1471,19 @@ pub fn withdraw_junior_subscription(
         token::transfer(cpi_ctx, actual_amount)?;
     }
 
+    emit!(crate::SubscriptionWithdrawn {
+        asset_pool: ctx.accounts.asset_pool.key(),
+        user: ctx.accounts.user.key(),
+        subscription_type: 1,
+        amount,
+        fee,
+        net_amount: actual_amount,
+        subscription_amount: ctx.accounts.subscription.amount,
+        pool_senior_amount: asset_pool.senior_amount,
+        pool_junior_amount: asset_pool.junior_amount,
+        timestamp: clock.unix_timestamp,
+    });
+
     msg!(
         "Junior subscription withdrawn: {} tokens, fee: {}",
         amount,
@@ -0,0 +1,495 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::instructions::distribution::accrue_senior_distribution;
+use crate::state::{
+    AssetPool, AssetWhitelist, InsuranceDraw, InsuranceFund, SeniorPool, SystemConfig,
+};
+use crate::waterfall::{
+    calculate_insurance_draw_limit, calculate_insurance_fee, calculate_senior_shortfall,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+// InsuranceFund* / InsuranceDraw* events are defined in lib.rs
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.super_admin == admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_WHITELIST],
+        bump
+    )]
+    pub asset_whitelist: Box<Account<'info, AssetWhitelist>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<InsuranceFund>(),
+        seeds = [seeds::INSURANCE_FUND, asset_mint.key().as_ref()],
+        bump
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    /// 保险基金 Token Vault PDA（由 InsuranceFund PDA 持有）
+    #[account(
+        init,
+        payer = admin,
+        token::mint = asset_mint,
+        token::authority = insurance_fund,
+        seeds = [seeds::INSURANCE_FUND_VAULT, asset_mint.key().as_ref()],
+        bump
+    )]
+    pub insurance_fund_vault: Box<Account<'info, TokenAccount>>,
+
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 为资产代币创建保险基金
+/// fee_share: 平台费计提比例（基点）；max_draw_per_pool: 单个资产池累计可提取上限
+pub fn initialize_insurance_fund(
+    ctx: Context<InitializeInsuranceFund>,
+    fee_share: u16,
+    max_draw_per_pool: u64,
+) -> Result<()> {
+    let asset_mint = ctx.accounts.asset_mint.key();
+    require!(
+        ctx.accounts.asset_whitelist.assets.contains(&asset_mint),
+        PencilError::AssetNotSupported
+    );
+    require!(fee_share <= BASIS_POINTS, PencilError::InvalidInsuranceFund);
+
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    insurance_fund.asset_mint = asset_mint;
+    insurance_fund.vault = ctx.accounts.insurance_fund_vault.key();
+    insurance_fund.fee_share = fee_share;
+    insurance_fund.max_draw_per_pool = max_draw_per_pool;
+    insurance_fund.total_collected = 0;
+    insurance_fund.total_drawn = 0;
+
+    emit!(crate::InsuranceFundInitialized {
+        insurance_fund: insurance_fund.key(),
+        asset_mint,
+        fee_share,
+        max_draw_per_pool,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "保险基金已创建 - 资产: {}, 计提比例: {}, 单池上限: {}",
+        asset_mint,
+        fee_share,
+        max_draw_per_pool
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateInsuranceFund<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.super_admin == admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::INSURANCE_FUND, insurance_fund.asset_mint.as_ref()],
+        bump
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+}
+
+/// 更新保险基金计提比例与单池提取上限
+pub fn update_insurance_fund(
+    ctx: Context<UpdateInsuranceFund>,
+    fee_share: u16,
+    max_draw_per_pool: u64,
+) -> Result<()> {
+    require!(fee_share <= BASIS_POINTS, PencilError::InvalidInsuranceFund);
+
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    insurance_fund.fee_share = fee_share;
+    insurance_fund.max_draw_per_pool = max_draw_per_pool;
+
+    emit!(crate::InsuranceFundUpdated {
+        insurance_fund: insurance_fund.key(),
+        fee_share,
+        max_draw_per_pool,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "保险基金已更新 - 计提比例: {}, 单池上限: {}",
+        fee_share,
+        max_draw_per_pool
+    );
+
+    Ok(())
+}
+
+/// 从平台费中向保险基金计提，返回计提金额
+/// 资产代币尚未创建保险基金时不计提；地址由还款指令按种子校验，付款人无法绕过
+pub(crate) fn collect_insurance_fee<'info>(
+    insurance_fund: &AccountInfo<'info>,
+    insurance_fund_vault: &AccountInfo<'info>,
+    asset_pool: Pubkey,
+    payer: &Signer<'info>,
+    payer_token_account: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    platform_fee: u64,
+) -> Result<u64> {
+    if insurance_fund.owner != &crate::ID || insurance_fund.data_is_empty() {
+        return Ok(0);
+    }
+
+    let mut fund = InsuranceFund::try_deserialize(&mut &insurance_fund.try_borrow_data()?[..])?;
+    require!(
+        insurance_fund_vault.key() == fund.vault,
+        PencilError::InvalidAccount
+    );
+    let amount = calculate_insurance_fee(platform_fee, fund.fee_share)?;
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    let cpi_accounts = Transfer {
+        from: payer_token_account.to_account_info(),
+        to: insurance_fund_vault.clone(),
+        authority: payer.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+    fund.total_collected = fund
+        .total_collected
+        .checked_add(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    fund.try_serialize(&mut &mut insurance_fund.try_borrow_mut_data()?[..])?;
+
+    emit!(crate::InsuranceFeeCollected {
+        insurance_fund: insurance_fund.key(),
+        asset_pool,
+        amount,
+        total_collected: fund.total_collected,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("保险基金计提: {} tokens", amount);
+
+    Ok(amount)
+}
+
+/// 违约资产池的 Senior 缺口与可提取上限
+fn insurance_draw_limit(
+    asset_pool: &AssetPool,
+    senior_pool: &SeniorPool,
+    insurance_fund: &InsuranceFund,
+    insurance_draw: &InsuranceDraw,
+    fund_balance: u64,
+) -> Result<(u64, u64)> {
+    let shortfall = calculate_senior_shortfall(
+        asset_pool.senior_amount,
+        asset_pool.senior_fixed_rate,
+        asset_pool.repayment_count,
+        senior_pool.repaid_amount,
+    )?;
+    let limit = calculate_insurance_draw_limit(
+        shortfall,
+        insurance_fund.max_draw_per_pool,
+        insurance_draw.drawn_amount,
+        fund_balance,
+    );
+    Ok((shortfall, limit))
+}
+
+#[derive(Accounts)]
+pub struct ProposeInsuranceDraw<'info> {
+    #[account(mut)]
+    pub operation_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.operation_admin == operation_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        seeds = [seeds::SENIOR_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub senior_pool: Box<Account<'info, SeniorPool>>,
+
+    #[account(
+        seeds = [seeds::INSURANCE_FUND, asset_pool.asset_address.as_ref()],
+        bump
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    #[account(address = insurance_fund.vault @ PencilError::InvalidAccount)]
+    pub insurance_fund_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = operation_admin,
+        space = 8 + std::mem::size_of::<InsuranceDraw>(),
+        seeds = [seeds::INSURANCE_DRAW, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub insurance_draw: Box<Account<'info, InsuranceDraw>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 运营管理员为已记为违约的资产池提出保险基金提取提案，由超级管理员执行
+pub fn propose_insurance_draw(ctx: Context<ProposeInsuranceDraw>, amount: u64) -> Result<()> {
+    let asset_pool = &ctx.accounts.asset_pool;
+    require!(
+        asset_pool.originator_defaulted
+            && (asset_pool.status == asset_pool_status::FUNDED
+                || asset_pool.status == asset_pool_status::REPAYING),
+        PencilError::InvalidAssetPoolStatus
+    );
+
+    let insurance_draw = &mut ctx.accounts.insurance_draw;
+    require!(
+        amount > 0 && insurance_draw.pending_amount == 0,
+        PencilError::InvalidInsuranceDraw
+    );
+    let (shortfall, limit) = insurance_draw_limit(
+        asset_pool,
+        &ctx.accounts.senior_pool,
+        &ctx.accounts.insurance_fund,
+        insurance_draw,
+        ctx.accounts.insurance_fund_vault.amount,
+    )?;
+    require!(amount <= limit, PencilError::InsuranceDrawExceeded);
+
+    let clock = Clock::get()?;
+    insurance_draw.asset_pool = asset_pool.key();
+    insurance_draw.insurance_fund = ctx.accounts.insurance_fund.key();
+    insurance_draw.pending_amount = amount;
+    insurance_draw.proposer = ctx.accounts.operation_admin.key();
+    insurance_draw.proposed_at = clock.unix_timestamp;
+
+    emit!(crate::InsuranceDrawProposed {
+        insurance_fund: insurance_draw.insurance_fund,
+        asset_pool: asset_pool.key(),
+        proposer: insurance_draw.proposer,
+        amount,
+        shortfall,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "保险基金提取提案 - 资产池: {}, 金额: {}, Senior 缺口: {}",
+        asset_pool.key(),
+        amount,
+        shortfall
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteInsuranceDraw<'info> {
+    pub super_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.super_admin == super_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub senior_pool: Box<Account<'info, SeniorPool>>,
+
+    #[account(
+        mut,
+        address = senior_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub senior_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::INSURANCE_FUND, asset_pool.asset_address.as_ref()],
+        bump
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    #[account(
+        mut,
+        address = insurance_fund.vault @ PencilError::InvalidAccount
+    )]
+    pub insurance_fund_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::INSURANCE_DRAW, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub insurance_draw: Box<Account<'info, InsuranceDraw>>,
+
+    /// GROW Mint（按总量累加 Senior 分配指数）
+    #[account(
+        seeds = [seeds::GROW_TOKEN_MINT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub grow_token_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 执行提案：从保险基金划入优先池 Vault，按 Senior 还款入账
+/// 执行时重新校验缺口、单池上限与基金余额
+pub fn execute_insurance_draw(ctx: Context<ExecuteInsuranceDraw>) -> Result<()> {
+    let asset_pool = &ctx.accounts.asset_pool;
+    require!(
+        asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING,
+        PencilError::InvalidAssetPoolStatus
+    );
+
+    let amount = ctx.accounts.insurance_draw.pending_amount;
+    require!(amount > 0, PencilError::InvalidInsuranceDraw);
+    let (_, limit) = insurance_draw_limit(
+        asset_pool,
+        &ctx.accounts.senior_pool,
+        &ctx.accounts.insurance_fund,
+        &ctx.accounts.insurance_draw,
+        ctx.accounts.insurance_fund_vault.amount,
+    )?;
+    require!(amount <= limit, PencilError::InsuranceDrawExceeded);
+
+    let asset_mint = ctx.accounts.insurance_fund.asset_mint;
+    let insurance_fund_seeds = &[
+        seeds::INSURANCE_FUND,
+        asset_mint.as_ref(),
+        &[ctx.bumps.insurance_fund],
+    ];
+    let signer_seeds = &[&insurance_fund_seeds[..]];
+    let cpi_accounts = Transfer {
+        from: ctx.accounts.insurance_fund_vault.to_account_info(),
+        to: ctx.accounts.senior_pool_vault.to_account_info(),
+        authority: ctx.accounts.insurance_fund.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    token::transfer(cpi_ctx, amount)?;
+
+    // 与还款一致：计入 Senior 已还金额、早退流动性与分配指数
+    let senior_pool = &mut ctx.accounts.senior_pool;
+    senior_pool.repaid_amount = senior_pool
+        .repaid_amount
+        .checked_add(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    senior_pool.exit_liquidity = senior_pool
+        .exit_liquidity
+        .checked_add(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    accrue_senior_distribution(senior_pool, ctx.accounts.grow_token_mint.supply, amount)?;
+
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    insurance_fund.total_drawn = insurance_fund
+        .total_drawn
+        .checked_add(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    let insurance_draw = &mut ctx.accounts.insurance_draw;
+    insurance_draw.drawn_amount = insurance_draw
+        .drawn_amount
+        .checked_add(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    insurance_draw.pending_amount = 0;
+
+    emit!(crate::InsuranceDrawExecuted {
+        insurance_fund: insurance_fund.key(),
+        asset_pool: asset_pool.key(),
+        approver: ctx.accounts.super_admin.key(),
+        amount,
+        pool_drawn: insurance_draw.drawn_amount,
+        fund_balance: ctx.accounts.insurance_fund_vault.amount - amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "保险基金提取完成 - 资产池: {}, 金额: {}, 累计: {}",
+        asset_pool.key(),
+        amount,
+        insurance_draw.drawn_amount
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelInsuranceDraw<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.super_admin == admin.key()
+            || system_config.operation_admin == admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::INSURANCE_DRAW, insurance_draw.asset_pool.as_ref()],
+        bump
+    )]
+    pub insurance_draw: Box<Account<'info, InsuranceDraw>>,
+}
+
+/// 撤销待执行的提取提案（超级管理员或运营管理员）
+pub fn cancel_insurance_draw(ctx: Context<CancelInsuranceDraw>) -> Result<()> {
+    let insurance_draw = &mut ctx.accounts.insurance_draw;
+    let amount = insurance_draw.pending_amount;
+    require!(amount > 0, PencilError::InvalidInsuranceDraw);
+    insurance_draw.pending_amount = 0;
+
+    emit!(crate::InsuranceDrawCancelled {
+        insurance_fund: insurance_draw.insurance_fund,
+        asset_pool: insurance_draw.asset_pool,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("保险基金提取提案已撤销: {}", insurance_draw.asset_pool);
+
+    Ok(())
+}
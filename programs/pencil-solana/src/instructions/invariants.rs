@@ -0,0 +1,287 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::state::{AssetPool, FirstLossPool, JuniorInterestPool, SeniorPool, SystemConfig};
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+/// 资产池各 Vault 的账面应有余额
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExpectedVaultBalances {
+    /// 募资 Vault：募资阶段为认购总额，募资完成后应为 0
+    pub asset_pool_vault: u64,
+    /// 优先池 Vault：Senior 存款 + Senior 已收还款 - 锁定 GROW 已领取的分配
+    pub senior_pool_vault: u64,
+    /// 首损池 Vault：Junior 存款 - 已动用 - 已提取本金
+    pub first_loss_pool_vault: u64,
+    /// 利息池 Vault：Junior 利息总额 - 已分配利息
+    pub junior_interest_pool_vault: u64,
+}
+
+/// 根据 AssetPool 与各子池账面重新计算各 Vault 应有余额
+pub fn calculate_expected_vault_balances(
+    asset_pool: &AssetPool,
+    senior_pool: &SeniorPool,
+    first_loss_pool: &FirstLossPool,
+    junior_interest_pool: &JuniorInterestPool,
+) -> Result<ExpectedVaultBalances> {
+    // 募资完成前认购资金留在募资 Vault 中
+    let funding_open = asset_pool.status == asset_pool_status::CREATED
+        || asset_pool.status == asset_pool_status::APPROVED
+        || asset_pool.status == asset_pool_status::CANCELLED;
+    let asset_pool_vault = if funding_open {
+        asset_pool
+            .senior_amount
+            .checked_add(asset_pool.junior_amount)
+            .ok_or(PencilError::ArithmeticOverflow)?
+    } else {
+        0
+    };
+
+    Ok(ExpectedVaultBalances {
+        asset_pool_vault,
+        senior_pool_vault: senior_pool
+            .total_deposits
+            .checked_add(senior_pool.repaid_amount)
+            .ok_or(PencilError::ArithmeticOverflow)?
+            .checked_sub(senior_pool.distributed_amount)
+            .ok_or(PencilError::ArithmeticOverflow)?,
+        first_loss_pool_vault: expected_first_loss_balance(first_loss_pool)?,
+        junior_interest_pool_vault: junior_interest_pool
+            .total_interest
+            .checked_sub(junior_interest_pool.distributed_interest)
+            .ok_or(PencilError::ArithmeticOverflow)?,
+    })
+}
+
+/// 首损池 Vault 应有余额
+/// 已动用金额与已提取本金均按实际转出的代币数量记录，两者互不重叠
+pub fn expected_first_loss_balance(first_loss_pool: &FirstLossPool) -> Result<u64> {
+    Ok(first_loss_pool
+        .total_deposits
+        .checked_sub(first_loss_pool.repaid_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_sub(first_loss_pool.withdrawn_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?)
+}
+
+/// 计算单个 Vault 的账实差异，返回 (短缺, 盈余)
+pub fn calculate_vault_drift(expected: u64, actual: u64) -> (u64, u64) {
+    if actual < expected {
+        (expected - actual, 0)
+    } else {
+        (0, actual - expected)
+    }
+}
+
+/// 资产池对账（无需权限，任何人可调用）
+#[derive(Accounts)]
+pub struct CheckPoolInvariants<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.related_accounts_initialized @ PencilError::RelatedAccountsNotInitialized
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        seeds = [seeds::SENIOR_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub senior_pool: Box<Account<'info, SeniorPool>>,
+
+    #[account(
+        seeds = [seeds::FIRST_LOSS_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub first_loss_pool: Box<Account<'info, FirstLossPool>>,
+
+    #[account(
+        seeds = [seeds::JUNIOR_INTEREST_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub junior_interest_pool: Box<Account<'info, JuniorInterestPool>>,
+
+    #[account(address = asset_pool.asset_pool_vault @ PencilError::InvalidAccount)]
+    pub asset_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = senior_pool.vault @ PencilError::InvalidAccount)]
+    pub senior_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = first_loss_pool.vault @ PencilError::InvalidAccount)]
+    pub first_loss_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = junior_interest_pool.vault @ PencilError::InvalidAccount)]
+    pub junior_interest_pool_vault: Box<Account<'info, TokenAccount>>,
+}
+
+pub fn check_pool_invariants(ctx: Context<CheckPoolInvariants>) -> Result<()> {
+    let clock = Clock::get()?;
+
+    let expected = calculate_expected_vault_balances(
+        &ctx.accounts.asset_pool,
+        &ctx.accounts.senior_pool,
+        &ctx.accounts.first_loss_pool,
+        &ctx.accounts.junior_interest_pool,
+    )?;
+    let actual = ExpectedVaultBalances {
+        asset_pool_vault: ctx.accounts.asset_pool_vault.amount,
+        senior_pool_vault: ctx.accounts.senior_pool_vault.amount,
+        first_loss_pool_vault: ctx.accounts.first_loss_pool_vault.amount,
+        junior_interest_pool_vault: ctx.accounts.junior_interest_pool_vault.amount,
+    };
+
+    // 短缺说明资金少于账面，盈余（例如他人直接转入）不影响兑付，只记录不暂停
+    let mut total_shortfall = 0u64;
+    let mut total_surplus = 0u64;
+    for (expected_balance, actual_balance) in [
+        (expected.asset_pool_vault, actual.asset_pool_vault),
+        (expected.senior_pool_vault, actual.senior_pool_vault),
+        (expected.first_loss_pool_vault, actual.first_loss_pool_vault),
+        (
+            expected.junior_interest_pool_vault,
+            actual.junior_interest_pool_vault,
+        ),
+    ] {
+        let (shortfall, surplus) = calculate_vault_drift(expected_balance, actual_balance);
+        total_shortfall = total_shortfall
+            .checked_add(shortfall)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        total_surplus = total_surplus
+            .checked_add(surplus)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+
+    // 短缺超过阈值时自动暂停资产池
+    let max_vault_drift = ctx.accounts.system_config.max_vault_drift;
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let auto_paused =
+        max_vault_drift > 0 && total_shortfall > max_vault_drift && !asset_pool.paused;
+    if auto_paused {
+        asset_pool.paused = true;
+        msg!(
+            "Asset pool paused: shortfall {} exceeds threshold {}",
+            total_shortfall,
+            max_vault_drift
+        );
+    }
+
+    emit!(crate::PoolInvariantsChecked {
+        asset_pool: asset_pool.key(),
+        expected_asset_pool_vault: expected.asset_pool_vault,
+        actual_asset_pool_vault: actual.asset_pool_vault,
+        expected_senior_pool_vault: expected.senior_pool_vault,
+        actual_senior_pool_vault: actual.senior_pool_vault,
+        expected_first_loss_pool_vault: expected.first_loss_pool_vault,
+        actual_first_loss_pool_vault: actual.first_loss_pool_vault,
+        expected_junior_interest_pool_vault: expected.junior_interest_pool_vault,
+        actual_junior_interest_pool_vault: actual.junior_interest_pool_vault,
+        total_shortfall,
+        total_surplus,
+        auto_paused,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Pool invariants checked - shortfall: {}, surplus: {}",
+        total_shortfall,
+        total_surplus
+    );
+
+    Ok(())
+}
+
+/// 手动暂停/恢复单个资产池
+#[derive(Accounts)]
+pub struct SetAssetPoolPaused<'info> {
+    #[account(mut)]
+    pub super_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.super_admin == super_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Account<'info, AssetPool>,
+}
+
+pub fn set_asset_pool_paused(ctx: Context<SetAssetPoolPaused>, paused: bool) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let clock = Clock::get()?;
+
+    asset_pool.paused = paused;
+
+    emit!(crate::AssetPoolPauseUpdated {
+        asset_pool: asset_pool.key(),
+        paused,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Asset pool paused: {}", paused);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::waterfall::calculate_principal_withdrawal;
+
+    fn first_loss_pool(total_deposits: u64) -> FirstLossPool {
+        FirstLossPool {
+            asset_pool: Pubkey::default(),
+            junior_nft: Pubkey::default(),
+            total_deposits,
+            repaid_amount: 0,
+            vault: Pubkey::default(),
+            withdrawn_shares: 0,
+            withdrawn_amount: 0,
+            _reserved: [0u8; 80],
+        }
+    }
+
+    #[test]
+    fn test_first_loss_balance_after_draw_and_withdrawals() {
+        // Junior 存款 200（两个 NFT 各 100），还款不足时动用 50
+        let mut pool = first_loss_pool(200);
+        let mut vault = 200u64;
+        pool.repaid_amount = 50;
+        vault -= 50;
+        assert_eq!(expected_first_loss_balance(&pool).unwrap(), vault);
+
+        // 资产池完成后两个 NFT 依次提取本金，对账始终一致
+        for shares in [100, 100] {
+            let amount = calculate_principal_withdrawal(
+                vault,
+                shares,
+                pool.total_deposits,
+                pool.withdrawn_shares,
+            )
+            .unwrap();
+            assert_eq!(amount, 75);
+            vault -= amount;
+            pool.withdrawn_shares += shares;
+            pool.withdrawn_amount += amount;
+            assert_eq!(expected_first_loss_balance(&pool).unwrap(), vault);
+        }
+        assert_eq!(vault, 0);
+
+        // 份额全部提取后不能再次提取
+        assert!(calculate_principal_withdrawal(vault, 100, 200, 200).is_err());
+    }
+}
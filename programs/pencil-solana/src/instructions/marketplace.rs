@@ -0,0 +1,671 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::instructions::utils::pool_fee_destination;
+use crate::state::{AssetPool, JuniorNFTMetadata, Listing, SystemConfig};
+use crate::waterfall::bps_of;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+// ListingCreated / ListingFilled / ListingCancelled events are defined in lib.rs
+
+/// 挂单与成交只在募资完成后、资产池完成前进行
+fn require_tradable(asset_pool: &AssetPool) -> Result<()> {
+    require!(
+        asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING,
+        PencilError::InvalidAssetPoolStatus
+    );
+    require!(
+        asset_pool.related_accounts_initialized,
+        PencilError::RelatedAccountsNotInitialized
+    );
+    Ok(())
+}
+
+/// 托管卖方持仓并写入挂单
+#[allow(clippy::too_many_arguments)]
+fn open_listing<'info>(
+    listing: &mut Account<'info, Listing>,
+    asset_pool: &Account<'info, AssetPool>,
+    seller: &Signer<'info>,
+    seller_position_account: &Account<'info, TokenAccount>,
+    listing_escrow: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    listing_id: u64,
+    kind: u8,
+    nft_id: u64,
+    amount: u64,
+    unit_price: u64,
+) -> Result<()> {
+    require!(amount > 0 && unit_price > 0, PencilError::InvalidListing);
+    require!(
+        seller_position_account.amount >= amount,
+        PencilError::InsufficientBalance
+    );
+
+    let escrow_cpi_accounts = Transfer {
+        from: seller_position_account.to_account_info(),
+        to: listing_escrow.to_account_info(),
+        authority: seller.to_account_info(),
+    };
+    let escrow_cpi_program = token_program.to_account_info();
+    token::transfer(
+        CpiContext::new(escrow_cpi_program, escrow_cpi_accounts),
+        amount,
+    )?;
+
+    let clock = Clock::get()?;
+    listing.asset_pool = asset_pool.key();
+    listing.seller = seller.key();
+    listing.listing_id = listing_id;
+    listing.kind = kind;
+    listing.position_mint = listing_escrow.mint;
+    listing.nft_id = nft_id;
+    listing.amount = amount;
+    listing.filled_amount = 0;
+    listing.unit_price = unit_price;
+    listing.created_at = clock.unix_timestamp;
+
+    emit!(crate::ListingCreated {
+        asset_pool: asset_pool.key(),
+        listing: listing.key(),
+        seller: seller.key(),
+        listing_id,
+        kind,
+        nft_id,
+        amount,
+        unit_price,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "挂单已创建 - 卖方: {}, 挂单 ID: {}, 类型: {}, 数量: {}, 单价: {}",
+        seller.key(),
+        listing_id,
+        kind,
+        amount,
+        unit_price
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct CreateGrowListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + std::mem::size_of::<Listing>(),
+        seeds = [
+            seeds::LISTING,
+            asset_pool.key().as_ref(),
+            seller.key().as_ref(),
+            &listing_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    #[account(
+        seeds = [seeds::GROW_TOKEN_MINT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub grow_token_mint: Box<Account<'info, Mint>>,
+
+    /// 挂单托管账户（资产池为权限人）
+    #[account(
+        init,
+        payer = seller,
+        seeds = [seeds::LISTING_ESCROW, listing.key().as_ref()],
+        bump,
+        token::mint = grow_token_mint,
+        token::authority = asset_pool
+    )]
+    pub listing_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = grow_token_mint,
+        token::authority = seller
+    )]
+    pub seller_grow_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 挂出 GROW：数量 `amount`，每个 GROW 的价格 `unit_price`（以资产代币计，可部分成交）
+pub fn create_grow_listing(
+    ctx: Context<CreateGrowListing>,
+    listing_id: u64,
+    amount: u64,
+    unit_price: u64,
+) -> Result<()> {
+    require_tradable(&ctx.accounts.asset_pool)?;
+
+    open_listing(
+        &mut ctx.accounts.listing,
+        &ctx.accounts.asset_pool,
+        &ctx.accounts.seller,
+        &ctx.accounts.seller_grow_account,
+        &ctx.accounts.listing_escrow,
+        &ctx.accounts.token_program,
+        listing_id,
+        listing_kind::GROW,
+        0,
+        amount,
+        unit_price,
+    )
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: u64, nft_id: u64)]
+pub struct CreateJuniorNftListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + std::mem::size_of::<Listing>(),
+        seeds = [
+            seeds::LISTING,
+            asset_pool.key().as_ref(),
+            seller.key().as_ref(),
+            &listing_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    #[account(
+        seeds = [seeds::JUNIOR_NFT_METADATA, asset_pool.key().as_ref(), &nft_id.to_le_bytes()],
+        bump,
+        constraint = nft_metadata.owner == seller.key() @ PencilError::Unauthorized,
+        constraint = !nft_metadata.principal_withdrawn @ PencilError::NoPrincipalToWithdraw
+    )]
+    pub nft_metadata: Box<Account<'info, JuniorNFTMetadata>>,
+
+    #[account(
+        seeds = [seeds::JUNIOR_NFT_MINT, asset_pool.key().as_ref(), &nft_id.to_le_bytes()],
+        bump
+    )]
+    pub junior_nft_mint: Box<Account<'info, Mint>>,
+
+    /// 挂单托管账户（资产池为权限人）
+    #[account(
+        init,
+        payer = seller,
+        seeds = [seeds::LISTING_ESCROW, listing.key().as_ref()],
+        bump,
+        token::mint = junior_nft_mint,
+        token::authority = asset_pool
+    )]
+    pub listing_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = junior_nft_mint,
+        token::authority = seller
+    )]
+    pub seller_nft_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 挂出 Junior NFT：整单价格 `price`，只能整单成交
+/// 未领取的利息随 NFT 一并转让，卖方需在挂单前自行领取
+pub fn create_junior_nft_listing(
+    ctx: Context<CreateJuniorNftListing>,
+    listing_id: u64,
+    nft_id: u64,
+    price: u64,
+) -> Result<()> {
+    require_tradable(&ctx.accounts.asset_pool)?;
+
+    open_listing(
+        &mut ctx.accounts.listing,
+        &ctx.accounts.asset_pool,
+        &ctx.accounts.seller,
+        &ctx.accounts.seller_nft_account,
+        &ctx.accounts.listing_escrow,
+        &ctx.accounts.token_program,
+        listing_id,
+        listing_kind::JUNIOR_NFT,
+        nft_id,
+        1,
+        price,
+    )
+}
+
+/// 成交挂单
+///
+/// Junior NFT 挂单需传入 nft_metadata，成交后所有者改为买方；GROW 挂单不传
+#[derive(Accounts)]
+pub struct FillListing<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [
+            seeds::LISTING,
+            asset_pool.key().as_ref(),
+            listing.seller.as_ref(),
+            &listing.listing_id.to_le_bytes()
+        ],
+        bump,
+        has_one = seller @ PencilError::InvalidListing,
+        has_one = position_mint @ PencilError::InvalidListing
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    /// CHECK: 挂单卖方，由 has_one 约束校验，接收货款与关闭账户的租金
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    pub position_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::JUNIOR_NFT_METADATA, asset_pool.key().as_ref(), &listing.nft_id.to_le_bytes()],
+        bump
+    )]
+    pub nft_metadata: Option<Box<Account<'info, JuniorNFTMetadata>>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::LISTING_ESCROW, listing.key().as_ref()],
+        bump
+    )]
+    pub listing_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = position_mint,
+        associated_token::authority = buyer
+    )]
+    pub buyer_position_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = asset_mint,
+        token::authority = buyer
+    )]
+    pub buyer_asset_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = asset_mint,
+        token::authority = seller
+    )]
+    pub seller_asset_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = pool_fee_destination(&asset_pool, &system_config) @ PencilError::InvalidAccount
+    )]
+    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = asset_pool.asset_address @ PencilError::InvalidAccount)]
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 二级市场挂单单笔成交
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ListingSettlement {
+    /// 买方支付的总金额（含协议费）
+    pub cost: u64,
+    /// 协议费
+    pub fee: u64,
+}
+
+impl ListingSettlement {
+    /// 卖方实际获得的金额
+    pub fn net(&self) -> Result<u64> {
+        Ok(self
+            .cost
+            .checked_sub(self.fee)
+            .ok_or(PencilError::ArithmeticOverflow)?)
+    }
+}
+
+/// 计算挂单成交金额
+/// cost = fill_amount × unit_price / unit（向上取整，避免拆单压低卖方所得），协议费从 cost 中扣除
+pub fn calculate_listing_settlement(
+    fill_amount: u64,
+    unit_price: u64,
+    unit: u64,
+    fee_rate: u16,
+) -> Result<ListingSettlement> {
+    require!(unit > 0, PencilError::ArithmeticOverflow);
+
+    let cost = (fill_amount as u128)
+        .checked_mul(unit_price as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_add(unit as u128 - 1)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_div(unit as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let cost = u64::try_from(cost).map_err(|_| error!(PencilError::ArithmeticOverflow))?;
+    let fee = bps_of(cost, fee_rate)?;
+
+    Ok(ListingSettlement { cost, fee })
+}
+
+/// 成交挂单：买方支付 `amount × 单价`（协议费转入金库），托管的持仓转给买方
+/// GROW 挂单可部分成交；全部成交后关闭托管账户与挂单账户，租金退还卖方
+pub fn fill_listing(ctx: Context<FillListing>, amount: u64) -> Result<()> {
+    require_tradable(&ctx.accounts.asset_pool)?;
+
+    let listing = &ctx.accounts.listing;
+    require!(
+        ctx.accounts.buyer.key() != listing.seller,
+        PencilError::InvalidListing
+    );
+    require!(amount > 0, PencilError::InvalidListing);
+    require!(amount <= listing.amount, PencilError::ListingAmountExceeded);
+
+    let unit = match listing.kind {
+        listing_kind::GROW => DECIMAL_MULTIPLIER,
+        listing_kind::JUNIOR_NFT => {
+            require!(amount == listing.amount, PencilError::InvalidListing);
+            1
+        }
+        _ => return err!(PencilError::InvalidListing),
+    };
+    let settlement = calculate_listing_settlement(
+        amount,
+        listing.unit_price,
+        unit,
+        ctx.accounts.system_config.marketplace_fee_rate,
+    )?;
+    let net_amount = settlement.net()?;
+
+    // 1. 买方付款：净额给卖方，协议费进金库
+    if net_amount > 0 {
+        let payment_cpi_accounts = Transfer {
+            from: ctx.accounts.buyer_asset_account.to_account_info(),
+            to: ctx.accounts.seller_asset_account.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        };
+        let payment_cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new(payment_cpi_program, payment_cpi_accounts),
+            net_amount,
+        )?;
+    }
+    if settlement.fee > 0 {
+        let fee_cpi_accounts = Transfer {
+            from: ctx.accounts.buyer_asset_account.to_account_info(),
+            to: ctx.accounts.treasury_ata.to_account_info(),
+            authority: ctx.accounts.buyer.to_account_info(),
+        };
+        let fee_cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new(fee_cpi_program, fee_cpi_accounts),
+            settlement.fee,
+        )?;
+    }
+
+    // 2. 托管的持仓转给买方
+    let asset_pool = &ctx.accounts.asset_pool;
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[ctx.bumps.asset_pool],
+    ];
+    let signer_seeds = &[&asset_pool_seeds[..]];
+    let position_cpi_accounts = Transfer {
+        from: ctx.accounts.listing_escrow.to_account_info(),
+        to: ctx.accounts.buyer_position_account.to_account_info(),
+        authority: ctx.accounts.asset_pool.to_account_info(),
+    };
+    let position_cpi_program = ctx.accounts.token_program.to_account_info();
+    token::transfer(
+        CpiContext::new_with_signer(position_cpi_program, position_cpi_accounts, signer_seeds),
+        amount,
+    )?;
+
+    // 3. Junior NFT 所有者改为买方
+    if ctx.accounts.listing.kind == listing_kind::JUNIOR_NFT {
+        let buyer_key = ctx.accounts.buyer.key();
+        let nft_metadata = ctx
+            .accounts
+            .nft_metadata
+            .as_mut()
+            .ok_or(PencilError::InvalidAccount)?;
+        nft_metadata.owner = buyer_key;
+    }
+
+    let listing = &mut ctx.accounts.listing;
+    listing.amount = listing
+        .amount
+        .checked_sub(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    listing.filled_amount = listing
+        .filled_amount
+        .checked_add(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let remaining = listing.amount;
+
+    emit!(crate::ListingFilled {
+        asset_pool: ctx.accounts.asset_pool.key(),
+        listing: listing.key(),
+        seller: listing.seller,
+        buyer: ctx.accounts.buyer.key(),
+        listing_id: listing.listing_id,
+        kind: listing.kind,
+        nft_id: listing.nft_id,
+        amount,
+        cost: settlement.cost,
+        fee: settlement.fee,
+        remaining,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "挂单成交 - 挂单 ID: {}, 买方: {}, 数量: {}, 金额: {}, 协议费: {}, 剩余: {}",
+        listing.listing_id,
+        ctx.accounts.buyer.key(),
+        amount,
+        settlement.cost,
+        settlement.fee,
+        remaining
+    );
+
+    // 4. 全部成交后关闭托管账户与挂单账户
+    if remaining == 0 {
+        let close_cpi_accounts = CloseAccount {
+            account: ctx.accounts.listing_escrow.to_account_info(),
+            destination: ctx.accounts.seller.to_account_info(),
+            authority: ctx.accounts.asset_pool.to_account_info(),
+        };
+        let close_cpi_program = ctx.accounts.token_program.to_account_info();
+        token::close_account(CpiContext::new_with_signer(
+            close_cpi_program,
+            close_cpi_accounts,
+            signer_seeds,
+        ))?;
+        ctx.accounts
+            .listing
+            .close(ctx.accounts.seller.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            seeds::LISTING,
+            asset_pool.key().as_ref(),
+            seller.key().as_ref(),
+            &listing.listing_id.to_le_bytes()
+        ],
+        bump,
+        has_one = seller @ PencilError::Unauthorized
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::LISTING_ESCROW, listing.key().as_ref()],
+        bump
+    )]
+    pub listing_escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = listing.position_mint,
+        token::authority = seller
+    )]
+    pub seller_position_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// 撤单：退还未成交的持仓并关闭托管账户与挂单账户（任何状态均可撤单）
+pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+    let remaining = ctx.accounts.listing.amount;
+
+    let asset_pool = &ctx.accounts.asset_pool;
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[ctx.bumps.asset_pool],
+    ];
+    let signer_seeds = &[&asset_pool_seeds[..]];
+
+    if remaining > 0 {
+        let transfer_cpi_accounts = Transfer {
+            from: ctx.accounts.listing_escrow.to_account_info(),
+            to: ctx.accounts.seller_position_account.to_account_info(),
+            authority: ctx.accounts.asset_pool.to_account_info(),
+        };
+        let transfer_cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(transfer_cpi_program, transfer_cpi_accounts, signer_seeds),
+            remaining,
+        )?;
+    }
+
+    let close_cpi_accounts = CloseAccount {
+        account: ctx.accounts.listing_escrow.to_account_info(),
+        destination: ctx.accounts.seller.to_account_info(),
+        authority: ctx.accounts.asset_pool.to_account_info(),
+    };
+    let close_cpi_program = ctx.accounts.token_program.to_account_info();
+    token::close_account(CpiContext::new_with_signer(
+        close_cpi_program,
+        close_cpi_accounts,
+        signer_seeds,
+    ))?;
+
+    let listing = &ctx.accounts.listing;
+    emit!(crate::ListingCancelled {
+        asset_pool: asset_pool.key(),
+        listing: listing.key(),
+        seller: listing.seller,
+        listing_id: listing.listing_id,
+        returned_amount: remaining,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "挂单已撤销 - 挂单 ID: {}, 退还数量: {}",
+        listing.listing_id,
+        remaining
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listing_settlement_rounds_cost_up() {
+        // 单价 1.05：成交 10 个 GROW 支付 10.5，协议费 1%
+        let settlement =
+            calculate_listing_settlement(10_000_000, 1_050_000, DECIMAL_MULTIPLIER, 100).unwrap();
+        assert_eq!(settlement.cost, 10_500_000);
+        assert_eq!(settlement.fee, 105_000);
+        assert_eq!(settlement.net().unwrap(), 10_395_000);
+        // 不足最小单位的部分向上取整
+        let settlement = calculate_listing_settlement(1, 1_050_000, DECIMAL_MULTIPLIER, 0).unwrap();
+        assert_eq!(settlement.cost, 2);
+        // NFT 按整单计价
+        let settlement = calculate_listing_settlement(1, 300, 1, 500).unwrap();
+        assert_eq!((settlement.cost, settlement.fee), (300, 15));
+        assert!(calculate_listing_settlement(u64::MAX, u64::MAX, 1, 0).is_err());
+    }
+}
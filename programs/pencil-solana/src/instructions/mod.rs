@@ -3,6 +3,16 @@ pub mod asset_pool;
 pub mod funding;
 pub mod repayment;
 pub mod tokens;
+pub mod invariants;
+pub mod originator;
+pub mod distribution;
+pub mod exit_queue;
+pub mod marketplace;
+pub mod reserve;
+pub mod insurance;
+pub mod fee_split;
+pub mod referral;
+pub mod pool_params;
 pub mod utils;
 
 pub use system_config::*;
@@ -10,5 +20,15 @@ pub use asset_pool::*;
 pub use funding::*;
 pub use repayment::*;
 pub use tokens::*;
+pub use invariants::*;
+pub use originator::*;
+pub use distribution::*;
+pub use exit_queue::*;
+pub use marketplace::*;
+pub use reserve::*;
+pub use insurance::*;
+pub use fee_split::*;
+pub use referral::*;
+pub use pool_params::*;
 pub use utils::*;
 
@@ -0,0 +1,444 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::instructions::funding::funding_failed;
+use crate::state::{AssetPool, Originator, SystemConfig};
+use crate::waterfall::calculate_remaining_principal;
+use anchor_lang::prelude::*;
+
+/// 调整资产池计入发起方的未偿本金，增加时校验敞口上限
+pub fn set_pool_exposure(
+    originator: &mut Account<Originator>,
+    asset_pool_key: Pubkey,
+    asset_pool: &mut AssetPool,
+    exposure: u64,
+    timestamp: i64,
+) -> Result<()> {
+    let originator_key = originator.key();
+    update_pool_exposure(
+        originator,
+        originator_key,
+        asset_pool_key,
+        asset_pool,
+        exposure,
+        timestamp,
+    )
+}
+
+fn update_pool_exposure(
+    originator: &mut Originator,
+    originator_key: Pubkey,
+    asset_pool_key: Pubkey,
+    asset_pool: &mut AssetPool,
+    exposure: u64,
+    timestamp: i64,
+) -> Result<()> {
+    let outstanding = originator
+        .outstanding_principal
+        .checked_sub(asset_pool.originator_exposure)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_add(exposure)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    if exposure > asset_pool.originator_exposure {
+        require!(
+            outstanding <= originator.max_outstanding,
+            PencilError::OriginatorExposureExceeded
+        );
+    }
+
+    originator.outstanding_principal = outstanding;
+    asset_pool.originator_exposure = exposure;
+
+    emit!(crate::OriginatorExposureUpdated {
+        originator: originator_key,
+        asset_pool: asset_pool_key,
+        pool_exposure: exposure,
+        outstanding_principal: outstanding,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// 资产池应计入发起方的敞口
+/// 募资完成前为募资目标（募资期结束且募资失败后为 0）；募资完成后为未偿本金（Senior + Junior），
+/// 随已还期数递减；完成或取消后为 0
+pub fn pool_exposure(asset_pool: &AssetPool, timestamp: i64) -> Result<u64> {
+    match asset_pool.status {
+        asset_pool_status::CREATED | asset_pool_status::APPROVED => {
+            if timestamp > asset_pool.funding_end_time && funding_failed(asset_pool)? {
+                Ok(0)
+            } else {
+                Ok(asset_pool.total_amount)
+            }
+        }
+        asset_pool_status::FUNDED | asset_pool_status::REPAYING => calculate_remaining_principal(
+            asset_pool.total_amount,
+            asset_pool.repayment_count,
+            asset_pool.repaid_periods as u64,
+        ),
+        _ => Ok(0),
+    }
+}
+
+/// 按资产池当前状态同步敞口（无变化时同样发出事件），完成或取消后首次释放时记入发起方历史
+fn sync_pool_exposure(
+    originator: &mut Originator,
+    originator_key: Pubkey,
+    asset_pool_key: Pubkey,
+    asset_pool: &mut AssetPool,
+    timestamp: i64,
+) -> Result<()> {
+    let exposure = pool_exposure(asset_pool, timestamp)?;
+    if exposure == 0 && asset_pool.originator_exposure != 0 {
+        if asset_pool.status == asset_pool_status::COMPLETED {
+            originator.completed_pools = originator
+                .completed_pools
+                .checked_add(1)
+                .ok_or(PencilError::ArithmeticOverflow)?;
+        } else if asset_pool.status == asset_pool_status::CANCELLED {
+            originator.cancelled_pools = originator
+                .cancelled_pools
+                .checked_add(1)
+                .ok_or(PencilError::ArithmeticOverflow)?;
+        }
+    }
+
+    update_pool_exposure(
+        originator,
+        originator_key,
+        asset_pool_key,
+        asset_pool,
+        exposure,
+        timestamp,
+    )
+}
+
+/// 读取资产池创建者的发起方账户（地址由指令按种子校验）
+/// 发起方登记前创建的资产池，其创建者可能没有发起方账户，此时返回 None
+pub(crate) fn load_originator(originator: &AccountInfo) -> Result<Option<Originator>> {
+    if originator.owner != &crate::ID || originator.data_is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Originator::try_deserialize(
+        &mut &originator.try_borrow_data()?[..],
+    )?))
+}
+
+/// 状态变化后同步发起方敞口并写回发起方账户
+pub(crate) fn sync_originator_account(
+    originator_info: &AccountInfo,
+    originator: &mut Originator,
+    asset_pool_key: Pubkey,
+    asset_pool: &mut AssetPool,
+    timestamp: i64,
+) -> Result<()> {
+    sync_pool_exposure(
+        originator,
+        originator_info.key(),
+        asset_pool_key,
+        asset_pool,
+        timestamp,
+    )?;
+    originator.try_serialize(&mut &mut originator_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// 调整资产池计入发起方的敞口并写回发起方账户；创建者没有发起方账户时跳过
+pub(crate) fn set_originator_account_exposure(
+    originator_info: &AccountInfo,
+    asset_pool_key: Pubkey,
+    asset_pool: &mut AssetPool,
+    exposure: u64,
+    timestamp: i64,
+) -> Result<()> {
+    let Some(mut originator) = load_originator(originator_info)? else {
+        return Ok(());
+    };
+    update_pool_exposure(
+        &mut originator,
+        originator_info.key(),
+        asset_pool_key,
+        asset_pool,
+        exposure,
+        timestamp,
+    )?;
+    originator.try_serialize(&mut &mut originator_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// 资产池取消时释放敞口并记入发起方历史；创建者没有发起方账户时跳过
+/// 募资失败后的退款已将敞口释放为 0，因此取消次数在此处计数，而非释放敞口时
+pub(crate) fn record_pool_cancellation(
+    originator_info: &AccountInfo,
+    asset_pool_key: Pubkey,
+    asset_pool: &mut AssetPool,
+    timestamp: i64,
+) -> Result<()> {
+    let Some(mut originator) = load_originator(originator_info)? else {
+        return Ok(());
+    };
+    originator.cancelled_pools = originator
+        .cancelled_pools
+        .checked_add(1)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    update_pool_exposure(
+        &mut originator,
+        originator_info.key(),
+        asset_pool_key,
+        asset_pool,
+        0,
+        timestamp,
+    )?;
+    originator.try_serialize(&mut &mut originator_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct RegisterOriginator<'info> {
+    #[account(mut)]
+    pub operation_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.operation_admin == operation_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+
+    #[account(
+        init,
+        payer = operation_admin,
+        space = 8 + std::mem::size_of::<Originator>() + 4 + (32 * MAX_ORIGINATOR_ASSETS), // 账户 + Vec长度 + 最多10个资产
+        seeds = [seeds::ORIGINATOR, authority.as_ref()],
+        bump
+    )]
+    pub originator: Account<'info, Originator>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn register_originator(
+    ctx: Context<RegisterOriginator>,
+    authority: Pubkey,
+    legal_name_hash: [u8; 32],
+    max_outstanding: u64,
+    allowed_assets: Vec<Pubkey>,
+) -> Result<()> {
+    require!(
+        allowed_assets.len() <= MAX_ORIGINATOR_ASSETS,
+        PencilError::TooManyOriginatorAssets
+    );
+
+    let originator = &mut ctx.accounts.originator;
+    let clock = Clock::get()?;
+
+    originator.authority = authority;
+    originator.legal_name_hash = legal_name_hash;
+    originator.max_outstanding = max_outstanding;
+    originator.outstanding_principal = 0;
+    originator.allowed_assets = allowed_assets;
+    originator.active = true;
+    originator.registered_at = clock.unix_timestamp;
+
+    emit!(crate::OriginatorRegistered {
+        originator: originator.key(),
+        authority,
+        legal_name_hash,
+        max_outstanding,
+        allowed_assets: originator.allowed_assets.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Originator registered: {}", authority);
+    msg!("Max outstanding: {}", max_outstanding);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateOriginator<'info> {
+    pub operation_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.operation_admin == operation_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, originator.authority.as_ref()],
+        bump
+    )]
+    pub originator: Account<'info, Originator>,
+}
+
+/// 调整敞口上限与可发行资产，或停用发起方（已有资产池不受影响）
+pub fn update_originator(
+    ctx: Context<UpdateOriginator>,
+    max_outstanding: u64,
+    allowed_assets: Vec<Pubkey>,
+    active: bool,
+) -> Result<()> {
+    require!(
+        allowed_assets.len() <= MAX_ORIGINATOR_ASSETS,
+        PencilError::TooManyOriginatorAssets
+    );
+
+    let originator = &mut ctx.accounts.originator;
+    originator.max_outstanding = max_outstanding;
+    originator.allowed_assets = allowed_assets;
+    originator.active = active;
+
+    emit!(crate::OriginatorUpdated {
+        originator: originator.key(),
+        max_outstanding,
+        allowed_assets: originator.allowed_assets.clone(),
+        active,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Originator updated: {}", originator.authority);
+
+    Ok(())
+}
+
+// 按资产池状态同步发起方敞口（任何人可调用）
+// 募资完成、退款、取消、还款与违约记录会自动同步；此指令用于补同步此前的资产池
+#[derive(Accounts)]
+pub struct SyncOriginatorExposure<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: Account<'info, Originator>,
+}
+
+pub fn sync_originator_exposure(ctx: Context<SyncOriginatorExposure>) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let originator = &mut ctx.accounts.originator;
+
+    let originator_key = originator.key();
+    let asset_pool_key = asset_pool.key();
+    sync_pool_exposure(
+        originator,
+        originator_key,
+        asset_pool_key,
+        asset_pool,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    msg!(
+        "Originator exposure synced: {}",
+        originator.outstanding_principal
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RecordOriginatorDefault<'info> {
+    pub operation_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.operation_admin == operation_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING @ PencilError::InvalidAssetPoolStatus
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: Account<'info, Originator>,
+}
+
+/// 运营管理员记录资产池违约
+pub fn record_originator_default(ctx: Context<RecordOriginatorDefault>) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let originator = &mut ctx.accounts.originator;
+
+    require!(
+        !asset_pool.originator_defaulted,
+        PencilError::OriginatorDefaultAlreadyRecorded
+    );
+    asset_pool.originator_defaulted = true;
+    originator.defaulted_pools = originator
+        .defaulted_pools
+        .checked_add(1)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    let clock = Clock::get()?;
+    let originator_key = originator.key();
+    let asset_pool_key = asset_pool.key();
+    sync_pool_exposure(
+        originator,
+        originator_key,
+        asset_pool_key,
+        asset_pool,
+        clock.unix_timestamp,
+    )?;
+
+    emit!(crate::OriginatorDefaultRecorded {
+        originator: originator.key(),
+        asset_pool: asset_pool.key(),
+        defaulted_pools: originator.defaulted_pools,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Originator default recorded: {}", asset_pool.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DelegateServicer<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, authority.key().as_ref()],
+        bump
+    )]
+    pub originator: Account<'info, Originator>,
+}
+
+/// 发起方委托还款服务方代为还款；传入 Pubkey::default 撤销委托
+pub fn delegate_servicer(ctx: Context<DelegateServicer>, servicer: Pubkey) -> Result<()> {
+    let originator = &mut ctx.accounts.originator;
+    let old_servicer = originator.servicer;
+    originator.servicer = servicer;
+
+    emit!(crate::ServicerDelegated {
+        originator: originator.key(),
+        authority: originator.authority,
+        old_servicer,
+        new_servicer: servicer,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Servicer delegated: {}", servicer);
+
+    Ok(())
+}
@@ -0,0 +1,269 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::instructions::asset_pool::open_amendment_withdraw_window;
+use crate::state::{AssetPool, PoolParamBounds, SystemConfig};
+use anchor_lang::prelude::*;
+
+// PoolParamBoundsUpdated / PoolParamsSynced events are defined in lib.rs
+
+/// 资产池参数（按 pool_param 编号），None 表示继承 SystemConfig 默认值
+pub type PoolParams = [Option<u16>; POOL_PARAM_COUNT];
+
+/// 参数的协议上下限
+pub fn protocol_bounds(param: u8) -> Result<PoolParamBounds> {
+    let (min, max) = match param {
+        pool_param::PLATFORM_FEE => (0, MAX_PLATFORM_FEE),
+        pool_param::SENIOR_EARLY_BEFORE_EXIT_FEE
+        | pool_param::SENIOR_EARLY_AFTER_EXIT_FEE
+        | pool_param::JUNIOR_EARLY_BEFORE_EXIT_FEE => (0, MAX_EARLY_EXIT_FEE),
+        pool_param::MIN_JUNIOR_RATIO => (MIN_JUNIOR_RATIO, MAX_JUNIOR_RATIO),
+        _ => return err!(PencilError::InvalidPoolParam),
+    };
+    Ok(PoolParamBounds { min, max })
+}
+
+/// 超出协议上下限时沿用各参数原有的错误码
+fn protocol_error(param: u8) -> PencilError {
+    match param {
+        pool_param::PLATFORM_FEE => PencilError::InvalidPlatformFee,
+        pool_param::MIN_JUNIOR_RATIO => PencilError::InvalidMinJuniorRatio,
+        _ => PencilError::InvalidEarlyExitFee,
+    }
+}
+
+/// 系统设置的取值范围，未设置时为 None
+pub fn system_bounds(system_config: &SystemConfig, param: u8) -> Option<PoolParamBounds> {
+    (system_config.pool_param_bounds_set & (1 << param) != 0)
+        .then(|| system_config.pool_param_bounds[param as usize])
+}
+
+/// SystemConfig 中的参数默认值
+pub fn system_default(system_config: &SystemConfig, param: u8) -> Result<u16> {
+    match param {
+        pool_param::PLATFORM_FEE => Ok(system_config.platform_fee_rate),
+        pool_param::SENIOR_EARLY_BEFORE_EXIT_FEE => {
+            Ok(system_config.senior_early_before_exit_fee_rate)
+        }
+        pool_param::SENIOR_EARLY_AFTER_EXIT_FEE => {
+            Ok(system_config.senior_early_after_exit_fee_rate)
+        }
+        pool_param::JUNIOR_EARLY_BEFORE_EXIT_FEE => {
+            Ok(system_config.junior_early_before_exit_fee_rate)
+        }
+        pool_param::MIN_JUNIOR_RATIO => Ok(system_config.default_min_junior_ratio),
+        _ => err!(PencilError::InvalidPoolParam),
+    }
+}
+
+/// 资产池字段中的生效值
+pub fn values_of(asset_pool: &AssetPool) -> [u16; POOL_PARAM_COUNT] {
+    [
+        asset_pool.platform_fee,
+        asset_pool.senior_early_before_exit_fee,
+        asset_pool.senior_early_after_exit_fee,
+        asset_pool.junior_early_before_exit_fee,
+        asset_pool.min_junior_ratio,
+    ]
+}
+
+/// 资产池参数，继承的参数为 None
+pub fn params_of(asset_pool: &AssetPool) -> PoolParams {
+    let values = values_of(asset_pool);
+    std::array::from_fn(|param| {
+        (asset_pool.inherited_params & (1 << param) == 0).then_some(values[param])
+    })
+}
+
+/// 校验取值是否在系统设置的范围内（未设置范围时不限制）
+pub fn check_system_bounds(system_config: &SystemConfig, param: u8, value: u16) -> Result<()> {
+    if let Some(bounds) = system_bounds(system_config, param) {
+        require!(
+            (bounds.min..=bounds.max).contains(&value),
+            PencilError::PoolParamOutOfBounds
+        );
+    }
+    Ok(())
+}
+
+/// 校验创建者覆盖值：先满足协议上下限，再满足系统设置的范围
+pub fn validate_override(system_config: &SystemConfig, param: u8, value: u16) -> Result<()> {
+    let bounds = protocol_bounds(param)?;
+    require!(
+        (bounds.min..=bounds.max).contains(&value),
+        protocol_error(param)
+    );
+    check_system_bounds(system_config, param, value)
+}
+
+/// 解析全部参数：覆盖值经校验后生效，其余取 SystemConfig 默认值。
+/// 返回 (生效值, 继承位图)
+pub fn resolve(
+    system_config: &SystemConfig,
+    params: &PoolParams,
+) -> Result<([u16; POOL_PARAM_COUNT], u8)> {
+    let mut values = [0u16; POOL_PARAM_COUNT];
+    let mut inherited_params = 0u8;
+    for (index, value) in params.iter().enumerate() {
+        let param = index as u8;
+        values[index] = match value {
+            Some(value) => {
+                validate_override(system_config, param, *value)?;
+                *value
+            }
+            None => {
+                inherited_params |= 1 << param;
+                system_default(system_config, param)?
+            }
+        };
+    }
+    Ok((values, inherited_params))
+}
+
+/// 写入单个参数的生效值
+fn set_value(asset_pool: &mut AssetPool, param: u8, value: u16) {
+    match param {
+        pool_param::PLATFORM_FEE => asset_pool.platform_fee = value,
+        pool_param::SENIOR_EARLY_BEFORE_EXIT_FEE => asset_pool.senior_early_before_exit_fee = value,
+        pool_param::SENIOR_EARLY_AFTER_EXIT_FEE => asset_pool.senior_early_after_exit_fee = value,
+        pool_param::JUNIOR_EARLY_BEFORE_EXIT_FEE => asset_pool.junior_early_before_exit_fee = value,
+        _ => asset_pool.min_junior_ratio = value,
+    }
+}
+
+/// 解析参数并写入资产池；资产池字段始终保存生效值，读取方无需关心继承关系
+pub fn apply(
+    asset_pool: &mut AssetPool,
+    system_config: &SystemConfig,
+    params: &PoolParams,
+) -> Result<()> {
+    let (values, inherited_params) = resolve(system_config, params)?;
+    for (param, value) in values.into_iter().enumerate() {
+        set_value(asset_pool, param as u8, value);
+    }
+    asset_pool.inherited_params = inherited_params;
+    Ok(())
+}
+
+/// 将继承的参数刷新为当前 SystemConfig 默认值，覆盖值保持不变
+pub fn sync_inherited(asset_pool: &mut AssetPool, system_config: &SystemConfig) -> Result<()> {
+    for param in 0..POOL_PARAM_COUNT as u8 {
+        if asset_pool.inherited_params & (1 << param) != 0 {
+            set_value(asset_pool, param, system_default(system_config, param)?);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPoolParamBounds<'info> {
+    pub system_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.system_admin == system_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+}
+
+/// 设置创建者覆盖参数时的取值范围，None 清除（仅受协议上下限约束）。
+/// 范围须在协议上下限内，且包含当前系统默认值
+pub fn set_pool_param_bounds(
+    ctx: Context<SetPoolParamBounds>,
+    param: u8,
+    bounds: Option<PoolParamBounds>,
+) -> Result<()> {
+    let system_config = &mut ctx.accounts.system_config;
+    let protocol = protocol_bounds(param)?;
+    let old_bounds = system_bounds(system_config, param);
+
+    match bounds {
+        Some(bounds) => {
+            require!(
+                protocol.min <= bounds.min
+                    && bounds.min <= bounds.max
+                    && bounds.max <= protocol.max,
+                PencilError::InvalidPoolParam
+            );
+            require!(
+                (bounds.min..=bounds.max).contains(&system_default(system_config, param)?),
+                PencilError::PoolParamOutOfBounds
+            );
+            system_config.pool_param_bounds[param as usize] = bounds;
+            system_config.pool_param_bounds_set |= 1 << param;
+        }
+        None => {
+            system_config.pool_param_bounds[param as usize] = PoolParamBounds::default();
+            system_config.pool_param_bounds_set &= !(1 << param);
+        }
+    }
+
+    emit!(crate::PoolParamBoundsUpdated {
+        param,
+        old_bounds,
+        new_bounds: bounds,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("资产池参数范围已更新 - 参数: {}, 范围: {:?}", param, bounds);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SyncPoolParams<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.status == asset_pool_status::CREATED
+            || asset_pool.status == asset_pool_status::APPROVED @ PencilError::InvalidAssetPoolStatus
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+}
+
+/// 将继承参数刷新为当前 SystemConfig 默认值（任何人可调用）。
+/// 募资结束后参数固定；已有认购时生效值变化与修订条款一样开放免手续费撤资窗口
+pub fn sync_pool_params(ctx: Context<SyncPoolParams>) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let clock = Clock::get()?;
+    require!(
+        clock.unix_timestamp <= asset_pool.funding_end_time,
+        PencilError::AmendmentNotAllowed
+    );
+
+    let previous = values_of(asset_pool);
+    sync_inherited(asset_pool, &ctx.accounts.system_config)?;
+    let values = values_of(asset_pool);
+    if values == previous {
+        return Ok(());
+    }
+
+    if asset_pool.senior_amount > 0 || asset_pool.junior_amount > 0 {
+        let funding_end_time = asset_pool.funding_end_time;
+        open_amendment_withdraw_window(asset_pool, funding_end_time, clock.unix_timestamp)?;
+    }
+
+    emit!(crate::PoolParamsSynced {
+        asset_pool: asset_pool.key(),
+        inherited_params: asset_pool.inherited_params,
+        old_values: previous,
+        new_values: values,
+        withdraw_deadline: asset_pool.amendment_withdraw_deadline,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("资产池参数已同步 - 生效值: {:?}", values);
+
+    Ok(())
+}
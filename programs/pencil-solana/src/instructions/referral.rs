@@ -0,0 +1,567 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::state::{AssetPool, ReferralProgram, ReferralRecord, Subscription, SystemConfig};
+use crate::waterfall::{bps_of, calculate_per_period_amount, calculate_platform_fee};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, CreateAccount};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+// ReferralTermsUpdated / ReferralRecorded / ReferralRewardPaid events are defined in lib.rs
+
+#[derive(Accounts)]
+pub struct SetReferralTerms<'info> {
+    #[account(mut)]
+    pub treasury_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.treasury_admin == treasury_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = treasury_admin,
+        space = 8 + std::mem::size_of::<ReferralProgram>(),
+        seeds = [seeds::REFERRAL_PROGRAM, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub referral_program: Box<Account<'info, ReferralProgram>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// 设置资产池推荐奖励条款（募资完成前）
+/// 奖励从手续费归集账户支付，资产池须已开启手续费归集
+pub fn set_referral_terms(
+    ctx: Context<SetReferralTerms>,
+    reward_mode: u8,
+    reward_rate: u16,
+) -> Result<()> {
+    let asset_pool = &ctx.accounts.asset_pool;
+    require!(
+        asset_pool.status == asset_pool_status::CREATED
+            || asset_pool.status == asset_pool_status::APPROVED,
+        PencilError::InvalidAssetPoolStatus
+    );
+    require!(asset_pool.fee_split_enabled, PencilError::InvalidReferral);
+    require!(
+        (reward_mode == referral_reward_mode::UPFRONT
+            || reward_mode == referral_reward_mode::FEE_SHARE)
+            && reward_rate <= BASIS_POINTS,
+        PencilError::InvalidReferral
+    );
+
+    let referral_program = &mut ctx.accounts.referral_program;
+    referral_program.asset_pool = asset_pool.key();
+    referral_program.reward_mode = reward_mode;
+    referral_program.reward_rate = reward_rate;
+
+    emit!(crate::ReferralTermsUpdated {
+        asset_pool: asset_pool.key(),
+        reward_mode,
+        reward_rate,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "推荐奖励条款已更新 - 资产池: {}, 模式: {}, 比例: {}",
+        asset_pool.key(),
+        reward_mode,
+        reward_rate
+    );
+
+    Ok(())
+}
+
+/// 计算推荐奖励
+/// UPFRONT: 推荐认购额 × 奖励比例
+/// FEE_SHARE: 推荐认购额按募资总额分摊的全期平台费 × 奖励比例
+pub fn calculate_referral_reward(
+    referred_amount: u64,
+    reward_mode: u8,
+    reward_rate: u16,
+    total_amount: u64,
+    repayment_count: u64,
+    repayment_rate: u16,
+    platform_fee_rate: u16,
+) -> Result<u64> {
+    let reward_base = match reward_mode {
+        referral_reward_mode::UPFRONT => referred_amount,
+        referral_reward_mode::FEE_SHARE => {
+            if total_amount == 0 {
+                return Ok(0);
+            }
+            let per_period_total =
+                calculate_per_period_amount(total_amount, repayment_count, repayment_rate)?;
+            let total_platform_fee = calculate_platform_fee(per_period_total, platform_fee_rate)?
+                .checked_mul(repayment_count)
+                .ok_or(PencilError::ArithmeticOverflow)?;
+            ((total_platform_fee as u128)
+                .checked_mul(referred_amount as u128)
+                .ok_or(PencilError::ArithmeticOverflow)?
+                / total_amount as u128) as u64
+        }
+        _ => return err!(PencilError::InvalidReferral),
+    };
+    bps_of(reward_base, reward_rate)
+}
+
+/// 按条款计算推荐认购额对应的奖励
+fn referral_reward(
+    asset_pool: &AssetPool,
+    referral_program: &ReferralProgram,
+    referred_amount: u64,
+) -> Result<u64> {
+    calculate_referral_reward(
+        referred_amount,
+        referral_program.reward_mode,
+        referral_program.reward_rate,
+        asset_pool.total_amount,
+        asset_pool.repayment_count,
+        asset_pool.repayment_rate,
+        asset_pool.platform_fee,
+    )
+}
+
+/// 读取已创建的推荐奖励条款
+fn load_referral_program(referral_program: &AccountInfo) -> Result<Option<ReferralProgram>> {
+    if referral_program.owner != &crate::ID || referral_program.data_is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(ReferralProgram::try_deserialize(
+        &mut &referral_program.try_borrow_data()?[..],
+    )?))
+}
+
+/// 校验推荐记录与推荐奖励条款地址
+fn verify_referral_accounts(
+    asset_pool: &Pubkey,
+    referrer: &Pubkey,
+    referral_record: &AccountInfo,
+    referral_program: &AccountInfo,
+) -> Result<u8> {
+    let (expected_record, record_bump) = Pubkey::find_program_address(
+        &[seeds::REFERRAL, asset_pool.as_ref(), referrer.as_ref()],
+        &crate::ID,
+    );
+    let (expected_program, _) =
+        Pubkey::find_program_address(&[seeds::REFERRAL_PROGRAM, asset_pool.as_ref()], &crate::ID);
+    require_keys_eq!(
+        referral_record.key(),
+        expected_record,
+        PencilError::InvalidReferral
+    );
+    require_keys_eq!(
+        referral_program.key(),
+        expected_program,
+        PencilError::InvalidReferral
+    );
+    Ok(record_bump)
+}
+
+/// 认购时记录推荐人并累计其在资产池内的推荐额
+/// 推荐人只能在首次认购时指定，此后追加认购须传入同一推荐人。
+/// 须先设置奖励条款，使全部推荐额计入 referral_program，distribute_fees 才能足额预留奖励
+///
+/// referral_accounts: [referrer, referral_record (mut), referral_program (mut)]，
+/// 未经推荐的订阅不传
+pub(crate) fn record_referral<'info>(
+    referral_accounts: &[AccountInfo<'info>],
+    asset_pool: Pubkey,
+    subscription: &mut Subscription,
+    first_subscription: bool,
+    user: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    let [referrer_info, record_info, program_info, ..] = referral_accounts else {
+        require!(
+            subscription.referrer == Pubkey::default(),
+            PencilError::ReferralRequired
+        );
+        return Ok(());
+    };
+
+    let referrer = referrer_info.key();
+    if subscription.referrer == Pubkey::default() {
+        require!(
+            first_subscription && referrer != user.key() && referrer != Pubkey::default(),
+            PencilError::InvalidReferral
+        );
+        subscription.referrer = referrer;
+    } else {
+        require_keys_eq!(
+            subscription.referrer,
+            referrer,
+            PencilError::InvalidReferral
+        );
+    }
+
+    let record_bump = verify_referral_accounts(&asset_pool, &referrer, record_info, program_info)?;
+    let Some(mut referral_program) = load_referral_program(program_info)? else {
+        return err!(PencilError::ReferralTermsNotSet);
+    };
+
+    // 推荐人在该资产池的首个推荐订阅时创建推荐记录
+    let mut record = if record_info.data_is_empty() {
+        let space = 8 + std::mem::size_of::<ReferralRecord>();
+        let record_seeds = &[
+            seeds::REFERRAL,
+            asset_pool.as_ref(),
+            referrer.as_ref(),
+            &[record_bump],
+        ];
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                CreateAccount {
+                    from: user.to_account_info(),
+                    to: record_info.clone(),
+                },
+                &[&record_seeds[..]],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &crate::ID,
+        )?;
+        ReferralRecord {
+            asset_pool,
+            referrer,
+            senior_amount: 0,
+            junior_amount: 0,
+            subscription_count: 0,
+            settled: false,
+            reward_amount: 0,
+            paid_amount: 0,
+            _reserved: [0u8; 32],
+        }
+    } else {
+        require!(
+            record_info.owner == &crate::ID,
+            PencilError::InvalidReferral
+        );
+        ReferralRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?
+    };
+    require!(!record.settled, PencilError::InvalidReferral);
+
+    if subscription.subscription_type == 0 {
+        record.senior_amount = record
+            .senior_amount
+            .checked_add(amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    } else {
+        record.junior_amount = record
+            .junior_amount
+            .checked_add(amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+    if first_subscription {
+        record.subscription_count = record
+            .subscription_count
+            .checked_add(1)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+    record.try_serialize(&mut &mut record_info.try_borrow_mut_data()?[..])?;
+
+    // 累计未结算的推荐认购额
+    referral_program.referred_amount = referral_program
+        .referred_amount
+        .checked_add(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    referral_program.try_serialize(&mut &mut program_info.try_borrow_mut_data()?[..])?;
+
+    emit!(crate::ReferralRecorded {
+        asset_pool,
+        referrer,
+        user: user.key(),
+        subscription_type: subscription.subscription_type,
+        amount,
+        referred_senior_amount: record.senior_amount,
+        referred_junior_amount: record.junior_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "推荐认购已记录 - 推荐人: {}, 用户: {}, 金额: {}",
+        referrer,
+        user.key(),
+        amount
+    );
+
+    Ok(())
+}
+
+/// 撤回认购时扣减推荐人的推荐额
+///
+/// referral_accounts: [referral_record (mut), referral_program (mut)]，未经推荐的订阅不传
+pub(crate) fn release_referral(
+    referral_accounts: &[AccountInfo],
+    asset_pool: Pubkey,
+    subscription: &Subscription,
+    amount: u64,
+) -> Result<()> {
+    if subscription.referrer == Pubkey::default() {
+        return Ok(());
+    }
+    let [record_info, program_info, ..] = referral_accounts else {
+        return err!(PencilError::ReferralRequired);
+    };
+    verify_referral_accounts(
+        &asset_pool,
+        &subscription.referrer,
+        record_info,
+        program_info,
+    )?;
+    require!(
+        record_info.owner == &crate::ID,
+        PencilError::InvalidReferral
+    );
+
+    let mut record = ReferralRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?;
+    require!(!record.settled, PencilError::InvalidReferral);
+    if subscription.subscription_type == 0 {
+        record.senior_amount = record
+            .senior_amount
+            .checked_sub(amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    } else {
+        record.junior_amount = record
+            .junior_amount
+            .checked_sub(amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+    if subscription.amount == 0 {
+        record.subscription_count = record.subscription_count.saturating_sub(1);
+    }
+    record.try_serialize(&mut &mut record_info.try_borrow_mut_data()?[..])?;
+
+    let Some(mut referral_program) = load_referral_program(program_info)? else {
+        return err!(PencilError::ReferralTermsNotSet);
+    };
+    referral_program.referred_amount = referral_program.referred_amount.saturating_sub(amount);
+    referral_program.try_serialize(&mut &mut program_info.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
+
+/// 归集账户中须为推荐奖励保留的金额（未支付的已结算奖励 + 募资完成后尚未结算的奖励）
+pub(crate) fn referral_holdback(
+    referral_program: &AccountInfo,
+    asset_pool: &AssetPool,
+) -> Result<u64> {
+    let Some(referral_program) = load_referral_program(referral_program)? else {
+        return Ok(0);
+    };
+    let unsettled = match asset_pool.status {
+        asset_pool_status::FUNDED | asset_pool_status::REPAYING | asset_pool_status::COMPLETED => {
+            referral_reward(
+                asset_pool,
+                &referral_program,
+                referral_program.referred_amount,
+            )?
+        }
+        _ => 0,
+    };
+    referral_program
+        .reward_owed
+        .checked_add(unsettled)
+        .ok_or(PencilError::ArithmeticOverflow.into())
+}
+
+/// 结算并支付推荐奖励（任何人可调用）
+/// 募资完成后首次调用时按条款结算，归集账户余额不足时可再次调用补付
+#[derive(Accounts)]
+pub struct ClaimReferralReward<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::REFERRAL_PROGRAM, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub referral_program: Box<Account<'info, ReferralProgram>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::REFERRAL, asset_pool.key().as_ref(), referral_record.referrer.as_ref()],
+        bump
+    )]
+    pub referral_record: Box<Account<'info, ReferralRecord>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::FEE_COLLECTOR, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub fee_collector: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = get_associated_token_address(&referral_record.referrer, &asset_mint.key()) @ PencilError::InvalidReferral
+    )]
+    pub referrer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = asset_pool.asset_address @ PencilError::InvalidAccount)]
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn claim_referral_reward(ctx: Context<ClaimReferralReward>) -> Result<()> {
+    let asset_pool = &ctx.accounts.asset_pool;
+    require!(
+        asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING
+            || asset_pool.status == asset_pool_status::COMPLETED,
+        PencilError::InvalidAssetPoolStatus
+    );
+
+    let referral_program = &mut ctx.accounts.referral_program;
+    let referral_record = &mut ctx.accounts.referral_record;
+
+    // 首次调用时按募资完成后的资产池条款结算
+    if !referral_record.settled {
+        let referred_amount = referral_record
+            .senior_amount
+            .checked_add(referral_record.junior_amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        let reward = referral_reward(asset_pool, referral_program, referred_amount)?;
+        referral_record.reward_amount = reward;
+        referral_record.settled = true;
+        referral_program.referred_amount = referral_program
+            .referred_amount
+            .saturating_sub(referred_amount);
+        referral_program.reward_owed = referral_program
+            .reward_owed
+            .checked_add(reward)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+
+    let amount = referral_record
+        .reward_amount
+        .checked_sub(referral_record.paid_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .min(ctx.accounts.fee_collector.amount);
+    if amount > 0 {
+        let asset_pool_seeds = &[
+            seeds::ASSET_POOL,
+            asset_pool.creator.as_ref(),
+            &asset_pool.name,
+            &[ctx.bumps.asset_pool],
+        ];
+        let signer_seeds = &[&asset_pool_seeds[..]];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.fee_collector.to_account_info(),
+            to: ctx.accounts.referrer_token_account.to_account_info(),
+            authority: asset_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, amount)?;
+
+        referral_record.paid_amount = referral_record
+            .paid_amount
+            .checked_add(amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        referral_program.reward_owed = referral_program
+            .reward_owed
+            .checked_sub(amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        referral_program.total_paid = referral_program
+            .total_paid
+            .checked_add(amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+
+    emit!(crate::ReferralRewardPaid {
+        asset_pool: asset_pool.key(),
+        referrer: referral_record.referrer,
+        amount,
+        reward_amount: referral_record.reward_amount,
+        paid_amount: referral_record.paid_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "推荐奖励已支付 - 推荐人: {}, 金额: {}, 累计: {}/{}",
+        referral_record.referrer,
+        amount,
+        referral_record.paid_amount,
+        referral_record.reward_amount
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referral_reward_by_mode() {
+        // 预付：按推荐认购额直接计算
+        assert_eq!(
+            calculate_referral_reward(
+                200_000,
+                referral_reward_mode::UPFRONT,
+                50,
+                1_000_000,
+                3,
+                300,
+                500
+            )
+            .unwrap(),
+            1_000
+        );
+        // 平台费分成：每期 363_333，平台费 18_166，三期 54_498，按 20% 分摊后取 10%
+        assert_eq!(
+            calculate_referral_reward(
+                200_000,
+                referral_reward_mode::FEE_SHARE,
+                1000,
+                1_000_000,
+                3,
+                300,
+                500
+            )
+            .unwrap(),
+            1_089
+        );
+        assert_eq!(
+            calculate_referral_reward(
+                200_000,
+                referral_reward_mode::FEE_SHARE,
+                1000,
+                0,
+                3,
+                300,
+                500
+            )
+            .unwrap(),
+            0
+        );
+        assert!(calculate_referral_reward(200_000, 2, 1000, 1_000_000, 3, 300, 500).is_err());
+    }
+}
@@ -1,14 +1,45 @@
 use crate::constants::*;
 use crate::errors::PencilError;
+use crate::instructions::distribution::accrue_senior_distribution;
+use crate::instructions::exit_queue::queued_exit_amount;
+use crate::instructions::insurance::collect_insurance_fee;
+use crate::instructions::originator::{load_originator, sync_originator_account};
+use crate::instructions::reserve::{load_pool_reserve, release_pool_reserve, settle_reserve_flow};
+use crate::instructions::tokens::{create_junior_nft_position, next_issued_nft_id};
+use crate::instructions::utils::pool_fee_destination;
 use crate::state::{
-    AssetPool, AssetWhitelist, FirstLossPool, JuniorInterestPool, JuniorNFTMetadata,
-    RepaymentRecord, SeniorPool, SystemConfig,
+    AssetPool, AssetWhitelist, FirstLossPool, JuniorInterestPool, JuniorNFTMetadata, Originator,
+    PoolReserve, RepaymentRecord, SeniorPool, SystemConfig,
+};
+use crate::waterfall::{
+    apply_pool_reserve, calculate_current_period, calculate_junior_claimable,
+    calculate_per_period_amount, calculate_prepayment_interest, calculate_prepayment_penalty,
+    calculate_principal_withdrawal, calculate_remaining_principal, calculate_senior_amount,
+    distribute_early_exit, distribute_junior_exit, distribute_repayment, instant_exit_liquidity,
+    RepaymentDistribution, ReserveFlow,
 };
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Burn, Token, TokenAccount, Transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Burn, CloseAccount, Mint, Token, TokenAccount, Transfer};
 
 // RepaymentDistributed event is now defined in lib.rs
 
+/// 付款人既非还款义务人也非受托服务方时视为第三方代偿
+/// （旧资产池未设置还款义务人时以创建者为准，创建者未注册发起方时没有受托服务方）
+fn is_third_party_payer(
+    asset_pool: &AssetPool,
+    originator: Option<&Originator>,
+    payer: Pubkey,
+) -> bool {
+    let repayment_authority = if asset_pool.repayment_authority == Pubkey::default() {
+        asset_pool.creator
+    } else {
+        asset_pool.repayment_authority
+    };
+    payer != repayment_authority
+        && originator.is_none_or(|originator| payer != originator.servicer)
+}
+
 #[derive(Accounts)]
 #[instruction(amount: u64, period: u64)]
 pub struct Repay<'info> {
@@ -35,6 +66,14 @@ pub struct Repay<'info> {
     )]
     pub asset_pool: Box<Account<'info, AssetPool>>,
 
+    /// CHECK: 发起方 PDA，用于识别受托还款服务方并同步敞口；创建者未注册时不存在
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: UncheckedAccount<'info>,
+
     #[account(
         mut,
         seeds = [seeds::SENIOR_POOL, asset_pool.key().as_ref()],
@@ -65,14 +104,25 @@ pub struct Repay<'info> {
 
     #[account(
         mut,
-        token::mint = asset_mint,
-        token::authority = asset_pool
+        address = senior_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub senior_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = first_loss_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub first_loss_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = junior_interest_pool.vault @ PencilError::InvalidAccount
     )]
-    pub asset_pool_vault: Box<Account<'info, TokenAccount>>,
+    pub junior_interest_pool_vault: Box<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
-        token::mint = asset_mint
+        address = pool_fee_destination(&asset_pool, &system_config) @ PencilError::InvalidAccount
     )]
     pub treasury_ata: Box<Account<'info, TokenAccount>>,
 
@@ -88,11 +138,40 @@ pub struct Repay<'info> {
     )]
     pub repayment_record: Box<Account<'info, RepaymentRecord>>,
 
+    /// GROW Mint（按总量累加 Senior 分配指数）
+    #[account(
+        seeds = [seeds::GROW_TOKEN_MINT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub grow_token_mint: Box<Account<'info, anchor_spl::token::Mint>>,
+
+    /// CHECK: 资产代币的保险基金 PDA，尚未创建时不计提
+    #[account(
+        mut,
+        seeds = [seeds::INSURANCE_FUND, asset_pool.asset_address.as_ref()],
+        bump
+    )]
+    pub insurance_fund: UncheckedAccount<'info>,
+
+    /// CHECK: 保险基金 Token Vault PDA，尚未创建时不计提
+    #[account(
+        mut,
+        seeds = [seeds::INSURANCE_FUND_VAULT, asset_pool.asset_address.as_ref()],
+        bump
+    )]
+    pub insurance_fund_vault: UncheckedAccount<'info>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn repay(ctx: Context<Repay>, amount: u64, period: u64) -> Result<()> {
+/// 按期还款
+/// 资产池启用储备金时需在 remaining_accounts 传入 [pool_reserve, reserve_vault]
+pub fn repay<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Repay<'info>>,
+    amount: u64,
+    period: u64,
+) -> Result<()> {
     require!(amount > 0, PencilError::InvalidRepaymentAmount);
 
     let asset_pool = &mut ctx.accounts.asset_pool;
@@ -125,284 +204,652 @@ pub fn repay(ctx: Context<Repay>, amount: u64, period: u64) -> Result<()> {
     );
 
     // 验证当前时间是否已到该期数
-    let calculated_period =
-        calculate_current_period(asset_pool.funding_end_time, asset_pool.repayment_period)?;
+    let calculated_period = calculate_current_period(
+        asset_pool.funding_end_time,
+        asset_pool.repayment_period,
+        clock.unix_timestamp,
+    )?;
     require!(period <= calculated_period, PencilError::RepaymentNotDue);
 
-    // 2. 接收还款到资产池 Vault
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.payer_token_account.to_account_info(),
-        to: ctx.accounts.asset_pool_vault.to_account_info(),
-        authority: ctx.accounts.payer.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, amount)?;
+    // 任何人都可以代为还款，第三方代偿会被标记
+    let payer_key = ctx.accounts.payer.key();
+    let originator = load_originator(&ctx.accounts.originator)?;
+    let third_party = is_third_party_payer(asset_pool, originator.as_ref(), payer_key);
 
-    // 3. 计算每期应还总额
+    // 2. 计算每期应还总额
     let per_period_total = calculate_per_period_amount(
         asset_pool.total_amount,
         asset_pool.repayment_count,
         asset_pool.repayment_rate,
     )?;
 
-    // 验证还款金额是否足够
-    require!(
-        amount >= per_period_total,
-        PencilError::InvalidRepaymentAmount
+    // 3. 计算 Senior 应得本息
+    let senior_amount = calculate_senior_amount(
+        asset_pool.senior_amount,
+        asset_pool.repayment_count,
+        asset_pool.senior_fixed_rate,
+    )?;
+
+    // FirstLossPool 可用余额（账面余额与 Vault 实际余额取小）
+    let first_loss_pool = &ctx.accounts.first_loss_pool;
+    let first_loss_available = first_loss_pool
+        .total_deposits
+        .checked_sub(first_loss_pool.repaid_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .min(ctx.accounts.first_loss_pool_vault.amount);
+
+    // 4-6. 还款瀑布：平台费 -> Senior 本息（不足由 FirstLossPool 补足） -> Junior 利息
+    let distribution = distribute_repayment(
+        amount,
+        per_period_total,
+        asset_pool.platform_fee,
+        senior_amount,
+        first_loss_available,
+    )?;
+
+    // 储备金：截留部分平台费与 Junior 利息，Senior 缺口先于首损池补足
+    let pool_reserve = load_pool_reserve(asset_pool, ctx.remaining_accounts)?;
+    let (distribution, reserve_flow) = match &pool_reserve {
+        Some((reserve, reserve_vault)) => apply_pool_reserve(
+            distribution,
+            reserve_vault.amount,
+            reserve.fee_share,
+            reserve.interest_share,
+        )?,
+        None => (distribution, ReserveFlow::default()),
+    };
+    let RepaymentDistribution {
+        platform_fee,
+        first_loss_used,
+        junior_interest,
+        ..
+    } = distribution;
+    let actual_senior_amount = distribution.senior_total()?;
+
+    if actual_senior_amount < senior_amount {
+        msg!(
+            "警告: FirstLossPool 余额不足，Senior 实际获得: {} tokens",
+            actual_senior_amount
+        );
+    } else if first_loss_used > 0 {
+        msg!("FirstLossPool补足差额: {} tokens", first_loss_used);
+    }
+    if reserve_flow.used > 0 {
+        msg!("储备金补足差额: {} tokens", reserve_flow.used);
+    }
+
+    // 5-6. 资金划转与账面更新；最后一期还款后资产池完成
+    let asset_pool_key = asset_pool.key();
+    let repaid_periods = asset_pool
+        .repaid_periods
+        .checked_add(1)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let completed = period == asset_pool.repayment_count;
+    let accounts = &mut *ctx.accounts;
+    settle_repayment(
+        RepaymentAccounts {
+            asset_pool: &mut accounts.asset_pool,
+            asset_pool_bump: ctx.bumps.asset_pool,
+            originator: &accounts.originator,
+            payer: &accounts.payer,
+            payer_token_account: &accounts.payer_token_account,
+            senior_pool: &mut accounts.senior_pool,
+            senior_pool_vault: &accounts.senior_pool_vault,
+            first_loss_pool: &mut accounts.first_loss_pool,
+            first_loss_pool_vault: &accounts.first_loss_pool_vault,
+            junior_interest_pool: &mut accounts.junior_interest_pool,
+            junior_interest_pool_vault: &accounts.junior_interest_pool_vault,
+            treasury_ata: &accounts.treasury_ata,
+            grow_token_mint: &accounts.grow_token_mint,
+            insurance_fund: &accounts.insurance_fund,
+            insurance_fund_vault: &accounts.insurance_fund_vault,
+            token_program: &accounts.token_program,
+        },
+        &distribution,
+        reserve_flow,
+        pool_reserve,
+        originator,
+        amount,
+        repaid_periods,
+        completed,
+        clock.unix_timestamp,
+    )?;
+    if completed {
+        msg!("All repayments completed. Pool status set to COMPLETED.");
+    }
+
+    // 7. 创建 RepaymentRecord 并记录期数
+    let repayment_record = &mut ctx.accounts.repayment_record;
+    repayment_record.asset_pool = asset_pool_key;
+    repayment_record.period = period;
+    repayment_record.amount = amount;
+    repayment_record.repaid_at = clock.unix_timestamp;
+    repayment_record.status = repayment_status::COMPLETED;
+    repayment_record.payer = payer_key;
+    repayment_record.third_party = third_party;
+
+    // 8. 发出 RepaymentDistributed 事件
+    emit!(crate::RepaymentDistributed {
+        asset_pool: asset_pool_key,
+        period,
+        total_amount: amount,
+        platform_fee,
+        senior_amount: actual_senior_amount,
+        junior_interest,
+        payer: payer_key,
+        third_party,
+        timestamp: clock.unix_timestamp,
+    });
+
+    if third_party {
+        msg!("第三方代偿还款: {}", payer_key);
+    }
+    msg!(
+        "还款分配完成 - 期数: {}, 总额: {}, 平台费: {}, Senior: {}, Junior利息: {}",
+        period,
+        amount,
+        platform_fee,
+        actual_senior_amount,
+        junior_interest
     );
 
-    // 4. 计算并转账平台费至金库
-    let platform_fee = calculate_platform_fee(per_period_total, asset_pool.platform_fee)?;
+    Ok(())
+}
+
+/// repay 与 prepay_remaining 共用的结算账户
+struct RepaymentAccounts<'a, 'info> {
+    asset_pool: &'a mut Account<'info, AssetPool>,
+    asset_pool_bump: u8,
+    originator: &'a UncheckedAccount<'info>,
+    payer: &'a Signer<'info>,
+    payer_token_account: &'a Account<'info, TokenAccount>,
+    senior_pool: &'a mut Account<'info, SeniorPool>,
+    senior_pool_vault: &'a Account<'info, TokenAccount>,
+    first_loss_pool: &'a mut Account<'info, FirstLossPool>,
+    first_loss_pool_vault: &'a Account<'info, TokenAccount>,
+    junior_interest_pool: &'a mut Account<'info, JuniorInterestPool>,
+    junior_interest_pool_vault: &'a Account<'info, TokenAccount>,
+    treasury_ata: &'a Account<'info, TokenAccount>,
+    grow_token_mint: &'a Account<'info, Mint>,
+    insurance_fund: &'a UncheckedAccount<'info>,
+    insurance_fund_vault: &'a UncheckedAccount<'info>,
+    token_program: &'a Program<'info, Token>,
+}
 
-    // 准备 PDA 签名种子（在修改 asset_pool 之前）
-    let asset_pool_bump = ctx.bumps.asset_pool;
-    let asset_pool_creator = asset_pool.creator;
-    let asset_pool_name = asset_pool.name.clone();
+/// 按已计算的分配结算一笔还款：平台费计提保险基金后进入金库，Senior 本息与首损补足
+/// 进入优先池 Vault，Junior 利息进入利息池 Vault；随后结算储备金、更新各子池与资产池账面，
+/// 资产池完成时释放储备金，最后同步发起方敞口
+#[allow(clippy::too_many_arguments)]
+fn settle_repayment<'info>(
+    accounts: RepaymentAccounts<'_, 'info>,
+    distribution: &RepaymentDistribution,
+    reserve_flow: ReserveFlow,
+    mut pool_reserve: Option<(Account<'info, PoolReserve>, Account<'info, TokenAccount>)>,
+    mut originator: Option<Originator>,
+    amount: u64,
+    repaid_periods: u16,
+    completed: bool,
+    timestamp: i64,
+) -> Result<()> {
+    let RepaymentAccounts {
+        asset_pool,
+        asset_pool_bump,
+        originator: originator_info,
+        payer,
+        payer_token_account,
+        senior_pool,
+        senior_pool_vault,
+        first_loss_pool,
+        first_loss_pool_vault,
+        junior_interest_pool,
+        junior_interest_pool_vault,
+        treasury_ata,
+        grow_token_mint,
+        insurance_fund,
+        insurance_fund_vault,
+        token_program,
+    } = accounts;
+    let RepaymentDistribution {
+        platform_fee,
+        senior_from_repayment,
+        first_loss_used,
+        junior_interest,
+        ..
+    } = *distribution;
+    let actual_senior_amount = distribution.senior_total()?;
+
+    // 平台费按比例计提保险基金，其余进入金库
+    let insurance_fee = collect_insurance_fee(
+        insurance_fund,
+        insurance_fund_vault,
+        asset_pool.key(),
+        payer,
+        payer_token_account,
+        token_program,
+        platform_fee,
+    )?;
+    let treasury_fee = platform_fee
+        .checked_sub(insurance_fee)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    if treasury_fee > 0 {
+        let cpi_accounts = Transfer {
+            from: payer_token_account.to_account_info(),
+            to: treasury_ata.to_account_info(),
+            authority: payer.to_account_info(),
+        };
+        let cpi_program = token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), treasury_fee)?;
+    }
+
+    if senior_from_repayment > 0 {
+        let cpi_accounts = Transfer {
+            from: payer_token_account.to_account_info(),
+            to: senior_pool_vault.to_account_info(),
+            authority: payer.to_account_info(),
+        };
+        let cpi_program = token_program.to_account_info();
+        token::transfer(
+            CpiContext::new(cpi_program, cpi_accounts),
+            senior_from_repayment,
+        )?;
+    }
 
-    if platform_fee > 0 {
+    if junior_interest > 0 {
+        let cpi_accounts = Transfer {
+            from: payer_token_account.to_account_info(),
+            to: junior_interest_pool_vault.to_account_info(),
+            authority: payer.to_account_info(),
+        };
+        let cpi_program = token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), junior_interest)?;
+    }
+
+    if first_loss_used > 0 {
         let asset_pool_seeds = &[
             seeds::ASSET_POOL,
-            asset_pool_creator.as_ref(),
-            &asset_pool_name,
+            asset_pool.creator.as_ref(),
+            &asset_pool.name,
             &[asset_pool_bump],
         ];
         let signer_seeds = &[&asset_pool_seeds[..]];
 
         let cpi_accounts = Transfer {
-            from: ctx.accounts.asset_pool_vault.to_account_info(),
-            to: ctx.accounts.treasury_ata.to_account_info(),
+            from: first_loss_pool_vault.to_account_info(),
+            to: senior_pool_vault.to_account_info(),
             authority: asset_pool.to_account_info(),
         };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_program = token_program.to_account_info();
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        token::transfer(cpi_ctx, platform_fee)?;
+        token::transfer(cpi_ctx, first_loss_used)?;
     }
 
-    // 5. 计算 Senior 应得本息
-    let senior_amount = calculate_senior_amount(
-        asset_pool.senior_amount,
-        asset_pool.repayment_count,
-        asset_pool.senior_fixed_rate,
-    )?;
-
-    // 6. 分配 Senior 应得本息（记录在 SeniorPool 状态中）
-    let mut actual_senior_amount = senior_amount;
-
-    // 计算当前 Vault 中可用于 Senior 的余额（扣除平台费后）
-    let available_for_senior = ctx
-        .accounts
-        .asset_pool_vault
-        .amount
-        .checked_sub(platform_fee)
-        .ok_or(PencilError::ArithmeticOverflow)?;
-
-    // 7. 如果可用余额不足，从 FirstLossPool 补足
-    let mut first_loss_used = 0u64;
-    if available_for_senior < senior_amount {
-        let shortfall = senior_amount
-            .checked_sub(available_for_senior)
-            .ok_or(PencilError::ArithmeticOverflow)?;
-
-        // 检查 FirstLossPool 是否有足够余额（通过 total_deposits - repaid_amount 计算）
-        let first_loss_pool = &ctx.accounts.first_loss_pool;
-        let first_loss_available = first_loss_pool
-            .total_deposits
-            .checked_sub(first_loss_pool.repaid_amount)
-            .ok_or(PencilError::ArithmeticOverflow)?;
-
-        if first_loss_available >= shortfall {
-            // FirstLossPool 有足够余额，记录使用金额
-            first_loss_used = shortfall;
-            msg!("FirstLossPool补足差额: {} tokens", shortfall);
-        } else {
-            // FirstLossPool 余额不足，只能分配可用金额
-            first_loss_used = first_loss_available;
-            actual_senior_amount = available_for_senior
-                .checked_add(first_loss_used)
-                .ok_or(PencilError::ArithmeticOverflow)?;
-            msg!(
-                "警告: FirstLossPool 余额不足，Senior 实际获得: {} tokens",
-                actual_senior_amount
-            );
-        }
-    }
+    let reserve_balance = match pool_reserve.as_mut() {
+        Some((reserve, reserve_vault)) => settle_reserve_flow(
+            reserve,
+            reserve_vault,
+            asset_pool,
+            asset_pool_bump,
+            payer,
+            payer_token_account,
+            senior_pool_vault,
+            token_program,
+            reserve_flow,
+        )?,
+        None => 0,
+    };
 
-    // 更新 SeniorPool 已还款金额（资金留在 asset_pool_vault 中）
-    let senior_pool = &mut ctx.accounts.senior_pool;
+    // 更新各子池账面
     senior_pool.repaid_amount = senior_pool
         .repaid_amount
         .checked_add(actual_senior_amount)
         .ok_or(PencilError::ArithmeticOverflow)?;
+    senior_pool.exit_liquidity = senior_pool
+        .exit_liquidity
+        .checked_add(actual_senior_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    accrue_senior_distribution(senior_pool, grow_token_mint.supply, actual_senior_amount)?;
 
-    // 更新 FirstLossPool 已使用金额
     if first_loss_used > 0 {
-        let first_loss_pool = &mut ctx.accounts.first_loss_pool;
         first_loss_pool.repaid_amount = first_loss_pool
             .repaid_amount
             .checked_add(first_loss_used)
             .ok_or(PencilError::ArithmeticOverflow)?;
     }
 
-    // 8. 计算剩余金额并分配至 JuniorInterestPool（记录在状态中）
-    let junior_interest = amount
-        .checked_sub(platform_fee)
-        .ok_or(PencilError::ArithmeticOverflow)?
-        .checked_sub(actual_senior_amount)
-        .ok_or(PencilError::ArithmeticOverflow)?;
-
     if junior_interest > 0 {
-        // 更新 JuniorInterestPool（资金留在 asset_pool_vault 中）
-        let junior_interest_pool = &mut ctx.accounts.junior_interest_pool;
         junior_interest_pool.total_interest = junior_interest_pool
             .total_interest
             .checked_add(junior_interest)
             .ok_or(PencilError::ArithmeticOverflow)?;
     }
 
-    // 9. 创建 RepaymentRecord 并记录期数
-    let repayment_record = &mut ctx.accounts.repayment_record;
-    repayment_record.asset_pool = asset_pool.key();
-    repayment_record.period = period;
-    repayment_record.amount = amount;
-    repayment_record.repaid_at = clock.unix_timestamp;
-    repayment_record.status = repayment_status::COMPLETED;
-
     // 更新资产池
     asset_pool.repaid_amount = asset_pool
         .repaid_amount
         .checked_add(amount)
         .ok_or(PencilError::ArithmeticOverflow)?;
+    asset_pool.repaid_periods = repaid_periods;
+    asset_pool.status = if completed {
+        asset_pool_status::COMPLETED
+    } else {
+        asset_pool_status::REPAYING
+    };
 
-    // 更新资产池状态为 REPAYING
-    if asset_pool.status == asset_pool_status::FUNDED {
-        asset_pool.status = asset_pool_status::REPAYING;
+    // 资产池完成，释放储备金
+    if completed {
+        if let Some((reserve, reserve_vault)) = pool_reserve.as_mut() {
+            release_pool_reserve(
+                reserve,
+                reserve_vault,
+                reserve_balance,
+                asset_pool,
+                asset_pool_bump,
+                first_loss_pool,
+                first_loss_pool_vault,
+                junior_interest_pool,
+                junior_interest_pool_vault,
+                treasury_ata,
+                token_program,
+            )?;
+        }
     }
 
-    // 检查是否是最后一期还款，如果是则将状态设置为 COMPLETED
-    if period == asset_pool.repayment_count {
-        asset_pool.status = asset_pool_status::COMPLETED;
-        msg!("All repayments completed. Pool status set to COMPLETED.");
+    // 未偿本金减少（或资产池完成），同步发起方敞口
+    if let Some(originator) = originator.as_mut() {
+        let asset_pool_key = asset_pool.key();
+        sync_originator_account(
+            originator_info,
+            originator,
+            asset_pool_key,
+            asset_pool,
+            timestamp,
+        )?;
     }
 
-    // 10. 发出 RepaymentDistributed 事件
-    emit!(crate::RepaymentDistributed {
-        asset_pool: asset_pool.key(),
-        period,
-        total_amount: amount,
-        platform_fee,
-        senior_amount: actual_senior_amount,
-        junior_interest,
-        timestamp: clock.unix_timestamp,
-    });
-
-    msg!(
-        "还款分配完成 - 期数: {}, 总额: {}, 平台费: {}, Senior: {}, Junior利息: {}",
-        period,
-        amount,
-        platform_fee,
-        actual_senior_amount,
-        junior_interest
-    );
-
     Ok(())
 }
 
-/// 计算当前应还期数
-/// repayment_period: 还款周期（秒数）
-fn calculate_current_period(funding_end_time: i64, repayment_period: u64) -> Result<u64> {
-    let clock = Clock::get()?;
-    let current_time = clock.unix_timestamp;
+#[derive(Accounts)]
+pub struct PrepayRemaining<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
 
-    // 如果还未到募资结束时间，返回 0
-    if current_time < funding_end_time {
-        return Ok(0);
-    }
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
 
-    // 计算已过去的时间（秒）
-    let elapsed_seconds = current_time
-        .checked_sub(funding_end_time)
-        .ok_or(PencilError::ArithmeticOverflow)?;
+    #[account(
+        seeds = [seeds::ASSET_WHITELIST],
+        bump
+    )]
+    pub asset_whitelist: Box<Account<'info, AssetWhitelist>>,
 
-    // 计算当前期数（直接用秒数计算，支持任意时间单位）
-    let count = if repayment_period > 0 {
-        (elapsed_seconds as u64) / repayment_period
-    } else {
-        0
-    };
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
 
-    // 与EVM一致：如果还没到第一个还款期，也允许还第一期
-    // return count > 0 ? count : 1
-    let period = if count > 0 { count } else { 1 };
+    /// CHECK: 发起方 PDA，用于识别受托还款服务方并同步敞口；创建者未注册时不存在
+    #[account(
+        mut,
+        seeds = [seeds::ORIGINATOR, asset_pool.creator.as_ref()],
+        bump
+    )]
+    pub originator: UncheckedAccount<'info>,
 
-    Ok(period)
-}
+    #[account(
+        mut,
+        seeds = [seeds::SENIOR_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub senior_pool: Box<Account<'info, SeniorPool>>,
 
-/// 计算每期应还总额 = 本金/期数 + 本金 × 还款利率
-fn calculate_per_period_amount(
-    total_amount: u64,
-    repayment_count: u64,
-    repayment_rate: u16,
-) -> Result<u64> {
-    require!(repayment_count > 0, PencilError::InvalidRepaymentCount);
-
-    // 每期本金
-    let principal_per_period = total_amount
-        .checked_div(repayment_count)
-        .ok_or(PencilError::ArithmeticOverflow)?;
+    #[account(
+        mut,
+        seeds = [seeds::FIRST_LOSS_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub first_loss_pool: Box<Account<'info, FirstLossPool>>,
 
-    // 每期利息 = 本金 × 还款利率 / 10000
-    let interest_per_period = ((total_amount as u128)
-        .checked_mul(repayment_rate as u128)
-        .ok_or(PencilError::ArithmeticOverflow)?)
-    .checked_div(BASIS_POINTS as u128)
-    .ok_or(PencilError::ArithmeticOverflow)? as u64;
+    #[account(
+        mut,
+        seeds = [seeds::JUNIOR_INTEREST_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub junior_interest_pool: Box<Account<'info, JuniorInterestPool>>,
 
-    // 每期总额
-    let per_period_total = principal_per_period
-        .checked_add(interest_per_period)
-        .ok_or(PencilError::ArithmeticOverflow)?;
+    #[account(
+        mut,
+        token::mint = asset_mint,
+        token::authority = payer
+    )]
+    pub payer_token_account: Box<Account<'info, TokenAccount>>,
 
-    Ok(per_period_total)
-}
+    #[account(
+        mut,
+        address = senior_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub senior_pool_vault: Box<Account<'info, TokenAccount>>,
 
-/// 计算平台费 = 每期应还金额 × 平台费率
-fn calculate_platform_fee(per_period_amount: u64, platform_fee_rate: u16) -> Result<u64> {
-    let platform_fee = ((per_period_amount as u128)
-        .checked_mul(platform_fee_rate as u128)
-        .ok_or(PencilError::ArithmeticOverflow)?)
-    .checked_div(BASIS_POINTS as u128)
-    .ok_or(PencilError::ArithmeticOverflow)? as u64;
+    #[account(
+        mut,
+        address = first_loss_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub first_loss_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = junior_interest_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub junior_interest_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = pool_fee_destination(&asset_pool, &system_config) @ PencilError::InvalidAccount
+    )]
+    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+
+    pub asset_mint: Box<Account<'info, anchor_spl::token::Mint>>,
+
+    /// GROW Mint（按总量累加 Senior 分配指数）
+    #[account(
+        seeds = [seeds::GROW_TOKEN_MINT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub grow_token_mint: Box<Account<'info, anchor_spl::token::Mint>>,
+
+    /// CHECK: 资产代币的保险基金 PDA，尚未创建时不计提
+    #[account(
+        mut,
+        seeds = [seeds::INSURANCE_FUND, asset_pool.asset_address.as_ref()],
+        bump
+    )]
+    pub insurance_fund: UncheckedAccount<'info>,
+
+    /// CHECK: 保险基金 Token Vault PDA，尚未创建时不计提
+    #[account(
+        mut,
+        seeds = [seeds::INSURANCE_FUND_VAULT, asset_pool.asset_address.as_ref()],
+        bump
+    )]
+    pub insurance_fund_vault: UncheckedAccount<'info>,
 
-    Ok(platform_fee)
+    pub token_program: Program<'info, Token>,
 }
 
-/// 计算 Senior 应得本息 = Senior本金/期数 + Senior本金 × Senior固定利率
-fn calculate_senior_amount(
-    senior_total: u64,
-    repayment_count: u64,
-    senior_fixed_rate: u16,
-) -> Result<u64> {
-    require!(repayment_count > 0, PencilError::InvalidRepaymentCount);
-
-    // 每期 Senior 本金
-    let senior_principal_per_period = senior_total
-        .checked_div(repayment_count)
-        .ok_or(PencilError::ArithmeticOverflow)?;
+/// 一次性提前偿还所有剩余期数
+/// 应付 = 剩余本金 + 利息（按资产池计息方式）+ 违约金，按还款瀑布分配后资产池直接完成
+/// 资产池启用储备金时需在 remaining_accounts 传入 [pool_reserve, reserve_vault]
+pub fn prepay_remaining<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PrepayRemaining<'info>>,
+    max_amount: u64,
+) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+    let clock = Clock::get()?;
+
+    // 验证资产在白名单中
+    let asset_address = asset_pool.asset_address;
+    require!(
+        ctx.accounts.asset_whitelist.assets.contains(&asset_address),
+        PencilError::AssetNotSupported
+    );
+
+    require!(
+        asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING,
+        PencilError::InvalidAssetPoolStatus
+    );
+    require!(
+        asset_pool.related_accounts_initialized,
+        PencilError::RelatedAccountsNotInitialized
+    );
+
+    let repaid_periods = asset_pool.repaid_periods as u64;
+    let repayment_count = asset_pool.repayment_count;
+    require!(
+        repaid_periods < repayment_count,
+        PencilError::InvalidRepaymentPeriod
+    );
+    let remaining_periods = repayment_count - repaid_periods;
+    let elapsed = clock
+        .unix_timestamp
+        .saturating_sub(asset_pool.funding_end_time)
+        .max(0) as u64;
+
+    // 1. 剩余本金与利息（资产池整体与 Senior 分别计算）
+    let principal =
+        calculate_remaining_principal(asset_pool.total_amount, repayment_count, repaid_periods)?;
+    let interest = calculate_prepayment_interest(
+        asset_pool.total_amount,
+        asset_pool.repayment_rate,
+        repayment_count,
+        repaid_periods,
+        asset_pool.prepayment_policy,
+        elapsed,
+        asset_pool.repayment_period,
+    )?;
+    let senior_due =
+        calculate_remaining_principal(asset_pool.senior_amount, repayment_count, repaid_periods)?
+            .checked_add(calculate_prepayment_interest(
+                asset_pool.senior_amount,
+                asset_pool.senior_fixed_rate,
+                repayment_count,
+                repaid_periods,
+                asset_pool.prepayment_policy,
+                elapsed,
+                asset_pool.repayment_period,
+            )?)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+
+    // 2. 违约金（按剩余本金计），随瀑布余额进入 Junior 利息池
+    let penalty = calculate_prepayment_penalty(principal, asset_pool.prepayment_penalty_rate)?;
+    let scheduled = principal
+        .checked_add(interest)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let amount = scheduled
+        .checked_add(penalty)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    require!(amount <= max_amount, PencilError::PrepaymentAmountExceeded);
+
+    // 3. 还款瀑布：平台费按本息计算，违约金不收平台费
+    let first_loss_pool = &ctx.accounts.first_loss_pool;
+    let first_loss_available = first_loss_pool
+        .total_deposits
+        .checked_sub(first_loss_pool.repaid_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .min(ctx.accounts.first_loss_pool_vault.amount);
+    let distribution = distribute_repayment(
+        amount,
+        scheduled,
+        asset_pool.platform_fee,
+        senior_due,
+        first_loss_available,
+    )?;
+
+    // 储备金：截留部分平台费与 Junior 利息，Senior 缺口先于首损池补足
+    let pool_reserve = load_pool_reserve(asset_pool, ctx.remaining_accounts)?;
+    let (distribution, reserve_flow) = match &pool_reserve {
+        Some((reserve, reserve_vault)) => apply_pool_reserve(
+            distribution,
+            reserve_vault.amount,
+            reserve.fee_share,
+            reserve.interest_share,
+        )?,
+        None => (distribution, ReserveFlow::default()),
+    };
+    let RepaymentDistribution {
+        platform_fee,
+        junior_interest,
+        ..
+    } = distribution;
+    let actual_senior_amount = distribution.senior_total()?;
+
+    let payer_key = ctx.accounts.payer.key();
+    let originator = load_originator(&ctx.accounts.originator)?;
+    let third_party = is_third_party_payer(asset_pool, originator.as_ref(), payer_key);
+
+    // 4-5. 资金划转与账面更新，资产池直接完成
+    let asset_pool_key = asset_pool.key();
+    let repaid_periods =
+        u16::try_from(repayment_count).map_err(|_| PencilError::ArithmeticOverflow)?;
+    let accounts = &mut *ctx.accounts;
+    settle_repayment(
+        RepaymentAccounts {
+            asset_pool: &mut accounts.asset_pool,
+            asset_pool_bump: ctx.bumps.asset_pool,
+            originator: &accounts.originator,
+            payer: &accounts.payer,
+            payer_token_account: &accounts.payer_token_account,
+            senior_pool: &mut accounts.senior_pool,
+            senior_pool_vault: &accounts.senior_pool_vault,
+            first_loss_pool: &mut accounts.first_loss_pool,
+            first_loss_pool_vault: &accounts.first_loss_pool_vault,
+            junior_interest_pool: &mut accounts.junior_interest_pool,
+            junior_interest_pool_vault: &accounts.junior_interest_pool_vault,
+            treasury_ata: &accounts.treasury_ata,
+            grow_token_mint: &accounts.grow_token_mint,
+            insurance_fund: &accounts.insurance_fund,
+            insurance_fund_vault: &accounts.insurance_fund_vault,
+            token_program: &accounts.token_program,
+        },
+        &distribution,
+        reserve_flow,
+        pool_reserve,
+        originator,
+        amount,
+        repaid_periods,
+        true,
+        clock.unix_timestamp,
+    )?;
 
-    // 每期 Senior 利息 = Senior本金 × Senior固定利率 / 10000
-    let senior_interest_per_period = ((senior_total as u128)
-        .checked_mul(senior_fixed_rate as u128)
-        .ok_or(PencilError::ArithmeticOverflow)?)
-    .checked_div(BASIS_POINTS as u128)
-    .ok_or(PencilError::ArithmeticOverflow)? as u64;
+    emit!(crate::PrepaymentProcessed {
+        asset_pool: asset_pool_key,
+        payer: payer_key,
+        third_party,
+        remaining_periods,
+        principal,
+        interest,
+        penalty,
+        platform_fee,
+        senior_amount: actual_senior_amount,
+        junior_interest,
+        timestamp: clock.unix_timestamp,
+    });
 
-    // 每期 Senior 总额
-    let senior_per_period = senior_principal_per_period
-        .checked_add(senior_interest_per_period)
-        .ok_or(PencilError::ArithmeticOverflow)?;
+    msg!(
+        "提前还款完成 - 剩余期数: {}, 本金: {}, 利息: {}, 违约金: {}",
+        remaining_periods,
+        principal,
+        interest,
+        penalty
+    );
 
-    Ok(senior_per_period)
+    Ok(())
 }
 
 // InterestClaimed event is now defined in lib.rs
@@ -423,7 +870,8 @@ pub struct ClaimJuniorInterest<'info> {
     #[account(
         mut,
         seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
-        bump
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
     )]
     pub asset_pool: Box<Account<'info, AssetPool>>,
 
@@ -472,10 +920,9 @@ pub struct ClaimJuniorInterest<'info> {
 
     #[account(
         mut,
-        token::mint = asset_mint,
-        token::authority = asset_pool
+        address = junior_interest_pool.vault @ PencilError::InvalidAccount
     )]
-    pub asset_pool_vault: Box<Account<'info, TokenAccount>>,
+    pub junior_interest_pool_vault: Box<Account<'info, TokenAccount>>,
 
     pub asset_mint: Box<Account<'info, anchor_spl::token::Mint>>,
 
@@ -493,24 +940,12 @@ pub fn claim_junior_interest(ctx: Context<ClaimJuniorInterest>, nft_id: u64) ->
     // 2. 从 JuniorInterestPool 计算可领取利息
     // 公式: 可领取 = (JuniorInterestPool 总额 × NFT 本金) / Junior 总本金 - 已领取
 
-    // 获取 Junior 总本金
-    let junior_total_principal = first_loss_pool.total_deposits;
-    require!(
-        junior_total_principal > 0,
-        PencilError::InvalidPrincipalCalculation
-    );
-
-    // 计算该 NFT 应得的总利息份额
-    let nft_share = ((junior_interest_pool.total_interest as u128)
-        .checked_mul(nft_metadata.principal as u128)
-        .ok_or(PencilError::ArithmeticOverflow)?)
-    .checked_div(junior_total_principal as u128)
-    .ok_or(PencilError::ArithmeticOverflow)? as u64;
-
-    // 计算可领取利息（总份额 - 已领取）
-    let claimable_interest = nft_share
-        .checked_sub(nft_metadata.claimed_interest)
-        .ok_or(PencilError::ArithmeticOverflow)?;
+    let claimable_interest = calculate_junior_claimable(
+        junior_interest_pool.total_interest,
+        nft_metadata.principal,
+        first_loss_pool.total_deposits,
+        nft_metadata.claimed_interest,
+    )?;
 
     require!(claimable_interest > 0, PencilError::NoInterestToClaim);
 
@@ -525,7 +960,7 @@ pub fn claim_junior_interest(ctx: Context<ClaimJuniorInterest>, nft_id: u64) ->
         PencilError::InsufficientPoolFunds
     );
 
-    // 3. 转账利息至用户 ATA
+    // 3. 从利息池 Vault 转账利息至用户 ATA
     let asset_pool_bump = ctx.bumps.asset_pool;
     let asset_pool_creator = asset_pool.creator;
     let asset_pool_name = asset_pool.name.clone();
@@ -538,7 +973,7 @@ pub fn claim_junior_interest(ctx: Context<ClaimJuniorInterest>, nft_id: u64) ->
     let signer_seeds = &[&asset_pool_seeds[..]];
 
     let transfer_cpi_accounts = Transfer {
-        from: ctx.accounts.asset_pool_vault.to_account_info(),
+        from: ctx.accounts.junior_interest_pool_vault.to_account_info(),
         to: ctx.accounts.user_asset_account.to_account_info(),
         authority: ctx.accounts.asset_pool.to_account_info(),
     };
@@ -597,7 +1032,8 @@ pub struct WithdrawPrincipal<'info> {
         mut,
         seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
         bump,
-        constraint = asset_pool.status == asset_pool_status::COMPLETED @ PencilError::InvalidAssetPoolStatus
+        constraint = asset_pool.status == asset_pool_status::COMPLETED @ PencilError::InvalidAssetPoolStatus,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
     )]
     pub asset_pool: Box<Account<'info, AssetPool>>,
 
@@ -640,10 +1076,9 @@ pub struct WithdrawPrincipal<'info> {
 
     #[account(
         mut,
-        token::mint = asset_mint,
-        token::authority = asset_pool
+        address = first_loss_pool.vault @ PencilError::InvalidAccount
     )]
-    pub asset_pool_vault: Box<Account<'info, TokenAccount>>,
+    pub first_loss_pool_vault: Box<Account<'info, TokenAccount>>,
 
     pub asset_mint: Box<Account<'info, anchor_spl::token::Mint>>,
 
@@ -672,27 +1107,23 @@ pub fn withdraw_principal(ctx: Context<WithdrawPrincipal>, nft_id: u64) -> Resul
     // 5. 计算按比例分配的金额（首损机制）
     // 按照EVM逻辑：assetAmount = vaultBalance * userShares / totalRemainingShares
 
-    // 获取vault当前余额
-    let vault_balance = ctx.accounts.asset_pool_vault.amount;
+    // 获取首损池 vault 当前余额
+    let vault_balance = ctx.accounts.first_loss_pool_vault.amount;
 
     // 计算剩余未提取的总份额
     let total_remaining_shares = first_loss_pool
         .total_deposits
-        .checked_sub(first_loss_pool.repaid_amount)
+        .checked_sub(first_loss_pool.withdrawn_shares)
         .ok_or(PencilError::ArithmeticOverflow)?;
 
-    require!(
-        total_remaining_shares > 0,
-        PencilError::InvalidPrincipalCalculation
-    );
-
     // 按比例计算用户应得金额
     // actual_amount = vault_balance * user_shares / total_remaining_shares
-    let actual_amount = (vault_balance as u128)
-        .checked_mul(user_shares as u128)
-        .ok_or(PencilError::ArithmeticOverflow)?
-        .checked_div(total_remaining_shares as u128)
-        .ok_or(PencilError::ArithmeticOverflow)? as u64;
+    let actual_amount = calculate_principal_withdrawal(
+        vault_balance,
+        user_shares,
+        first_loss_pool.total_deposits,
+        first_loss_pool.withdrawn_shares,
+    )?;
 
     msg!(
         "Junior本金提取计算 - Vault余额: {}, 用户份额: {}, 剩余总份额: {}, 实际金额: {}",
@@ -716,7 +1147,7 @@ pub fn withdraw_principal(ctx: Context<WithdrawPrincipal>, nft_id: u64) -> Resul
     let signer_seeds = &[&asset_pool_seeds[..]];
 
     let transfer_cpi_accounts = Transfer {
-        from: ctx.accounts.asset_pool_vault.to_account_info(),
+        from: ctx.accounts.first_loss_pool_vault.to_account_info(),
         to: ctx.accounts.user_asset_account.to_account_info(),
         authority: ctx.accounts.asset_pool.to_account_info(),
     };
@@ -725,12 +1156,15 @@ pub fn withdraw_principal(ctx: Context<WithdrawPrincipal>, nft_id: u64) -> Resul
         CpiContext::new_with_signer(transfer_cpi_program, transfer_cpi_accounts, signer_seeds);
     token::transfer(transfer_cpi_ctx, actual_amount)?;
 
-    // 更新 FirstLossPool repaid_amount（记录已处理的份额）
-    // 注意：这里记录的是用户份额，不是实际转账金额（按比例分配机制）
-    first_loss_pool.repaid_amount = first_loss_pool
-        .repaid_amount
+    // 份额与实际转账金额分开记录（按比例分配机制），repaid_amount 仅记录已动用金额
+    first_loss_pool.withdrawn_shares = first_loss_pool
+        .withdrawn_shares
         .checked_add(user_shares)
         .ok_or(PencilError::ArithmeticOverflow)?;
+    first_loss_pool.withdrawn_amount = first_loss_pool
+        .withdrawn_amount
+        .checked_add(actual_amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
 
     // 7. 标记 JuniorNFTMetadata.principal_withdrawn = true
     nft_metadata.principal_withdrawn = true;
@@ -773,7 +1207,8 @@ pub struct EarlyExitSenior<'info> {
     #[account(
         mut,
         seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
-        bump
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
     )]
     pub asset_pool: Box<Account<'info, AssetPool>>,
 
@@ -784,12 +1219,12 @@ pub struct EarlyExitSenior<'info> {
     )]
     pub senior_pool: Box<Account<'info, SeniorPool>>,
 
+    /// CHECK: Senior 早退队列 PDA，尚未创建时视为空队列
     #[account(
-        mut,
-        seeds = [seeds::FIRST_LOSS_POOL, asset_pool.key().as_ref()],
+        seeds = [seeds::SENIOR_EXIT_QUEUE, asset_pool.key().as_ref()],
         bump
     )]
-    pub first_loss_pool: Box<Account<'info, FirstLossPool>>,
+    pub exit_queue: UncheckedAccount<'info>,
 
     #[account(
         mut,
@@ -814,14 +1249,13 @@ pub struct EarlyExitSenior<'info> {
 
     #[account(
         mut,
-        token::mint = asset_mint,
-        token::authority = asset_pool
+        address = senior_pool.vault @ PencilError::InvalidAccount
     )]
-    pub asset_pool_vault: Box<Account<'info, TokenAccount>>,
+    pub senior_pool_vault: Box<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
-        token::mint = asset_mint
+        address = pool_fee_destination(&asset_pool, &system_config) @ PencilError::InvalidAccount
     )]
     pub treasury_ata: Box<Account<'info, TokenAccount>>,
 
@@ -831,6 +1265,10 @@ pub struct EarlyExitSenior<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Senior 早退
+/// 还款期间按面值即时退出，只能动用尚未分配的 Senior 还款，不动用 FirstLossPool；
+/// 早退队列中有待撮合请求或流动性不足时须通过 request_senior_exit 排队
+/// 资产池完成后按 GROW 比例提取优先池 Vault
 pub fn early_exit_senior(ctx: Context<EarlyExitSenior>, amount: u64) -> Result<()> {
     require!(amount > 0, PencilError::InvalidEarlyExitAmount);
 
@@ -871,16 +1309,20 @@ pub fn early_exit_senior(ctx: Context<EarlyExitSenior>, amount: u64) -> Result<(
         asset_pool.senior_early_after_exit_fee
     };
 
-    // 3. 计算早退费用和净退款金额
-    let exit_fee = ((amount as u128)
-        .checked_mul(early_exit_fee_rate as u128)
-        .ok_or(PencilError::ArithmeticOverflow)?)
-    .checked_div(BASIS_POINTS as u128)
-    .ok_or(PencilError::ArithmeticOverflow)? as u64;
-
-    let net_refund_amount = amount
-        .checked_sub(exit_fee)
+    // 3. 计算早退费用与退款：即时早退不超过尚未分配的 Senior 还款，并为排队请求按净值预留流动性
+    let vault_balance = ctx.accounts.senior_pool_vault.amount;
+    let senior_value = vault_balance
+        .checked_add(ctx.accounts.senior_pool.distributed_amount)
         .ok_or(PencilError::ArithmeticOverflow)?;
+    let liquidity = instant_exit_liquidity(
+        ctx.accounts.senior_pool.exit_liquidity.min(vault_balance),
+        queued_exit_amount(&ctx.accounts.exit_queue)?,
+        senior_value,
+        ctx.accounts.grow_token_mint.supply,
+    )?;
+    let distribution = distribute_early_exit(amount, early_exit_fee_rate, liquidity)?;
+    let exit_fee = distribution.exit_fee;
+    let actual_refund = distribution.refund;
 
     // 4. 销毁用户的 GROW Token
     let burn_cpi_accounts = Burn {
@@ -909,7 +1351,7 @@ pub fn early_exit_senior(ctx: Context<EarlyExitSenior>, amount: u64) -> Result<(
     // 5. 转账早退费用至金库 ATA
     if exit_fee > 0 {
         let fee_transfer_cpi_accounts = Transfer {
-            from: ctx.accounts.asset_pool_vault.to_account_info(),
+            from: ctx.accounts.senior_pool_vault.to_account_info(),
             to: ctx.accounts.treasury_ata.to_account_info(),
             authority: ctx.accounts.asset_pool.to_account_info(),
         };
@@ -927,47 +1369,10 @@ pub fn early_exit_senior(ctx: Context<EarlyExitSenior>, amount: u64) -> Result<(
         );
     }
 
-    // 6. 检查资产池 Vault 余额是否足够
-    let vault_balance = ctx.accounts.asset_pool_vault.amount;
-    let mut actual_refund = net_refund_amount;
-    let mut first_loss_used = 0u64;
-
-    // 7. 如果 Vault 余额不足，从 FirstLossPool 请求补足
-    if vault_balance < net_refund_amount {
-        let shortfall = net_refund_amount
-            .checked_sub(vault_balance)
-            .ok_or(PencilError::ArithmeticOverflow)?;
-
-        // 检查 FirstLossPool 可用余额
-        let first_loss_pool = &ctx.accounts.first_loss_pool;
-        let first_loss_available = first_loss_pool
-            .total_deposits
-            .checked_sub(first_loss_pool.repaid_amount)
-            .ok_or(PencilError::ArithmeticOverflow)?;
-
-        if first_loss_available >= shortfall {
-            // FirstLossPool 有足够余额
-            first_loss_used = shortfall;
-            msg!("FirstLossPool 补足早退差额: {} tokens", first_loss_used);
-        } else {
-            // FirstLossPool 余额不足，只能退还可用金额
-            first_loss_used = first_loss_available;
-            actual_refund = vault_balance
-                .checked_add(first_loss_used)
-                .ok_or(PencilError::ArithmeticOverflow)?;
-            msg!(
-                "警告: 资金不足，实际退款: {} tokens (Vault: {}, FirstLoss: {})",
-                actual_refund,
-                vault_balance,
-                first_loss_used
-            );
-        }
-    }
-
-    // 8. 转账净退款金额至用户 ATA
+    // 6. 退款从优先池 Vault 转给用户
     if actual_refund > 0 {
         let refund_transfer_cpi_accounts = Transfer {
-            from: ctx.accounts.asset_pool_vault.to_account_info(),
+            from: ctx.accounts.senior_pool_vault.to_account_info(),
             to: ctx.accounts.user_asset_account.to_account_info(),
             authority: ctx.accounts.asset_pool.to_account_info(),
         };
@@ -978,27 +1383,24 @@ pub fn early_exit_senior(ctx: Context<EarlyExitSenior>, amount: u64) -> Result<(
             signer_seeds,
         );
         token::transfer(refund_transfer_cpi_ctx, actual_refund)?;
-
-        msg!("Net refund transferred to user: {} tokens", actual_refund);
     }
 
-    // 更新 FirstLossPool 已使用金额
-    if first_loss_used > 0 {
-        let first_loss_pool = &mut ctx.accounts.first_loss_pool;
-        first_loss_pool.repaid_amount = first_loss_pool
-            .repaid_amount
-            .checked_add(first_loss_used)
-            .ok_or(PencilError::ArithmeticOverflow)?;
+    if actual_refund > 0 {
+        msg!("Net refund transferred to user: {} tokens", actual_refund);
     }
 
-    // 更新 SeniorPool 总存款（减少早退金额）
+    // 更新 SeniorPool 总存款与早退可用流动性（早退费用与退款均来自优先池 Vault）
     let senior_pool = &mut ctx.accounts.senior_pool;
     senior_pool.total_deposits = senior_pool
         .total_deposits
         .checked_sub(amount)
         .ok_or(PencilError::ArithmeticOverflow)?;
+    senior_pool.exit_liquidity = senior_pool
+        .exit_liquidity
+        .checked_sub(amount)
+        .ok_or(PencilError::ArithmeticOverflow)?;
 
-    // 9. 发出 EarlyExitProcessed 事件
+    // 7. 发出 EarlyExitProcessed 事件
     emit!(crate::EarlyExitProcessed {
         asset_pool: asset_pool.key(),
         user: ctx.accounts.user.key(),
@@ -1031,12 +1433,15 @@ fn handle_senior_withdraw_after_completion(
         PencilError::InvalidPrincipalCalculation
     );
 
-    // 2. 获取vault当前余额
-    let vault_balance = ctx.accounts.asset_pool_vault.amount;
+    // 2. 获取优先池 vault 当前余额（加回锁定 GROW 已领取的分配，保证各持有人按同一基数计算）
+    let vault_balance = ctx.accounts.senior_pool_vault.amount;
+    let distributable = (vault_balance as u128)
+        .checked_add(ctx.accounts.senior_pool.distributed_amount as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?;
 
     // 3. 按比例计算用户应得金额（类似Junior的按比例分配）
-    // actual_amount = vault_balance * user_grow_tokens / total_grow_supply
-    let actual_amount = (vault_balance as u128)
+    // actual_amount = (vault_balance + distributed_amount) * user_grow_tokens / total_grow_supply
+    let actual_amount = distributable
         .checked_mul(amount as u128)
         .ok_or(PencilError::ArithmeticOverflow)?
         .checked_div(grow_total_supply as u128)
@@ -1072,7 +1477,7 @@ fn handle_senior_withdraw_after_completion(
 
     // 6. 转账按比例计算的金额给用户
     let transfer_cpi_accounts = Transfer {
-        from: ctx.accounts.asset_pool_vault.to_account_info(),
+        from: ctx.accounts.senior_pool_vault.to_account_info(),
         to: ctx.accounts.user_asset_account.to_account_info(),
         authority: ctx.accounts.asset_pool.to_account_info(),
     };
@@ -1081,12 +1486,29 @@ fn handle_senior_withdraw_after_completion(
         CpiContext::new_with_signer(transfer_cpi_program, transfer_cpi_accounts, signer_seeds);
     token::transfer(transfer_cpi_ctx, actual_amount)?;
 
-    // 7. 更新SeniorPool的total_deposits
+    // 7. 更新SeniorPool账面：GROW 与 Senior 本金 1:1，超出本金的部分来自已还款金额
     let senior_pool = &mut ctx.accounts.senior_pool;
+    let principal_part = amount.min(senior_pool.total_deposits);
+    let repaid_part = actual_amount
+        .saturating_sub(principal_part)
+        .min(senior_pool.repaid_amount);
     senior_pool.total_deposits = senior_pool
         .total_deposits
-        .checked_sub(actual_amount)
+        .checked_sub(principal_part)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    senior_pool.repaid_amount = senior_pool
+        .repaid_amount
+        .checked_sub(repaid_part)
         .ok_or(PencilError::ArithmeticOverflow)?;
+    senior_pool.exit_liquidity = senior_pool.exit_liquidity.saturating_sub(actual_amount);
+
+    emit!(crate::SeniorWithdrawn {
+        asset_pool: ctx.accounts.asset_pool.key(),
+        user: ctx.accounts.user.key(),
+        grow_burned: amount,
+        amount: actual_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
 
     msg!(
         "Senior 正常提取完成 - 用户: {}, GROW销毁: {}, 实际金额: {}",
@@ -1097,3 +1519,363 @@ fn handle_senior_withdraw_after_completion(
 
     Ok(())
 }
+
+// JuniorEarlyExitProcessed event is defined in lib.rs
+
+/// Junior 早退
+///
+/// 未指定接盘方时从首损池退款，要求退出后 Junior 占比不低于 min_junior_ratio；
+/// 指定接盘方 (with_replacement) 时由接盘方付款并获得新的 Junior NFT，首损池资金不变；
+/// 新 NFT 编号由资产池按 issued_nft_count 分配。
+///
+/// 接盘时 remaining_accounts 依次传入
+/// [buyer (signer), buyer_asset_account, new_nft_mint, new_nft_metadata, buyer_nft_account]
+#[derive(Accounts)]
+#[instruction(nft_id: u64)]
+pub struct EarlyExitJunior<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = !asset_pool.paused @ PencilError::AssetPoolPaused
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        seeds = [seeds::SENIOR_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub senior_pool: Box<Account<'info, SeniorPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::FIRST_LOSS_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub first_loss_pool: Box<Account<'info, FirstLossPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::JUNIOR_INTEREST_POOL, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub junior_interest_pool: Box<Account<'info, JuniorInterestPool>>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [seeds::JUNIOR_NFT_METADATA, asset_pool.key().as_ref(), &nft_id.to_le_bytes()],
+        bump,
+        constraint = nft_metadata.owner == user.key() @ PencilError::Unauthorized,
+        constraint = !nft_metadata.principal_withdrawn @ PencilError::NoPrincipalToWithdraw
+    )]
+    pub nft_metadata: Box<Account<'info, JuniorNFTMetadata>>,
+
+    #[account(
+        mut,
+        token::mint = junior_nft_mint,
+        token::authority = user,
+        constraint = user_nft_account.amount == 1 @ PencilError::NFTNotOwnedByUser
+    )]
+    pub user_nft_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::JUNIOR_NFT_MINT, asset_pool.key().as_ref(), &nft_id.to_le_bytes()],
+        bump
+    )]
+    pub junior_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = asset_mint,
+        token::authority = user
+    )]
+    pub user_asset_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = first_loss_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub first_loss_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = junior_interest_pool.vault @ PencilError::InvalidAccount
+    )]
+    pub junior_interest_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = pool_fee_destination(&asset_pool, &system_config) @ PencilError::InvalidAccount
+    )]
+    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = asset_pool.asset_address @ PencilError::InvalidAccount)]
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn early_exit_junior<'info>(
+    ctx: Context<'_, '_, 'info, 'info, EarlyExitJunior<'info>>,
+    nft_id: u64,
+    with_replacement: bool,
+) -> Result<()> {
+    // 接盘方的新 NFT 编号由资产池分配
+    let replacement_nft_id = if with_replacement {
+        Some(next_issued_nft_id(&mut ctx.accounts.asset_pool)?)
+    } else {
+        None
+    };
+    let asset_pool = &ctx.accounts.asset_pool;
+    let clock = Clock::get()?;
+
+    // 1. 募资完成后、资产池完成前允许早退
+    require!(
+        asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING,
+        PencilError::InvalidAssetPoolStatus
+    );
+    require!(
+        asset_pool.related_accounts_initialized,
+        PencilError::RelatedAccountsNotInitialized
+    );
+    if with_replacement {
+        require!(
+            ctx.remaining_accounts.len() == 5,
+            PencilError::InvalidAccount
+        );
+    }
+
+    let asset_pool_key = asset_pool.key();
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[ctx.bumps.asset_pool],
+    ];
+    let signer_seeds = &[&asset_pool_seeds[..]];
+
+    // 2. 先结清该 NFT 未领取的利息，使其已领取利息等于按本金计算的全部份额
+    let principal = ctx.accounts.nft_metadata.principal;
+    let interest_claimed = calculate_junior_claimable(
+        ctx.accounts.junior_interest_pool.total_interest,
+        principal,
+        ctx.accounts.first_loss_pool.total_deposits,
+        ctx.accounts.nft_metadata.claimed_interest,
+    )?;
+    if interest_claimed > 0 {
+        let junior_interest_pool = &ctx.accounts.junior_interest_pool;
+        require!(
+            junior_interest_pool
+                .total_interest
+                .checked_sub(junior_interest_pool.distributed_interest)
+                .ok_or(PencilError::ArithmeticOverflow)?
+                >= interest_claimed,
+            PencilError::InsufficientPoolFunds
+        );
+        let interest_cpi_accounts = Transfer {
+            from: ctx.accounts.junior_interest_pool_vault.to_account_info(),
+            to: ctx.accounts.user_asset_account.to_account_info(),
+            authority: ctx.accounts.asset_pool.to_account_info(),
+        };
+        let interest_cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(
+            CpiContext::new_with_signer(interest_cpi_program, interest_cpi_accounts, signer_seeds),
+            interest_claimed,
+        )?;
+        let junior_interest_pool = &mut ctx.accounts.junior_interest_pool;
+        junior_interest_pool.distributed_interest = junior_interest_pool
+            .distributed_interest
+            .checked_add(interest_claimed)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+    let claimed_interest = ctx
+        .accounts
+        .nft_metadata
+        .claimed_interest
+        .checked_add(interest_claimed)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    // 3. 按首损池剩余资金折算本金价值并计算早退费用
+    let distribution = distribute_junior_exit(
+        principal,
+        ctx.accounts.first_loss_pool.total_deposits,
+        ctx.accounts.first_loss_pool_vault.amount,
+        ctx.accounts.senior_pool.total_deposits,
+        asset_pool.junior_early_before_exit_fee,
+    )?;
+    let exit_fee = distribution.exit_fee;
+    let net_amount = distribution.net()?;
+
+    // 4. 销毁 NFT 并关闭用户的 NFT Token 账户（元数据账户由 close 约束关闭）
+    let burn_cpi_accounts = Burn {
+        mint: ctx.accounts.junior_nft_mint.to_account_info(),
+        from: ctx.accounts.user_nft_account.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let burn_cpi_program = ctx.accounts.token_program.to_account_info();
+    token::burn(CpiContext::new(burn_cpi_program, burn_cpi_accounts), 1)?;
+
+    let close_cpi_accounts = CloseAccount {
+        account: ctx.accounts.user_nft_account.to_account_info(),
+        destination: ctx.accounts.user.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let close_cpi_program = ctx.accounts.token_program.to_account_info();
+    token::close_account(CpiContext::new(close_cpi_program, close_cpi_accounts))?;
+
+    let buyer_key = if let Some(new_nft_id) = replacement_nft_id {
+        // 5a. 接盘方付款并承接持仓：首损池与利息池账面不变
+        let buyer = Signer::try_from(&ctx.remaining_accounts[0])?;
+        let buyer_asset_info = &ctx.remaining_accounts[1];
+        let buyer_asset_account = Account::<TokenAccount>::try_from(buyer_asset_info)?;
+        require!(
+            buyer_asset_account.mint == ctx.accounts.asset_mint.key()
+                && buyer_asset_account.owner == buyer.key(),
+            PencilError::InvalidAccount
+        );
+
+        if exit_fee > 0 {
+            let fee_cpi_accounts = Transfer {
+                from: buyer_asset_info.clone(),
+                to: ctx.accounts.treasury_ata.to_account_info(),
+                authority: buyer.to_account_info(),
+            };
+            let fee_cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(CpiContext::new(fee_cpi_program, fee_cpi_accounts), exit_fee)?;
+        }
+        if net_amount > 0 {
+            let payment_cpi_accounts = Transfer {
+                from: buyer_asset_info.clone(),
+                to: ctx.accounts.user_asset_account.to_account_info(),
+                authority: buyer.to_account_info(),
+            };
+            let payment_cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(
+                CpiContext::new(payment_cpi_program, payment_cpi_accounts),
+                net_amount,
+            )?;
+        }
+
+        create_junior_nft_position(
+            &ctx.accounts.asset_pool,
+            ctx.bumps.asset_pool,
+            &buyer,
+            &ctx.remaining_accounts[2],
+            &ctx.remaining_accounts[3],
+            &ctx.remaining_accounts[4],
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+            &ctx.accounts.system_program,
+            JuniorNFTMetadata {
+                nft_id: new_nft_id,
+                asset_pool: asset_pool_key,
+                owner: buyer.key(),
+                principal,
+                claimed_interest,
+                principal_withdrawn: false,
+                created_at: clock.unix_timestamp,
+                _reserved: [0u8; 128],
+            },
+        )?;
+
+        Some(buyer.key())
+    } else {
+        // 5b. 从首损池退款：退出后 Junior 占比仍需覆盖 Senior
+        require!(
+            distribution.junior_ratio_after >= asset_pool.min_junior_ratio as u64,
+            PencilError::InvalidJuniorRatio
+        );
+
+        if exit_fee > 0 {
+            let fee_cpi_accounts = Transfer {
+                from: ctx.accounts.first_loss_pool_vault.to_account_info(),
+                to: ctx.accounts.treasury_ata.to_account_info(),
+                authority: ctx.accounts.asset_pool.to_account_info(),
+            };
+            let fee_cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(
+                CpiContext::new_with_signer(fee_cpi_program, fee_cpi_accounts, signer_seeds),
+                exit_fee,
+            )?;
+        }
+        if net_amount > 0 {
+            let refund_cpi_accounts = Transfer {
+                from: ctx.accounts.first_loss_pool_vault.to_account_info(),
+                to: ctx.accounts.user_asset_account.to_account_info(),
+                authority: ctx.accounts.asset_pool.to_account_info(),
+            };
+            let refund_cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer(
+                CpiContext::new_with_signer(refund_cpi_program, refund_cpi_accounts, signer_seeds),
+                net_amount,
+            )?;
+        }
+
+        // 首损池按份额扣减本金与已动用金额，剩余 NFT 的折算比例不变
+        let first_loss_pool = &mut ctx.accounts.first_loss_pool;
+        let used_part = principal
+            .saturating_sub(distribution.gross_amount)
+            .min(first_loss_pool.repaid_amount);
+        first_loss_pool.total_deposits = first_loss_pool
+            .total_deposits
+            .checked_sub(principal)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        first_loss_pool.repaid_amount = first_loss_pool
+            .repaid_amount
+            .checked_sub(used_part)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+
+        // 利息池同步扣除该 NFT 的全部利息份额，剩余 NFT 的可领取利息不变
+        let junior_interest_pool = &mut ctx.accounts.junior_interest_pool;
+        junior_interest_pool.total_interest = junior_interest_pool
+            .total_interest
+            .checked_sub(claimed_interest)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        junior_interest_pool.distributed_interest = junior_interest_pool
+            .distributed_interest
+            .checked_sub(claimed_interest)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+
+        None
+    };
+
+    emit!(crate::JuniorEarlyExitProcessed {
+        asset_pool: asset_pool_key,
+        user: ctx.accounts.user.key(),
+        nft_id,
+        principal,
+        interest_claimed,
+        amount: distribution.gross_amount,
+        fee: exit_fee,
+        net_amount,
+        buyer: buyer_key,
+        replacement_nft_id,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Junior 早退完成 - NFT ID: {}, 用户: {}, 本金: {}, 费用: {}, 净额: {}",
+        nft_id,
+        ctx.accounts.user.key(),
+        principal,
+        exit_fee,
+        net_amount
+    );
+
+    Ok(())
+}
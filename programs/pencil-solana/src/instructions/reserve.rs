@@ -0,0 +1,365 @@
+use crate::constants::*;
+use crate::errors::PencilError;
+use crate::state::{AssetPool, FirstLossPool, JuniorInterestPool, PoolReserve, SystemConfig};
+use crate::waterfall::ReserveFlow;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+// PoolReserveInitialized / PoolReserveUpdated / PoolReserveReleased events are defined in lib.rs
+
+#[derive(Accounts)]
+pub struct InitializePoolReserve<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.super_admin == admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + std::mem::size_of::<PoolReserve>(),
+        seeds = [seeds::POOL_RESERVE, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub pool_reserve: Box<Account<'info, PoolReserve>>,
+
+    /// 储备金 Token Vault PDA（由 AssetPool PDA 持有）
+    #[account(
+        init,
+        payer = admin,
+        token::mint = asset_mint,
+        token::authority = asset_pool,
+        seeds = [seeds::POOL_RESERVE_VAULT, asset_pool.key().as_ref()],
+        bump
+    )]
+    pub reserve_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = asset_pool.asset_address @ PencilError::InvalidAccount)]
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/// 为资产池开启储备金（仅限募资完成前，避免改变已认购 Junior 的收益预期）
+/// fee_share: 平台费截留比例；interest_share: Junior 利息截留比例（均为基点）
+pub fn initialize_pool_reserve(
+    ctx: Context<InitializePoolReserve>,
+    fee_share: u16,
+    interest_share: u16,
+) -> Result<()> {
+    let asset_pool = &mut ctx.accounts.asset_pool;
+
+    require!(
+        asset_pool.status == asset_pool_status::CREATED
+            || asset_pool.status == asset_pool_status::APPROVED,
+        PencilError::InvalidAssetPoolStatus
+    );
+    require!(
+        fee_share <= BASIS_POINTS && interest_share <= BASIS_POINTS,
+        PencilError::InvalidPoolReserve
+    );
+
+    let pool_reserve = &mut ctx.accounts.pool_reserve;
+    pool_reserve.asset_pool = asset_pool.key();
+    pool_reserve.vault = ctx.accounts.reserve_vault.key();
+    pool_reserve.fee_share = fee_share;
+    pool_reserve.interest_share = interest_share;
+    pool_reserve.funded_from_fees = 0;
+    pool_reserve.funded_from_interest = 0;
+    pool_reserve.used_amount = 0;
+    pool_reserve.released = false;
+
+    asset_pool.reserve_enabled = true;
+
+    let clock = Clock::get()?;
+    emit!(crate::PoolReserveInitialized {
+        asset_pool: asset_pool.key(),
+        reserve: pool_reserve.key(),
+        fee_share,
+        interest_share,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "储备金已开启 - 资产池: {}, 平台费截留: {}, 利息截留: {}",
+        asset_pool.key(),
+        fee_share,
+        interest_share
+    );
+
+    Ok(())
+}
+
+/// 读取资产池储备金
+/// 未启用时返回 None；启用后 remaining_accounts 须以 [pool_reserve, reserve_vault] 开头
+pub(crate) fn load_pool_reserve<'info>(
+    asset_pool: &Account<'info, AssetPool>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<Option<(Account<'info, PoolReserve>, Account<'info, TokenAccount>)>> {
+    if !asset_pool.reserve_enabled {
+        return Ok(None);
+    }
+
+    let [reserve_info, vault_info, ..] = remaining_accounts else {
+        return err!(PencilError::PoolReserveRequired);
+    };
+    let pool_reserve = Account::<PoolReserve>::try_from(reserve_info)?;
+    require!(
+        pool_reserve.asset_pool == asset_pool.key(),
+        PencilError::InvalidAccount
+    );
+    require!(
+        vault_info.key() == pool_reserve.vault,
+        PencilError::InvalidAccount
+    );
+    require!(!pool_reserve.released, PencilError::InvalidPoolReserve);
+    let reserve_vault = Account::<TokenAccount>::try_from(vault_info)?;
+
+    Ok(Some((pool_reserve, reserve_vault)))
+}
+
+/// 结算本期储备金流水：付款人存入截留部分，储备金 Vault 补足 Senior 缺口
+/// 返回结算后的储备金余额
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn settle_reserve_flow<'info>(
+    pool_reserve: &mut Account<'info, PoolReserve>,
+    reserve_vault: &Account<'info, TokenAccount>,
+    asset_pool: &Account<'info, AssetPool>,
+    asset_pool_bump: u8,
+    payer: &Signer<'info>,
+    payer_token_account: &Account<'info, TokenAccount>,
+    senior_pool_vault: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    flow: ReserveFlow,
+) -> Result<u64> {
+    let deposit = flow.deposit()?;
+    if deposit > 0 {
+        let cpi_accounts = Transfer {
+            from: payer_token_account.to_account_info(),
+            to: reserve_vault.to_account_info(),
+            authority: payer.to_account_info(),
+        };
+        let cpi_program = token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), deposit)?;
+    }
+
+    if flow.used > 0 {
+        let asset_pool_seeds = &[
+            seeds::ASSET_POOL,
+            asset_pool.creator.as_ref(),
+            &asset_pool.name,
+            &[asset_pool_bump],
+        ];
+        let signer_seeds = &[&asset_pool_seeds[..]];
+
+        let cpi_accounts = Transfer {
+            from: reserve_vault.to_account_info(),
+            to: senior_pool_vault.to_account_info(),
+            authority: asset_pool.to_account_info(),
+        };
+        let cpi_program = token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, flow.used)?;
+    }
+
+    pool_reserve.funded_from_fees = pool_reserve
+        .funded_from_fees
+        .checked_add(flow.from_fees)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    pool_reserve.funded_from_interest = pool_reserve
+        .funded_from_interest
+        .checked_add(flow.from_interest)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    pool_reserve.used_amount = pool_reserve
+        .used_amount
+        .checked_add(flow.used)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    pool_reserve.exit(&crate::ID)?;
+
+    let balance = reserve_vault
+        .amount
+        .checked_add(deposit)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_sub(flow.used)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    if deposit > 0 || flow.used > 0 {
+        let clock = Clock::get()?;
+        emit!(crate::PoolReserveUpdated {
+            asset_pool: asset_pool.key(),
+            from_fees: flow.from_fees,
+            from_interest: flow.from_interest,
+            used: flow.used,
+            balance,
+            timestamp: clock.unix_timestamp,
+        });
+        msg!(
+            "储备金结算 - 截留平台费: {}, 截留利息: {}, 补足Senior: {}, 余额: {}",
+            flow.from_fees,
+            flow.from_interest,
+            flow.used,
+            balance
+        );
+    }
+
+    Ok(balance)
+}
+
+/// 资产池完成时的储备金释放
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveRelease {
+    /// 返还首损池（弥补此前动用的 Junior 本金）
+    pub to_first_loss: u64,
+    /// 转入 Junior 利息池
+    pub to_junior_interest: u64,
+    /// 转入金库
+    pub to_treasury: u64,
+}
+
+/// 储备金释放规则
+/// 1. 先返还首损池已动用的金额；
+/// 2. 剩余部分按累计截留来源拆分：来自 Junior 利息的比例转入利息池，其余转入金库
+pub fn calculate_reserve_release(
+    balance: u64,
+    first_loss_used: u64,
+    funded_from_fees: u64,
+    funded_from_interest: u64,
+) -> Result<ReserveRelease> {
+    let to_first_loss = balance.min(first_loss_used);
+    let remaining = balance - to_first_loss;
+    let funded = (funded_from_fees as u128)
+        .checked_add(funded_from_interest as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let to_junior_interest = if funded == 0 {
+        0
+    } else {
+        (remaining as u128)
+            .checked_mul(funded_from_interest as u128)
+            .ok_or(PencilError::ArithmeticOverflow)?
+            .checked_div(funded)
+            .ok_or(PencilError::ArithmeticOverflow)? as u64
+    };
+
+    Ok(ReserveRelease {
+        to_first_loss,
+        to_junior_interest,
+        to_treasury: remaining - to_junior_interest,
+    })
+}
+
+/// 资产池完成时释放储备金
+/// 先返还首损池已动用的 Junior 本金，剩余按累计截留来源拆分至利息池与金库
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn release_pool_reserve<'info>(
+    pool_reserve: &mut Account<'info, PoolReserve>,
+    reserve_vault: &Account<'info, TokenAccount>,
+    balance: u64,
+    asset_pool: &Account<'info, AssetPool>,
+    asset_pool_bump: u8,
+    first_loss_pool: &mut Account<'info, FirstLossPool>,
+    first_loss_pool_vault: &Account<'info, TokenAccount>,
+    junior_interest_pool: &mut Account<'info, JuniorInterestPool>,
+    junior_interest_pool_vault: &Account<'info, TokenAccount>,
+    treasury_ata: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+) -> Result<ReserveRelease> {
+    let release = calculate_reserve_release(
+        balance,
+        first_loss_pool.repaid_amount,
+        pool_reserve.funded_from_fees,
+        pool_reserve.funded_from_interest,
+    )?;
+
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[asset_pool_bump],
+    ];
+    let signer_seeds = &[&asset_pool_seeds[..]];
+    for (to, amount) in [
+        (first_loss_pool_vault, release.to_first_loss),
+        (junior_interest_pool_vault, release.to_junior_interest),
+        (treasury_ata, release.to_treasury),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        let cpi_accounts = Transfer {
+            from: reserve_vault.to_account_info(),
+            to: to.to_account_info(),
+            authority: asset_pool.to_account_info(),
+        };
+        let cpi_program = token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, amount)?;
+    }
+
+    // 返还首损池后，Junior 可赎回的本金相应恢复
+    first_loss_pool.repaid_amount = first_loss_pool
+        .repaid_amount
+        .checked_sub(release.to_first_loss)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    junior_interest_pool.total_interest = junior_interest_pool
+        .total_interest
+        .checked_add(release.to_junior_interest)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    pool_reserve.released = true;
+    pool_reserve.exit(&crate::ID)?;
+
+    let clock = Clock::get()?;
+    emit!(crate::PoolReserveReleased {
+        asset_pool: asset_pool.key(),
+        to_first_loss: release.to_first_loss,
+        to_junior_interest: release.to_junior_interest,
+        to_treasury: release.to_treasury,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "储备金已释放 - 返还首损池: {}, Junior利息: {}, 金库: {}",
+        release.to_first_loss,
+        release.to_junior_interest,
+        release.to_treasury
+    );
+
+    Ok(release)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_release_restores_first_loss_first() {
+        let release = calculate_reserve_release(100, 30, 40, 120).unwrap();
+        assert_eq!(
+            release,
+            ReserveRelease {
+                to_first_loss: 30,
+                to_junior_interest: 52,
+                to_treasury: 18
+            }
+        );
+        let release = calculate_reserve_release(20, 30, 40, 120).unwrap();
+        assert_eq!((release.to_first_loss, release.to_treasury), (20, 0));
+        assert_eq!(
+            calculate_reserve_release(10, 0, 0, 0).unwrap().to_treasury,
+            10
+        );
+    }
+}
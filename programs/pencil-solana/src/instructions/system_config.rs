@@ -1,7 +1,10 @@
 use crate::constants::*;
 use crate::errors::PencilError;
-use crate::state::SystemConfig;
+use crate::instructions::pool_params::check_system_bounds;
+use crate::state::{AssetPool, SystemConfig};
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 
 #[derive(Accounts)]
 pub struct InitializeSystemConfig<'info> {
@@ -49,8 +52,7 @@ pub fn initialize_system_config(
         PencilError::InvalidEarlyExitFee
     );
     require!(
-        default_min_junior_ratio >= MIN_JUNIOR_RATIO
-            && default_min_junior_ratio <= MAX_JUNIOR_RATIO,
+        (MIN_JUNIOR_RATIO..=MAX_JUNIOR_RATIO).contains(&default_min_junior_ratio),
         PencilError::InvalidMinJuniorRatio
     );
 
@@ -84,6 +86,17 @@ pub fn initialize_system_config(
     );
     msg!("Default min junior ratio: {}", default_min_junior_ratio);
 
+    emit!(crate::SystemConfigInitialized {
+        super_admin: system_config.super_admin,
+        treasury: system_config.treasury,
+        platform_fee_rate,
+        senior_early_before_exit_fee_rate,
+        senior_early_after_exit_fee_rate,
+        junior_early_before_exit_fee_rate,
+        default_min_junior_ratio,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
 }
 
@@ -223,6 +236,20 @@ pub enum FeeType {
     SeniorEarlyBeforeExitFee,
     SeniorEarlyAfterExitFee,
     JuniorEarlyBeforeExitFee,
+    MarketplaceFee,
+}
+
+impl FeeType {
+    /// 对应的资产池参数编号，市场手续费不属于资产池参数
+    pub fn pool_param(self) -> Option<u8> {
+        match self {
+            FeeType::PlatformFee => Some(pool_param::PLATFORM_FEE),
+            FeeType::SeniorEarlyBeforeExitFee => Some(pool_param::SENIOR_EARLY_BEFORE_EXIT_FEE),
+            FeeType::SeniorEarlyAfterExitFee => Some(pool_param::SENIOR_EARLY_AFTER_EXIT_FEE),
+            FeeType::JuniorEarlyBeforeExitFee => Some(pool_param::JUNIOR_EARLY_BEFORE_EXIT_FEE),
+            FeeType::MarketplaceFee => None,
+        }
+    }
 }
 
 #[derive(Accounts)]
@@ -288,8 +315,23 @@ pub fn update_fee_rate(
             msg!("Junior early before exit fee rate updated to: {}", new_rate);
             (3u8, old)
         }
+        FeeType::MarketplaceFee => {
+            require!(
+                new_rate <= MAX_MARKETPLACE_FEE,
+                PencilError::InvalidMarketplaceFee
+            );
+            let old = system_config.marketplace_fee_rate;
+            system_config.marketplace_fee_rate = new_rate;
+            msg!("Marketplace fee rate updated to: {}", new_rate);
+            (4u8, old)
+        }
     };
 
+    // 资产池参数默认值须在系统设置的取值范围内
+    if let Some(param) = fee_type.pool_param() {
+        check_system_bounds(system_config, param, new_rate)?;
+    }
+
     // Emit FeeRateUpdated event
     emit!(crate::FeeRateUpdated {
         fee_type: fee_type_u8,
@@ -319,13 +361,133 @@ pub struct SetTreasury<'info> {
 
 pub fn set_treasury(ctx: Context<SetTreasury>, treasury: Pubkey) -> Result<()> {
     let system_config = &mut ctx.accounts.system_config;
+    let old_treasury = system_config.treasury;
     system_config.treasury = treasury;
 
+    emit!(crate::TreasuryUpdated {
+        updated_by: ctx.accounts.system_admin.key(),
+        old_treasury,
+        new_treasury: treasury,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     msg!("Treasury updated to: {}", treasury);
 
     Ok(())
 }
 
+/// 金库变更后迁移资产池的金库记录（系统管理员）
+/// 为当前金库创建资产代币 ATA，并更新各资产池的 treasury / treasury_ata；
+/// 已开启手续费归集的资产池保留归集账户
+///
+/// remaining_accounts: 待迁移的资产池（mut），资产代币须为 asset_mint
+#[derive(Accounts)]
+pub struct MigratePoolTreasury<'info> {
+    #[account(mut)]
+    pub system_admin: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.system_admin == system_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    /// CHECK: 当前金库账户
+    #[account(address = system_config.treasury @ PencilError::InvalidTreasuryAddress)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = system_admin,
+        associated_token::mint = asset_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+
+    pub asset_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_pool_treasury<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MigratePoolTreasury<'info>>,
+) -> Result<()> {
+    let treasury = ctx.accounts.system_config.treasury;
+    let treasury_ata = ctx.accounts.treasury_ata.key();
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    for asset_pool_info in ctx.remaining_accounts.iter() {
+        let mut asset_pool = Account::<AssetPool>::try_from(asset_pool_info)?;
+        require!(
+            asset_pool.related_accounts_initialized,
+            PencilError::RelatedAccountsNotInitialized
+        );
+        require_keys_eq!(
+            asset_pool.asset_address,
+            ctx.accounts.asset_mint.key(),
+            PencilError::InvalidAccount
+        );
+
+        let old_treasury = asset_pool.treasury;
+        asset_pool.treasury = treasury;
+        if !asset_pool.fee_split_enabled {
+            asset_pool.treasury_ata = treasury_ata;
+        }
+        asset_pool.exit(&crate::ID)?;
+
+        emit!(crate::PoolTreasuryMigrated {
+            asset_pool: asset_pool.key(),
+            old_treasury,
+            new_treasury: treasury,
+            treasury_ata: asset_pool.treasury_ata,
+            timestamp,
+        });
+    }
+
+    msg!(
+        "Pool treasury migrated - treasury: {}, pools: {}",
+        treasury,
+        ctx.remaining_accounts.len()
+    );
+
+    Ok(())
+}
+
+// ==================== Vault Drift Threshold ====================
+
+#[derive(Accounts)]
+pub struct SetMaxVaultDrift<'info> {
+    #[account(mut)]
+    pub system_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = system_config.system_admin == system_admin.key() @ PencilError::Unauthorized
+    )]
+    pub system_config: Account<'info, SystemConfig>,
+}
+
+pub fn set_max_vault_drift(ctx: Context<SetMaxVaultDrift>, max_vault_drift: u64) -> Result<()> {
+    let system_config = &mut ctx.accounts.system_config;
+    let old_max_vault_drift = system_config.max_vault_drift;
+    system_config.max_vault_drift = max_vault_drift;
+
+    emit!(crate::MaxVaultDriftUpdated {
+        old_max_vault_drift,
+        new_max_vault_drift: max_vault_drift,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Max vault drift updated to: {}", max_vault_drift);
+
+    Ok(())
+}
+
 // ==================== Asset Whitelist Management ====================
 
 use crate::state::AssetWhitelist;
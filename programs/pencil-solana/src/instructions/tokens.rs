@@ -1,17 +1,21 @@
 use crate::constants::*;
 use crate::errors::PencilError;
-use crate::state::{AssetPool, JuniorNFTMetadata};
+use crate::instructions::utils::split_claimed_interest;
+use crate::state::{AssetPool, JuniorNFTMetadata, SystemConfig};
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount};
-
-/// GROW Token Mint 创建逻辑
-/// PDA seeds: [b"grow_token_mint", asset_pool.key()]
-/// Mint Authority: AssetPool PDA
-/// Decimals: 与资产代币一致
-///
-/// 注意：GROW Token Mint 在 initialize_related_accounts 指令中创建
-/// 本文件提供 mint_to 和 burn 操作
+use anchor_lang::system_program::{self, CreateAccount};
+use anchor_spl::associated_token::{self, AssociatedToken};
+use anchor_spl::token::{
+    self, Burn, CloseAccount, InitializeMint2, Mint, MintTo, Token, TokenAccount,
+};
+
+// GROW Token Mint 创建逻辑
+// PDA seeds: [b"grow_token_mint", asset_pool.key()]
+// Mint Authority: AssetPool PDA
+// Decimals: 与资产代币一致
+//
+// 注意：GROW Token Mint 在 initialize_related_accounts 指令中创建
+// 本文件提供 mint_to 和 burn 操作
 
 /// Mint GROW Token 给用户
 /// 用于募资完成后分发代币
@@ -75,6 +79,13 @@ pub fn mint_grow_token(ctx: Context<MintGrowToken>, amount: u64) -> Result<()> {
     let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, asset_pool_signer);
     token::mint_to(cpi_ctx, amount)?;
 
+    emit!(crate::GrowTokenMinted {
+        asset_pool: ctx.accounts.asset_pool.key(),
+        recipient: ctx.accounts.recipient.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     msg!(
         "GROW Token minted: {} tokens to {}",
         amount,
@@ -132,6 +143,13 @@ pub fn burn_grow_token(ctx: Context<BurnGrowToken>, amount: u64) -> Result<()> {
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
     token::burn(cpi_ctx, amount)?;
 
+    emit!(crate::GrowTokenBurned {
+        asset_pool: ctx.accounts.asset_pool.key(),
+        user: ctx.accounts.user.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     msg!(
         "GROW Token burned: {} tokens from {}",
         amount,
@@ -141,14 +159,14 @@ pub fn burn_grow_token(ctx: Context<BurnGrowToken>, amount: u64) -> Result<()> {
     Ok(())
 }
 
-/// Junior NFT Mint 创建逻辑
-/// PDA seeds: [b"junior_nft_mint", asset_pool.key(), nft_id]
-/// Mint Authority: AssetPool PDA
-/// Supply: 1 (每个 NFT 唯一)
-/// Decimals: 0 (NFT 不可分割)
-///
-/// 注意：每个 Junior NFT 都有独立的 Mint 账户
-/// 在 distribute_junior_nft 指令中创建和铸造
+// Junior NFT Mint 创建逻辑
+// PDA seeds: [b"junior_nft_mint", asset_pool.key(), nft_id]
+// Mint Authority: AssetPool PDA
+// Supply: 1 (每个 NFT 唯一)
+// Decimals: 0 (NFT 不可分割)
+//
+// 注意：每个 Junior NFT 都有独立的 Mint 账户
+// 在 distribute_junior_nft 指令中创建和铸造
 
 /// Mint Junior NFT 给用户
 /// 用于募资完成后分发 NFT
@@ -203,6 +221,7 @@ pub struct MintJuniorNFT<'info> {
 
 pub fn mint_junior_nft(ctx: Context<MintJuniorNFT>, nft_id: u64, principal: u64) -> Result<()> {
     require!(principal > 0, PencilError::InvalidSubscriptionAmount);
+    require!(nft_id < JUNIOR_NFT_ISSUED_ID_BASE, PencilError::InvalidNFT);
 
     let asset_pool = &ctx.accounts.asset_pool;
     let clock = Clock::get()?;
@@ -236,6 +255,14 @@ pub fn mint_junior_nft(ctx: Context<MintJuniorNFT>, nft_id: u64, principal: u64)
     nft_metadata.principal_withdrawn = false;
     nft_metadata.created_at = clock.unix_timestamp;
 
+    emit!(crate::JuniorNftMinted {
+        asset_pool: asset_pool.key(),
+        recipient: ctx.accounts.recipient.key(),
+        nft_id,
+        principal,
+        timestamp: clock.unix_timestamp,
+    });
+
     msg!(
         "Junior NFT minted: ID {}, Principal {} to {}",
         nft_id,
@@ -245,3 +272,479 @@ pub fn mint_junior_nft(ctx: Context<MintJuniorNFT>, nft_id: u64, principal: u64)
 
     Ok(())
 }
+
+/// 拆分 Junior NFT
+/// 销毁原 NFT，按 amounts 为每一份铸造新的独立 Mint NFT
+/// 已领取利息按本金比例拆分，资产池层面的总额保持不变
+/// 新 NFT 编号由资产池按 issued_nft_count 依次分配，不占用分发编号
+///
+/// remaining_accounts: 每个新 NFT 依次传入
+/// [junior_nft_mint, nft_metadata, user_token_account]
+#[derive(Accounts)]
+#[instruction(nft_id: u64)]
+pub struct SplitJuniorNFT<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING
+            || asset_pool.status == asset_pool_status::COMPLETED @ PencilError::InvalidAssetPoolStatus
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [seeds::JUNIOR_NFT_METADATA, asset_pool.key().as_ref(), &nft_id.to_le_bytes()],
+        bump,
+        constraint = nft_metadata.owner == user.key() @ PencilError::NFTNotOwnedByUser,
+        constraint = !nft_metadata.principal_withdrawn @ PencilError::PrincipalAlreadyWithdrawn
+    )]
+    pub nft_metadata: Box<Account<'info, JuniorNFTMetadata>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::JUNIOR_NFT_MINT, asset_pool.key().as_ref(), &nft_id.to_le_bytes()],
+        bump
+    )]
+    pub junior_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = junior_nft_mint,
+        token::authority = user,
+        constraint = user_nft_account.amount == 1 @ PencilError::NFTNotOwnedByUser
+    )]
+    pub user_nft_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn split_junior_nft<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SplitJuniorNFT<'info>>,
+    nft_id: u64,
+    amounts: Vec<u64>,
+) -> Result<()> {
+    require!(
+        amounts.len() >= 2 && amounts.len() <= MAX_NFT_SPLIT_COUNT,
+        PencilError::InvalidSplitAmounts
+    );
+    require!(
+        ctx.remaining_accounts.len() == amounts.len() * 3,
+        PencilError::InvalidAccount
+    );
+
+    // 验证拆分金额之和等于原 NFT 本金
+    let principal = ctx.accounts.nft_metadata.principal;
+    let mut total = 0u64;
+    for amount in amounts.iter() {
+        require!(*amount > 0, PencilError::InvalidSplitAmounts);
+        total = total
+            .checked_add(*amount)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+    require!(total == principal, PencilError::InvalidSplitAmounts);
+
+    let new_nft_ids = amounts
+        .iter()
+        .map(|_| next_issued_nft_id(&mut ctx.accounts.asset_pool))
+        .collect::<Result<Vec<u64>>>()?;
+
+    let claimed_shares = split_claimed_interest(
+        ctx.accounts.nft_metadata.claimed_interest,
+        principal,
+        &amounts,
+    )?;
+
+    // 1. 销毁原 NFT 并关闭用户的 NFT Token 账户
+    let burn_cpi_accounts = Burn {
+        mint: ctx.accounts.junior_nft_mint.to_account_info(),
+        from: ctx.accounts.user_nft_account.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::burn(CpiContext::new(cpi_program, burn_cpi_accounts), 1)?;
+
+    let close_cpi_accounts = CloseAccount {
+        account: ctx.accounts.user_nft_account.to_account_info(),
+        destination: ctx.accounts.user.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::close_account(CpiContext::new(cpi_program, close_cpi_accounts))?;
+
+    // 2. 为每一份铸造新的 NFT
+    let asset_pool_key = ctx.accounts.asset_pool.key();
+    let clock = Clock::get()?;
+    for (index, new_nft_id) in new_nft_ids.iter().enumerate() {
+        let accounts = &ctx.remaining_accounts[index * 3..index * 3 + 3];
+        create_junior_nft_position(
+            &ctx.accounts.asset_pool,
+            ctx.bumps.asset_pool,
+            &ctx.accounts.user,
+            &accounts[0],
+            &accounts[1],
+            &accounts[2],
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+            &ctx.accounts.system_program,
+            JuniorNFTMetadata {
+                nft_id: *new_nft_id,
+                asset_pool: asset_pool_key,
+                owner: ctx.accounts.user.key(),
+                principal: amounts[index],
+                claimed_interest: claimed_shares[index],
+                principal_withdrawn: false,
+                created_at: clock.unix_timestamp,
+                _reserved: [0u8; 128],
+            },
+        )?;
+    }
+
+    emit!(crate::JuniorNFTSplit {
+        asset_pool: asset_pool_key,
+        user: ctx.accounts.user.key(),
+        nft_id,
+        new_nft_ids: new_nft_ids.clone(),
+        amounts: amounts.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Junior NFT {} split into {} NFTs: {:?}",
+        nft_id,
+        new_nft_ids.len(),
+        new_nft_ids
+    );
+
+    Ok(())
+}
+
+/// 合并 Junior NFT
+/// 将多个同一资产池、同一所有者的 NFT 合并到目标 NFT
+/// 源 NFT 被销毁，本金与已领取利息累加到目标 NFT
+///
+/// remaining_accounts: 每个源 NFT 依次传入
+/// [nft_metadata, junior_nft_mint, user_nft_account]
+#[derive(Accounts)]
+#[instruction(target_nft_id: u64)]
+pub struct MergeJuniorNFTs<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::SYSTEM_CONFIG],
+        bump,
+        constraint = !system_config.paused @ PencilError::SystemPaused
+    )]
+    pub system_config: Box<Account<'info, SystemConfig>>,
+
+    #[account(
+        seeds = [seeds::ASSET_POOL, asset_pool.creator.as_ref(), &asset_pool.name],
+        bump,
+        constraint = asset_pool.status == asset_pool_status::FUNDED
+            || asset_pool.status == asset_pool_status::REPAYING
+            || asset_pool.status == asset_pool_status::COMPLETED @ PencilError::InvalidAssetPoolStatus
+    )]
+    pub asset_pool: Box<Account<'info, AssetPool>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::JUNIOR_NFT_METADATA, asset_pool.key().as_ref(), &target_nft_id.to_le_bytes()],
+        bump,
+        constraint = target_metadata.owner == user.key() @ PencilError::NFTNotOwnedByUser,
+        constraint = !target_metadata.principal_withdrawn @ PencilError::PrincipalAlreadyWithdrawn
+    )]
+    pub target_metadata: Box<Account<'info, JuniorNFTMetadata>>,
+
+    #[account(
+        seeds = [seeds::JUNIOR_NFT_MINT, asset_pool.key().as_ref(), &target_nft_id.to_le_bytes()],
+        bump
+    )]
+    pub target_nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        token::mint = target_nft_mint,
+        token::authority = user,
+        constraint = target_nft_account.amount == 1 @ PencilError::NFTNotOwnedByUser
+    )]
+    pub target_nft_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn merge_junior_nfts<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MergeJuniorNFTs<'info>>,
+    target_nft_id: u64,
+    source_nft_ids: Vec<u64>,
+) -> Result<()> {
+    require!(
+        !source_nft_ids.is_empty() && source_nft_ids.len() < MAX_NFT_SPLIT_COUNT,
+        PencilError::InvalidMergeParams
+    );
+    require!(
+        ctx.remaining_accounts.len() == source_nft_ids.len() * 3,
+        PencilError::InvalidAccount
+    );
+
+    let asset_pool_key = ctx.accounts.asset_pool.key();
+    let user_key = ctx.accounts.user.key();
+    let mut merged_principal = 0u64;
+    let mut merged_claimed_interest = 0u64;
+
+    for (index, source_nft_id) in source_nft_ids.iter().enumerate() {
+        require!(
+            *source_nft_id != target_nft_id && !source_nft_ids[..index].contains(source_nft_id),
+            PencilError::InvalidMergeParams
+        );
+
+        let metadata_info = &ctx.remaining_accounts[index * 3];
+        let mint_info = &ctx.remaining_accounts[index * 3 + 1];
+        let token_account_info = &ctx.remaining_accounts[index * 3 + 2];
+
+        // 验证源 NFT 的 PDA 地址
+        let (expected_metadata, _) = Pubkey::find_program_address(
+            &[
+                seeds::JUNIOR_NFT_METADATA,
+                asset_pool_key.as_ref(),
+                &source_nft_id.to_le_bytes(),
+            ],
+            ctx.program_id,
+        );
+        let (expected_mint, _) = Pubkey::find_program_address(
+            &[
+                seeds::JUNIOR_NFT_MINT,
+                asset_pool_key.as_ref(),
+                &source_nft_id.to_le_bytes(),
+            ],
+            ctx.program_id,
+        );
+        require_keys_eq!(metadata_info.key(), expected_metadata, PencilError::InvalidNFT);
+        require_keys_eq!(mint_info.key(), expected_mint, PencilError::InvalidNFT);
+
+        let source_metadata = Account::<JuniorNFTMetadata>::try_from(metadata_info)?;
+        require!(
+            source_metadata.owner == user_key,
+            PencilError::NFTNotOwnedByUser
+        );
+        require!(
+            !source_metadata.principal_withdrawn,
+            PencilError::PrincipalAlreadyWithdrawn
+        );
+
+        let source_token_account = Account::<TokenAccount>::try_from(token_account_info)?;
+        require!(
+            source_token_account.mint == expected_mint
+                && source_token_account.owner == user_key
+                && source_token_account.amount == 1,
+            PencilError::NFTNotOwnedByUser
+        );
+
+        merged_principal = merged_principal
+            .checked_add(source_metadata.principal)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        merged_claimed_interest = merged_claimed_interest
+            .checked_add(source_metadata.claimed_interest)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+
+        // 销毁源 NFT 并关闭 Token 账户
+        let burn_cpi_accounts = Burn {
+            mint: mint_info.clone(),
+            from: token_account_info.clone(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::burn(CpiContext::new(cpi_program, burn_cpi_accounts), 1)?;
+
+        let close_cpi_accounts = CloseAccount {
+            account: token_account_info.clone(),
+            destination: ctx.accounts.user.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::close_account(CpiContext::new(cpi_program, close_cpi_accounts))?;
+
+        // 关闭源 NFT 元数据账户，租金退还给用户
+        source_metadata.close(ctx.accounts.user.to_account_info())?;
+    }
+
+    let target_metadata = &mut ctx.accounts.target_metadata;
+    target_metadata.principal = target_metadata
+        .principal
+        .checked_add(merged_principal)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    target_metadata.claimed_interest = target_metadata
+        .claimed_interest
+        .checked_add(merged_claimed_interest)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    let clock = Clock::get()?;
+    emit!(crate::JuniorNFTsMerged {
+        asset_pool: asset_pool_key,
+        user: user_key,
+        target_nft_id,
+        source_nft_ids: source_nft_ids.clone(),
+        principal: target_metadata.principal,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Junior NFTs {:?} merged into {}, principal {}",
+        source_nft_ids,
+        target_nft_id,
+        target_metadata.principal
+    );
+
+    Ok(())
+}
+
+/// 程序新铸 Junior NFT 的编号
+pub fn issued_nft_id(issued_nft_count: u32) -> u64 {
+    JUNIOR_NFT_ISSUED_ID_BASE + issued_nft_count as u64
+}
+
+/// 分配下一个新铸 Junior NFT 编号
+pub(crate) fn next_issued_nft_id(asset_pool: &mut AssetPool) -> Result<u64> {
+    let nft_id = issued_nft_id(asset_pool.issued_nft_count);
+    asset_pool.issued_nft_count = asset_pool
+        .issued_nft_count
+        .checked_add(1)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    Ok(nft_id)
+}
+
+/// 手动创建一个 Junior NFT 持仓（Mint + 元数据 + 用户 ATA）并铸造 1 枚
+/// 用于数量不定、无法通过 Accounts 约束声明的场景（例如拆分）
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_junior_nft_position<'info>(
+    asset_pool: &Account<'info, AssetPool>,
+    asset_pool_bump: u8,
+    payer: &Signer<'info>,
+    mint_info: &AccountInfo<'info>,
+    metadata_info: &AccountInfo<'info>,
+    token_account_info: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    associated_token_program: &Program<'info, AssociatedToken>,
+    system_program: &Program<'info, System>,
+    metadata: JuniorNFTMetadata,
+) -> Result<()> {
+    let asset_pool_key = asset_pool.key();
+    let nft_id_bytes = metadata.nft_id.to_le_bytes();
+
+    let (expected_mint, mint_bump) = Pubkey::find_program_address(
+        &[seeds::JUNIOR_NFT_MINT, asset_pool_key.as_ref(), &nft_id_bytes],
+        &crate::ID,
+    );
+    let (expected_metadata, metadata_bump) = Pubkey::find_program_address(
+        &[seeds::JUNIOR_NFT_METADATA, asset_pool_key.as_ref(), &nft_id_bytes],
+        &crate::ID,
+    );
+    require_keys_eq!(mint_info.key(), expected_mint, PencilError::InvalidNFT);
+    require_keys_eq!(metadata_info.key(), expected_metadata, PencilError::InvalidNFT);
+
+    let rent = Rent::get()?;
+
+    // 创建 NFT Mint (decimals = 0, authority = AssetPool PDA)
+    let mint_seeds = &[
+        seeds::JUNIOR_NFT_MINT,
+        asset_pool_key.as_ref(),
+        &nft_id_bytes,
+        &[mint_bump],
+    ];
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            CreateAccount {
+                from: payer.to_account_info(),
+                to: mint_info.clone(),
+            },
+            &[&mint_seeds[..]],
+        ),
+        rent.minimum_balance(Mint::LEN),
+        Mint::LEN as u64,
+        &token_program.key(),
+    )?;
+    token::initialize_mint2(
+        CpiContext::new(
+            token_program.to_account_info(),
+            InitializeMint2 {
+                mint: mint_info.clone(),
+            },
+        ),
+        0,
+        &asset_pool_key,
+        None,
+    )?;
+
+    // 创建用户 ATA
+    associated_token::create(CpiContext::new(
+        associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: payer.to_account_info(),
+            associated_token: token_account_info.clone(),
+            authority: payer.to_account_info(),
+            mint: mint_info.clone(),
+            system_program: system_program.to_account_info(),
+            token_program: token_program.to_account_info(),
+        },
+    ))?;
+
+    // 铸造 NFT (supply = 1)
+    let asset_pool_seeds = &[
+        seeds::ASSET_POOL,
+        asset_pool.creator.as_ref(),
+        &asset_pool.name,
+        &[asset_pool_bump],
+    ];
+    let asset_pool_signer = &[&asset_pool_seeds[..]];
+    let cpi_accounts = MintTo {
+        mint: mint_info.clone(),
+        to: token_account_info.clone(),
+        authority: asset_pool.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        cpi_accounts,
+        asset_pool_signer,
+    );
+    token::mint_to(cpi_ctx, 1)?;
+
+    // 创建 NFT 元数据账户
+    let metadata_space = 8 + std::mem::size_of::<JuniorNFTMetadata>();
+    let metadata_seeds = &[
+        seeds::JUNIOR_NFT_METADATA,
+        asset_pool_key.as_ref(),
+        &nft_id_bytes,
+        &[metadata_bump],
+    ];
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            CreateAccount {
+                from: payer.to_account_info(),
+                to: metadata_info.clone(),
+            },
+            &[&metadata_seeds[..]],
+        ),
+        rent.minimum_balance(metadata_space),
+        metadata_space as u64,
+        &crate::ID,
+    )?;
+    let mut data = metadata_info.try_borrow_mut_data()?;
+    metadata.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
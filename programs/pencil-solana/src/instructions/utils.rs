@@ -1,23 +1,34 @@
 use crate::errors::PencilError;
+use crate::state::{AssetPool, SystemConfig};
+use crate::waterfall::bps_of;
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
 
 /// 计算利息
 pub fn calculate_interest(principal: u64, rate: u16, periods: u64) -> Result<u64> {
-    let rate_decimal = (rate as u128) / 10000u128;
     let interest = ((principal as u128)
-        .checked_mul(rate_decimal)
+        .checked_mul(rate as u128)
         .ok_or(PencilError::ArithmeticOverflow)?
         .checked_mul(periods as u128)
-        .ok_or(PencilError::ArithmeticOverflow)?) / 365u128;
+        .ok_or(PencilError::ArithmeticOverflow)?)
+        / 10000u128
+        / 365u128;
     Ok(interest as u64)
 }
 
 /// 计算费用
 pub fn calculate_fee(amount: u64, fee_rate: u16) -> Result<u64> {
-    let fee = ((amount as u128)
-        .checked_mul(fee_rate as u128)
-        .ok_or(PencilError::ArithmeticOverflow)?) / 10000u128;
-    Ok(fee as u64)
+    bps_of(amount, fee_rate)
+}
+
+/// 资产池手续费去向
+/// 开启手续费归集时为归集账户，否则为当前系统金库的资产代币 ATA（随 set_treasury 更新）
+pub fn pool_fee_destination(asset_pool: &AssetPool, system_config: &SystemConfig) -> Pubkey {
+    if asset_pool.fee_split_enabled {
+        asset_pool.treasury_ata
+    } else {
+        get_associated_token_address(&system_config.treasury, &asset_pool.asset_address)
+    }
 }
 
 /// 计算当前还款期数
@@ -84,6 +95,50 @@ pub fn calculate_senior_price(
     Ok(price)
 }
 
+/// 按本金比例拆分 Junior NFT 的已领取利息
+/// 各份先向下取整，余数按最大余数法逐份加 1，保证拆分后已领取利息总额不变，
+/// 且每份最多比按比例计算的值多 1（领取时的处理见 calculate_junior_claimable）
+pub fn split_claimed_interest(
+    claimed_interest: u64,
+    principal: u64,
+    amounts: &[u64],
+) -> Result<Vec<u64>> {
+    require!(principal > 0, PencilError::InvalidPrincipalCalculation);
+
+    let mut result = Vec::with_capacity(amounts.len());
+    let mut remainders = Vec::with_capacity(amounts.len());
+    let mut allocated = 0u64;
+    for amount in amounts {
+        let scaled = (claimed_interest as u128)
+            .checked_mul(*amount as u128)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        let share = (scaled / principal as u128) as u64;
+        allocated = allocated
+            .checked_add(share)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+        result.push(share);
+        remainders.push(scaled % principal as u128);
+    }
+
+    // 余数小于份数，按舍去部分从大到小分配，相同时靠前的优先
+    let leftover = claimed_interest
+        .checked_sub(allocated)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    require!(
+        leftover <= amounts.len() as u64,
+        PencilError::InvalidPrincipalCalculation
+    );
+    let mut order: Vec<usize> = (0..amounts.len()).collect();
+    order.sort_by(|a, b| remainders[*b].cmp(&remainders[*a]));
+    for index in order.into_iter().take(leftover as usize) {
+        result[index] = result[index]
+            .checked_add(1)
+            .ok_or(PencilError::ArithmeticOverflow)?;
+    }
+
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,5 +177,19 @@ mod tests {
         let result = validate_junior_ratio(1000, 10000, 2000);
         assert!(result.is_err());
     }
-}
 
+    #[test]
+    fn test_split_claimed_interest() {
+        // 本金 1000 拆分为 333 / 333 / 334，已领取利息 100
+        let shares = split_claimed_interest(100, 1000, &[333, 333, 334]).unwrap();
+        assert_eq!(shares, vec![33, 33, 34]);
+        assert_eq!(shares.iter().sum::<u64>(), 100);
+
+        // 余数分散到各份，任何一份都不超过按比例向上取整的值
+        let shares = split_claimed_interest(10, 3, &[1, 1, 1]).unwrap();
+        assert_eq!(shares, vec![4, 3, 3]);
+        let shares = split_claimed_interest(10, 6, &[1, 2, 3]).unwrap();
+        assert_eq!(shares, vec![2, 3, 5]);
+        assert_eq!(shares.iter().sum::<u64>(), 10);
+    }
+}
@@ -6,8 +6,10 @@ pub mod constants;
 pub mod errors;
 pub mod instructions;
 pub mod state;
+pub mod waterfall;
 
 use instructions::*;
+use state::{FeeRecipient, PoolParamBounds};
 
 // ==================== Events ====================
 
@@ -31,7 +33,7 @@ pub struct SystemUnpaused {
 
 #[event]
 pub struct FeeRateUpdated {
-    pub fee_type: u8, // 0: PlatformFee, 1: SeniorEarlyBeforeExitFee, 2: SeniorEarlyAfterExitFee, 3: JuniorEarlyBeforeExitFee
+    pub fee_type: u8, // 0: PlatformFee, 1: SeniorEarlyBeforeExitFee, 2: SeniorEarlyAfterExitFee, 3: JuniorEarlyBeforeExitFee, 4: MarketplaceFee
     pub old_rate: u16,
     pub new_rate: u16,
     pub timestamp: i64,
@@ -53,6 +55,9 @@ pub struct RelatedAccountsInitialized {
     pub junior_interest_pool: Pubkey,
     pub grow_token: Pubkey,
     pub asset_pool_vault: Pubkey,
+    pub senior_pool_vault: Pubkey,
+    pub first_loss_pool_vault: Pubkey,
+    pub junior_interest_pool_vault: Pubkey,
     pub treasury_ata: Pubkey,
     pub timestamp: i64,
 }
@@ -82,6 +87,255 @@ pub struct RepaymentDistributed {
     pub platform_fee: u64,
     pub senior_amount: u64,
     pub junior_interest: u64,
+    pub payer: Pubkey,
+    pub third_party: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PrepaymentProcessed {
+    pub asset_pool: Pubkey,
+    pub payer: Pubkey,
+    pub third_party: bool,
+    pub remaining_periods: u64,
+    pub principal: u64,
+    pub interest: u64,
+    pub penalty: u64,
+    pub platform_fee: u64,
+    pub senior_amount: u64,
+    pub junior_interest: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SeniorDistributionClaimed {
+    pub asset_pool: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub locked_amount: u64,
+    pub grow_redeemed: u64,
+    pub distribution_index: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SeniorDistributionUnlocked {
+    pub asset_pool: Pubkey,
+    pub holder: Pubkey,
+    pub grow_unlocked: u64,
+    pub payout: u64,
+    pub repaid: u64,
+    pub locked_amount: u64,
+    pub distribution_index: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SeniorExitRequested {
+    pub asset_pool: Pubkey,
+    pub holder: Pubkey,
+    pub request_id: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SeniorExitFilled {
+    pub asset_pool: Pubkey,
+    pub holder: Pubkey,
+    pub request_id: u64,
+    pub grow_filled: u64,
+    pub payout: u64,
+    pub exit_fee: u64,
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SeniorExitCancelled {
+    pub asset_pool: Pubkey,
+    pub holder: Pubkey,
+    pub request_id: u64,
+    pub returned_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingCreated {
+    pub asset_pool: Pubkey,
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub listing_id: u64,
+    pub kind: u8, // 0: GROW, 1: Junior NFT
+    pub nft_id: u64,
+    pub amount: u64,
+    pub unit_price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingFilled {
+    pub asset_pool: Pubkey,
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub listing_id: u64,
+    pub kind: u8,
+    pub nft_id: u64,
+    pub amount: u64,
+    pub cost: u64,
+    pub fee: u64,
+    pub remaining: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingCancelled {
+    pub asset_pool: Pubkey,
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub listing_id: u64,
+    pub returned_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolReserveInitialized {
+    pub asset_pool: Pubkey,
+    pub reserve: Pubkey,
+    pub fee_share: u16,
+    pub interest_share: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolReserveUpdated {
+    pub asset_pool: Pubkey,
+    pub from_fees: u64,
+    pub from_interest: u64,
+    pub used: u64,
+    pub balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolReserveReleased {
+    pub asset_pool: Pubkey,
+    pub to_first_loss: u64,
+    pub to_junior_interest: u64,
+    pub to_treasury: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceFundInitialized {
+    pub insurance_fund: Pubkey,
+    pub asset_mint: Pubkey,
+    pub fee_share: u16,
+    pub max_draw_per_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceFundUpdated {
+    pub insurance_fund: Pubkey,
+    pub fee_share: u16,
+    pub max_draw_per_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceFeeCollected {
+    pub insurance_fund: Pubkey,
+    pub asset_pool: Pubkey,
+    pub amount: u64,
+    pub total_collected: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceDrawProposed {
+    pub insurance_fund: Pubkey,
+    pub asset_pool: Pubkey,
+    pub proposer: Pubkey,
+    pub amount: u64,
+    pub shortfall: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceDrawExecuted {
+    pub insurance_fund: Pubkey,
+    pub asset_pool: Pubkey,
+    pub approver: Pubkey,
+    pub amount: u64,
+    pub pool_drawn: u64,
+    pub fund_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceDrawCancelled {
+    pub insurance_fund: Pubkey,
+    pub asset_pool: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeSplitUpdated {
+    pub fee_split: Pubkey,
+    pub owner: Pubkey,
+    pub recipients: Vec<Pubkey>,
+    pub shares: Vec<u16>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeCollectorInitialized {
+    pub asset_pool: Pubkey,
+    pub fee_collector: Pubkey,
+    pub previous_treasury_ata: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesDistributed {
+    pub asset_pool: Pubkey,
+    pub fee_split: Pubkey,
+    pub amount: u64,
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralTermsUpdated {
+    pub asset_pool: Pubkey,
+    pub reward_mode: u8,
+    pub reward_rate: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralRecorded {
+    pub asset_pool: Pubkey,
+    pub referrer: Pubkey,
+    pub user: Pubkey,
+    pub subscription_type: u8,
+    pub amount: u64,
+    pub referred_senior_amount: u64,
+    pub referred_junior_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralRewardPaid {
+    pub asset_pool: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+    pub reward_amount: u64,
+    pub paid_amount: u64,
     pub timestamp: i64,
 }
 
@@ -95,6 +349,30 @@ pub struct EarlyExitProcessed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct JuniorEarlyExitProcessed {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub nft_id: u64,
+    pub principal: u64,
+    pub interest_claimed: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub net_amount: u64,
+    pub buyer: Option<Pubkey>,
+    pub replacement_nft_id: Option<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SeniorWithdrawn {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub grow_burned: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct InterestClaimed {
     pub asset_pool: Pubkey,
@@ -113,6 +391,363 @@ pub struct PrincipalWithdrawn {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct JuniorNFTSplit {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub nft_id: u64,
+    pub new_nft_ids: Vec<u64>,
+    pub amounts: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct JuniorNFTsMerged {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub target_nft_id: u64,
+    pub source_nft_ids: Vec<u64>,
+    pub principal: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolInvariantsChecked {
+    pub asset_pool: Pubkey,
+    pub expected_asset_pool_vault: u64,
+    pub actual_asset_pool_vault: u64,
+    pub expected_senior_pool_vault: u64,
+    pub actual_senior_pool_vault: u64,
+    pub expected_first_loss_pool_vault: u64,
+    pub actual_first_loss_pool_vault: u64,
+    pub expected_junior_interest_pool_vault: u64,
+    pub actual_junior_interest_pool_vault: u64,
+    pub total_shortfall: u64,
+    pub total_surplus: u64,
+    pub auto_paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AssetPoolPauseUpdated {
+    pub asset_pool: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SystemConfigInitialized {
+    pub super_admin: Pubkey,
+    pub treasury: Pubkey,
+    pub platform_fee_rate: u16,
+    pub senior_early_before_exit_fee_rate: u16,
+    pub senior_early_after_exit_fee_rate: u16,
+    pub junior_early_before_exit_fee_rate: u16,
+    pub default_min_junior_ratio: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub updated_by: Pubkey,
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolTreasuryMigrated {
+    pub asset_pool: Pubkey,
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+    pub treasury_ata: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolParamBoundsUpdated {
+    pub param: u8,
+    /// None 表示未设置，仅受协议上下限约束
+    pub old_bounds: Option<PoolParamBounds>,
+    pub new_bounds: Option<PoolParamBounds>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolParamsSynced {
+    pub asset_pool: Pubkey,
+    pub inherited_params: u8,
+    /// 按 pool_param 编号排列的生效值
+    pub old_values: [u16; constants::POOL_PARAM_COUNT],
+    pub new_values: [u16; constants::POOL_PARAM_COUNT],
+    /// 免手续费撤资截止时间（0 表示无）
+    pub withdraw_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxVaultDriftUpdated {
+    pub old_max_vault_drift: u64,
+    pub new_max_vault_drift: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AssetPoolCreated {
+    pub asset_pool: Pubkey,
+    pub creator: Pubkey,
+    pub asset_mint: Pubkey,
+    pub name: String,
+    pub total_amount: u64,
+    pub min_amount: u64,
+    pub min_junior_ratio: u16,
+    /// 继承 SystemConfig 默认值的参数位图 (1 << pool_param)
+    pub inherited_params: u8,
+    pub repayment_count: u64,
+    pub funding_start_time: i64,
+    pub funding_end_time: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolApproved {
+    pub asset_pool: Pubkey,
+    pub admin: Pubkey,
+    pub status: u8,
+    pub total_amount: u64,
+    pub funding_start_time: i64,
+    pub funding_end_time: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Subscribed {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub subscription_type: u8, // 0: Senior, 1: Junior
+    pub amount: u64,
+    /// 认购后该用户的认购总额
+    pub subscription_amount: u64,
+    /// 认购后资产池的 Senior / Junior 总额
+    pub pool_senior_amount: u64,
+    pub pool_junior_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionWithdrawn {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub subscription_type: u8,
+    pub amount: u64,
+    pub fee: u64,
+    pub net_amount: u64,
+    /// 撤资后该用户的剩余认购额
+    pub subscription_amount: u64,
+    /// 撤资后资产池的 Senior / Junior 总额
+    pub pool_senior_amount: u64,
+    pub pool_junior_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionRefunded {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub subscription_type: u8,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundingCompleted {
+    pub asset_pool: Pubkey,
+    pub status: u8,
+    pub total_amount: u64,
+    pub min_amount: u64,
+    pub senior_amount: u64,
+    pub junior_amount: u64,
+    pub junior_ratio: u64, // 基点
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolCancelled {
+    pub asset_pool: Pubkey,
+    pub authority: Pubkey,
+    pub status: u8,
+    pub senior_amount: u64,
+    pub junior_amount: u64,
+    pub min_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SeniorTokenDistributed {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    /// 分发后 GROW Token 总供应量
+    pub grow_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct JuniorNftDistributed {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub nft_id: u64,
+    pub junior_nft_mint: Pubkey,
+    pub principal: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GrowTokenMinted {
+    pub asset_pool: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GrowTokenBurned {
+    pub asset_pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct JuniorNftMinted {
+    pub asset_pool: Pubkey,
+    pub recipient: Pubkey,
+    pub nft_id: u64,
+    pub principal: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AssetPoolAmended {
+    pub asset_pool: Pubkey,
+    pub creator: Pubkey,
+    /// 审批后的修订由超级管理员共同签名
+    pub admin: Option<Pubkey>,
+    pub status: u8,
+    pub terms: AssetPoolTerms,
+    pub economic_terms_changed: bool,
+    /// 免手续费撤资截止时间（0 表示无）
+    pub withdraw_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundingClosedEarly {
+    pub asset_pool: Pubkey,
+    pub closed_by: Pubkey,
+    pub original_funding_end_time: i64,
+    pub funding_end_time: i64,
+    pub subscribed_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundingExtended {
+    pub asset_pool: Pubkey,
+    pub operator: Pubkey,
+    pub old_funding_end_time: i64,
+    pub new_funding_end_time: i64,
+    /// 免手续费撤资截止时间（0 表示无）
+    pub withdraw_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AssetPoolRejected {
+    pub asset_pool: Pubkey,
+    pub admin: Pubkey,
+    pub creator: Pubkey,
+    pub status: u8,
+    pub reason_code: u16,
+    /// 退还给创建者的租金 (lamports)
+    pub rent_refunded: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AssetPoolWithdrawn {
+    pub asset_pool: Pubkey,
+    pub creator: Pubkey,
+    pub status: u8,
+    /// 退还给创建者的租金 (lamports)
+    pub rent_refunded: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OriginatorRegistered {
+    pub originator: Pubkey,
+    pub authority: Pubkey,
+    pub legal_name_hash: [u8; 32],
+    pub max_outstanding: u64,
+    pub allowed_assets: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OriginatorUpdated {
+    pub originator: Pubkey,
+    pub max_outstanding: u64,
+    pub allowed_assets: Vec<Pubkey>,
+    pub active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OriginatorExposureUpdated {
+    pub originator: Pubkey,
+    pub asset_pool: Pubkey,
+    /// 该资产池当前计入的金额
+    pub pool_exposure: u64,
+    /// 发起方未偿本金合计
+    pub outstanding_principal: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OriginatorDefaultRecorded {
+    pub originator: Pubkey,
+    pub asset_pool: Pubkey,
+    pub defaulted_pools: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RepaymentAuthorityUpdated {
+    pub asset_pool: Pubkey,
+    pub creator: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PrepaymentTermsUpdated {
+    pub asset_pool: Pubkey,
+    pub policy: u8,
+    pub penalty_rate: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ServicerDelegated {
+    pub originator: Pubkey,
+    pub authority: Pubkey,
+    pub old_servicer: Pubkey,
+    pub new_servicer: Pubkey,
+    pub timestamp: i64,
+}
+
 #[program]
 pub mod pencil_solana {
     use super::*;
@@ -164,6 +799,12 @@ pub mod pencil_solana {
         instructions::set_treasury(ctx, treasury)
     }
 
+    pub fn migrate_pool_treasury<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigratePoolTreasury<'info>>,
+    ) -> Result<()> {
+        instructions::migrate_pool_treasury(ctx)
+    }
+
     pub fn set_asset_supported(
         ctx: Context<SetAssetSupported>,
         asset: Pubkey,
@@ -172,15 +813,28 @@ pub mod pencil_solana {
         instructions::set_asset_supported(ctx, asset, supported)
     }
 
+    pub fn set_max_vault_drift(ctx: Context<SetMaxVaultDrift>, max_vault_drift: u64) -> Result<()> {
+        instructions::set_max_vault_drift(ctx, max_vault_drift)
+    }
+
+    pub fn set_pool_param_bounds(
+        ctx: Context<SetPoolParamBounds>,
+        param: u8,
+        bounds: Option<PoolParamBounds>,
+    ) -> Result<()> {
+        instructions::set_pool_param_bounds(ctx, param, bounds)
+    }
+
     // ==================== Asset Pool ====================
+    #[allow(clippy::too_many_arguments)]
     pub fn create_asset_pool(
         ctx: Context<CreateAssetPool>,
         name: String,
-        platform_fee: u16,
-        senior_early_before_exit_fee: u16,
-        senior_early_after_exit_fee: u16,
-        junior_early_before_exit_fee: u16,
-        min_junior_ratio: u16,
+        platform_fee: Option<u16>,
+        senior_early_before_exit_fee: Option<u16>,
+        senior_early_after_exit_fee: Option<u16>,
+        junior_early_before_exit_fee: Option<u16>,
+        min_junior_ratio: Option<u16>,
         repayment_rate: u16,
         senior_fixed_rate: u16,
         repayment_period: u64,
@@ -221,12 +875,93 @@ pub mod pencil_solana {
         instructions::initialize_related_accounts(ctx)
     }
 
+    pub fn register_originator(
+        ctx: Context<RegisterOriginator>,
+        authority: Pubkey,
+        legal_name_hash: [u8; 32],
+        max_outstanding: u64,
+        allowed_assets: Vec<Pubkey>,
+    ) -> Result<()> {
+        instructions::register_originator(
+            ctx,
+            authority,
+            legal_name_hash,
+            max_outstanding,
+            allowed_assets,
+        )
+    }
+
+    pub fn update_originator(
+        ctx: Context<UpdateOriginator>,
+        max_outstanding: u64,
+        allowed_assets: Vec<Pubkey>,
+        active: bool,
+    ) -> Result<()> {
+        instructions::update_originator(ctx, max_outstanding, allowed_assets, active)
+    }
+
+    pub fn sync_originator_exposure(ctx: Context<SyncOriginatorExposure>) -> Result<()> {
+        instructions::sync_originator_exposure(ctx)
+    }
+
+    pub fn record_originator_default(ctx: Context<RecordOriginatorDefault>) -> Result<()> {
+        instructions::record_originator_default(ctx)
+    }
+
+    pub fn delegate_servicer(ctx: Context<DelegateServicer>, servicer: Pubkey) -> Result<()> {
+        instructions::delegate_servicer(ctx, servicer)
+    }
+
+    pub fn amend_asset_pool(ctx: Context<AmendAssetPool>, terms: AssetPoolTerms) -> Result<()> {
+        instructions::amend_asset_pool(ctx, terms)
+    }
+
+    pub fn amend_approved_asset_pool(
+        ctx: Context<AmendApprovedAssetPool>,
+        terms: AssetPoolTerms,
+    ) -> Result<()> {
+        instructions::amend_approved_asset_pool(ctx, terms)
+    }
+
+    pub fn sync_pool_params(ctx: Context<SyncPoolParams>) -> Result<()> {
+        instructions::sync_pool_params(ctx)
+    }
+
+    pub fn reject_asset_pool(ctx: Context<RejectAssetPool>, reason_code: u16) -> Result<()> {
+        instructions::reject_asset_pool(ctx, reason_code)
+    }
+
+    pub fn withdraw_asset_pool(ctx: Context<WithdrawAssetPool>) -> Result<()> {
+        instructions::withdraw_asset_pool(ctx)
+    }
+
+    pub fn set_repayment_authority(
+        ctx: Context<SetRepaymentAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::set_repayment_authority(ctx, new_authority)
+    }
+
+    pub fn set_prepayment_terms(
+        ctx: Context<SetPrepaymentTerms>,
+        policy: u8,
+        penalty_rate: u16,
+    ) -> Result<()> {
+        instructions::set_prepayment_terms(ctx, policy, penalty_rate)
+    }
+
     // ==================== Funding ====================
-    pub fn subscribe_senior(ctx: Context<SubscribeSenior>, amount: u64) -> Result<()> {
+    pub fn subscribe_senior<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubscribeSenior<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         instructions::subscribe_senior(ctx, amount)
     }
 
-    pub fn subscribe_junior(ctx: Context<SubscribeJunior>, amount: u64) -> Result<()> {
+    pub fn subscribe_junior<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubscribeJunior<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         instructions::subscribe_junior(ctx, amount)
     }
 
@@ -234,6 +969,14 @@ pub mod pencil_solana {
         instructions::complete_funding(ctx)
     }
 
+    pub fn close_funding_early(ctx: Context<CloseFundingEarly>) -> Result<()> {
+        instructions::close_funding_early(ctx)
+    }
+
+    pub fn extend_funding(ctx: Context<ExtendFunding>, new_end: i64) -> Result<()> {
+        instructions::extend_funding(ctx, new_end)
+    }
+
     pub fn distribute_senior_token(ctx: Context<DistributeSeniorToken>) -> Result<()> {
         instructions::distribute_senior_token(ctx)
     }
@@ -265,19 +1008,36 @@ pub mod pencil_solana {
         instructions::cancel_asset_pool(ctx)
     }
 
-    pub fn withdraw_senior_subscription(ctx: Context<WithdrawSeniorSubscription>, amount: u64) -> Result<()> {
+    pub fn withdraw_senior_subscription<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawSeniorSubscription<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         instructions::withdraw_senior_subscription(ctx, amount)
     }
 
-    pub fn withdraw_junior_subscription(ctx: Context<WithdrawJuniorSubscription>, amount: u64) -> Result<()> {
+    pub fn withdraw_junior_subscription<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawJuniorSubscription<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         instructions::withdraw_junior_subscription(ctx, amount)
     }
 
     // ==================== Repayment ====================
-    pub fn repay(ctx: Context<Repay>, amount: u64, period: u64) -> Result<()> {
+    pub fn repay<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Repay<'info>>,
+        amount: u64,
+        period: u64,
+    ) -> Result<()> {
         instructions::repay(ctx, amount, period)
     }
 
+    pub fn prepay_remaining<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PrepayRemaining<'info>>,
+        max_amount: u64,
+    ) -> Result<()> {
+        instructions::prepay_remaining(ctx, max_amount)
+    }
+
     pub fn claim_junior_interest(ctx: Context<ClaimJuniorInterest>, nft_id: u64) -> Result<()> {
         instructions::claim_junior_interest(ctx, nft_id)
     }
@@ -290,6 +1050,155 @@ pub mod pencil_solana {
         instructions::early_exit_senior(ctx, amount)
     }
 
+    pub fn early_exit_junior<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EarlyExitJunior<'info>>,
+        nft_id: u64,
+        with_replacement: bool,
+    ) -> Result<()> {
+        instructions::early_exit_junior(ctx, nft_id, with_replacement)
+    }
+
+    pub fn claim_senior_distribution(
+        ctx: Context<ClaimSeniorDistribution>,
+        lock_amount: u64,
+    ) -> Result<()> {
+        instructions::claim_senior_distribution(ctx, lock_amount)
+    }
+
+    pub fn unlock_senior_distribution(
+        ctx: Context<UnlockSeniorDistribution>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::unlock_senior_distribution(ctx, amount)
+    }
+
+    pub fn request_senior_exit(ctx: Context<RequestSeniorExit>, amount: u64) -> Result<()> {
+        instructions::request_senior_exit(ctx, amount)
+    }
+
+    pub fn cancel_senior_exit(ctx: Context<CancelSeniorExit>, request_id: u64) -> Result<()> {
+        instructions::cancel_senior_exit(ctx, request_id)
+    }
+
+    pub fn process_senior_exits<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessSeniorExits<'info>>,
+    ) -> Result<()> {
+        instructions::process_senior_exits(ctx)
+    }
+
+    // ==================== Marketplace ====================
+
+    pub fn create_grow_listing(
+        ctx: Context<CreateGrowListing>,
+        listing_id: u64,
+        amount: u64,
+        unit_price: u64,
+    ) -> Result<()> {
+        instructions::create_grow_listing(ctx, listing_id, amount, unit_price)
+    }
+
+    pub fn create_junior_nft_listing(
+        ctx: Context<CreateJuniorNftListing>,
+        listing_id: u64,
+        nft_id: u64,
+        price: u64,
+    ) -> Result<()> {
+        instructions::create_junior_nft_listing(ctx, listing_id, nft_id, price)
+    }
+
+    pub fn fill_listing(ctx: Context<FillListing>, amount: u64) -> Result<()> {
+        instructions::fill_listing(ctx, amount)
+    }
+
+    pub fn cancel_listing(ctx: Context<CancelListing>) -> Result<()> {
+        instructions::cancel_listing(ctx)
+    }
+
+    // ==================== Pool Reserve ====================
+    pub fn initialize_pool_reserve(
+        ctx: Context<InitializePoolReserve>,
+        fee_share: u16,
+        interest_share: u16,
+    ) -> Result<()> {
+        instructions::initialize_pool_reserve(ctx, fee_share, interest_share)
+    }
+
+    // ==================== Insurance Fund ====================
+    pub fn initialize_insurance_fund(
+        ctx: Context<InitializeInsuranceFund>,
+        fee_share: u16,
+        max_draw_per_pool: u64,
+    ) -> Result<()> {
+        instructions::initialize_insurance_fund(ctx, fee_share, max_draw_per_pool)
+    }
+
+    pub fn update_insurance_fund(
+        ctx: Context<UpdateInsuranceFund>,
+        fee_share: u16,
+        max_draw_per_pool: u64,
+    ) -> Result<()> {
+        instructions::update_insurance_fund(ctx, fee_share, max_draw_per_pool)
+    }
+
+    pub fn propose_insurance_draw(ctx: Context<ProposeInsuranceDraw>, amount: u64) -> Result<()> {
+        instructions::propose_insurance_draw(ctx, amount)
+    }
+
+    pub fn execute_insurance_draw(ctx: Context<ExecuteInsuranceDraw>) -> Result<()> {
+        instructions::execute_insurance_draw(ctx)
+    }
+
+    pub fn cancel_insurance_draw(ctx: Context<CancelInsuranceDraw>) -> Result<()> {
+        instructions::cancel_insurance_draw(ctx)
+    }
+
+    // ==================== Fee Split ====================
+    pub fn set_system_fee_split(
+        ctx: Context<SetSystemFeeSplit>,
+        recipients: Vec<FeeRecipient>,
+    ) -> Result<()> {
+        instructions::set_system_fee_split(ctx, recipients)
+    }
+
+    pub fn set_pool_fee_split(
+        ctx: Context<SetPoolFeeSplit>,
+        recipients: Vec<FeeRecipient>,
+    ) -> Result<()> {
+        instructions::set_pool_fee_split(ctx, recipients)
+    }
+
+    pub fn initialize_fee_collector(ctx: Context<InitializeFeeCollector>) -> Result<()> {
+        instructions::initialize_fee_collector(ctx)
+    }
+
+    pub fn distribute_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeFees<'info>>,
+    ) -> Result<()> {
+        instructions::distribute_fees(ctx)
+    }
+
+    // ==================== Referrals ====================
+    pub fn set_referral_terms(
+        ctx: Context<SetReferralTerms>,
+        reward_mode: u8,
+        reward_rate: u16,
+    ) -> Result<()> {
+        instructions::set_referral_terms(ctx, reward_mode, reward_rate)
+    }
+
+    pub fn claim_referral_reward(ctx: Context<ClaimReferralReward>) -> Result<()> {
+        instructions::claim_referral_reward(ctx)
+    }
+
+    // ==================== Invariants ====================
+    pub fn check_pool_invariants(ctx: Context<CheckPoolInvariants>) -> Result<()> {
+        instructions::check_pool_invariants(ctx)
+    }
+
+    pub fn set_asset_pool_paused(ctx: Context<SetAssetPoolPaused>, paused: bool) -> Result<()> {
+        instructions::set_asset_pool_paused(ctx, paused)
+    }
+
     // ==================== Token Management ====================
     pub fn mint_grow_token(ctx: Context<MintGrowToken>, amount: u64) -> Result<()> {
         instructions::mint_grow_token(ctx, amount)
@@ -302,4 +1211,20 @@ pub mod pencil_solana {
     pub fn mint_junior_nft(ctx: Context<MintJuniorNFT>, nft_id: u64, principal: u64) -> Result<()> {
         instructions::mint_junior_nft(ctx, nft_id, principal)
     }
+
+    pub fn split_junior_nft<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SplitJuniorNFT<'info>>,
+        nft_id: u64,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        instructions::split_junior_nft(ctx, nft_id, amounts)
+    }
+
+    pub fn merge_junior_nfts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MergeJuniorNFTs<'info>>,
+        target_nft_id: u64,
+        source_nft_ids: Vec<u64>,
+    ) -> Result<()> {
+        instructions::merge_junior_nfts(ctx, target_nft_id, source_nft_ids)
+    }
 }
@@ -1,3 +1,4 @@
+use crate::constants::{MAX_FEE_RECIPIENTS, POOL_PARAM_COUNT};
 use anchor_lang::prelude::*;
 
 /// 系统配置账户
@@ -27,8 +28,23 @@ pub struct SystemConfig {
     pub initialized: bool,
     /// 系统暂停状态
     pub paused: bool,
+    /// 资产池账实差异阈值，超过后对账指令自动暂停资产池 (0 表示不自动暂停)
+    pub max_vault_drift: u64,
+    /// 二级市场协议费率 (基点)
+    pub marketplace_fee_rate: u16,
+    /// 创建者覆盖资产池参数时的取值范围 (按 pool_param 编号)
+    pub pool_param_bounds: [PoolParamBounds; POOL_PARAM_COUNT],
+    /// 已设置取值范围的参数位图 (1 << pool_param)，未设置的参数仅受协议上限约束
+    pub pool_param_bounds_set: u8,
     /// 预留空间
-    pub _reserved: [u8; 127],
+    pub _reserved: [u8; 96],
+}
+
+/// 资产池参数取值范围 (基点，含两端)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolParamBounds {
+    pub min: u16,
+    pub max: u16,
 }
 
 /// 资产池账户
@@ -98,8 +114,32 @@ pub struct AssetPool {
     pub asset_pool_vault: Pubkey,
     /// 金库 ATA
     pub treasury_ata: Pubkey,
+    /// 资产池暂停状态（对账发现差异时自动暂停）
+    pub paused: bool,
+    /// 经济条款修订后免手续费撤资的截止时间（0 表示无）
+    pub amendment_withdraw_deadline: i64,
+    /// 还款义务人（默认为创建者，可由创建者变更）
+    pub repayment_authority: Pubkey,
+    /// 计入发起方未偿本金的金额
+    pub originator_exposure: u64,
+    /// 是否已被记为违约
+    pub originator_defaulted: bool,
+    /// 已还期数
+    pub repaid_periods: u16,
+    /// 提前还款计息方式 (prepayment_policy)
+    pub prepayment_policy: u8,
+    /// 提前还款违约金费率 (基点，按剩余本金计)
+    pub prepayment_penalty_rate: u16,
+    /// 是否启用储备金（启用后还款需传入 PoolReserve 及其 Vault）
+    pub reserve_enabled: bool,
+    /// 手续费是否经由资产池手续费归集账户分账（启用后 treasury_ata 指向归集账户）
+    pub fee_split_enabled: bool,
+    /// 继承 SystemConfig 默认值的参数位图 (1 << pool_param)，其余参数为创建者覆盖值
+    pub inherited_params: u8,
+    /// 程序新铸 Junior NFT 的计数（拆分等），编号 = JUNIOR_NFT_ISSUED_ID_BASE + 计数
+    pub issued_nft_count: u32,
     /// 预留空间
-    pub _reserved: [u8; 63],
+    pub _reserved: [u8; 1],
 }
 
 /// 募资账户
@@ -134,8 +174,10 @@ pub struct Subscription {
     pub status: u8,
     /// 订阅时间
     pub subscribed_at: i64,
+    /// 推荐人（未经推荐为默认地址）
+    pub referrer: Pubkey,
     /// 预留空间
-    pub _reserved: [u8; 128],
+    pub _reserved: [u8; 96],
 }
 
 /// 优先池账户
@@ -149,8 +191,16 @@ pub struct SeniorPool {
     pub total_deposits: u64,
     /// 已还款金额
     pub repaid_amount: u64,
+    /// 优先池 Token Vault（Senior 本金与还款）
+    pub vault: Pubkey,
+    /// 每个 GROW 累计分得的 Senior 还款（放大 DISTRIBUTION_INDEX_SCALE 倍）
+    pub distribution_index: u128,
+    /// 锁定 GROW 已领取、尚未赎回结清的分配金额
+    pub distributed_amount: u64,
+    /// 可用于撮合早退队列的 Senior 还款（每期还款累加，成交扣减）
+    pub exit_liquidity: u64,
     /// 预留空间
-    pub _reserved: [u8; 128],
+    pub _reserved: [u8; 64],
 }
 
 /// 首损池账户
@@ -162,10 +212,16 @@ pub struct FirstLossPool {
     pub junior_nft: Pubkey,
     /// 总存款金额
     pub total_deposits: u64,
-    /// 已还款金额
+    /// 已动用金额（补足 Senior 缺口，不含 Junior 本金提取）
     pub repaid_amount: u64,
+    /// 首损池 Token Vault（Junior 本金）
+    pub vault: Pubkey,
+    /// 已提取本金的 NFT 份额合计
+    pub withdrawn_shares: u64,
+    /// 已提取的本金金额
+    pub withdrawn_amount: u64,
     /// 预留空间
-    pub _reserved: [u8; 128],
+    pub _reserved: [u8; 80],
 }
 
 /// 利息池账户
@@ -179,8 +235,10 @@ pub struct JuniorInterestPool {
     pub total_interest: u64,
     /// 已分配利息
     pub distributed_interest: u64,
+    /// 利息池 Token Vault（未分配的 Junior 利息）
+    pub vault: Pubkey,
     /// 预留空间
-    pub _reserved: [u8; 128],
+    pub _reserved: [u8; 96],
 }
 
 /// 还款记录
@@ -196,8 +254,12 @@ pub struct RepaymentRecord {
     pub repaid_at: i64,
     /// 还款状态
     pub status: u8,
+    /// 实际付款人
+    pub payer: Pubkey,
+    /// 是否为第三方代偿（付款人既非还款义务人也非受托服务方）
+    pub third_party: bool,
     /// 预留空间
-    pub _reserved: [u8; 128],
+    pub _reserved: [u8; 95],
 }
 
 /// Junior NFT 元数据
@@ -221,6 +283,220 @@ pub struct JuniorNFTMetadata {
     pub _reserved: [u8; 128],
 }
 
+/// Senior 分配检查点（每个持有人每个资产池一个）
+/// 锁定的 GROW 托管在资产池名下，不可转让，避免同一份额被重复领取
+#[account]
+pub struct SeniorCheckpoint {
+    /// 资产池地址
+    pub asset_pool: Pubkey,
+    /// 持有人地址
+    pub holder: Pubkey,
+    /// 锁定的 GROW 数量
+    pub locked_amount: u64,
+    /// 已领取的分配金额（赎回时从应得金额中扣除）
+    pub claimed_amount: u64,
+    /// 上次结算时的累计指数
+    pub index: u128,
+    /// 预留空间
+    pub _reserved: [u8; 64],
+}
+
+/// Senior 早退队列（每个资产池一个）
+/// 请求按 ID 先进先出撮合，head 为下一个待撮合的请求
+#[account]
+pub struct SeniorExitQueue {
+    /// 资产池地址
+    pub asset_pool: Pubkey,
+    /// 下一个待撮合的请求 ID
+    pub head: u64,
+    /// 下一个新请求的 ID
+    pub tail: u64,
+    /// 队列中待撮合的 GROW 总量
+    pub queued_amount: u64,
+    /// 预留空间
+    pub _reserved: [u8; 64],
+}
+
+/// Senior 早退请求
+/// 排队的 GROW 托管在资产池名下，全部成交后账户关闭并退还租金
+#[account]
+pub struct SeniorExitRequest {
+    /// 资产池地址
+    pub asset_pool: Pubkey,
+    /// 请求人地址
+    pub holder: Pubkey,
+    /// 请求 ID
+    pub request_id: u64,
+    /// 待成交的 GROW 数量
+    pub amount: u64,
+    /// 已成交的 GROW 数量
+    pub filled_amount: u64,
+    /// 已支付的净额（扣除早退费用）
+    pub paid_amount: u64,
+    /// 请求时间
+    pub requested_at: i64,
+    /// 预留空间
+    pub _reserved: [u8; 32],
+}
+
+/// 二级市场挂单
+/// 挂出的 GROW 或 Junior NFT 托管在资产池名下，全部成交或撤单后账户关闭并退还租金
+#[account]
+pub struct Listing {
+    /// 资产池地址
+    pub asset_pool: Pubkey,
+    /// 卖方地址
+    pub seller: Pubkey,
+    /// 挂单 ID（卖方自定义）
+    pub listing_id: u64,
+    /// 挂单类型 (listing_kind)
+    pub kind: u8,
+    /// 挂出的 GROW Mint 或 Junior NFT Mint
+    pub position_mint: Pubkey,
+    /// Junior NFT ID（GROW 挂单为 0）
+    pub nft_id: u64,
+    /// 剩余可成交数量（NFT 为 1）
+    pub amount: u64,
+    /// 已成交数量
+    pub filled_amount: u64,
+    /// 单价：GROW 为每个 GROW 的价格，NFT 为整单价格
+    pub unit_price: u64,
+    /// 挂单时间
+    pub created_at: i64,
+    /// 预留空间
+    pub _reserved: [u8; 32],
+}
+
+/// 资产池储备金
+/// 按比例截留平台费与 Junior 利息，Senior 缺口先由储备金补足再动用首损池，
+/// 资产池完成时按释放规则清空
+#[account]
+pub struct PoolReserve {
+    /// 资产池地址
+    pub asset_pool: Pubkey,
+    /// 储备金 Token Vault
+    pub vault: Pubkey,
+    /// 平台费截留比例 (基点)
+    pub fee_share: u16,
+    /// Junior 利息截留比例 (基点)
+    pub interest_share: u16,
+    /// 累计从平台费截留的金额
+    pub funded_from_fees: u64,
+    /// 累计从 Junior 利息截留的金额
+    pub funded_from_interest: u64,
+    /// 累计补足 Senior 的金额
+    pub used_amount: u64,
+    /// 是否已在资产池完成时释放
+    pub released: bool,
+    /// 预留空间
+    pub _reserved: [u8; 64],
+}
+
+/// 协议级保险基金（每种资产代币一个）
+/// 从所有资产池的平台费中按比例计提，经管理员提案后补足违约资产池的 Senior 缺口
+#[account]
+pub struct InsuranceFund {
+    /// 资产代币 Mint
+    pub asset_mint: Pubkey,
+    /// 保险基金 Token Vault
+    pub vault: Pubkey,
+    /// 平台费计提比例 (基点)
+    pub fee_share: u16,
+    /// 单个资产池累计可提取上限
+    pub max_draw_per_pool: u64,
+    /// 累计计提金额
+    pub total_collected: u64,
+    /// 累计提取金额
+    pub total_drawn: u64,
+    /// 预留空间
+    pub _reserved: [u8; 64],
+}
+
+/// 资产池的保险基金提取记录
+#[account]
+pub struct InsuranceDraw {
+    /// 资产池地址
+    pub asset_pool: Pubkey,
+    /// 保险基金地址
+    pub insurance_fund: Pubkey,
+    /// 待执行的提案金额（0 表示无提案）
+    pub pending_amount: u64,
+    /// 提案人
+    pub proposer: Pubkey,
+    /// 提案时间
+    pub proposed_at: i64,
+    /// 累计已提取金额
+    pub drawn_amount: u64,
+    /// 预留空间
+    pub _reserved: [u8; 32],
+}
+
+/// 手续费分账收款方
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeRecipient {
+    /// 收款地址（分账转入其资产代币 ATA）
+    pub recipient: Pubkey,
+    /// 分账比例 (基点)
+    pub share: u16,
+}
+
+/// 手续费分账配置
+/// 系统级以 SystemConfig 为种子，资产池级以 AssetPool 为种子；资产池级配置优先
+#[account]
+pub struct FeeSplit {
+    /// 所属账户（SystemConfig 或 AssetPool）
+    pub owner: Pubkey,
+    /// 收款方数量
+    pub recipient_count: u8,
+    /// 收款方列表（前 recipient_count 项有效，比例合计为 10000）
+    pub recipients: [FeeRecipient; MAX_FEE_RECIPIENTS],
+    /// 预留空间
+    pub _reserved: [u8; 64],
+}
+
+/// 资产池推荐奖励条款
+/// 奖励在募资完成后按推荐人结算，从手续费归集账户支付
+#[account]
+pub struct ReferralProgram {
+    /// 资产池地址
+    pub asset_pool: Pubkey,
+    /// 奖励模式（referral_reward_mode）
+    pub reward_mode: u8,
+    /// 奖励比例（基点）
+    pub reward_rate: u16,
+    /// 尚未结算的推荐认购总额
+    pub referred_amount: u64,
+    /// 已结算未支付的奖励
+    pub reward_owed: u64,
+    /// 累计已支付奖励
+    pub total_paid: u64,
+    /// 预留空间
+    pub _reserved: [u8; 64],
+}
+
+/// 推荐人在资产池内的推荐记录
+#[account]
+pub struct ReferralRecord {
+    /// 资产池地址
+    pub asset_pool: Pubkey,
+    /// 推荐人
+    pub referrer: Pubkey,
+    /// 推荐的 Senior 认购额
+    pub senior_amount: u64,
+    /// 推荐的 Junior 认购额
+    pub junior_amount: u64,
+    /// 推荐的订阅数
+    pub subscription_count: u32,
+    /// 是否已结算奖励
+    pub settled: bool,
+    /// 结算的奖励金额
+    pub reward_amount: u64,
+    /// 已支付奖励
+    pub paid_amount: u64,
+    /// 预留空间
+    pub _reserved: [u8; 32],
+}
+
 /// 资产白名单账户
 #[account]
 pub struct AssetWhitelist {
@@ -229,3 +505,34 @@ pub struct AssetWhitelist {
     /// 支持的资产列表
     pub assets: Vec<Pubkey>,
 }
+
+/// 资产发起方（借款人）账户
+#[account]
+pub struct Originator {
+    /// 发起方钱包，用于签名创建资产池
+    pub authority: Pubkey,
+    /// 法定名称哈希
+    pub legal_name_hash: [u8; 32],
+    /// 最大未偿本金敞口
+    pub max_outstanding: u64,
+    /// 所有资产池的未偿本金合计
+    pub outstanding_principal: u64,
+    /// 允许发行的资产（需同时在系统白名单中）
+    pub allowed_assets: Vec<Pubkey>,
+    /// 已创建资产池数
+    pub pool_count: u64,
+    /// 已完成资产池数
+    pub completed_pools: u64,
+    /// 已取消（募资失败）资产池数
+    pub cancelled_pools: u64,
+    /// 违约资产池数
+    pub defaulted_pools: u64,
+    /// 是否启用
+    pub active: bool,
+    /// 注册时间
+    pub registered_at: i64,
+    /// 受托还款服务方（Pubkey::default 表示未委托）
+    pub servicer: Pubkey,
+    /// 预留空间
+    pub _reserved: [u8; 32],
+}
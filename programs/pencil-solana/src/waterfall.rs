@@ -0,0 +1,1238 @@
+//! 还款瀑布计算
+//! Pure, clock-free repayment waterfall math shared by the on-chain
+//! instructions and off-chain tooling, plus a pool simulation used to
+//! replay sequences of repay / claim / exit events.
+
+use crate::constants::{prepayment_policy, BASIS_POINTS};
+use crate::errors::PencilError;
+use anchor_lang::prelude::*;
+
+/// 计算当前应还期数
+/// repayment_period: 还款周期（秒数）
+pub fn calculate_current_period(
+    funding_end_time: i64,
+    repayment_period: u64,
+    current_time: i64,
+) -> Result<u64> {
+    // 如果还未到募资结束时间，返回 0
+    if current_time < funding_end_time {
+        return Ok(0);
+    }
+
+    // 计算已过去的时间（秒）
+    let elapsed_seconds = current_time
+        .checked_sub(funding_end_time)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    // 计算当前期数（直接用秒数计算，支持任意时间单位）
+    let count = (elapsed_seconds as u64)
+        .checked_div(repayment_period)
+        .unwrap_or(0);
+
+    // 与EVM一致：如果还没到第一个还款期，也允许还第一期
+    // return count > 0 ? count : 1
+    let period = if count > 0 { count } else { 1 };
+
+    Ok(period)
+}
+
+/// 计算每期应还总额 = 本金/期数 + 本金 × 还款利率
+pub fn calculate_per_period_amount(
+    total_amount: u64,
+    repayment_count: u64,
+    repayment_rate: u16,
+) -> Result<u64> {
+    require!(repayment_count > 0, PencilError::InvalidRepaymentCount);
+
+    // 每期本金
+    let principal_per_period = total_amount
+        .checked_div(repayment_count)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    // 每期利息 = 本金 × 还款利率 / 10000
+    let interest_per_period = bps_of(total_amount, repayment_rate)?;
+
+    // 每期总额
+    let per_period_total = principal_per_period
+        .checked_add(interest_per_period)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    Ok(per_period_total)
+}
+
+/// 计算平台费 = 每期应还金额 × 平台费率
+pub fn calculate_platform_fee(per_period_amount: u64, platform_fee_rate: u16) -> Result<u64> {
+    bps_of(per_period_amount, platform_fee_rate)
+}
+
+/// 计算 Senior 应得本息 = Senior本金/期数 + Senior本金 × Senior固定利率
+pub fn calculate_senior_amount(
+    senior_total: u64,
+    repayment_count: u64,
+    senior_fixed_rate: u16,
+) -> Result<u64> {
+    require!(repayment_count > 0, PencilError::InvalidRepaymentCount);
+
+    // 每期 Senior 本金
+    let senior_principal_per_period = senior_total
+        .checked_div(repayment_count)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    // 每期 Senior 利息 = Senior本金 × Senior固定利率 / 10000
+    let senior_interest_per_period = bps_of(senior_total, senior_fixed_rate)?;
+
+    // 每期 Senior 总额
+    let senior_per_period = senior_principal_per_period
+        .checked_add(senior_interest_per_period)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    Ok(senior_per_period)
+}
+
+/// 按基点计算金额 = amount × rate / 10000（向下取整）
+/// 用于利息、违约金以及各类手续费分成
+pub fn bps_of(amount: u64, rate: u16) -> Result<u64> {
+    Ok(((amount as u128)
+        .checked_mul(rate as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?)
+    .checked_div(BASIS_POINTS as u128)
+    .ok_or(PencilError::ArithmeticOverflow)? as u64)
+}
+
+/// 已还 repaid_periods 期后的剩余本金（末期承担整除余数）
+pub fn calculate_remaining_principal(
+    principal: u64,
+    repayment_count: u64,
+    repaid_periods: u64,
+) -> Result<u64> {
+    require!(repayment_count > 0, PencilError::InvalidRepaymentCount);
+    require!(
+        repaid_periods <= repayment_count,
+        PencilError::InvalidRepaymentPeriod
+    );
+
+    let paid = (principal / repayment_count)
+        .checked_mul(repaid_periods)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    Ok(principal
+        .checked_sub(paid)
+        .ok_or(PencilError::ArithmeticOverflow)?)
+}
+
+/// 提前还款应付利息
+/// FULL_INTEREST：剩余各期利息全额；
+/// ACCRUED_INTEREST：自募资结束起按时间线性计息，扣除已还各期利息，
+/// 不超过剩余各期利息
+pub fn calculate_prepayment_interest(
+    principal: u64,
+    rate: u16,
+    repayment_count: u64,
+    repaid_periods: u64,
+    policy: u8,
+    elapsed: u64,
+    repayment_period: u64,
+) -> Result<u64> {
+    require!(
+        repaid_periods <= repayment_count,
+        PencilError::InvalidRepaymentPeriod
+    );
+    let per_period = bps_of(principal, rate)? as u128;
+    let remaining = per_period
+        .checked_mul((repayment_count - repaid_periods) as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    if policy == prepayment_policy::FULL_INTEREST {
+        return Ok(remaining as u64);
+    }
+
+    let accrued = per_period
+        .checked_mul(elapsed as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_div(repayment_period as u128)
+        .unwrap_or(u128::MAX);
+    let paid = per_period
+        .checked_mul(repaid_periods as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    Ok(accrued.saturating_sub(paid).min(remaining) as u64)
+}
+
+/// 提前还款违约金 = 剩余本金 × 违约金费率
+pub fn calculate_prepayment_penalty(remaining_principal: u64, penalty_rate: u16) -> Result<u64> {
+    bps_of(remaining_principal, penalty_rate)
+}
+
+/// Junior 提取本金时按份额分得的首损池余额
+/// amount = vault_balance × shares / 剩余未提取份额
+pub fn calculate_principal_withdrawal(
+    vault_balance: u64,
+    shares: u64,
+    total_shares: u64,
+    withdrawn_shares: u64,
+) -> Result<u64> {
+    let remaining_shares = total_shares
+        .checked_sub(withdrawn_shares)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    require!(
+        remaining_shares > 0 && shares <= remaining_shares,
+        PencilError::InvalidPrincipalCalculation
+    );
+    Ok(((vault_balance as u128)
+        .checked_mul(shares as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        / remaining_shares as u128) as u64)
+}
+
+/// 单期还款的分配结果
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RepaymentDistribution {
+    /// 平台费
+    pub platform_fee: u64,
+    /// Senior 本期应得本息
+    pub senior_due: u64,
+    /// 本次还款中直接分配给 Senior 的金额
+    pub senior_from_repayment: u64,
+    /// FirstLossPool 补足 Senior 的金额
+    pub first_loss_used: u64,
+    /// 分配给 JuniorInterestPool 的金额
+    pub junior_interest: u64,
+    /// 储备金补足 Senior 的金额（先于首损池）
+    pub reserve_used: u64,
+}
+
+impl RepaymentDistribution {
+    /// Senior 实际获得金额（还款 + 储备金补足 + 首损补足）
+    pub fn senior_total(&self) -> Result<u64> {
+        Ok(self
+            .senior_from_repayment
+            .checked_add(self.reserve_used)
+            .ok_or(PencilError::ArithmeticOverflow)?
+            .checked_add(self.first_loss_used)
+            .ok_or(PencilError::ArithmeticOverflow)?)
+    }
+}
+
+/// 还款瀑布：平台费 -> Senior 本息（不足由首损补足） -> Junior 利息
+pub fn distribute_repayment(
+    amount: u64,
+    per_period_total: u64,
+    platform_fee_rate: u16,
+    senior_due: u64,
+    first_loss_available: u64,
+) -> Result<RepaymentDistribution> {
+    // 验证还款金额是否足够
+    require!(
+        amount > 0 && amount >= per_period_total,
+        PencilError::InvalidRepaymentAmount
+    );
+
+    allocate_repayment(
+        amount,
+        per_period_total,
+        platform_fee_rate,
+        senior_due,
+        first_loss_available,
+    )
+}
+
+/// 按瀑布顺序分配任意金额，不校验是否足额
+/// 链上 `repay` 只接受足额还款；链下模拟用它计算违约期部分还款的分配
+pub fn allocate_repayment(
+    amount: u64,
+    per_period_total: u64,
+    platform_fee_rate: u16,
+    senior_due: u64,
+    first_loss_available: u64,
+) -> Result<RepaymentDistribution> {
+    // 平台费按应还金额计算，以实际还款为上限
+    let platform_fee = calculate_platform_fee(per_period_total, platform_fee_rate)?.min(amount);
+
+    // 扣除平台费后可用于分配的金额
+    let available = amount
+        .checked_sub(platform_fee)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let senior_from_repayment = senior_due.min(available);
+
+    // 不足部分由 FirstLossPool 补足（以可用余额为上限）
+    let shortfall = senior_due
+        .checked_sub(senior_from_repayment)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let first_loss_used = shortfall.min(first_loss_available);
+
+    let junior_interest = available
+        .checked_sub(senior_from_repayment)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    Ok(RepaymentDistribution {
+        platform_fee,
+        senior_due,
+        senior_from_repayment,
+        first_loss_used,
+        junior_interest,
+        reserve_used: 0,
+    })
+}
+
+/// 计算 Junior NFT 可领取利息
+/// 可领取 = (JuniorInterestPool 总额 × NFT 本金) / Junior 总本金 - 已领取
+/// 拆分 NFT 的取整可能使已领取略高于应得部分，此时可领取为 0
+pub fn calculate_junior_claimable(
+    total_interest: u64,
+    principal: u64,
+    junior_total_principal: u64,
+    claimed_interest: u64,
+) -> Result<u64> {
+    require!(
+        junior_total_principal > 0,
+        PencilError::InvalidPrincipalCalculation
+    );
+
+    let nft_share = ((total_interest as u128)
+        .checked_mul(principal as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?)
+    .checked_div(junior_total_principal as u128)
+    .ok_or(PencilError::ArithmeticOverflow)? as u64;
+
+    Ok(nft_share.saturating_sub(claimed_interest))
+}
+
+/// Senior 早退的分配结果
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EarlyExitDistribution {
+    /// 早退费用
+    pub exit_fee: u64,
+    /// 从优先池 Vault 支付的退款
+    pub refund: u64,
+}
+
+/// Senior 即时早退：按面值扣除早退费用后从优先池 Vault 退款
+/// 只能动用尚未分配的 Senior 还款（available_liquidity），不动用 FirstLossPool；
+/// 超出时须通过早退队列排队
+pub fn distribute_early_exit(
+    amount: u64,
+    exit_fee_rate: u16,
+    available_liquidity: u64,
+) -> Result<EarlyExitDistribution> {
+    require!(amount > 0, PencilError::InvalidEarlyExitAmount);
+    require!(
+        amount <= available_liquidity,
+        PencilError::ExitLiquidityExceeded
+    );
+
+    let exit_fee = bps_of(amount, exit_fee_rate)?;
+    let refund = amount
+        .checked_sub(exit_fee)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+
+    Ok(EarlyExitDistribution { exit_fee, refund })
+}
+
+/// Junior 早退的结算结果
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JuniorExitDistribution {
+    /// 按首损池剩余资金折算的本金价值（含早退费用）
+    pub gross_amount: u64,
+    /// 早退费用
+    pub exit_fee: u64,
+    /// 早退后的 Junior 占比（基点）
+    pub junior_ratio_after: u64,
+}
+
+impl JuniorExitDistribution {
+    /// 用户实际获得的金额
+    pub fn net(&self) -> Result<u64> {
+        Ok(self
+            .gross_amount
+            .checked_sub(self.exit_fee)
+            .ok_or(PencilError::ArithmeticOverflow)?)
+    }
+}
+
+/// Junior 早退：按首损池剩余资金比例折算本金价值并扣除早退费用，
+/// 同时计算该份额退出后 Junior 本金占 Senior + Junior 的比例
+pub fn distribute_junior_exit(
+    principal: u64,
+    first_loss_deposits: u64,
+    first_loss_vault_balance: u64,
+    senior_deposits: u64,
+    exit_fee_rate: u16,
+) -> Result<JuniorExitDistribution> {
+    require!(
+        principal > 0 && principal <= first_loss_deposits,
+        PencilError::InvalidPrincipalCalculation
+    );
+
+    let gross_amount = (first_loss_vault_balance as u128)
+        .checked_mul(principal as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_div(first_loss_deposits as u128)
+        .ok_or(PencilError::ArithmeticOverflow)? as u64;
+    let exit_fee = bps_of(gross_amount, exit_fee_rate)?;
+
+    let junior_after = first_loss_deposits - principal;
+    let total_after = junior_after
+        .checked_add(senior_deposits)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let junior_ratio_after = if total_after == 0 {
+        0
+    } else {
+        (junior_after as u128)
+            .checked_mul(BASIS_POINTS as u128)
+            .ok_or(PencilError::ArithmeticOverflow)?
+            .checked_div(total_after as u128)
+            .ok_or(PencilError::ArithmeticOverflow)? as u64
+    };
+
+    Ok(JuniorExitDistribution {
+        gross_amount,
+        exit_fee,
+        junior_ratio_after,
+    })
+}
+
+/// 早退队列单笔成交
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExitFill {
+    /// 成交的 GROW 数量
+    pub grow_filled: u64,
+    /// 按净值计算的成交金额（含早退费用）
+    pub payout: u64,
+    /// 早退费用
+    pub exit_fee: u64,
+}
+
+impl ExitFill {
+    /// 请求人实际获得的金额
+    pub fn net(&self) -> Result<u64> {
+        Ok(self
+            .payout
+            .checked_sub(self.exit_fee)
+            .ok_or(PencilError::ArithmeticOverflow)?)
+    }
+}
+
+/// 按净值撮合早退请求
+/// 每个 GROW 净值 = senior_value / grow_supply，成交金额不超过可用流动性，允许部分成交
+pub fn calculate_exit_fill(
+    grow_amount: u64,
+    senior_value: u64,
+    grow_supply: u64,
+    liquidity: u64,
+    exit_fee_rate: u16,
+) -> Result<ExitFill> {
+    if grow_amount == 0 || senior_value == 0 || grow_supply == 0 {
+        return Ok(ExitFill::default());
+    }
+
+    let max_by_liquidity = (liquidity as u128)
+        .checked_mul(grow_supply as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_div(senior_value as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let grow_filled = (grow_amount as u128).min(max_by_liquidity) as u64;
+    let payout = (grow_filled as u128)
+        .checked_mul(senior_value as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_div(grow_supply as u128)
+        .ok_or(PencilError::ArithmeticOverflow)? as u64;
+    let exit_fee = bps_of(payout, exit_fee_rate)?;
+
+    Ok(ExitFill {
+        grow_filled,
+        payout,
+        exit_fee,
+    })
+}
+
+/// 即时早退可用的流动性：扣除按净值为早退队列预留的部分（向上取整）
+/// 排队请求优先，但不因少量排队 GROW 阻塞全部即时早退
+pub fn instant_exit_liquidity(
+    liquidity: u64,
+    queued_grow: u64,
+    senior_value: u64,
+    grow_supply: u64,
+) -> Result<u64> {
+    if queued_grow == 0 || grow_supply == 0 {
+        return Ok(liquidity);
+    }
+    let reserved = (queued_grow as u128)
+        .checked_mul(senior_value as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .div_ceil(grow_supply as u128);
+    Ok((liquidity as u128).saturating_sub(reserved) as u64)
+}
+
+/// 储备金本期的流入与使用
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveFlow {
+    /// 从平台费截留的金额
+    pub from_fees: u64,
+    /// 从 Junior 利息截留的金额
+    pub from_interest: u64,
+    /// 补足 Senior 缺口的金额
+    pub used: u64,
+}
+
+impl ReserveFlow {
+    /// 付款人本期存入储备金的金额
+    pub fn deposit(&self) -> Result<u64> {
+        Ok(self
+            .from_fees
+            .checked_add(self.from_interest)
+            .ok_or(PencilError::ArithmeticOverflow)?)
+    }
+}
+
+/// 在还款瀑布上叠加储备金
+/// 平台费与 Junior 利息（Senior 足额后的剩余）按比例截留进储备金；
+/// Senior 缺口先由储备金（含本期截留）补足，剩余缺口再由首损池补足
+pub fn apply_pool_reserve(
+    distribution: RepaymentDistribution,
+    reserve_balance: u64,
+    fee_share: u16,
+    interest_share: u16,
+) -> Result<(RepaymentDistribution, ReserveFlow)> {
+    let from_fees = bps_of(distribution.platform_fee, fee_share)?;
+    let from_interest = bps_of(distribution.junior_interest, interest_share)?;
+
+    let shortfall = distribution
+        .senior_due
+        .checked_sub(distribution.senior_from_repayment)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let reserve_available = reserve_balance
+        .checked_add(from_fees)
+        .ok_or(PencilError::ArithmeticOverflow)?
+        .checked_add(from_interest)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let used = shortfall.min(reserve_available);
+    let first_loss_used = distribution.first_loss_used.min(shortfall - used);
+
+    Ok((
+        RepaymentDistribution {
+            platform_fee: distribution.platform_fee - from_fees,
+            junior_interest: distribution.junior_interest - from_interest,
+            first_loss_used,
+            reserve_used: used,
+            ..distribution
+        },
+        ReserveFlow {
+            from_fees,
+            from_interest,
+            used,
+        },
+    ))
+}
+
+/// 保险基金从平台费中计提的金额 = 平台费 × 计提比例
+pub fn calculate_insurance_fee(platform_fee: u64, fee_share: u16) -> Result<u64> {
+    bps_of(platform_fee, fee_share)
+}
+
+/// Senior 尚未收回的本息 = Senior 本金 + 全部期数利息 − 已分配给优先池的金额
+pub fn calculate_senior_shortfall(
+    senior_amount: u64,
+    senior_fixed_rate: u16,
+    repayment_count: u64,
+    senior_repaid: u64,
+) -> Result<u64> {
+    let interest = (bps_of(senior_amount, senior_fixed_rate)? as u128)
+        .checked_mul(repayment_count as u128)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    let owed = (senior_amount as u128)
+        .checked_add(interest)
+        .ok_or(PencilError::ArithmeticOverflow)?;
+    Ok(owed.saturating_sub(senior_repaid as u128) as u64)
+}
+
+/// 单个资产池可从保险基金提取的上限：不超过 Senior 缺口、剩余额度与基金余额
+pub fn calculate_insurance_draw_limit(
+    shortfall: u64,
+    max_draw_per_pool: u64,
+    drawn_amount: u64,
+    fund_balance: u64,
+) -> u64 {
+    shortfall
+        .min(max_draw_per_pool.saturating_sub(drawn_amount))
+        .min(fund_balance)
+}
+
+/// 资产池条款（模拟用）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolTerms {
+    pub senior_amount: u64,
+    pub junior_amount: u64,
+    pub repayment_count: u64,
+    pub repayment_rate: u16,
+    pub senior_fixed_rate: u16,
+    pub platform_fee: u16,
+    pub senior_early_exit_fee: u16,
+}
+
+impl PoolTerms {
+    pub fn total_amount(&self) -> Result<u64> {
+        Ok(self
+            .senior_amount
+            .checked_add(self.junior_amount)
+            .ok_or(PencilError::ArithmeticOverflow)?)
+    }
+}
+
+/// 模拟中的 Junior 持仓
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JuniorPosition {
+    pub principal: u64,
+    pub claimed_interest: u64,
+}
+
+/// 模拟事件
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimulationEvent {
+    /// 借款人偿还下一期
+    Repay { amount: u64 },
+    /// Junior 持仓领取利息
+    ClaimJuniorInterest { position: usize },
+    /// Senior 早退（销毁 GROW）
+    SeniorEarlyExit { amount: u64 },
+}
+
+/// 单个事件的处理结果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimulationOutcome {
+    Repaid(RepaymentDistribution),
+    InterestClaimed(u64),
+    SeniorExited(EarlyExitDistribution),
+}
+
+/// 资产池资金流模拟
+/// 与链上指令使用相同的瀑布计算，Vault 余额与子池账面分别记录
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolSimulation {
+    pub terms: PoolTerms,
+    pub junior_positions: Vec<JuniorPosition>,
+    /// 已还期数
+    pub repaid_periods: u64,
+    /// GROW 流通量
+    pub grow_supply: u64,
+
+    // Vault 余额
+    pub senior_vault: u64,
+    pub first_loss_vault: u64,
+    pub junior_interest_vault: u64,
+
+    // 子池账面
+    pub senior_total_deposits: u64,
+    pub senior_repaid_amount: u64,
+    /// 可用于早退的 Senior 还款
+    pub senior_exit_liquidity: u64,
+    pub first_loss_total_deposits: u64,
+    pub first_loss_repaid_amount: u64,
+    pub total_interest: u64,
+    pub distributed_interest: u64,
+
+    // 资金进出
+    pub total_repaid: u64,
+    pub treasury_received: u64,
+    pub paid_to_senior: u64,
+    pub paid_to_junior: u64,
+}
+
+impl PoolSimulation {
+    /// 募资完成后的初始状态，junior_principals 之和即 Junior 总额
+    pub fn new(terms: PoolTerms, junior_principals: &[u64]) -> Result<Self> {
+        let mut junior_total = 0u64;
+        for principal in junior_principals {
+            junior_total = junior_total
+                .checked_add(*principal)
+                .ok_or(PencilError::ArithmeticOverflow)?;
+        }
+        require!(
+            junior_total == terms.junior_amount,
+            PencilError::InvalidPrincipalCalculation
+        );
+        require!(
+            terms.repayment_count > 0,
+            PencilError::InvalidRepaymentCount
+        );
+
+        Ok(Self {
+            terms,
+            junior_positions: junior_principals
+                .iter()
+                .map(|principal| JuniorPosition {
+                    principal: *principal,
+                    claimed_interest: 0,
+                })
+                .collect(),
+            repaid_periods: 0,
+            grow_supply: terms.senior_amount,
+            senior_vault: terms.senior_amount,
+            first_loss_vault: terms.junior_amount,
+            junior_interest_vault: 0,
+            senior_total_deposits: terms.senior_amount,
+            senior_repaid_amount: 0,
+            senior_exit_liquidity: 0,
+            first_loss_total_deposits: terms.junior_amount,
+            first_loss_repaid_amount: 0,
+            total_interest: 0,
+            distributed_interest: 0,
+            total_repaid: 0,
+            treasury_received: 0,
+            paid_to_senior: 0,
+            paid_to_junior: 0,
+        })
+    }
+
+    /// 每期应还总额
+    pub fn per_period_total(&self) -> Result<u64> {
+        calculate_per_period_amount(
+            self.terms.total_amount()?,
+            self.terms.repayment_count,
+            self.terms.repayment_rate,
+        )
+    }
+
+    /// 是否已还完所有期数
+    pub fn is_completed(&self) -> bool {
+        self.repaid_periods >= self.terms.repayment_count
+    }
+
+    /// 首损池可用余额（账面余额与 Vault 余额取小）
+    fn first_loss_available(&self) -> Result<u64> {
+        Ok(self
+            .first_loss_total_deposits
+            .checked_sub(self.first_loss_repaid_amount)
+            .ok_or(PencilError::ArithmeticOverflow)?
+            .min(self.first_loss_vault))
+    }
+
+    /// 处理一个事件，失败时状态保持不变
+    pub fn apply(&mut self, event: SimulationEvent) -> Result<SimulationOutcome> {
+        let mut next = self.clone();
+        let outcome = match event {
+            SimulationEvent::Repay { amount } => next.repay(amount)?,
+            SimulationEvent::ClaimJuniorInterest { position } => {
+                next.claim_junior_interest(position)?
+            }
+            SimulationEvent::SeniorEarlyExit { amount } => next.senior_early_exit(amount)?,
+        };
+        *self = next;
+        Ok(outcome)
+    }
+
+    fn repay(&mut self, amount: u64) -> Result<SimulationOutcome> {
+        require!(!self.is_completed(), PencilError::InvalidRepaymentPeriod);
+
+        let senior_due = calculate_senior_amount(
+            self.terms.senior_amount,
+            self.terms.repayment_count,
+            self.terms.senior_fixed_rate,
+        )?;
+        let distribution = distribute_repayment(
+            amount,
+            self.per_period_total()?,
+            self.terms.platform_fee,
+            senior_due,
+            self.first_loss_available()?,
+        )?;
+        let senior_total = distribution.senior_total()?;
+
+        self.treasury_received = add(self.treasury_received, distribution.platform_fee)?;
+        self.senior_vault = add(self.senior_vault, senior_total)?;
+        self.first_loss_vault = sub(self.first_loss_vault, distribution.first_loss_used)?;
+        self.junior_interest_vault = add(self.junior_interest_vault, distribution.junior_interest)?;
+
+        self.senior_repaid_amount = add(self.senior_repaid_amount, senior_total)?;
+        self.senior_exit_liquidity = add(self.senior_exit_liquidity, senior_total)?;
+        self.first_loss_repaid_amount =
+            add(self.first_loss_repaid_amount, distribution.first_loss_used)?;
+        self.total_interest = add(self.total_interest, distribution.junior_interest)?;
+
+        self.total_repaid = add(self.total_repaid, amount)?;
+        self.repaid_periods += 1;
+
+        Ok(SimulationOutcome::Repaid(distribution))
+    }
+
+    fn claim_junior_interest(&mut self, position: usize) -> Result<SimulationOutcome> {
+        let junior_total = self.first_loss_total_deposits;
+        let junior_position = self
+            .junior_positions
+            .get_mut(position)
+            .ok_or(PencilError::InvalidNFT)?;
+
+        let claimable = calculate_junior_claimable(
+            self.total_interest,
+            junior_position.principal,
+            junior_total,
+            junior_position.claimed_interest,
+        )?;
+        require!(claimable > 0, PencilError::NoInterestToClaim);
+
+        let undistributed = sub(self.total_interest, self.distributed_interest)?;
+        require!(
+            undistributed >= claimable,
+            PencilError::InsufficientPoolFunds
+        );
+
+        junior_position.claimed_interest = add(junior_position.claimed_interest, claimable)?;
+        self.junior_interest_vault = sub(self.junior_interest_vault, claimable)?;
+        self.distributed_interest = add(self.distributed_interest, claimable)?;
+        self.paid_to_junior = add(self.paid_to_junior, claimable)?;
+
+        Ok(SimulationOutcome::InterestClaimed(claimable))
+    }
+
+    fn senior_early_exit(&mut self, amount: u64) -> Result<SimulationOutcome> {
+        require!(!self.is_completed(), PencilError::InvalidAssetPoolStatus);
+        require!(amount <= self.grow_supply, PencilError::InsufficientBalance);
+
+        let distribution = distribute_early_exit(
+            amount,
+            self.terms.senior_early_exit_fee,
+            self.senior_exit_liquidity.min(self.senior_vault),
+        )?;
+
+        self.senior_vault = sub(self.senior_vault, amount)?;
+        self.senior_exit_liquidity = sub(self.senior_exit_liquidity, amount)?;
+        self.senior_total_deposits = sub(self.senior_total_deposits, amount)?;
+        self.grow_supply = sub(self.grow_supply, amount)?;
+
+        self.treasury_received = add(self.treasury_received, distribution.exit_fee)?;
+        self.paid_to_senior = add(self.paid_to_senior, distribution.refund)?;
+
+        Ok(SimulationOutcome::SeniorExited(distribution))
+    }
+
+    /// 资金守恒：流入 = Vault 余额 + 流出
+    pub fn total_inflow(&self) -> Result<u64> {
+        add(self.terms.total_amount()?, self.total_repaid)
+    }
+
+    pub fn total_held_and_paid(&self) -> Result<u64> {
+        [
+            self.first_loss_vault,
+            self.junior_interest_vault,
+            self.treasury_received,
+            self.paid_to_senior,
+            self.paid_to_junior,
+        ]
+        .iter()
+        .try_fold(self.senior_vault, |acc, value| add(acc, *value))
+    }
+}
+
+fn add(a: u64, b: u64) -> Result<u64> {
+    Ok(a.checked_add(b).ok_or(PencilError::ArithmeticOverflow)?)
+}
+
+fn sub(a: u64, b: u64) -> Result<u64> {
+    Ok(a.checked_sub(b).ok_or(PencilError::ArithmeticOverflow)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // 单笔金额上限 1e15（10 亿 USDC，6 位小数）
+    const MAX_AMOUNT: u64 = 1_000_000_000_000_000;
+
+    fn terms_strategy() -> impl Strategy<Value = PoolTerms> {
+        (
+            1u64..MAX_AMOUNT,
+            1u64..MAX_AMOUNT,
+            1u64..=120,
+            1u16..=10000,
+            1u16..=10000,
+            0u16..=5000,
+            0u16..=2000,
+        )
+            .prop_map(
+                |(
+                    senior_amount,
+                    junior_amount,
+                    repayment_count,
+                    repayment_rate,
+                    senior_fixed_rate,
+                    platform_fee,
+                    senior_early_exit_fee,
+                )| PoolTerms {
+                    senior_amount,
+                    junior_amount,
+                    repayment_count,
+                    repayment_rate,
+                    senior_fixed_rate,
+                    platform_fee,
+                    senior_early_exit_fee,
+                },
+            )
+    }
+
+    #[derive(Clone, Debug)]
+    enum Step {
+        /// 还款金额 = 每期应还 + extra
+        Repay {
+            extra: u64,
+        },
+        Claim {
+            position: usize,
+        },
+        /// 按 GROW 流通量的千分比早退
+        Exit {
+            per_mille: u64,
+        },
+    }
+
+    fn step_strategy() -> impl Strategy<Value = Step> {
+        prop_oneof![
+            3 => (0u64..MAX_AMOUNT).prop_map(|extra| Step::Repay { extra }),
+            2 => (0usize..4).prop_map(|position| Step::Claim { position }),
+            1 => (1u64..=1000).prop_map(|per_mille| Step::Exit { per_mille }),
+        ]
+    }
+
+    fn split_junior(junior_amount: u64) -> Vec<u64> {
+        let first = junior_amount / 3;
+        let second = junior_amount / 4;
+        let third = junior_amount - first - second;
+        vec![first, second, third]
+    }
+
+    #[test]
+    fn test_junior_claimable_after_split_rounding() {
+        // 已领取 10 拆分为 4 / 3 / 3，总利息未增长时第一份不再可领取也不报错
+        assert_eq!(calculate_junior_claimable(10, 1, 3, 4).unwrap(), 0);
+        assert_eq!(calculate_junior_claimable(13, 1, 3, 4).unwrap(), 0);
+        assert_eq!(calculate_junior_claimable(16, 1, 3, 4).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_bps_of_rounds_down() {
+        assert_eq!(bps_of(1_001, 2500).unwrap(), 250);
+        assert_eq!(bps_of(u64::MAX, 10_000).unwrap(), u64::MAX);
+        assert_eq!(bps_of(u64::MAX, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_calculate_current_period() {
+        // 募资结束前为 0，第一期未到时允许还第一期
+        assert_eq!(calculate_current_period(1000, 30, 999).unwrap(), 0);
+        assert_eq!(calculate_current_period(1000, 30, 1010).unwrap(), 1);
+        assert_eq!(
+            calculate_current_period(1000, 30, 1000 + 30 * 3).unwrap(),
+            3
+        );
+        // 还款周期为 0 时不会 panic
+        assert_eq!(calculate_current_period(1000, 0, 5000).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_distribute_repayment_uses_first_loss_for_shortfall() {
+        // 每期应还 1000，平台费 10%，Senior 应得 950
+        let distribution = distribute_repayment(1000, 1000, 1000, 950, 500).unwrap();
+        assert_eq!(distribution.platform_fee, 100);
+        assert_eq!(distribution.senior_from_repayment, 900);
+        assert_eq!(distribution.first_loss_used, 50);
+        assert_eq!(distribution.junior_interest, 0);
+    }
+
+    #[test]
+    fn test_allocate_partial_repayment() {
+        // 足额还款时与 distribute_repayment 一致
+        assert_eq!(
+            allocate_repayment(1000, 1000, 1000, 950, 500).unwrap(),
+            distribute_repayment(1000, 1000, 1000, 950, 500).unwrap()
+        );
+        // 违约期只还 60：平台费以实际还款为上限，其余由首损补足
+        let distribution = allocate_repayment(60, 1000, 1000, 950, 500).unwrap();
+        assert_eq!(distribution.platform_fee, 60);
+        assert_eq!(distribution.senior_from_repayment, 0);
+        assert_eq!(distribution.first_loss_used, 500);
+        assert!(distribute_repayment(60, 1000, 1000, 950, 500).is_err());
+    }
+
+    #[test]
+    fn test_prepayment_quote() {
+        // 本金 1000 分 4 期，每期利息 1%
+        assert_eq!(calculate_remaining_principal(1000, 4, 1).unwrap(), 750);
+        assert_eq!(calculate_remaining_principal(1001, 4, 3).unwrap(), 251);
+        assert!(calculate_remaining_principal(1000, 4, 5).is_err());
+
+        // 全额计息：剩余 3 期利息
+        let full =
+            calculate_prepayment_interest(1000, 100, 4, 1, prepayment_policy::FULL_INTEREST, 0, 30)
+                .unwrap();
+        assert_eq!(full, 30);
+        // 按时计息：已过 1.5 期，已还 1 期，应付半期利息
+        let accrued = calculate_prepayment_interest(
+            1000,
+            100,
+            4,
+            1,
+            prepayment_policy::ACCRUED_INTEREST,
+            45,
+            30,
+        )
+        .unwrap();
+        assert_eq!(accrued, 5);
+        // 逾期很久也不超过剩余各期利息
+        let overdue = calculate_prepayment_interest(
+            1000,
+            100,
+            4,
+            1,
+            prepayment_policy::ACCRUED_INTEREST,
+            3000,
+            30,
+        )
+        .unwrap();
+        assert_eq!(overdue, full);
+
+        assert_eq!(calculate_prepayment_penalty(750, 200).unwrap(), 15);
+    }
+
+    #[test]
+    fn test_early_exit_is_capped_by_liquidity() {
+        let distribution = distribute_early_exit(1000, 100, 1000).unwrap();
+        assert_eq!(
+            distribution,
+            EarlyExitDistribution {
+                exit_fee: 10,
+                refund: 990,
+            }
+        );
+        assert_eq!(
+            distribute_early_exit(1001, 100, 1000).unwrap_err(),
+            PencilError::ExitLiquidityExceeded.into()
+        );
+    }
+
+    #[test]
+    fn test_junior_exit_shares_first_loss_usage() {
+        // 首损池 300 已动用 60：本金 100 折算 80，费用 2%
+        let distribution = distribute_junior_exit(100, 300, 240, 700, 200).unwrap();
+        assert_eq!(distribution.gross_amount, 80);
+        assert_eq!(distribution.exit_fee, 1);
+        assert_eq!(distribution.net().unwrap(), 79);
+        // 退出后 Junior 200 / (200 + 700)
+        assert_eq!(distribution.junior_ratio_after, 2222);
+        assert!(distribute_junior_exit(301, 300, 240, 700, 200).is_err());
+    }
+
+    #[test]
+    fn test_exit_fill_is_capped_by_liquidity() {
+        // 净值 1.2：流动性充足时全部成交，费用 1%
+        let fill = calculate_exit_fill(100, 1200, 1000, 1000, 100).unwrap();
+        assert_eq!(
+            (fill.grow_filled, fill.payout, fill.exit_fee),
+            (100, 120, 1)
+        );
+        assert_eq!(fill.net().unwrap(), 119);
+        // 流动性只够 50 个 GROW：部分成交
+        let fill = calculate_exit_fill(100, 1200, 1000, 60, 100).unwrap();
+        assert_eq!((fill.grow_filled, fill.payout), (50, 60));
+        // 流动性不足一个 GROW 时不成交
+        assert_eq!(
+            calculate_exit_fill(100, 1200, 1000, 1, 100).unwrap(),
+            ExitFill::default()
+        );
+    }
+
+    #[test]
+    fn test_instant_exit_liquidity_reserves_queued_value() {
+        // 净值 1.2：排队 10 个 GROW 预留 12
+        assert_eq!(instant_exit_liquidity(100, 10, 1200, 1000).unwrap(), 88);
+        // 预留向上取整
+        assert_eq!(instant_exit_liquidity(100, 1, 1201, 1000).unwrap(), 98);
+        // 排队价值超过流动性时不能即时早退
+        assert_eq!(instant_exit_liquidity(100, 100, 1200, 1000).unwrap(), 0);
+        assert_eq!(instant_exit_liquidity(100, 0, 1200, 1000).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_pool_reserve_covers_shortfall_before_first_loss() {
+        // 平台费 50、Senior 应得 900 只收到 850：储备金 30 + 本期截留 10 先补足，剩余 10 由首损补足
+        let distribution = RepaymentDistribution {
+            platform_fee: 50,
+            senior_due: 900,
+            senior_from_repayment: 850,
+            first_loss_used: 50,
+            junior_interest: 0,
+            reserve_used: 0,
+        };
+        let (distribution, flow) = apply_pool_reserve(distribution, 30, 2000, 5000).unwrap();
+        assert_eq!(
+            flow,
+            ReserveFlow {
+                from_fees: 10,
+                from_interest: 0,
+                used: 40
+            }
+        );
+        assert_eq!(distribution.platform_fee, 40);
+        assert_eq!(distribution.first_loss_used, 10);
+        assert_eq!(distribution.senior_total().unwrap(), 900);
+
+        // 足额还款：只截留，不动用
+        let distribution = allocate_repayment(1100, 1100, 500, 900, 1000).unwrap();
+        let (distribution, flow) = apply_pool_reserve(distribution, 30, 2000, 5000).unwrap();
+        assert_eq!((flow.from_fees, flow.from_interest, flow.used), (11, 72, 0));
+        assert_eq!(distribution.platform_fee, 44);
+        assert_eq!(distribution.junior_interest, 73);
+        assert_eq!(distribution.first_loss_used, 0);
+    }
+
+    #[test]
+    fn test_insurance_draw_limited_by_shortfall_cap_and_balance() {
+        // Senior 900，每期 1%，共 3 期：应收 927，已收 600 → 缺口 327
+        let shortfall = calculate_senior_shortfall(900, 100, 3, 600).unwrap();
+        assert_eq!(shortfall, 327);
+        assert_eq!(calculate_senior_shortfall(900, 100, 3, 1_000).unwrap(), 0);
+
+        assert_eq!(
+            calculate_insurance_draw_limit(shortfall, 500, 0, 1_000),
+            327
+        );
+        assert_eq!(
+            calculate_insurance_draw_limit(shortfall, 500, 300, 1_000),
+            200
+        );
+        assert_eq!(
+            calculate_insurance_draw_limit(shortfall, 500, 600, 1_000),
+            0
+        );
+        assert_eq!(calculate_insurance_draw_limit(shortfall, 500, 0, 50), 50);
+        assert_eq!(calculate_insurance_fee(55, 2000).unwrap(), 11);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_per_period_helpers_never_overflow(
+            total in 0u64..MAX_AMOUNT,
+            count in 1u64..=120,
+            rate in 0u16..=10000,
+            fee in 0u16..=5000,
+        ) {
+            let per_period = calculate_per_period_amount(total, count, rate).unwrap();
+            let platform_fee = calculate_platform_fee(per_period, fee).unwrap();
+            let senior = calculate_senior_amount(total, count, rate).unwrap();
+            prop_assert!(platform_fee <= per_period);
+            prop_assert_eq!(senior, per_period);
+        }
+
+        #[test]
+        fn prop_current_period_is_monotonic(
+            funding_end_time in 0i64..2_000_000_000,
+            repayment_period in 1u64..31_536_000,
+            elapsed in 0i64..1_000_000_000,
+            delta in 0i64..1_000_000_000,
+        ) {
+            let earlier = calculate_current_period(
+                funding_end_time,
+                repayment_period,
+                funding_end_time + elapsed,
+            ).unwrap();
+            let later = calculate_current_period(
+                funding_end_time,
+                repayment_period,
+                funding_end_time + elapsed + delta,
+            ).unwrap();
+            prop_assert!(earlier >= 1);
+            prop_assert!(later >= earlier);
+        }
+
+        #[test]
+        fn prop_repayment_distribution_conserves_amount(
+            per_period_total in 1u64..MAX_AMOUNT,
+            extra in 0u64..MAX_AMOUNT,
+            platform_fee_rate in 0u16..=5000,
+            senior_due in 0u64..MAX_AMOUNT,
+            first_loss_available in 0u64..MAX_AMOUNT,
+        ) {
+            let amount = per_period_total + extra;
+            let distribution = distribute_repayment(
+                amount,
+                per_period_total,
+                platform_fee_rate,
+                senior_due,
+                first_loss_available,
+            ).unwrap();
+
+            // 还款金额全部分配：平台费 + Senior + Junior 利息
+            prop_assert_eq!(
+                distribution.platform_fee
+                    + distribution.senior_from_repayment
+                    + distribution.junior_interest,
+                amount
+            );
+            // Senior 优先：Junior 有利息时 Senior 必须已足额
+            if distribution.junior_interest > 0 {
+                prop_assert_eq!(distribution.senior_from_repayment, senior_due);
+                prop_assert_eq!(distribution.first_loss_used, 0);
+            }
+            // 首损只补足缺口，且不超过可用余额
+            prop_assert!(distribution.senior_total().unwrap() <= senior_due);
+            prop_assert!(distribution.first_loss_used <= first_loss_available);
+        }
+
+        #[test]
+        fn prop_simulation_conserves_funds(
+            terms in terms_strategy(),
+            steps in prop::collection::vec(step_strategy(), 1..60),
+        ) {
+            let mut simulation = PoolSimulation::new(terms, &split_junior(terms.junior_amount)).unwrap();
+            let per_period_total = simulation.per_period_total().unwrap();
+
+            for step in steps {
+                let event = match step {
+                    Step::Repay { extra } => SimulationEvent::Repay {
+                        amount: per_period_total + extra,
+                    },
+                    Step::Claim { position } => SimulationEvent::ClaimJuniorInterest { position },
+                    Step::Exit { per_mille } => SimulationEvent::SeniorEarlyExit {
+                        amount: (simulation.grow_supply * per_mille / 1000).max(1),
+                    },
+                };
+                let before = simulation.clone();
+
+                match simulation.apply(event) {
+                    Ok(SimulationOutcome::Repaid(distribution)) => {
+                        // Senior 优先于 Junior
+                        if distribution.junior_interest > 0 {
+                            prop_assert_eq!(distribution.senior_from_repayment, distribution.senior_due);
+                        }
+                        prop_assert!(distribution.first_loss_used <= before.first_loss_vault);
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        // 失败的事件不改变状态，且不能是溢出
+                        prop_assert_eq!(&simulation, &before);
+                        prop_assert_ne!(error, PencilError::ArithmeticOverflow.into());
+                    }
+                }
+
+                // 资金守恒
+                prop_assert_eq!(
+                    simulation.total_inflow().unwrap(),
+                    simulation.total_held_and_paid().unwrap()
+                );
+                // 账实一致（与 check_pool_invariants 的口径相同）
+                prop_assert_eq!(
+                    simulation.junior_interest_vault,
+                    simulation.total_interest - simulation.distributed_interest
+                );
+                prop_assert_eq!(
+                    simulation.first_loss_vault,
+                    simulation.first_loss_total_deposits - simulation.first_loss_repaid_amount
+                );
+            }
+
+            // Junior 领取的利息总额不超过利息池总额
+            let claimed: u64 = simulation
+                .junior_positions
+                .iter()
+                .map(|position| position.claimed_interest)
+                .sum();
+            prop_assert_eq!(claimed, simulation.distributed_interest);
+            prop_assert!(simulation.distributed_interest <= simulation.total_interest);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! 资产池条款修订集成测试
+//! 审批前创建者自行修订；审批后需超级管理员共同签名，且已有认购时修改经济条款
+//! 必须给投资者留出免手续费撤资窗口。
+
+mod common;
+
+use common::*;
+use pencil_solana::constants::{asset_pool_status, AMENDMENT_WITHDRAW_WINDOW};
+use pencil_solana::errors::PencilError;
+use pencil_solana::{AssetPoolAmended, SubscriptionWithdrawn};
+use solana_sdk::signature::Signer;
+
+#[test]
+fn test_creator_amends_before_approval() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().create(&mut env);
+
+    let mut terms = pool.terms(&mut env);
+    terms.total_amount = 2_000 * ONE_TOKEN;
+    terms.repayment_rate = 400;
+    pool.amend(&mut env, terms.clone()).unwrap();
+
+    let [amended] = env.events::<AssetPoolAmended>().try_into().ok().unwrap();
+    assert_eq!(amended.admin, None);
+    assert_eq!(amended.status, asset_pool_status::CREATED);
+    assert!(amended.economic_terms_changed);
+    // 没有认购，无需撤资窗口
+    assert_eq!(amended.withdraw_deadline, 0);
+    assert_eq!(pool.terms(&mut env), terms);
+
+    // 参数校验与创建时一致
+    let mut invalid = terms.clone();
+    invalid.min_amount = invalid.total_amount + 1;
+    let error = pool.amend(&mut env, invalid).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidFundingParams)
+    );
+
+    // 审批后只能走管理员修订路径
+    pool.approve(&mut env).unwrap();
+    let error = pool.amend(&mut env, terms).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidAssetPoolStatus)
+    );
+}
+
+#[test]
+fn test_admin_amendment_opens_withdraw_window() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().open(&mut env);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(&mut env, &carol, false, 250 * ONE_TOKEN)
+        .unwrap();
+
+    // 提高募资总额、延长募资期不属于经济条款，无需撤资窗口
+    let mut terms = pool.terms(&mut env);
+    terms.total_amount = 1_500 * ONE_TOKEN;
+    terms.funding_end_time += 3_600;
+    pool.amend_approved(&mut env, terms.clone()).unwrap();
+    let [amended] = env.events::<AssetPoolAmended>().try_into().ok().unwrap();
+    assert_eq!(amended.admin, Some(env.admin.pubkey()));
+    assert!(!amended.economic_terms_changed);
+    assert_eq!(amended.withdraw_deadline, 0);
+
+    // 募资结束时间不能提前，募资总额不能低于已认购金额
+    let mut shorter = terms.clone();
+    shorter.funding_end_time -= 1;
+    let error = pool.amend_approved(&mut env, shorter).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::AmendmentNotAllowed)
+    );
+    let mut smaller = terms.clone();
+    smaller.total_amount = 200 * ONE_TOKEN;
+    smaller.min_amount = 100 * ONE_TOKEN;
+    let error = pool.amend_approved(&mut env, smaller).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::AmendmentNotAllowed)
+    );
+
+    // 已有认购时修改利率，募资期必须覆盖撤资窗口
+    terms.senior_fixed_rate = 150;
+    let error = pool.amend_approved(&mut env, terms.clone()).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::AmendmentWithdrawWindowTooShort)
+    );
+
+    let now = env.now();
+    terms.funding_end_time = now + AMENDMENT_WITHDRAW_WINDOW;
+    pool.amend_approved(&mut env, terms).unwrap();
+    let [amended] = env.events::<AssetPoolAmended>().try_into().ok().unwrap();
+    assert!(amended.economic_terms_changed);
+    assert_eq!(amended.withdraw_deadline, now + AMENDMENT_WITHDRAW_WINDOW);
+    assert_eq!(
+        pool.state(&mut env).amendment_withdraw_deadline,
+        now + AMENDMENT_WITHDRAW_WINDOW
+    );
+
+    // 窗口内撤资免手续费
+    pool.withdraw_subscription(&mut env, &carol, false, 50 * ONE_TOKEN)
+        .unwrap();
+    let [withdrawn] = env
+        .events::<SubscriptionWithdrawn>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(withdrawn.fee, 0);
+    assert_eq!(withdrawn.net_amount, 50 * ONE_TOKEN);
+}
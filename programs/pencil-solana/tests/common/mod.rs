@@ -0,0 +1,1957 @@
+//! 集成测试公共工具
+//! In-process test harness built on LiteSVM.
+//!
+//! 加载 `anchor build` 生成的 `target/deploy/pencil_solana.so`（可通过 `SBF_OUT_DIR`
+//! 指定目录）。测试默认运行；未找到程序文件时打印跳过信息并直接返回，
+//! 设置 `PENCIL_REQUIRE_SBF` 后改为失败（CI 使用，避免测试被静默跳过）。
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_instruction, system_program, sysvar};
+use anchor_lang::{AccountDeserialize, Event, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{get_associated_token_address, spl_associated_token_account};
+use anchor_spl::token::spl_token;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use litesvm::LiteSVM;
+use pencil_solana::constants::seeds;
+use pencil_solana::instructions::{issued_nft_id, AssetPoolTerms, FeeType};
+use pencil_solana::state::*;
+use solana_sdk::clock::Clock;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::io::Write;
+use std::path::PathBuf;
+
+pub type TxResult = Result<(), TransactionError>;
+
+pub const ONE_TOKEN: u64 = 1_000_000;
+
+/// 测试开始时的链上时间
+pub const GENESIS_TIME: i64 = 1_700_000_000;
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &pencil_solana::ID).0
+}
+
+/// 编译后的程序文件
+fn program_path() -> PathBuf {
+    let dir = std::env::var("SBF_OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target/deploy"));
+    dir.join("pencil_solana.so")
+}
+
+pub struct TestEnv {
+    pub svm: LiteSVM,
+    pub payer: Keypair,
+    /// 部署者，同时担任所有管理员角色
+    pub admin: Keypair,
+    pub treasury: Keypair,
+    pub asset_mint: Keypair,
+    /// 最近一笔交易的日志
+    pub last_logs: Vec<String>,
+}
+
+impl TestEnv {
+    /// 启动 SVM 并加载程序；程序未编译时返回 None（测试跳过）
+    pub fn start() -> Option<Self> {
+        let path = program_path();
+        if !path.exists() {
+            let message = format!(
+                "{} not found: run `anchor build` (or set SBF_OUT_DIR) to run the integration tests",
+                path.display()
+            );
+            if std::env::var_os("PENCIL_REQUIRE_SBF").is_some() {
+                panic!("{message}");
+            }
+            // 直接写 stderr，绕过 libtest 的输出捕获，确保跳过信息可见
+            let test = std::thread::current().name().unwrap_or("test").to_string();
+            let _ = writeln!(std::io::stderr(), "skipping {test}: {message}");
+            return None;
+        }
+
+        let mut svm = LiteSVM::new();
+        svm.add_program_from_file(pencil_solana::ID, &path)
+            .expect("load program");
+
+        let payer = Keypair::new();
+        svm.airdrop(&payer.pubkey(), 1_000_000_000_000)
+            .expect("airdrop payer");
+
+        let mut env = Self {
+            svm,
+            payer,
+            admin: Keypair::new(),
+            treasury: Keypair::new(),
+            asset_mint: Keypair::new(),
+            last_logs: Vec::new(),
+        };
+        let admin = env.admin.pubkey();
+        env.airdrop(&admin, 100_000_000_000);
+        env.create_mint();
+        env.warp_to(GENESIS_TIME);
+        Some(env)
+    }
+
+    fn payer(&self) -> Keypair {
+        self.payer.insecure_clone()
+    }
+
+    /// 发送交易，每笔交易后刷新 blockhash，避免相同交易被判定为重复
+    pub fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> TxResult {
+        let payer = self.payer();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            self.svm.latest_blockhash(),
+        );
+        let result = self.svm.send_transaction(tx);
+        self.svm.expire_blockhash();
+        match result {
+            Ok(meta) => {
+                self.last_logs = meta.logs;
+                Ok(())
+            }
+            Err(failed) => {
+                self.last_logs = failed.meta.logs;
+                Err(failed.err)
+            }
+        }
+    }
+
+    /// 最近一笔交易中发出的 E 类型事件
+    pub fn events<E: Event>(&self) -> Vec<E> {
+        self.last_logs
+            .iter()
+            .filter_map(|log| log.strip_prefix("Program data: "))
+            .filter_map(|data| STANDARD.decode(data).ok())
+            .filter(|data| data.starts_with(E::DISCRIMINATOR))
+            .filter_map(|data| E::deserialize(&mut &data[E::DISCRIMINATOR.len()..]).ok())
+            .collect()
+    }
+
+    fn airdrop(&mut self, to: &Pubkey, lamports: u64) {
+        self.svm.airdrop(to, lamports).expect("airdrop");
+    }
+
+    /// 调整链上时间（Clock sysvar）
+    pub fn warp_to(&mut self, unix_timestamp: i64) {
+        let mut clock: Clock = self.svm.get_sysvar();
+        clock.unix_timestamp = unix_timestamp;
+        self.svm.set_sysvar(&clock);
+    }
+
+    pub fn now(&self) -> i64 {
+        self.svm.get_sysvar::<Clock>().unix_timestamp
+    }
+
+    fn create_mint(&mut self) {
+        let payer = self.payer();
+        let mint = self.asset_mint.insecure_clone();
+        let instructions = [
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                self.svm
+                    .minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::ID,
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                6,
+            )
+            .expect("initialize mint"),
+        ];
+        self.process(&instructions, &[&mint]).expect("mint");
+    }
+
+    /// 创建 owner 的资产 ATA（已存在则跳过）
+    pub fn create_ata(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let ata = get_associated_token_address(owner, mint);
+        if self.account_exists(&ata) {
+            return ata;
+        }
+        let payer = self.payer();
+        let ix =
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &payer.pubkey(),
+                owner,
+                mint,
+                &spl_token::ID,
+            );
+        self.process(&[ix], &[]).expect("create ata");
+        ata
+    }
+
+    /// 创建一个持有 SOL 和资产代币的用户
+    pub fn new_user(&mut self, asset_amount: u64) -> Keypair {
+        let user = Keypair::new();
+        self.airdrop(&user.pubkey(), 10_000_000_000);
+        let ata = self.create_ata(&user.pubkey(), &self.asset_mint.pubkey());
+        if asset_amount > 0 {
+            self.mint_asset(&ata, asset_amount);
+        }
+        user
+    }
+
+    fn mint_asset(&mut self, to: &Pubkey, amount: u64) {
+        let payer = self.payer();
+        let ix = spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &self.asset_mint.pubkey(),
+            to,
+            &payer.pubkey(),
+            &[],
+            amount,
+        )
+        .expect("mint_to");
+        self.process(&[ix], &[]).expect("mint asset");
+    }
+
+    pub fn asset_ata(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address(owner, &self.asset_mint.pubkey())
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.svm.get_balance(address).unwrap_or(0)
+    }
+
+    pub fn account_exists(&self, address: &Pubkey) -> bool {
+        self.svm.get_account(address).is_some()
+    }
+
+    pub fn account<T: AccountDeserialize>(&self, address: &Pubkey) -> T {
+        let account = self
+            .svm
+            .get_account(address)
+            .unwrap_or_else(|| panic!("account {} not found", address));
+        T::try_deserialize(&mut account.data.as_slice()).expect("deserialize")
+    }
+
+    pub fn token_balance(&self, address: &Pubkey) -> u64 {
+        let account = self
+            .svm
+            .get_account(address)
+            .unwrap_or_else(|| panic!("token account {} not found", address));
+        spl_token::state::Account::unpack(&account.data)
+            .expect("token account")
+            .amount
+    }
+}
+
+pub fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: pencil_solana::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// ==================== System Config ====================
+
+pub struct SystemConfigBuilder {
+    pub platform_fee_rate: u16,
+    pub senior_early_before_exit_fee_rate: u16,
+    pub senior_early_after_exit_fee_rate: u16,
+    pub junior_early_before_exit_fee_rate: u16,
+    pub default_min_junior_ratio: u16,
+    /// 二级市场协议费率（非 0 时初始化后通过 update_fee_rate 设置）
+    pub marketplace_fee_rate: u16,
+    /// env.admin 作为发起方的敞口上限
+    pub originator_max_outstanding: u64,
+}
+
+impl Default for SystemConfigBuilder {
+    fn default() -> Self {
+        Self {
+            platform_fee_rate: 500,
+            senior_early_before_exit_fee_rate: 100,
+            senior_early_after_exit_fee_rate: 200,
+            junior_early_before_exit_fee_rate: 300,
+            default_min_junior_ratio: 1000,
+            marketplace_fee_rate: 0,
+            originator_max_outstanding: 1_000_000 * ONE_TOKEN,
+        }
+    }
+}
+
+impl SystemConfigBuilder {
+    pub fn marketplace_fee_rate(mut self, rate: u16) -> Self {
+        self.marketplace_fee_rate = rate;
+        self
+    }
+
+    pub fn originator_max_outstanding(mut self, max_outstanding: u64) -> Self {
+        self.originator_max_outstanding = max_outstanding;
+        self
+    }
+
+    /// 初始化 SystemConfig 并将资产加入白名单
+    pub fn build(self, env: &mut TestEnv) {
+        let admin = env.admin.insecure_clone();
+        let treasury = env.treasury.insecure_clone();
+        let system_config = pda(&[seeds::SYSTEM_CONFIG]);
+
+        let initialize = instruction(
+            pencil_solana::accounts::InitializeSystemConfig {
+                payer: admin.pubkey(),
+                system_config,
+                treasury: treasury.pubkey(),
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::InitializeSystemConfig {
+                platform_fee_rate: self.platform_fee_rate,
+                senior_early_before_exit_fee_rate: self.senior_early_before_exit_fee_rate,
+                senior_early_after_exit_fee_rate: self.senior_early_after_exit_fee_rate,
+                junior_early_before_exit_fee_rate: self.junior_early_before_exit_fee_rate,
+                default_min_junior_ratio: self.default_min_junior_ratio,
+            },
+        );
+        let whitelist = instruction(
+            pencil_solana::accounts::SetAssetSupported {
+                operation_admin: admin.pubkey(),
+                system_config,
+                asset_whitelist: pda(&[seeds::ASSET_WHITELIST]),
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::SetAssetSupported {
+                asset: env.asset_mint.pubkey(),
+                supported: true,
+            },
+        );
+        // env.admin 同时是资产池创建者，注册为发起方
+        let register = instruction(
+            pencil_solana::accounts::RegisterOriginator {
+                operation_admin: admin.pubkey(),
+                system_config,
+                originator: pda(&[seeds::ORIGINATOR, admin.pubkey().as_ref()]),
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::RegisterOriginator {
+                authority: admin.pubkey(),
+                legal_name_hash: [7; 32],
+                max_outstanding: self.originator_max_outstanding,
+                allowed_assets: vec![env.asset_mint.pubkey()],
+            },
+        );
+        let mut instructions = vec![initialize, whitelist, register];
+        if self.marketplace_fee_rate > 0 {
+            instructions.push(instruction(
+                pencil_solana::accounts::UpdateFeeRate {
+                    system_admin: admin.pubkey(),
+                    system_config,
+                },
+                pencil_solana::instruction::UpdateFeeRate {
+                    fee_type: FeeType::MarketplaceFee,
+                    new_rate: self.marketplace_fee_rate,
+                },
+            ));
+        }
+        env.process(&instructions, &[&admin, &treasury])
+            .expect("initialize system config");
+    }
+}
+
+/// 系统管理员修改默认费率
+pub fn update_fee_rate(env: &mut TestEnv, fee_type: FeeType, new_rate: u16) -> TxResult {
+    let admin = env.admin.insecure_clone();
+    let ix = instruction(
+        pencil_solana::accounts::UpdateFeeRate {
+            system_admin: admin.pubkey(),
+            system_config: pda(&[seeds::SYSTEM_CONFIG]),
+        },
+        pencil_solana::instruction::UpdateFeeRate { fee_type, new_rate },
+    );
+    env.process(&[ix], &[&admin])
+}
+
+/// 设置创建者覆盖资产池参数时的取值范围
+pub fn set_pool_param_bounds(
+    env: &mut TestEnv,
+    param: u8,
+    bounds: Option<PoolParamBounds>,
+) -> TxResult {
+    let admin = env.admin.insecure_clone();
+    let ix = instruction(
+        pencil_solana::accounts::SetPoolParamBounds {
+            system_admin: admin.pubkey(),
+            system_config: pda(&[seeds::SYSTEM_CONFIG]),
+        },
+        pencil_solana::instruction::SetPoolParamBounds { param, bounds },
+    );
+    env.process(&[ix], &[&admin])
+}
+
+fn fee_recipients(recipients: &[(Pubkey, u16)]) -> Vec<FeeRecipient> {
+    recipients
+        .iter()
+        .map(|(recipient, share)| FeeRecipient {
+            recipient: *recipient,
+            share: *share,
+        })
+        .collect()
+}
+
+/// 设置系统级手续费分账（env.admin 为金库管理员）
+pub fn set_system_fee_split(env: &mut TestEnv, recipients: &[(Pubkey, u16)]) -> TxResult {
+    let admin = env.admin.insecure_clone();
+    let system_config = pda(&[seeds::SYSTEM_CONFIG]);
+    let ix = instruction(
+        pencil_solana::accounts::SetSystemFeeSplit {
+            treasury_admin: admin.pubkey(),
+            system_config,
+            fee_split: pda(&[seeds::FEE_SPLIT, system_config.as_ref()]),
+            system_program: system_program::ID,
+        },
+        pencil_solana::instruction::SetSystemFeeSplit {
+            recipients: fee_recipients(recipients),
+        },
+    );
+    env.process(&[ix], &[&admin])
+}
+
+/// 更换系统金库（env.admin 为系统管理员）
+pub fn set_treasury(env: &mut TestEnv, treasury: &Pubkey) -> TxResult {
+    let admin = env.admin.insecure_clone();
+    let ix = instruction(
+        pencil_solana::accounts::SetTreasury {
+            system_admin: admin.pubkey(),
+            system_config: pda(&[seeds::SYSTEM_CONFIG]),
+        },
+        pencil_solana::instruction::SetTreasury {
+            treasury: *treasury,
+        },
+    );
+    env.process(&[ix], &[&admin])
+}
+
+/// 为当前金库创建资产代币 ATA 并迁移资产池的金库记录
+pub fn migrate_pool_treasury(
+    env: &mut TestEnv,
+    treasury: &Pubkey,
+    asset_pools: &[Pubkey],
+) -> TxResult {
+    let admin = env.admin.insecure_clone();
+    let mut ix = instruction(
+        pencil_solana::accounts::MigratePoolTreasury {
+            system_admin: admin.pubkey(),
+            system_config: pda(&[seeds::SYSTEM_CONFIG]),
+            treasury: *treasury,
+            treasury_ata: env.asset_ata(treasury),
+            asset_mint: env.asset_mint.pubkey(),
+            token_program: spl_token::ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        pencil_solana::instruction::MigratePoolTreasury {},
+    );
+    ix.accounts.extend(
+        asset_pools
+            .iter()
+            .map(|asset_pool| AccountMeta::new(*asset_pool, false)),
+    );
+    env.process(&[ix], &[&admin])
+}
+
+// ==================== Asset Pool ====================
+
+#[derive(Clone)]
+pub struct PoolBuilder {
+    pub name: String,
+    /// 费率与最低 Junior 占比；None 继承 SystemConfig 默认值
+    pub platform_fee: Option<u16>,
+    pub senior_early_before_exit_fee: Option<u16>,
+    pub senior_early_after_exit_fee: Option<u16>,
+    pub junior_early_before_exit_fee: Option<u16>,
+    pub min_junior_ratio: Option<u16>,
+    pub repayment_rate: u16,
+    pub senior_fixed_rate: u16,
+    pub repayment_period: u64,
+    pub repayment_count: u64,
+    pub total_amount: u64,
+    pub min_amount: u64,
+    /// 募资开始时间（相对当前链上时间的偏移）
+    pub funding_start_offset: i64,
+    /// 募资时长（秒）
+    pub funding_duration: i64,
+}
+
+impl Default for PoolBuilder {
+    fn default() -> Self {
+        Self {
+            name: "Test Pool".to_string(),
+            platform_fee: Some(500),
+            senior_early_before_exit_fee: Some(100),
+            senior_early_after_exit_fee: Some(200),
+            junior_early_before_exit_fee: Some(300),
+            min_junior_ratio: Some(2000),
+            repayment_rate: 300,
+            senior_fixed_rate: 100,
+            repayment_period: 30,
+            repayment_count: 3,
+            total_amount: 1_000 * ONE_TOKEN,
+            min_amount: 500 * ONE_TOKEN,
+            funding_start_offset: 0,
+            funding_duration: 3_600,
+        }
+    }
+}
+
+impl PoolBuilder {
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn repayment_count(mut self, repayment_count: u64) -> Self {
+        self.repayment_count = repayment_count;
+        self
+    }
+
+    /// 费率与最低 Junior 占比全部继承 SystemConfig 默认值
+    pub fn inherit_params(mut self) -> Self {
+        self.platform_fee = None;
+        self.senior_early_before_exit_fee = None;
+        self.senior_early_after_exit_fee = None;
+        self.junior_early_before_exit_fee = None;
+        self.min_junior_ratio = None;
+        self
+    }
+
+    /// 创建资产池（状态 CREATED）
+    pub fn create(self, env: &mut TestEnv) -> Pool {
+        self.try_create(env).expect("create asset pool")
+    }
+
+    pub fn try_create(self, env: &mut TestEnv) -> Result<Pool, TransactionError> {
+        let creator = env.admin.insecure_clone();
+        let now = env.now();
+        let funding_start_time = now + self.funding_start_offset;
+        let funding_end_time = funding_start_time + self.funding_duration;
+
+        let pool = Pool::derive(
+            env,
+            creator.pubkey(),
+            &self.name,
+            funding_start_time,
+            funding_end_time,
+            self.repayment_period,
+        );
+        let ix = instruction(
+            pencil_solana::accounts::CreateAssetPool {
+                payer: creator.pubkey(),
+                system_config: pool.system_config,
+                asset_whitelist: pda(&[seeds::ASSET_WHITELIST]),
+                asset_pool: pool.asset_pool,
+                originator: pool.originator(),
+                asset_address: env.asset_mint.pubkey(),
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::CreateAssetPool {
+                name: self.name.clone(),
+                platform_fee: self.platform_fee,
+                senior_early_before_exit_fee: self.senior_early_before_exit_fee,
+                senior_early_after_exit_fee: self.senior_early_after_exit_fee,
+                junior_early_before_exit_fee: self.junior_early_before_exit_fee,
+                min_junior_ratio: self.min_junior_ratio,
+                repayment_rate: self.repayment_rate,
+                senior_fixed_rate: self.senior_fixed_rate,
+                repayment_period: self.repayment_period,
+                repayment_count: self.repayment_count,
+                total_amount: self.total_amount,
+                min_amount: self.min_amount,
+                funding_start_time,
+                funding_end_time,
+            },
+        );
+        env.process(&[ix], &[&creator])?;
+        Ok(pool)
+    }
+
+    /// 创建、审批并初始化所有关联账户（状态 APPROVED，可认购）
+    pub fn open(self, env: &mut TestEnv) -> Pool {
+        let pool = self.create(env);
+        pool.approve(env).expect("approve asset pool");
+        pool.initialize_related_accounts(env)
+            .expect("initialize related accounts");
+        pool
+    }
+}
+
+/// 资产池及其所有 PDA 地址
+pub struct Pool {
+    pub name: String,
+    pub creator: Pubkey,
+    pub asset_mint: Pubkey,
+    pub funding_start_time: i64,
+    pub funding_end_time: i64,
+    pub repayment_period: u64,
+    pub system_config: Pubkey,
+    pub asset_pool: Pubkey,
+    pub funding: Pubkey,
+    pub senior_pool: Pubkey,
+    pub first_loss_pool: Pubkey,
+    pub junior_interest_pool: Pubkey,
+    pub grow_token_mint: Pubkey,
+    pub junior_nft_mint: Pubkey,
+    pub senior_pool_vault: Pubkey,
+    pub first_loss_pool_vault: Pubkey,
+    pub junior_interest_pool_vault: Pubkey,
+    /// 募资 Vault 为普通 TokenAccount（非 PDA），由测试生成密钥
+    pub asset_pool_vault: Keypair,
+    pub treasury: Pubkey,
+    pub treasury_ata: Pubkey,
+}
+
+impl Pool {
+    fn derive(
+        env: &TestEnv,
+        creator: Pubkey,
+        name: &str,
+        funding_start_time: i64,
+        funding_end_time: i64,
+        repayment_period: u64,
+    ) -> Self {
+        let asset_pool = pda(&[seeds::ASSET_POOL, creator.as_ref(), name.as_bytes()]);
+        let key = asset_pool.as_ref();
+        let treasury = env.treasury.pubkey();
+        Self {
+            name: name.to_string(),
+            creator,
+            asset_mint: env.asset_mint.pubkey(),
+            funding_start_time,
+            funding_end_time,
+            repayment_period,
+            system_config: pda(&[seeds::SYSTEM_CONFIG]),
+            asset_pool,
+            funding: pda(&[seeds::FUNDING, key]),
+            senior_pool: pda(&[seeds::SENIOR_POOL, key]),
+            first_loss_pool: pda(&[seeds::FIRST_LOSS_POOL, key]),
+            junior_interest_pool: pda(&[seeds::JUNIOR_INTEREST_POOL, key]),
+            grow_token_mint: pda(&[seeds::GROW_TOKEN_MINT, key]),
+            junior_nft_mint: pda(&[seeds::JUNIOR_NFT_MINT, key]),
+            senior_pool_vault: pda(&[seeds::SENIOR_POOL_VAULT, key]),
+            first_loss_pool_vault: pda(&[seeds::FIRST_LOSS_POOL_VAULT, key]),
+            junior_interest_pool_vault: pda(&[seeds::JUNIOR_INTEREST_POOL_VAULT, key]),
+            asset_pool_vault: Keypair::new(),
+            treasury,
+            treasury_ata: get_associated_token_address(&treasury, &env.asset_mint.pubkey()),
+        }
+    }
+
+    pub fn subscription(&self, user: &Pubkey, senior: bool) -> Pubkey {
+        let tranche: &[u8] = if senior { b"senior" } else { b"junior" };
+        pda(&[
+            seeds::SUBSCRIPTION,
+            self.asset_pool.as_ref(),
+            user.as_ref(),
+            tranche,
+        ])
+    }
+
+    pub fn junior_nft_mint(&self, nft_id: u64) -> Pubkey {
+        pda(&[
+            seeds::JUNIOR_NFT_MINT,
+            self.asset_pool.as_ref(),
+            &nft_id.to_le_bytes(),
+        ])
+    }
+
+    pub fn junior_nft_metadata(&self, nft_id: u64) -> Pubkey {
+        pda(&[
+            seeds::JUNIOR_NFT_METADATA,
+            self.asset_pool.as_ref(),
+            &nft_id.to_le_bytes(),
+        ])
+    }
+
+    pub fn repayment_record(&self, period: u64) -> Pubkey {
+        pda(&[
+            seeds::REPAYMENT_RECORD,
+            self.asset_pool.as_ref(),
+            &period.to_le_bytes(),
+        ])
+    }
+
+    /// 第 period 期的还款到期时间
+    pub fn period_due_time(&self, period: u64) -> i64 {
+        self.funding_end_time + (self.repayment_period * period) as i64
+    }
+
+    pub fn originator(&self) -> Pubkey {
+        pda(&[seeds::ORIGINATOR, self.creator.as_ref()])
+    }
+
+    pub fn senior_checkpoint(&self, holder: &Pubkey) -> Pubkey {
+        pda(&[
+            seeds::SENIOR_CHECKPOINT,
+            self.asset_pool.as_ref(),
+            holder.as_ref(),
+        ])
+    }
+
+    pub fn grow_escrow(&self) -> Pubkey {
+        pda(&[seeds::GROW_ESCROW, self.asset_pool.as_ref()])
+    }
+
+    pub fn exit_queue(&self) -> Pubkey {
+        pda(&[seeds::SENIOR_EXIT_QUEUE, self.asset_pool.as_ref()])
+    }
+
+    pub fn exit_request(&self, request_id: u64) -> Pubkey {
+        pda(&[
+            seeds::SENIOR_EXIT_REQUEST,
+            self.asset_pool.as_ref(),
+            &request_id.to_le_bytes(),
+        ])
+    }
+
+    pub fn exit_escrow(&self) -> Pubkey {
+        pda(&[seeds::SENIOR_EXIT_ESCROW, self.asset_pool.as_ref()])
+    }
+
+    pub fn listing(&self, seller: &Pubkey, listing_id: u64) -> Pubkey {
+        pda(&[
+            seeds::LISTING,
+            self.asset_pool.as_ref(),
+            seller.as_ref(),
+            &listing_id.to_le_bytes(),
+        ])
+    }
+
+    pub fn listing_escrow(&self, seller: &Pubkey, listing_id: u64) -> Pubkey {
+        pda(&[
+            seeds::LISTING_ESCROW,
+            self.listing(seller, listing_id).as_ref(),
+        ])
+    }
+
+    /// 挂单持仓的 Mint：`nft_id` 为 None 时为 GROW
+    fn listing_position_mint(&self, nft_id: Option<u64>) -> Pubkey {
+        nft_id.map_or(self.grow_token_mint, |nft_id| self.junior_nft_mint(nft_id))
+    }
+
+    pub fn pool_reserve(&self) -> Pubkey {
+        pda(&[seeds::POOL_RESERVE, self.asset_pool.as_ref()])
+    }
+
+    pub fn reserve_vault(&self) -> Pubkey {
+        pda(&[seeds::POOL_RESERVE_VAULT, self.asset_pool.as_ref()])
+    }
+
+    /// 资产代币对应的保险基金（所有同币种资产池共用）
+    pub fn insurance_fund(&self) -> Pubkey {
+        pda(&[seeds::INSURANCE_FUND, self.asset_mint.as_ref()])
+    }
+
+    pub fn insurance_fund_vault(&self) -> Pubkey {
+        pda(&[seeds::INSURANCE_FUND_VAULT, self.asset_mint.as_ref()])
+    }
+
+    pub fn insurance_draw(&self) -> Pubkey {
+        pda(&[seeds::INSURANCE_DRAW, self.asset_pool.as_ref()])
+    }
+
+    /// 为资产池所用的资产代币创建保险基金
+    pub fn initialize_insurance_fund(
+        &self,
+        env: &mut TestEnv,
+        fee_share: u16,
+        max_draw_per_pool: u64,
+    ) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::InitializeInsuranceFund {
+                admin: admin.pubkey(),
+                system_config: self.system_config,
+                asset_whitelist: pda(&[seeds::ASSET_WHITELIST]),
+                insurance_fund: self.insurance_fund(),
+                insurance_fund_vault: self.insurance_fund_vault(),
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::InitializeInsuranceFund {
+                fee_share,
+                max_draw_per_pool,
+            },
+        );
+        env.process(&[ix], &[&admin])
+    }
+
+    pub fn propose_insurance_draw(&self, env: &mut TestEnv, amount: u64) -> TxResult {
+        let operator = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::ProposeInsuranceDraw {
+                operation_admin: operator.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                senior_pool: self.senior_pool,
+                insurance_fund: self.insurance_fund(),
+                insurance_fund_vault: self.insurance_fund_vault(),
+                insurance_draw: self.insurance_draw(),
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::ProposeInsuranceDraw { amount },
+        );
+        env.process(&[ix], &[&operator])
+    }
+
+    pub fn execute_insurance_draw(&self, env: &mut TestEnv) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::ExecuteInsuranceDraw {
+                super_admin: admin.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                senior_pool: self.senior_pool,
+                senior_pool_vault: self.senior_pool_vault,
+                insurance_fund: self.insurance_fund(),
+                insurance_fund_vault: self.insurance_fund_vault(),
+                insurance_draw: self.insurance_draw(),
+                grow_token_mint: self.grow_token_mint,
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::ExecuteInsuranceDraw {},
+        );
+        env.process(&[ix], &[&admin])
+    }
+
+    pub fn cancel_insurance_draw(&self, env: &mut TestEnv) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::CancelInsuranceDraw {
+                admin: admin.pubkey(),
+                system_config: self.system_config,
+                insurance_draw: self.insurance_draw(),
+            },
+            pencil_solana::instruction::CancelInsuranceDraw {},
+        );
+        env.process(&[ix], &[&admin])
+    }
+
+    /// 资产池级手续费分账配置
+    pub fn fee_split(&self) -> Pubkey {
+        pda(&[seeds::FEE_SPLIT, self.asset_pool.as_ref()])
+    }
+
+    pub fn fee_collector(&self) -> Pubkey {
+        pda(&[seeds::FEE_COLLECTOR, self.asset_pool.as_ref()])
+    }
+
+    pub fn set_pool_fee_split(&self, env: &mut TestEnv, recipients: &[(Pubkey, u16)]) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::SetPoolFeeSplit {
+                treasury_admin: admin.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                fee_split: self.fee_split(),
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::SetPoolFeeSplit {
+                recipients: fee_recipients(recipients),
+            },
+        );
+        env.process(&[ix], &[&admin])
+    }
+
+    /// 开启手续费归集，之后手续费转入归集账户（同步更新 treasury_ata）
+    pub fn initialize_fee_collector(&mut self, env: &mut TestEnv) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::InitializeFeeCollector {
+                treasury_admin: admin.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                fee_collector: self.fee_collector(),
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::InitializeFeeCollector {},
+        );
+        env.process(&[ix], &[&admin])?;
+        self.treasury_ata = self.fee_collector();
+        Ok(())
+    }
+
+    /// 分发归集的手续费，recipients 按分账配置顺序传入
+    pub fn distribute_fees(&self, env: &mut TestEnv, recipients: &[Pubkey]) -> TxResult {
+        let mut ix = instruction(
+            pencil_solana::accounts::DistributeFees {
+                caller: env.payer().pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                fee_collector: self.fee_collector(),
+                pool_fee_split: self.fee_split(),
+                system_fee_split: pda(&[seeds::FEE_SPLIT, self.system_config.as_ref()]),
+                referral_program: self.referral_program(),
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::DistributeFees {},
+        );
+        ix.accounts.extend(
+            recipients
+                .iter()
+                .map(|recipient| AccountMeta::new(env.asset_ata(recipient), false)),
+        );
+        env.process(&[ix], &[])
+    }
+
+    pub fn referral_program(&self) -> Pubkey {
+        pda(&[seeds::REFERRAL_PROGRAM, self.asset_pool.as_ref()])
+    }
+
+    /// 推荐人在资产池内的推荐记录
+    pub fn referral(&self, referrer: &Pubkey) -> Pubkey {
+        pda(&[seeds::REFERRAL, self.asset_pool.as_ref(), referrer.as_ref()])
+    }
+
+    pub fn set_referral_terms(
+        &self,
+        env: &mut TestEnv,
+        reward_mode: u8,
+        reward_rate: u16,
+    ) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::SetReferralTerms {
+                treasury_admin: admin.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                referral_program: self.referral_program(),
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::SetReferralTerms {
+                reward_mode,
+                reward_rate,
+            },
+        );
+        env.process(&[ix], &[&admin])
+    }
+
+    pub fn claim_referral_reward(&self, env: &mut TestEnv, referrer: &Pubkey) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::ClaimReferralReward {
+                caller: env.payer().pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                referral_program: self.referral_program(),
+                referral_record: self.referral(referrer),
+                fee_collector: self.fee_collector(),
+                referrer_token_account: env.asset_ata(referrer),
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::ClaimReferralReward {},
+        );
+        env.process(&[ix], &[])
+    }
+
+    /// 还款类指令附带的储备金账户（未启用储备金时链上忽略）
+    fn reserve_account_metas(&self) -> [AccountMeta; 2] {
+        [
+            AccountMeta::new(self.pool_reserve(), false),
+            AccountMeta::new(self.reserve_vault(), false),
+        ]
+    }
+
+    pub fn initialize_pool_reserve(
+        &self,
+        env: &mut TestEnv,
+        fee_share: u16,
+        interest_share: u16,
+    ) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::InitializePoolReserve {
+                admin: admin.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                pool_reserve: self.pool_reserve(),
+                reserve_vault: self.reserve_vault(),
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::InitializePoolReserve {
+                fee_share,
+                interest_share,
+            },
+        );
+        env.process(&[ix], &[&admin])
+    }
+
+    pub fn state(&self, env: &mut TestEnv) -> AssetPool {
+        env.account(&self.asset_pool)
+    }
+
+    pub fn approve(&self, env: &mut TestEnv) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::ApproveAssetPool {
+                admin: admin.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+            },
+            pencil_solana::instruction::ApproveAssetPool {
+                creator: self.creator,
+                name: self.name.clone(),
+            },
+        );
+        env.process(&[ix], &[&admin])
+    }
+
+    pub fn initialize_related_accounts(&self, env: &mut TestEnv) -> TxResult {
+        let payer = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::InitializeRelatedAccounts {
+                payer: payer.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                asset_mint: self.asset_mint,
+                funding: self.funding,
+                senior_pool: self.senior_pool,
+                first_loss_pool: self.first_loss_pool,
+                junior_interest_pool: self.junior_interest_pool,
+                grow_token_mint: self.grow_token_mint,
+                junior_nft_mint: self.junior_nft_mint,
+                asset_pool_vault: self.asset_pool_vault.pubkey(),
+                senior_pool_vault: self.senior_pool_vault,
+                first_loss_pool_vault: self.first_loss_pool_vault,
+                junior_interest_pool_vault: self.junior_interest_pool_vault,
+                treasury: self.treasury,
+                treasury_ata: self.treasury_ata,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            pencil_solana::instruction::InitializeRelatedAccounts {},
+        );
+        env.process(&[ix], &[&payer, &self.asset_pool_vault])
+    }
+
+    pub fn subscribe(
+        &self,
+        env: &mut TestEnv,
+        user: &Keypair,
+        senior: bool,
+        amount: u64,
+    ) -> TxResult {
+        self.subscribe_with_referrer(env, user, senior, amount, None)
+    }
+
+    /// 认购；经推荐时附带推荐人、推荐记录与推荐奖励条款账户
+    pub fn subscribe_with_referrer(
+        &self,
+        env: &mut TestEnv,
+        user: &Keypair,
+        senior: bool,
+        amount: u64,
+        referrer: Option<&Pubkey>,
+    ) -> TxResult {
+        let user_token_account = env.asset_ata(&user.pubkey());
+        let subscription = self.subscription(&user.pubkey(), senior);
+        let mut ix = if senior {
+            instruction(
+                pencil_solana::accounts::SubscribeSenior {
+                    user: user.pubkey(),
+                    system_config: self.system_config,
+                    asset_whitelist: pda(&[seeds::ASSET_WHITELIST]),
+                    asset_pool: self.asset_pool,
+                    user_token_account,
+                    pool_token_account: self.asset_pool_vault.pubkey(),
+                    asset_mint: self.asset_mint,
+                    subscription,
+                    token_program: spl_token::ID,
+                    system_program: system_program::ID,
+                },
+                pencil_solana::instruction::SubscribeSenior { amount },
+            )
+        } else {
+            instruction(
+                pencil_solana::accounts::SubscribeJunior {
+                    user: user.pubkey(),
+                    system_config: self.system_config,
+                    asset_whitelist: pda(&[seeds::ASSET_WHITELIST]),
+                    asset_pool: self.asset_pool,
+                    user_token_account,
+                    pool_token_account: self.asset_pool_vault.pubkey(),
+                    asset_mint: self.asset_mint,
+                    subscription,
+                    token_program: spl_token::ID,
+                    system_program: system_program::ID,
+                },
+                pencil_solana::instruction::SubscribeJunior { amount },
+            )
+        };
+        if let Some(referrer) = referrer {
+            ix.accounts.extend([
+                AccountMeta::new_readonly(*referrer, false),
+                AccountMeta::new(self.referral(referrer), false),
+                AccountMeta::new(self.referral_program(), false),
+            ]);
+        }
+        env.process(&[ix], &[user])
+    }
+
+    pub fn complete_funding(&self, env: &mut TestEnv) -> TxResult {
+        let payer = env.payer();
+        let ix = instruction(
+            pencil_solana::accounts::CompleteFunding {
+                payer: payer.pubkey(),
+                asset_pool: self.asset_pool,
+                senior_pool: self.senior_pool,
+                first_loss_pool: self.first_loss_pool,
+                asset_pool_vault: self.asset_pool_vault.pubkey(),
+                senior_pool_vault: self.senior_pool_vault,
+                first_loss_pool_vault: self.first_loss_pool_vault,
+                originator: self.originator(),
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::CompleteFunding {},
+        );
+        env.process(&[ix], &[])
+    }
+
+    pub fn distribute_senior_token(&self, env: &mut TestEnv, user: &Pubkey) -> TxResult {
+        let payer = env.payer();
+        let ix = instruction(
+            pencil_solana::accounts::DistributeSeniorToken {
+                payer: payer.pubkey(),
+                asset_pool: self.asset_pool,
+                subscription: self.subscription(user, true),
+                grow_token_mint: self.grow_token_mint,
+                user: *user,
+                user_token_account: get_associated_token_address(user, &self.grow_token_mint),
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::DistributeSeniorToken {},
+        );
+        env.process(&[ix], &[])
+    }
+
+    pub fn distribute_junior_nft(&self, env: &mut TestEnv, user: &Pubkey, nft_id: u64) -> TxResult {
+        let payer = env.payer();
+        let nft_mint = self.junior_nft_mint(nft_id);
+        let ix = instruction(
+            pencil_solana::accounts::DistributeJuniorNFT {
+                payer: payer.pubkey(),
+                asset_pool: self.asset_pool,
+                subscription: self.subscription(user, false),
+                junior_nft_mint: nft_mint,
+                user: *user,
+                user_token_account: get_associated_token_address(user, &nft_mint),
+                nft_metadata: self.junior_nft_metadata(nft_id),
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            pencil_solana::instruction::DistributeJuniorNft { nft_id },
+        );
+        env.process(&[ix], &[])
+    }
+
+    pub fn repay(&self, env: &mut TestEnv, payer: &Keypair, amount: u64, period: u64) -> TxResult {
+        let ix = self.repay_instruction(env, payer, amount, period);
+        env.process(&[ix], &[payer])
+    }
+
+    /// 还款指令（附带储备金账户）
+    pub fn repay_instruction(
+        &self,
+        env: &TestEnv,
+        payer: &Keypair,
+        amount: u64,
+        period: u64,
+    ) -> Instruction {
+        let mut ix = instruction(
+            pencil_solana::accounts::Repay {
+                payer: payer.pubkey(),
+                system_config: self.system_config,
+                asset_whitelist: pda(&[seeds::ASSET_WHITELIST]),
+                asset_pool: self.asset_pool,
+                originator: self.originator(),
+                senior_pool: self.senior_pool,
+                first_loss_pool: self.first_loss_pool,
+                junior_interest_pool: self.junior_interest_pool,
+                payer_token_account: env.asset_ata(&payer.pubkey()),
+                senior_pool_vault: self.senior_pool_vault,
+                first_loss_pool_vault: self.first_loss_pool_vault,
+                junior_interest_pool_vault: self.junior_interest_pool_vault,
+                treasury_ata: self.treasury_ata,
+                asset_mint: self.asset_mint,
+                repayment_record: self.repayment_record(period),
+                grow_token_mint: self.grow_token_mint,
+                insurance_fund: self.insurance_fund(),
+                insurance_fund_vault: self.insurance_fund_vault(),
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::Repay { amount, period },
+        );
+        ix.accounts.extend(self.reserve_account_metas());
+        ix
+    }
+
+    pub fn prepay_remaining(
+        &self,
+        env: &mut TestEnv,
+        payer: &Keypair,
+        max_amount: u64,
+    ) -> TxResult {
+        let mut ix = instruction(
+            pencil_solana::accounts::PrepayRemaining {
+                payer: payer.pubkey(),
+                system_config: self.system_config,
+                asset_whitelist: pda(&[seeds::ASSET_WHITELIST]),
+                asset_pool: self.asset_pool,
+                originator: self.originator(),
+                senior_pool: self.senior_pool,
+                first_loss_pool: self.first_loss_pool,
+                junior_interest_pool: self.junior_interest_pool,
+                payer_token_account: env.asset_ata(&payer.pubkey()),
+                senior_pool_vault: self.senior_pool_vault,
+                first_loss_pool_vault: self.first_loss_pool_vault,
+                junior_interest_pool_vault: self.junior_interest_pool_vault,
+                treasury_ata: self.treasury_ata,
+                asset_mint: self.asset_mint,
+                grow_token_mint: self.grow_token_mint,
+                insurance_fund: self.insurance_fund(),
+                insurance_fund_vault: self.insurance_fund_vault(),
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::PrepayRemaining { max_amount },
+        );
+        ix.accounts.extend(self.reserve_account_metas());
+        env.process(&[ix], &[payer])
+    }
+
+    pub fn claim_junior_interest(
+        &self,
+        env: &mut TestEnv,
+        user: &Keypair,
+        nft_id: u64,
+    ) -> TxResult {
+        let nft_mint = self.junior_nft_mint(nft_id);
+        let ix = instruction(
+            pencil_solana::accounts::ClaimJuniorInterest {
+                user: user.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                first_loss_pool: self.first_loss_pool,
+                junior_interest_pool: self.junior_interest_pool,
+                nft_metadata: self.junior_nft_metadata(nft_id),
+                user_nft_account: get_associated_token_address(&user.pubkey(), &nft_mint),
+                junior_nft_mint: nft_mint,
+                user_asset_account: env.asset_ata(&user.pubkey()),
+                junior_interest_pool_vault: self.junior_interest_pool_vault,
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::ClaimJuniorInterest { nft_id },
+        );
+        env.process(&[ix], &[user])
+    }
+
+    pub fn withdraw_principal(&self, env: &mut TestEnv, user: &Keypair, nft_id: u64) -> TxResult {
+        let nft_mint = self.junior_nft_mint(nft_id);
+        let ix = instruction(
+            pencil_solana::accounts::WithdrawPrincipal {
+                user: user.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                first_loss_pool: self.first_loss_pool,
+                nft_metadata: self.junior_nft_metadata(nft_id),
+                user_nft_account: get_associated_token_address(&user.pubkey(), &nft_mint),
+                junior_nft_mint: nft_mint,
+                user_asset_account: env.asset_ata(&user.pubkey()),
+                first_loss_pool_vault: self.first_loss_pool_vault,
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::WithdrawPrincipal { nft_id },
+        );
+        env.process(&[ix], &[user])
+    }
+
+    /// Senior 早退；池完成后为按比例提取
+    pub fn early_exit_senior(&self, env: &mut TestEnv, user: &Keypair, amount: u64) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::EarlyExitSenior {
+                user: user.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                senior_pool: self.senior_pool,
+                exit_queue: self.exit_queue(),
+                grow_token_mint: self.grow_token_mint,
+                user_grow_token_account: get_associated_token_address(
+                    &user.pubkey(),
+                    &self.grow_token_mint,
+                ),
+                user_asset_account: env.asset_ata(&user.pubkey()),
+                senior_pool_vault: self.senior_pool_vault,
+                treasury_ata: self.treasury_ata,
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::EarlyExitSenior { amount },
+        );
+        env.process(&[ix], &[user])
+    }
+
+    /// Junior 早退；`replacement` 为接盘方，其新 NFT 编号由资产池分配
+    pub fn early_exit_junior(
+        &self,
+        env: &mut TestEnv,
+        user: &Keypair,
+        nft_id: u64,
+        replacement: Option<&Keypair>,
+    ) -> TxResult {
+        let nft_mint = self.junior_nft_mint(nft_id);
+        let new_nft_id = issued_nft_id(self.state(env).issued_nft_count);
+        let mut ix = instruction(
+            pencil_solana::accounts::EarlyExitJunior {
+                user: user.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                senior_pool: self.senior_pool,
+                first_loss_pool: self.first_loss_pool,
+                junior_interest_pool: self.junior_interest_pool,
+                nft_metadata: self.junior_nft_metadata(nft_id),
+                user_nft_account: get_associated_token_address(&user.pubkey(), &nft_mint),
+                junior_nft_mint: nft_mint,
+                user_asset_account: env.asset_ata(&user.pubkey()),
+                first_loss_pool_vault: self.first_loss_pool_vault,
+                junior_interest_pool_vault: self.junior_interest_pool_vault,
+                treasury_ata: self.treasury_ata,
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::EarlyExitJunior {
+                nft_id,
+                with_replacement: replacement.is_some(),
+            },
+        );
+        let mut signers = vec![user];
+        if let Some(buyer) = replacement {
+            let new_nft_mint = self.junior_nft_mint(new_nft_id);
+            ix.accounts.extend([
+                AccountMeta::new(buyer.pubkey(), true),
+                AccountMeta::new(env.asset_ata(&buyer.pubkey()), false),
+                AccountMeta::new(new_nft_mint, false),
+                AccountMeta::new(self.junior_nft_metadata(new_nft_id), false),
+                AccountMeta::new(
+                    get_associated_token_address(&buyer.pubkey(), &new_nft_mint),
+                    false,
+                ),
+            ]);
+            signers.push(buyer);
+        }
+        env.process(&[ix], &signers)
+    }
+
+    /// 领取 Senior 分配并追加锁定 GROW；池完成后赎回锁定的 GROW
+    pub fn claim_senior_distribution(
+        &self,
+        env: &mut TestEnv,
+        holder: &Keypair,
+        lock_amount: u64,
+    ) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::ClaimSeniorDistribution {
+                holder: holder.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                senior_pool: self.senior_pool,
+                grow_token_mint: self.grow_token_mint,
+                checkpoint: self.senior_checkpoint(&holder.pubkey()),
+                grow_escrow: self.grow_escrow(),
+                holder_grow_account: get_associated_token_address(
+                    &holder.pubkey(),
+                    &self.grow_token_mint,
+                ),
+                holder_asset_account: env.asset_ata(&holder.pubkey()),
+                senior_pool_vault: self.senior_pool_vault,
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::ClaimSeniorDistribution { lock_amount },
+        );
+        env.process(&[ix], &[holder])
+    }
+
+    pub fn unlock_senior_distribution(
+        &self,
+        env: &mut TestEnv,
+        holder: &Keypair,
+        amount: u64,
+    ) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::UnlockSeniorDistribution {
+                holder: holder.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                senior_pool: self.senior_pool,
+                checkpoint: self.senior_checkpoint(&holder.pubkey()),
+                grow_escrow: self.grow_escrow(),
+                holder_grow_account: get_associated_token_address(
+                    &holder.pubkey(),
+                    &self.grow_token_mint,
+                ),
+                holder_asset_account: env.asset_ata(&holder.pubkey()),
+                senior_pool_vault: self.senior_pool_vault,
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::UnlockSeniorDistribution { amount },
+        );
+        env.process(&[ix], &[holder])
+    }
+
+    /// 提交早退请求，请求 ID 取队列当前 tail
+    pub fn request_senior_exit(
+        &self,
+        env: &mut TestEnv,
+        holder: &Keypair,
+        amount: u64,
+    ) -> TxResult {
+        let request_id = if env.account_exists(&self.exit_queue()) {
+            env.account::<SeniorExitQueue>(&self.exit_queue()).tail
+        } else {
+            0
+        };
+        let ix = instruction(
+            pencil_solana::accounts::RequestSeniorExit {
+                holder: holder.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                exit_queue: self.exit_queue(),
+                exit_request: self.exit_request(request_id),
+                grow_token_mint: self.grow_token_mint,
+                exit_escrow: self.exit_escrow(),
+                holder_grow_account: get_associated_token_address(
+                    &holder.pubkey(),
+                    &self.grow_token_mint,
+                ),
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::RequestSeniorExit { amount },
+        );
+        env.process(&[ix], &[holder])
+    }
+
+    pub fn cancel_senior_exit(
+        &self,
+        env: &mut TestEnv,
+        holder: &Keypair,
+        request_id: u64,
+    ) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::CancelSeniorExit {
+                holder: holder.pubkey(),
+                asset_pool: self.asset_pool,
+                exit_queue: self.exit_queue(),
+                exit_request: self.exit_request(request_id),
+                exit_escrow: self.exit_escrow(),
+                holder_grow_account: get_associated_token_address(
+                    &holder.pubkey(),
+                    &self.grow_token_mint,
+                ),
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::CancelSeniorExit { request_id },
+        );
+        env.process(&[ix], &[holder])
+    }
+
+    /// 撮合早退队列；`requests` 为从队首开始的 (request_id, holder)
+    pub fn process_senior_exits(&self, env: &mut TestEnv, requests: &[(u64, Pubkey)]) -> TxResult {
+        let mut ix = instruction(
+            pencil_solana::accounts::ProcessSeniorExits {
+                caller: env.payer().pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                senior_pool: self.senior_pool,
+                exit_queue: self.exit_queue(),
+                grow_token_mint: self.grow_token_mint,
+                exit_escrow: self.exit_escrow(),
+                senior_pool_vault: self.senior_pool_vault,
+                treasury_ata: self.treasury_ata,
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::ProcessSeniorExits {},
+        );
+        for (request_id, holder) in requests {
+            ix.accounts.extend([
+                AccountMeta::new(self.exit_request(*request_id), false),
+                AccountMeta::new(env.asset_ata(holder), false),
+                AccountMeta::new(*holder, false),
+            ]);
+        }
+        env.process(&[ix], &[])
+    }
+
+    pub fn create_grow_listing(
+        &self,
+        env: &mut TestEnv,
+        seller: &Keypair,
+        listing_id: u64,
+        amount: u64,
+        unit_price: u64,
+    ) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::CreateGrowListing {
+                seller: seller.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                listing: self.listing(&seller.pubkey(), listing_id),
+                grow_token_mint: self.grow_token_mint,
+                listing_escrow: self.listing_escrow(&seller.pubkey(), listing_id),
+                seller_grow_account: get_associated_token_address(
+                    &seller.pubkey(),
+                    &self.grow_token_mint,
+                ),
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::CreateGrowListing {
+                listing_id,
+                amount,
+                unit_price,
+            },
+        );
+        env.process(&[ix], &[seller])
+    }
+
+    pub fn create_junior_nft_listing(
+        &self,
+        env: &mut TestEnv,
+        seller: &Keypair,
+        listing_id: u64,
+        nft_id: u64,
+        price: u64,
+    ) -> TxResult {
+        let nft_mint = self.junior_nft_mint(nft_id);
+        let ix = instruction(
+            pencil_solana::accounts::CreateJuniorNftListing {
+                seller: seller.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                listing: self.listing(&seller.pubkey(), listing_id),
+                nft_metadata: self.junior_nft_metadata(nft_id),
+                junior_nft_mint: nft_mint,
+                listing_escrow: self.listing_escrow(&seller.pubkey(), listing_id),
+                seller_nft_account: get_associated_token_address(&seller.pubkey(), &nft_mint),
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::CreateJuniorNftListing {
+                listing_id,
+                nft_id,
+                price,
+            },
+        );
+        env.process(&[ix], &[seller])
+    }
+
+    /// 成交挂单；`nft_id` 为 None 时为 GROW 挂单
+    pub fn fill_listing(
+        &self,
+        env: &mut TestEnv,
+        buyer: &Keypair,
+        seller: &Pubkey,
+        listing_id: u64,
+        nft_id: Option<u64>,
+        amount: u64,
+    ) -> TxResult {
+        let position_mint = self.listing_position_mint(nft_id);
+        let ix = instruction(
+            pencil_solana::accounts::FillListing {
+                buyer: buyer.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                listing: self.listing(seller, listing_id),
+                seller: *seller,
+                position_mint,
+                nft_metadata: nft_id.map(|nft_id| self.junior_nft_metadata(nft_id)),
+                listing_escrow: self.listing_escrow(seller, listing_id),
+                buyer_position_account: get_associated_token_address(
+                    &buyer.pubkey(),
+                    &position_mint,
+                ),
+                buyer_asset_account: env.asset_ata(&buyer.pubkey()),
+                seller_asset_account: env.asset_ata(seller),
+                treasury_ata: self.treasury_ata,
+                asset_mint: self.asset_mint,
+                token_program: spl_token::ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            },
+            pencil_solana::instruction::FillListing { amount },
+        );
+        env.process(&[ix], &[buyer])
+    }
+
+    pub fn cancel_listing(
+        &self,
+        env: &mut TestEnv,
+        seller: &Keypair,
+        listing_id: u64,
+        nft_id: Option<u64>,
+    ) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::CancelListing {
+                seller: seller.pubkey(),
+                asset_pool: self.asset_pool,
+                listing: self.listing(&seller.pubkey(), listing_id),
+                listing_escrow: self.listing_escrow(&seller.pubkey(), listing_id),
+                seller_position_account: get_associated_token_address(
+                    &seller.pubkey(),
+                    &self.listing_position_mint(nft_id),
+                ),
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::CancelListing {},
+        );
+        env.process(&[ix], &[seller])
+    }
+
+    pub fn withdraw_subscription(
+        &self,
+        env: &mut TestEnv,
+        user: &Keypair,
+        senior: bool,
+        amount: u64,
+    ) -> TxResult {
+        self.withdraw_referred_subscription(env, user, senior, amount, None)
+    }
+
+    /// 撤回经推荐的认购，附带推荐记录与推荐奖励条款账户
+    pub fn withdraw_referred_subscription(
+        &self,
+        env: &mut TestEnv,
+        user: &Keypair,
+        senior: bool,
+        amount: u64,
+        referrer: Option<&Pubkey>,
+    ) -> TxResult {
+        let subscription = self.subscription(&user.pubkey(), senior);
+        let user_token_account = env.asset_ata(&user.pubkey());
+        let mut ix = if senior {
+            instruction(
+                pencil_solana::accounts::WithdrawSeniorSubscription {
+                    user: user.pubkey(),
+                    system_config: self.system_config,
+                    asset_pool: self.asset_pool,
+                    subscription,
+                    pool_token_account: self.asset_pool_vault.pubkey(),
+                    user_token_account,
+                    treasury_ata: self.treasury_ata,
+                    asset_mint: self.asset_mint,
+                    token_program: spl_token::ID,
+                },
+                pencil_solana::instruction::WithdrawSeniorSubscription { amount },
+            )
+        } else {
+            instruction(
+                pencil_solana::accounts::WithdrawJuniorSubscription {
+                    user: user.pubkey(),
+                    system_config: self.system_config,
+                    asset_pool: self.asset_pool,
+                    subscription,
+                    pool_token_account: self.asset_pool_vault.pubkey(),
+                    user_token_account,
+                    treasury_ata: self.treasury_ata,
+                    asset_mint: self.asset_mint,
+                    token_program: spl_token::ID,
+                },
+                pencil_solana::instruction::WithdrawJuniorSubscription { amount },
+            )
+        };
+        if let Some(referrer) = referrer {
+            ix.accounts.extend([
+                AccountMeta::new(self.referral(referrer), false),
+                AccountMeta::new(self.referral_program(), false),
+            ]);
+        }
+        env.process(&[ix], &[user])
+    }
+
+    pub fn close_funding_early(&self, env: &mut TestEnv, payer: &Keypair) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::CloseFundingEarly {
+                system_config: self.system_config,
+                complete_funding: pencil_solana::accounts::CompleteFunding {
+                    payer: payer.pubkey(),
+                    asset_pool: self.asset_pool,
+                    senior_pool: self.senior_pool,
+                    first_loss_pool: self.first_loss_pool,
+                    asset_pool_vault: self.asset_pool_vault.pubkey(),
+                    senior_pool_vault: self.senior_pool_vault,
+                    first_loss_pool_vault: self.first_loss_pool_vault,
+                    originator: self.originator(),
+                    token_program: spl_token::ID,
+                },
+            },
+            pencil_solana::instruction::CloseFundingEarly {},
+        );
+        env.process(&[ix], &[payer])
+    }
+
+    /// 由 operation_admin（测试中为 env.admin）延长募资期
+    pub fn extend_funding(&self, env: &mut TestEnv, new_end: i64) -> TxResult {
+        let operator = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::ExtendFunding {
+                operation_admin: operator.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+            },
+            pencil_solana::instruction::ExtendFunding { new_end },
+        );
+        env.process(&[ix], &[&operator])
+    }
+
+    /// 超级管理员驳回资产池
+    pub fn reject(&self, env: &mut TestEnv, reason_code: u16) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::RejectAssetPool {
+                admin: admin.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                creator: self.creator,
+                originator: self.originator(),
+            },
+            pencil_solana::instruction::RejectAssetPool { reason_code },
+        );
+        env.process(&[ix], &[&admin])
+    }
+
+    /// 创建者撤回资产池
+    pub fn withdraw_pool(&self, env: &mut TestEnv, creator: &Keypair) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::WithdrawAssetPool {
+                creator: creator.pubkey(),
+                asset_pool: self.asset_pool,
+                originator: self.originator(),
+            },
+            pencil_solana::instruction::WithdrawAssetPool {},
+        );
+        env.process(&[ix], &[creator])
+    }
+
+    pub fn sync_originator_exposure(&self, env: &mut TestEnv) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::SyncOriginatorExposure {
+                asset_pool: self.asset_pool,
+                originator: self.originator(),
+            },
+            pencil_solana::instruction::SyncOriginatorExposure {},
+        );
+        env.process(&[ix], &[])
+    }
+
+    pub fn record_originator_default(&self, env: &mut TestEnv) -> TxResult {
+        let operator = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::RecordOriginatorDefault {
+                operation_admin: operator.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                originator: self.originator(),
+            },
+            pencil_solana::instruction::RecordOriginatorDefault {},
+        );
+        env.process(&[ix], &[&operator])
+    }
+
+    pub fn set_repayment_authority(
+        &self,
+        env: &mut TestEnv,
+        creator: &Keypair,
+        new_authority: &Pubkey,
+    ) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::SetRepaymentAuthority {
+                creator: creator.pubkey(),
+                asset_pool: self.asset_pool,
+            },
+            pencil_solana::instruction::SetRepaymentAuthority {
+                new_authority: *new_authority,
+            },
+        );
+        env.process(&[ix], &[creator])
+    }
+
+    pub fn set_prepayment_terms(
+        &self,
+        env: &mut TestEnv,
+        policy: u8,
+        penalty_rate: u16,
+    ) -> TxResult {
+        let creator = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::SetPrepaymentTerms {
+                creator: creator.pubkey(),
+                asset_pool: self.asset_pool,
+            },
+            pencil_solana::instruction::SetPrepaymentTerms {
+                policy,
+                penalty_rate,
+            },
+        );
+        env.process(&[ix], &[&creator])
+    }
+
+    /// 当前链上条款
+    pub fn terms(&self, env: &mut TestEnv) -> AssetPoolTerms {
+        AssetPoolTerms::of(&self.state(env))
+    }
+
+    /// 审批前由创建者修订条款
+    pub fn amend(&self, env: &mut TestEnv, terms: AssetPoolTerms) -> TxResult {
+        let creator = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::AmendAssetPool {
+                creator: creator.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                originator: self.originator(),
+            },
+            pencil_solana::instruction::AmendAssetPool { terms },
+        );
+        env.process(&[ix], &[&creator])
+    }
+
+    /// 审批后由创建者和超级管理员共同修订条款（测试中为同一账户）
+    pub fn amend_approved(&self, env: &mut TestEnv, terms: AssetPoolTerms) -> TxResult {
+        let admin = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::AmendApprovedAssetPool {
+                creator: self.creator,
+                admin: admin.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                originator: self.originator(),
+            },
+            pencil_solana::instruction::AmendApprovedAssetPool { terms },
+        );
+        env.process(&[ix], &[&admin])
+    }
+
+    /// 将继承参数刷新为 SystemConfig 当前默认值
+    pub fn sync_params(&self, env: &mut TestEnv) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::SyncPoolParams {
+                caller: env.payer().pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+            },
+            pencil_solana::instruction::SyncPoolParams {},
+        );
+        env.process(&[ix], &[])
+    }
+
+    pub fn process_refund(&self, env: &mut TestEnv, user: &Keypair, senior: bool) -> TxResult {
+        let ix = instruction(
+            pencil_solana::accounts::ProcessRefund {
+                user: user.pubkey(),
+                asset_pool: self.asset_pool,
+                subscription: self.subscription(&user.pubkey(), senior),
+                pool_vault: self.asset_pool_vault.pubkey(),
+                user_token_account: env.asset_ata(&user.pubkey()),
+                asset_mint: self.asset_mint,
+                originator: self.originator(),
+                token_program: spl_token::ID,
+            },
+            pencil_solana::instruction::ProcessRefund {},
+        );
+        env.process(&[ix], &[user])
+    }
+
+    pub fn cancel(&self, env: &mut TestEnv) -> TxResult {
+        let authority = env.admin.insecure_clone();
+        let ix = instruction(
+            pencil_solana::accounts::CancelAssetPool {
+                authority: authority.pubkey(),
+                asset_pool: self.asset_pool,
+                pool_vault: self.asset_pool_vault.pubkey(),
+                asset_mint: self.asset_mint,
+                originator: self.originator(),
+            },
+            pencil_solana::instruction::CancelAssetPool {},
+        );
+        env.process(&[ix], &[&authority])
+    }
+
+    pub fn check_invariants(&self, env: &mut TestEnv) -> TxResult {
+        let caller = env.payer();
+        let ix = instruction(
+            pencil_solana::accounts::CheckPoolInvariants {
+                caller: caller.pubkey(),
+                system_config: self.system_config,
+                asset_pool: self.asset_pool,
+                senior_pool: self.senior_pool,
+                first_loss_pool: self.first_loss_pool,
+                junior_interest_pool: self.junior_interest_pool,
+                asset_pool_vault: self.asset_pool_vault.pubkey(),
+                senior_pool_vault: self.senior_pool_vault,
+                first_loss_pool_vault: self.first_loss_pool_vault,
+                junior_interest_pool_vault: self.junior_interest_pool_vault,
+            },
+            pencil_solana::instruction::CheckPoolInvariants {},
+        );
+        env.process(&[ix], &[])
+    }
+}
+
+/// 从交易错误中取出 Anchor 自定义错误码
+/// 发起方委托还款服务方
+pub fn delegate_servicer(env: &mut TestEnv, authority: &Keypair, servicer: &Pubkey) -> TxResult {
+    let ix = instruction(
+        pencil_solana::accounts::DelegateServicer {
+            authority: authority.pubkey(),
+            originator: pda(&[seeds::ORIGINATOR, authority.pubkey().as_ref()]),
+        },
+        pencil_solana::instruction::DelegateServicer {
+            servicer: *servicer,
+        },
+    );
+    env.process(&[ix], &[authority])
+}
+
+pub fn error_code(error: TransactionError) -> u32 {
+    use solana_sdk::instruction::InstructionError;
+    match error {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => code,
+        other => panic!("unexpected transaction error: {:?}", other),
+    }
+}
+
+pub fn pencil_error(error: pencil_solana::errors::PencilError) -> u32 {
+    anchor_lang::error::ERROR_CODE_OFFSET + error as u32
+}
@@ -0,0 +1,80 @@
+//! 认购生命周期事件集成测试
+//! 每个状态变更都发出带金额、手续费和变更后总额的事件。
+
+mod common;
+
+use common::*;
+use pencil_solana::constants::asset_pool_status;
+use pencil_solana::{
+    FundingCompleted, JuniorNftDistributed, SeniorTokenDistributed, Subscribed,
+    SubscriptionWithdrawn,
+};
+use solana_sdk::signature::Signer;
+
+#[test]
+fn test_subscription_lifecycle_events() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().open(&mut env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+
+    pool.subscribe(&mut env, &alice, true, 500 * ONE_TOKEN)
+        .unwrap();
+    pool.subscribe(&mut env, &alice, true, 300 * ONE_TOKEN)
+        .unwrap();
+    let [subscribed] = env.events::<Subscribed>().try_into().ok().unwrap();
+    assert_eq!(subscribed.user, alice.pubkey());
+    assert_eq!(subscribed.subscription_type, 0);
+    assert_eq!(subscribed.amount, 300 * ONE_TOKEN);
+    assert_eq!(subscribed.subscription_amount, 800 * ONE_TOKEN);
+    assert_eq!(subscribed.pool_senior_amount, 800 * ONE_TOKEN);
+
+    pool.subscribe(&mut env, &carol, false, 250 * ONE_TOKEN)
+        .unwrap();
+    // Junior 募资期内撤资，手续费 3%
+    pool.withdraw_subscription(&mut env, &carol, false, 50 * ONE_TOKEN)
+        .unwrap();
+    let [withdrawn] = env
+        .events::<SubscriptionWithdrawn>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(withdrawn.subscription_type, 1);
+    assert_eq!(withdrawn.amount, 50 * ONE_TOKEN);
+    assert_eq!(withdrawn.fee, 1_500_000);
+    assert_eq!(withdrawn.net_amount, withdrawn.amount - withdrawn.fee);
+    assert_eq!(withdrawn.subscription_amount, 200 * ONE_TOKEN);
+    assert_eq!(withdrawn.pool_senior_amount, 800 * ONE_TOKEN);
+    assert_eq!(withdrawn.pool_junior_amount, 200 * ONE_TOKEN);
+
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(&mut env).unwrap();
+    let [funded] = env.events::<FundingCompleted>().try_into().ok().unwrap();
+    assert_eq!(funded.status, asset_pool_status::FUNDED);
+    assert_eq!(funded.total_amount, 1_000 * ONE_TOKEN);
+    assert_eq!(funded.junior_ratio, 2000);
+
+    pool.distribute_senior_token(&mut env, &alice.pubkey())
+        .unwrap();
+    let [distributed] = env
+        .events::<SeniorTokenDistributed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(distributed.amount, 800 * ONE_TOKEN);
+    assert_eq!(distributed.grow_supply, 800 * ONE_TOKEN);
+
+    pool.distribute_junior_nft(&mut env, &carol.pubkey(), 1)
+        .unwrap();
+    let [nft] = env
+        .events::<JuniorNftDistributed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(nft.nft_id, 1);
+    assert_eq!(nft.principal, 200 * ONE_TOKEN);
+    assert_eq!(nft.junior_nft_mint, pool.junior_nft_mint(1));
+}
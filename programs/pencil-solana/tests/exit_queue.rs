@@ -0,0 +1,233 @@
+//! Senior 早退队列集成测试
+//! GROW 托管进队列后，由每期还款带来的 Senior 资金按先进先出撮合，
+//! 成交价为 GROW 净值扣除早退费用，允许部分成交与撤销，不动用 FirstLossPool。
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::Mint;
+use common::*;
+use pencil_solana::constants::asset_pool_status;
+use pencil_solana::errors::PencilError;
+use pencil_solana::state::{SeniorExitQueue, SeniorExitRequest, SeniorPool};
+use pencil_solana::waterfall;
+use pencil_solana::{SeniorExitCancelled, SeniorExitFilled};
+use solana_sdk::signature::{Keypair, Signer};
+
+/// 募资达标并分发 GROW，返回资产池、Alice、Bob 与每期应还总额
+fn funded_pool(env: &mut TestEnv) -> (Pool, Keypair, Keypair, u64) {
+    SystemConfigBuilder::default().build(env);
+    let pool = PoolBuilder::default().repayment_count(4).open(env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let bob = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &alice, true, 200 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &bob, true, 600 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &carol, false, 200 * ONE_TOKEN).unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+    pool.distribute_senior_token(env, &alice.pubkey()).unwrap();
+    pool.distribute_senior_token(env, &bob.pubkey()).unwrap();
+
+    let state = pool.state(env);
+    let per_period_total = waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap();
+    (pool, alice, bob, per_period_total)
+}
+
+/// 按当前链上状态计算请求的成交结果
+fn expected_fill(env: &mut TestEnv, pool: &Pool, request_id: u64) -> waterfall::ExitFill {
+    let senior_pool: SeniorPool = env.account(&pool.senior_pool);
+    let request: SeniorExitRequest = env.account(&pool.exit_request(request_id));
+    let vault = env.token_balance(&pool.senior_pool_vault);
+    let grow_supply = env.account::<Mint>(&pool.grow_token_mint).supply;
+    waterfall::calculate_exit_fill(
+        request.amount,
+        vault + senior_pool.distributed_amount,
+        grow_supply,
+        senior_pool.exit_liquidity.min(vault),
+        pool.state(env).senior_early_after_exit_fee,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_queue_fills_fifo_from_repayments() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, bob, per_period_total) = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    let alice_grow = get_associated_token_address(&alice.pubkey(), &pool.grow_token_mint);
+    let bob_grow = get_associated_token_address(&bob.pubkey(), &pool.grow_token_mint);
+    let requests = [(0, alice.pubkey()), (1, bob.pubkey())];
+
+    pool.request_senior_exit(&mut env, &alice, 200 * ONE_TOKEN)
+        .unwrap();
+    pool.request_senior_exit(&mut env, &bob, 100 * ONE_TOKEN)
+        .unwrap();
+    assert_eq!(env.token_balance(&alice_grow), 0);
+    assert_eq!(env.token_balance(&pool.exit_escrow()), 300 * ONE_TOKEN);
+    let queue: SeniorExitQueue = env.account(&pool.exit_queue());
+    assert_eq!((queue.head, queue.tail), (0, 2));
+
+    // 尚无还款时没有可用资金
+    let error = pool.process_senior_exits(&mut env, &requests).unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::NoExitFilled));
+
+    // 必须从队首开始传入
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, per_period_total, 1)
+        .unwrap();
+    let error = pool
+        .process_senior_exits(&mut env, &requests[1..])
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidExitRequest)
+    );
+
+    // 第 1 期的 Senior 资金只够 Alice 部分成交，Bob 继续排队
+    let first_loss_before = env.token_balance(&pool.first_loss_pool_vault);
+    let alice_before = env.token_balance(&env.asset_ata(&alice.pubkey()));
+    let treasury_before = env.token_balance(&pool.treasury_ata);
+    let expected = expected_fill(&mut env, &pool, 0);
+    assert!(expected.grow_filled > 0 && expected.grow_filled < 200 * ONE_TOKEN);
+    pool.process_senior_exits(&mut env, &requests).unwrap();
+    let [filled] = env.events::<SeniorExitFilled>().try_into().ok().unwrap();
+    assert_eq!(filled.request_id, 0);
+    assert_eq!(filled.grow_filled, expected.grow_filled);
+    assert_eq!(filled.exit_fee, expected.exit_fee);
+    assert_eq!(filled.remaining, 200 * ONE_TOKEN - expected.grow_filled);
+    assert_eq!(
+        env.token_balance(&env.asset_ata(&alice.pubkey())) - alice_before,
+        expected.net().unwrap()
+    );
+    assert_eq!(
+        env.token_balance(&pool.treasury_ata) - treasury_before,
+        expected.exit_fee
+    );
+    assert_eq!(
+        env.token_balance(&pool.first_loss_pool_vault),
+        first_loss_before
+    );
+    let queue: SeniorExitQueue = env.account(&pool.exit_queue());
+    assert_eq!(queue.head, 0);
+    pool.check_invariants(&mut env).unwrap();
+
+    // Bob 撤销请求取回 GROW
+    pool.cancel_senior_exit(&mut env, &bob, 1).unwrap();
+    let [cancelled] = env.events::<SeniorExitCancelled>().try_into().ok().unwrap();
+    assert_eq!(cancelled.returned_amount, 100 * ONE_TOKEN);
+    assert_eq!(env.token_balance(&bob_grow), 600 * ONE_TOKEN);
+    assert!(!env.account_exists(&pool.exit_request(1)));
+
+    // 第 2 期到账后 Alice 全部成交，已撤销的请求被跳过
+    env.warp_to(pool.period_due_time(2));
+    pool.repay(&mut env, &borrower, per_period_total, 2)
+        .unwrap();
+    pool.process_senior_exits(&mut env, &requests).unwrap();
+    let [filled] = env.events::<SeniorExitFilled>().try_into().ok().unwrap();
+    assert_eq!(filled.remaining, 0);
+    assert!(!env.account_exists(&pool.exit_request(0)));
+    let queue: SeniorExitQueue = env.account(&pool.exit_queue());
+    assert_eq!((queue.head, queue.queued_amount), (2, 0));
+    assert_eq!(env.token_balance(&pool.exit_escrow()), 0);
+    pool.check_invariants(&mut env).unwrap();
+}
+
+#[test]
+fn test_cancel_after_completion() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, _bob, _) = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    let alice_grow = get_associated_token_address(&alice.pubkey(), &pool.grow_token_mint);
+
+    // 非请求人不能撤销
+    pool.request_senior_exit(&mut env, &alice, 50 * ONE_TOKEN)
+        .unwrap();
+    let mallory = env.new_user(0);
+    env.create_ata(&mallory.pubkey(), &pool.grow_token_mint);
+    assert!(pool.cancel_senior_exit(&mut env, &mallory, 0).is_err());
+
+    pool.prepay_remaining(&mut env, &borrower, u64::MAX)
+        .unwrap();
+    assert_eq!(pool.state(&mut env).status, asset_pool_status::COMPLETED);
+
+    // 完成后不再撮合，也不能新增请求；撤销后按比例提取
+    let error = pool
+        .process_senior_exits(&mut env, &[(0, alice.pubkey())])
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidAssetPoolStatus)
+    );
+    assert!(pool
+        .request_senior_exit(&mut env, &alice, 10 * ONE_TOKEN)
+        .is_err());
+    pool.cancel_senior_exit(&mut env, &alice, 0).unwrap();
+    assert_eq!(env.token_balance(&alice_grow), 200 * ONE_TOKEN);
+    pool.early_exit_senior(&mut env, &alice, 200 * ONE_TOKEN)
+        .unwrap();
+    assert_eq!(env.token_balance(&alice_grow), 0);
+}
+
+#[test]
+fn test_instant_exit_limited_to_unallocated_liquidity() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, bob, per_period_total) = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+
+    // 尚无还款时不能即时早退，也不动用 FirstLossPool
+    let error = pool
+        .early_exit_senior(&mut env, &alice, ONE_TOKEN)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::ExitLiquidityExceeded)
+    );
+
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, per_period_total, 1)
+        .unwrap();
+    let liquidity = env.account::<SeniorPool>(&pool.senior_pool).exit_liquidity;
+    let error = pool
+        .early_exit_senior(&mut env, &alice, liquidity + 1)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::ExitLiquidityExceeded)
+    );
+
+    let first_loss_before = env.token_balance(&pool.first_loss_pool_vault);
+    pool.early_exit_senior(&mut env, &alice, 10 * ONE_TOKEN)
+        .unwrap();
+    assert_eq!(
+        env.account::<SeniorPool>(&pool.senior_pool).exit_liquidity,
+        liquidity - 10 * ONE_TOKEN
+    );
+    assert_eq!(
+        env.token_balance(&pool.first_loss_pool_vault),
+        first_loss_before
+    );
+
+    // 排队请求按净值预留流动性，少量排队不阻塞其余即时早退
+    pool.request_senior_exit(&mut env, &bob, ONE_TOKEN).unwrap();
+    pool.early_exit_senior(&mut env, &alice, ONE_TOKEN).unwrap();
+    let liquidity = env.account::<SeniorPool>(&pool.senior_pool).exit_liquidity;
+    let error = pool
+        .early_exit_senior(&mut env, &alice, liquidity)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::ExitLiquidityExceeded)
+    );
+}
@@ -0,0 +1,186 @@
+//! 手续费分账集成测试
+//! 开启归集账户后，平台费与提前退出费转入资产池的归集账户，任何人可调用 distribute_fees
+//! 按资产池级（优先）或系统级分账配置转入各收款方 ATA。
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use pencil_solana::errors::PencilError;
+use pencil_solana::instructions::calculate_fee_split;
+use pencil_solana::state::AssetPool;
+use pencil_solana::waterfall;
+use pencil_solana::{FeesDistributed, RepaymentDistributed, SeniorExitFilled};
+use solana_sdk::signature::{Keypair, Signer};
+
+/// 开启手续费归集并募资达标（Senior 800 / Junior 200），返回资产池、Senior 投资人与还款人
+fn funded_pool(env: &mut TestEnv) -> (Pool, Keypair, Keypair) {
+    SystemConfigBuilder::default().build(env);
+    let mut pool = PoolBuilder::default().open(env);
+    pool.initialize_fee_collector(env).unwrap();
+    let senior = env.new_user(1_000 * ONE_TOKEN);
+    let junior = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &senior, true, 800 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &junior, false, 200 * ONE_TOKEN)
+        .unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+    pool.distribute_senior_token(env, &senior.pubkey()).unwrap();
+
+    (pool, senior, env.new_user(10_000 * ONE_TOKEN))
+}
+
+fn per_period_total(state: &AssetPool) -> u64 {
+    waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap()
+}
+
+/// 创建持有资产代币 ATA 的收款方
+fn recipient(env: &mut TestEnv) -> Pubkey {
+    env.new_user(0).pubkey()
+}
+
+#[test]
+fn test_fees_route_through_collector_to_pool_split() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, senior, borrower) = funded_pool(&mut env);
+    let treasury_ata = env.asset_ata(&pool.treasury);
+    assert_eq!(pool.state(&mut env).treasury_ata, pool.fee_collector());
+
+    // 资产池级配置覆盖系统级配置
+    let protocol = recipient(&mut env);
+    let originator = recipient(&mut env);
+    let partner = recipient(&mut env);
+    set_system_fee_split(&mut env, &[(protocol, 10_000)]).unwrap();
+    pool.set_pool_fee_split(&mut env, &[(originator, 6000), (partner, 4000)])
+        .unwrap();
+
+    // 早退请求在还款后撮合，平台费与早退费都进入归集账户
+    pool.request_senior_exit(&mut env, &senior, 100 * ONE_TOKEN)
+        .unwrap();
+    let amount = per_period_total(&pool.state(&mut env));
+    env.warp_to(pool.period_due_time(1));
+    let treasury_before = env.token_balance(&treasury_ata);
+
+    // 仍指向旧金库的手续费账户被拒绝
+    let mut ix = pool.repay_instruction(&env, &borrower, amount, 1);
+    ix.accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == pool.treasury_ata)
+        .unwrap()
+        .pubkey = treasury_ata;
+    let error = env.process(&[ix], &[&borrower]).unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::InvalidAccount));
+
+    pool.repay(&mut env, &borrower, amount, 1).unwrap();
+    let [distributed] = env
+        .events::<RepaymentDistributed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    pool.process_senior_exits(&mut env, &[(0, senior.pubkey())])
+        .unwrap();
+    let [filled] = env.events::<SeniorExitFilled>().try_into().ok().unwrap();
+    assert!(filled.exit_fee > 0);
+
+    let collected = distributed.platform_fee + filled.exit_fee;
+    assert_eq!(env.token_balance(&pool.fee_collector()), collected);
+    assert_eq!(env.token_balance(&treasury_ata), treasury_before);
+
+    // 收款方顺序与配置不一致时拒绝分账
+    let error = pool
+        .distribute_fees(&mut env, &[partner, originator])
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidFeeRecipient)
+    );
+    let error = pool.distribute_fees(&mut env, &[originator]).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidFeeRecipient)
+    );
+
+    pool.distribute_fees(&mut env, &[originator, partner])
+        .unwrap();
+    let amounts = calculate_fee_split(collected, &[6000, 4000]).unwrap();
+    let [event] = env.events::<FeesDistributed>().try_into().ok().unwrap();
+    assert_eq!(event.fee_split, pool.fee_split());
+    assert_eq!(event.amount, collected);
+    assert_eq!(event.amounts, amounts);
+    assert_eq!(env.token_balance(&env.asset_ata(&originator)), amounts[0]);
+    assert_eq!(env.token_balance(&env.asset_ata(&partner)), amounts[1]);
+    assert_eq!(env.token_balance(&env.asset_ata(&protocol)), 0);
+    assert_eq!(env.token_balance(&pool.fee_collector()), 0);
+    pool.check_invariants(&mut env).unwrap();
+}
+
+#[test]
+fn test_system_split_applies_without_pool_split() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, _, borrower) = funded_pool(&mut env);
+    let protocol = recipient(&mut env);
+    let partner = recipient(&mut env);
+
+    // 比例合计须为 10000，且收款方不能重复
+    for recipients in [
+        vec![(protocol, 7000), (partner, 2000)],
+        vec![(protocol, 5000), (protocol, 5000)],
+        vec![],
+    ] {
+        let error = set_system_fee_split(&mut env, &recipients).unwrap_err();
+        assert_eq!(
+            error_code(error),
+            pencil_error(PencilError::InvalidFeeSplit)
+        );
+    }
+
+    let amount = per_period_total(&pool.state(&mut env));
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, amount, 1).unwrap();
+    let collected = env.token_balance(&pool.fee_collector());
+    assert!(collected > 0);
+
+    // 尚未配置任何分账时不能分发
+    let error = pool.distribute_fees(&mut env, &[protocol]).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidFeeSplit)
+    );
+
+    set_system_fee_split(&mut env, &[(protocol, 7500), (partner, 2500)]).unwrap();
+    pool.distribute_fees(&mut env, &[protocol, partner])
+        .unwrap();
+    let amounts = calculate_fee_split(collected, &[7500, 2500]).unwrap();
+    assert_eq!(env.token_balance(&env.asset_ata(&protocol)), amounts[0]);
+    assert_eq!(env.token_balance(&env.asset_ata(&partner)), amounts[1]);
+    assert_eq!(env.token_balance(&pool.fee_collector()), 0);
+}
+
+#[test]
+fn test_fee_collector_requires_active_pool() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let mut pool = PoolBuilder::default().open(&mut env);
+
+    // 募资失败并取消后不能再改写手续费账户
+    env.warp_to(pool.funding_end_time + 1);
+    pool.cancel(&mut env).unwrap();
+    let treasury_ata = pool.state(&mut env).treasury_ata;
+    let error = pool.initialize_fee_collector(&mut env).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidAssetPoolStatus)
+    );
+    assert_eq!(pool.state(&mut env).treasury_ata, treasury_ata);
+}
@@ -0,0 +1,126 @@
+//! 募资期调整集成测试
+//! close_funding_early 提前结束募资，extend_funding 延长募资期并给已认购投资者
+//! 免手续费撤资窗口。
+
+mod common;
+
+use common::*;
+use pencil_solana::constants::{asset_pool_status, MAX_FUNDING_PERIOD};
+use pencil_solana::errors::PencilError;
+use pencil_solana::{FundingClosedEarly, FundingCompleted, FundingExtended, SubscriptionWithdrawn};
+use solana_sdk::signature::Signer;
+
+#[test]
+fn test_close_funding_early() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().open(&mut env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(&mut env, &alice, true, 700 * ONE_TOKEN)
+        .unwrap();
+    pool.subscribe(&mut env, &carol, false, 200 * ONE_TOKEN)
+        .unwrap();
+
+    // 未达募资总额时只有 operation_admin 可以提前结束
+    let error = pool.close_funding_early(&mut env, &alice).unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::Unauthorized));
+
+    env.warp_to(pool.funding_start_time + 600);
+    let admin = env.admin.insecure_clone();
+    pool.close_funding_early(&mut env, &admin).unwrap();
+
+    let [closed] = env.events::<FundingClosedEarly>().try_into().ok().unwrap();
+    assert_eq!(closed.closed_by, admin.pubkey());
+    assert_eq!(closed.original_funding_end_time, pool.funding_end_time);
+    assert_eq!(closed.funding_end_time, pool.funding_start_time + 600);
+    assert_eq!(closed.subscribed_amount, 900 * ONE_TOKEN);
+    let [funded] = env.events::<FundingCompleted>().try_into().ok().unwrap();
+    assert_eq!(funded.total_amount, 900 * ONE_TOKEN);
+
+    let state = pool.state(&mut env);
+    assert_eq!(state.status, asset_pool_status::FUNDED);
+    assert_eq!(state.funding_end_time, pool.funding_start_time + 600);
+    assert_eq!(env.token_balance(&pool.senior_pool_vault), 700 * ONE_TOKEN);
+    assert_eq!(
+        env.token_balance(&pool.first_loss_pool_vault),
+        200 * ONE_TOKEN
+    );
+}
+
+#[test]
+fn test_anyone_closes_full_pool() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().open(&mut env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(&mut env, &alice, true, 800 * ONE_TOKEN)
+        .unwrap();
+    pool.subscribe(&mut env, &carol, false, 200 * ONE_TOKEN)
+        .unwrap();
+
+    pool.close_funding_early(&mut env, &alice).unwrap();
+    assert_eq!(pool.state(&mut env).status, asset_pool_status::FUNDED);
+}
+
+#[test]
+fn test_extend_funding_opens_withdraw_window() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().open(&mut env);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(&mut env, &carol, false, 200 * ONE_TOKEN)
+        .unwrap();
+
+    // 募资结束时间只能延后，且不超过最长募资期
+    let error = pool
+        .extend_funding(&mut env, pool.funding_end_time)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidTimeParameters)
+    );
+    let error = pool
+        .extend_funding(&mut env, pool.funding_start_time + MAX_FUNDING_PERIOD + 1)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidTimeParameters)
+    );
+
+    let new_end = pool.funding_end_time + 7_200;
+    pool.extend_funding(&mut env, new_end).unwrap();
+    let [extended] = env.events::<FundingExtended>().try_into().ok().unwrap();
+    assert_eq!(extended.old_funding_end_time, pool.funding_end_time);
+    assert_eq!(extended.new_funding_end_time, new_end);
+    // 窗口不超过新的募资结束时间
+    assert_eq!(extended.withdraw_deadline, new_end);
+    assert_eq!(pool.state(&mut env).funding_end_time, new_end);
+
+    // 窗口内不能提前结束募资，投资者可免手续费撤资
+    let admin = env.admin.insecure_clone();
+    let error = pool.close_funding_early(&mut env, &admin).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::WithdrawWindowOpen)
+    );
+    pool.withdraw_subscription(&mut env, &carol, false, 50 * ONE_TOKEN)
+        .unwrap();
+    let [withdrawn] = env
+        .events::<SubscriptionWithdrawn>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(withdrawn.fee, 0);
+
+    env.warp_to(new_end + 1);
+    let error = pool.extend_funding(&mut env, new_end + 3_600).unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::FundingEnded));
+}
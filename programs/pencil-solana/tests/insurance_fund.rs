@@ -0,0 +1,183 @@
+//! 保险基金集成测试
+//! 同一资产代币的所有资产池按比例从平台费中向保险基金计提；资产池被记为违约后，
+//! 运营管理员提案、超级管理员执行，从保险基金补足 Senior 缺口，单池累计不超过上限。
+
+mod common;
+
+use common::*;
+use pencil_solana::errors::PencilError;
+use pencil_solana::state::{InsuranceDraw, InsuranceFund, SeniorPool};
+use pencil_solana::waterfall;
+use pencil_solana::{InsuranceDrawExecuted, InsuranceFeeCollected, RepaymentDistributed};
+use solana_sdk::signature::Keypair;
+
+const FEE_SHARE: u16 = 4000;
+const MAX_DRAW_PER_POOL: u64 = 20 * ONE_TOKEN;
+
+/// 募资达标（Senior 800 / Junior 200）
+fn fund(env: &mut TestEnv, pool: &Pool) {
+    let senior = env.new_user(1_000 * ONE_TOKEN);
+    let junior = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &senior, true, 800 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &junior, false, 200 * ONE_TOKEN)
+        .unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+}
+
+/// 创建保险基金及两个同币种资产池，返回资产池与还款人
+fn funded_pools(env: &mut TestEnv) -> (Pool, Pool, Keypair) {
+    SystemConfigBuilder::default().build(env);
+    let pool_a = PoolBuilder::default().name("Pool A").open(env);
+    let pool_b = PoolBuilder::default().name("Pool B").open(env);
+    pool_a
+        .initialize_insurance_fund(env, FEE_SHARE, MAX_DRAW_PER_POOL)
+        .unwrap();
+    fund(env, &pool_a);
+    fund(env, &pool_b);
+    (pool_a, pool_b, env.new_user(10_000 * ONE_TOKEN))
+}
+
+fn per_period_total(env: &mut TestEnv, pool: &Pool) -> u64 {
+    let state = pool.state(env);
+    waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_insurance_fund_collects_platform_fees_across_pools() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool_a, pool_b, borrower) = funded_pools(&mut env);
+    assert_eq!(pool_a.insurance_fund(), pool_b.insurance_fund());
+
+    let mut collected = 0;
+    for pool in [&pool_a, &pool_b] {
+        let amount = per_period_total(&mut env, pool);
+        env.warp_to(pool.period_due_time(1));
+        let treasury_before = env.token_balance(&pool.treasury_ata);
+        pool.repay(&mut env, &borrower, amount, 1).unwrap();
+
+        let [distributed] = env
+            .events::<RepaymentDistributed>()
+            .try_into()
+            .ok()
+            .unwrap();
+        let insurance_fee =
+            waterfall::calculate_insurance_fee(distributed.platform_fee, FEE_SHARE).unwrap();
+        let [fee_collected] = env
+            .events::<InsuranceFeeCollected>()
+            .try_into()
+            .ok()
+            .unwrap();
+        assert_eq!(fee_collected.asset_pool, pool.asset_pool);
+        assert_eq!(fee_collected.amount, insurance_fee);
+        assert_eq!(
+            env.token_balance(&pool.treasury_ata) - treasury_before,
+            distributed.platform_fee - insurance_fee
+        );
+        collected += insurance_fee;
+        pool.check_invariants(&mut env).unwrap();
+    }
+
+    let insurance_fund: InsuranceFund = env.account(&pool_a.insurance_fund());
+    assert_eq!(insurance_fund.total_collected, collected);
+    assert_eq!(env.token_balance(&pool_a.insurance_fund_vault()), collected);
+}
+
+#[test]
+fn test_insurance_draw_covers_defaulted_pool_senior_shortfall() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool_a, pool_b, borrower) = funded_pools(&mut env);
+
+    // Pool A 按期还清，Pool B 只还第一期后违约
+    let amount = per_period_total(&mut env, &pool_a);
+    for period in 1..=3 {
+        env.warp_to(pool_a.period_due_time(period));
+        pool_a.repay(&mut env, &borrower, amount, period).unwrap();
+    }
+    pool_b.repay(&mut env, &borrower, amount, 1).unwrap();
+    let fund_balance = env.token_balance(&pool_b.insurance_fund_vault());
+    assert!(fund_balance > MAX_DRAW_PER_POOL);
+
+    // 未记为违约的资产池不能提案
+    let error = pool_b
+        .propose_insurance_draw(&mut env, ONE_TOKEN)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidAssetPoolStatus)
+    );
+    pool_b.record_originator_default(&mut env).unwrap();
+
+    // 超过单池上限的提案被拒绝
+    let error = pool_b
+        .propose_insurance_draw(&mut env, MAX_DRAW_PER_POOL + 1)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InsuranceDrawExceeded)
+    );
+
+    // 同一时间只能有一个待执行提案，撤销后可重新提案
+    pool_b.propose_insurance_draw(&mut env, ONE_TOKEN).unwrap();
+    let error = pool_b
+        .propose_insurance_draw(&mut env, ONE_TOKEN)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidInsuranceDraw)
+    );
+    pool_b.cancel_insurance_draw(&mut env).unwrap();
+
+    let draw = 15 * ONE_TOKEN;
+    pool_b.propose_insurance_draw(&mut env, draw).unwrap();
+    let senior_pool_before: SeniorPool = env.account(&pool_b.senior_pool);
+    let senior_vault_before = env.token_balance(&pool_b.senior_pool_vault);
+    pool_b.execute_insurance_draw(&mut env).unwrap();
+
+    let [executed] = env
+        .events::<InsuranceDrawExecuted>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(executed.amount, draw);
+    assert_eq!(executed.pool_drawn, draw);
+    assert_eq!(executed.fund_balance, fund_balance - draw);
+    assert_eq!(
+        env.token_balance(&pool_b.senior_pool_vault) - senior_vault_before,
+        draw
+    );
+    let senior_pool: SeniorPool = env.account(&pool_b.senior_pool);
+    assert_eq!(
+        senior_pool.repaid_amount - senior_pool_before.repaid_amount,
+        draw
+    );
+    let insurance_draw: InsuranceDraw = env.account(&pool_b.insurance_draw());
+    assert_eq!(
+        (insurance_draw.pending_amount, insurance_draw.drawn_amount),
+        (0, draw)
+    );
+    let insurance_fund: InsuranceFund = env.account(&pool_b.insurance_fund());
+    assert_eq!(insurance_fund.total_drawn, draw);
+    pool_b.check_invariants(&mut env).unwrap();
+
+    // 剩余额度 5 个代币
+    let error = pool_b
+        .propose_insurance_draw(&mut env, MAX_DRAW_PER_POOL - draw + 1)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InsuranceDrawExceeded)
+    );
+    pool_b
+        .propose_insurance_draw(&mut env, MAX_DRAW_PER_POOL - draw)
+        .unwrap();
+}
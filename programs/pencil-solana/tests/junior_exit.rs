@@ -0,0 +1,164 @@
+//! Junior 早退集成测试
+//! 从首损池退出时按首损池剩余资金折算本金并扣除早退费用，退出后 Junior 占比不得低于
+//! min_junior_ratio；由接盘方承接时资金来自接盘方，首损池不变，不受占比限制。
+
+mod common;
+
+use common::*;
+use pencil_solana::errors::PencilError;
+use pencil_solana::instructions::issued_nft_id;
+use pencil_solana::state::{FirstLossPool, JuniorInterestPool, JuniorNFTMetadata};
+use pencil_solana::waterfall;
+use pencil_solana::JuniorEarlyExitProcessed;
+use solana_sdk::signature::{Keypair, Signer};
+
+const ALICE_JUNIOR: u64 = 300 * ONE_TOKEN;
+const BOB_JUNIOR: u64 = 100 * ONE_TOKEN;
+
+/// 募资达标并分发 Junior NFT (Alice: 1, Bob: 2)，返回资产池、Alice、Bob 与每期应还总额
+fn funded_pool(env: &mut TestEnv) -> (Pool, Keypair, Keypair, u64) {
+    SystemConfigBuilder::default().build(env);
+    let pool = PoolBuilder::default().open(env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let bob = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &carol, true, 600 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &alice, false, ALICE_JUNIOR).unwrap();
+    pool.subscribe(env, &bob, false, BOB_JUNIOR).unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+    pool.distribute_junior_nft(env, &alice.pubkey(), 1).unwrap();
+    pool.distribute_junior_nft(env, &bob.pubkey(), 2).unwrap();
+
+    let state = pool.state(env);
+    let per_period_total = waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap();
+    (pool, alice, bob, per_period_total)
+}
+
+#[test]
+fn test_exit_from_first_loss_keeps_junior_ratio() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, bob, per_period_total) = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, per_period_total, 1)
+        .unwrap();
+    let interest_pool: JuniorInterestPool = env.account(&pool.junior_interest_pool);
+    let bob_interest = waterfall::calculate_junior_claimable(
+        interest_pool.total_interest,
+        BOB_JUNIOR,
+        ALICE_JUNIOR + BOB_JUNIOR,
+        0,
+    )
+    .unwrap();
+    let alice_interest = waterfall::calculate_junior_claimable(
+        interest_pool.total_interest,
+        ALICE_JUNIOR,
+        ALICE_JUNIOR + BOB_JUNIOR,
+        0,
+    )
+    .unwrap();
+
+    // Bob 退出后占比 300 / 900 仍高于 20%：结清利息并按本金扣费退款
+    let bob_before = env.token_balance(&env.asset_ata(&bob.pubkey()));
+    let treasury_before = env.token_balance(&pool.treasury_ata);
+    let state = pool.state(&mut env);
+    let fee = BOB_JUNIOR * state.junior_early_before_exit_fee as u64 / 10_000;
+    pool.early_exit_junior(&mut env, &bob, 2, None).unwrap();
+    let [exited] = env
+        .events::<JuniorEarlyExitProcessed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(exited.interest_claimed, bob_interest);
+    assert_eq!(exited.amount, BOB_JUNIOR);
+    assert_eq!(exited.fee, fee);
+    assert_eq!(exited.buyer, None);
+    assert_eq!(
+        env.token_balance(&env.asset_ata(&bob.pubkey())) - bob_before,
+        bob_interest + BOB_JUNIOR - fee
+    );
+    assert_eq!(env.token_balance(&pool.treasury_ata) - treasury_before, fee);
+    assert!(!env.account_exists(&pool.junior_nft_metadata(2)));
+    let first_loss_pool: FirstLossPool = env.account(&pool.first_loss_pool);
+    assert_eq!(first_loss_pool.total_deposits, ALICE_JUNIOR);
+    pool.check_invariants(&mut env).unwrap();
+
+    // Alice 退出后 Junior 占比为 0，只能等待接盘方
+    let error = pool
+        .early_exit_junior(&mut env, &alice, 1, None)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidJuniorRatio)
+    );
+
+    // Alice 的可领取利息不受 Bob 退出影响
+    let alice_before = env.token_balance(&env.asset_ata(&alice.pubkey()));
+    pool.claim_junior_interest(&mut env, &alice, 1).unwrap();
+    assert!(
+        (env.token_balance(&env.asset_ata(&alice.pubkey())) - alice_before)
+            .abs_diff(alice_interest)
+            <= 1
+    );
+    pool.check_invariants(&mut env).unwrap();
+}
+
+#[test]
+fn test_replacement_buyer_takes_over_position() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, _bob, _) = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    let dave = env.new_user(1_000 * ONE_TOKEN);
+
+    // 接盘方付款，不受 Junior 占比限制，首损池资金不变
+    let first_loss_before = env.token_balance(&pool.first_loss_pool_vault);
+    let alice_before = env.token_balance(&env.asset_ata(&alice.pubkey()));
+    let fee = ALICE_JUNIOR * pool.state(&mut env).junior_early_before_exit_fee as u64 / 10_000;
+    pool.early_exit_junior(&mut env, &alice, 1, Some(&dave))
+        .unwrap();
+    let [exited] = env
+        .events::<JuniorEarlyExitProcessed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(exited.buyer, Some(dave.pubkey()));
+    let new_nft_id = issued_nft_id(0);
+    assert_eq!(exited.replacement_nft_id, Some(new_nft_id));
+    assert_eq!(
+        env.token_balance(&env.asset_ata(&alice.pubkey())) - alice_before,
+        ALICE_JUNIOR - fee
+    );
+    assert_eq!(
+        env.token_balance(&env.asset_ata(&dave.pubkey())),
+        1_000 * ONE_TOKEN - ALICE_JUNIOR
+    );
+    assert_eq!(
+        env.token_balance(&pool.first_loss_pool_vault),
+        first_loss_before
+    );
+    assert!(!env.account_exists(&pool.junior_nft_metadata(1)));
+    let nft: JuniorNFTMetadata = env.account(&pool.junior_nft_metadata(new_nft_id));
+    assert_eq!(nft.owner, dave.pubkey());
+    assert_eq!(nft.principal, ALICE_JUNIOR);
+    pool.check_invariants(&mut env).unwrap();
+
+    // 接盘方持有到期并提取本金
+    pool.prepay_remaining(&mut env, &borrower, u64::MAX)
+        .unwrap();
+    pool.claim_junior_interest(&mut env, &dave, new_nft_id)
+        .unwrap();
+    pool.withdraw_principal(&mut env, &dave, new_nft_id)
+        .unwrap();
+    pool.check_invariants(&mut env).unwrap();
+}
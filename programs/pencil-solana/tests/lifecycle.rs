@@ -0,0 +1,388 @@
+//! 资产池完整生命周期集成测试
+//! create → approve → fund → distribute → repay × N → claim → withdraw，
+//! 以及募资失败退款等异常路径。
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use pencil_solana::constants::{asset_pool_status, subscription_status};
+use pencil_solana::errors::PencilError;
+use pencil_solana::state::*;
+use pencil_solana::waterfall;
+use solana_sdk::signature::{Keypair, Signer};
+
+struct Investors {
+    alice: Keypair,
+    bob: Keypair,
+    carol: Keypair,
+    dave: Keypair,
+}
+
+const ALICE_SENIOR: u64 = 600 * ONE_TOKEN;
+const BOB_SENIOR: u64 = 200 * ONE_TOKEN;
+const CAROL_JUNIOR: u64 = 150 * ONE_TOKEN;
+const DAVE_JUNIOR: u64 = 50 * ONE_TOKEN;
+const INITIAL_BALANCE: u64 = 1_000 * ONE_TOKEN;
+
+/// 募资达标并完成 GROW / Junior NFT 分发（Junior 占比 20%）
+fn funded_pool(env: &mut TestEnv) -> (Pool, Investors) {
+    SystemConfigBuilder::default().build(env);
+    let pool = PoolBuilder::default().open(env);
+
+    let investors = Investors {
+        alice: env.new_user(INITIAL_BALANCE),
+        bob: env.new_user(INITIAL_BALANCE),
+        carol: env.new_user(INITIAL_BALANCE),
+        dave: env.new_user(INITIAL_BALANCE),
+    };
+    pool.subscribe(env, &investors.alice, true, ALICE_SENIOR)
+        .unwrap();
+    pool.subscribe(env, &investors.bob, true, BOB_SENIOR)
+        .unwrap();
+    pool.subscribe(env, &investors.carol, false, CAROL_JUNIOR)
+        .unwrap();
+    pool.subscribe(env, &investors.dave, false, DAVE_JUNIOR)
+        .unwrap();
+
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+
+    pool.distribute_senior_token(env, &investors.alice.pubkey())
+        .unwrap();
+    pool.distribute_senior_token(env, &investors.bob.pubkey())
+        .unwrap();
+    pool.distribute_junior_nft(env, &investors.carol.pubkey(), 1)
+        .unwrap();
+    pool.distribute_junior_nft(env, &investors.dave.pubkey(), 2)
+        .unwrap();
+
+    (pool, investors)
+}
+
+fn per_period_distribution(state: &AssetPool) -> waterfall::RepaymentDistribution {
+    let per_period_total = waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap();
+    let senior_due = waterfall::calculate_senior_amount(
+        state.senior_amount,
+        state.repayment_count,
+        state.senior_fixed_rate,
+    )
+    .unwrap();
+    waterfall::distribute_repayment(
+        per_period_total,
+        per_period_total,
+        state.platform_fee,
+        senior_due,
+        0,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_full_lifecycle() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, investors) = funded_pool(&mut env);
+
+    // 募资完成：资金从募资 Vault 划入各子池 Vault
+    let state = pool.state(&mut env);
+    assert_eq!(state.status, asset_pool_status::FUNDED);
+    assert_eq!(state.total_amount, 1_000 * ONE_TOKEN);
+    assert_eq!(env.token_balance(&pool.asset_pool_vault.pubkey()), 0);
+    assert_eq!(
+        env.token_balance(&pool.senior_pool_vault),
+        ALICE_SENIOR + BOB_SENIOR
+    );
+    assert_eq!(
+        env.token_balance(&pool.first_loss_pool_vault),
+        CAROL_JUNIOR + DAVE_JUNIOR
+    );
+
+    // 分发：GROW 按认购金额铸造，Junior NFT 记录本金
+    let alice_grow = get_associated_token_address(&investors.alice.pubkey(), &pool.grow_token_mint);
+    assert_eq!(env.token_balance(&alice_grow), ALICE_SENIOR);
+    let carol_nft: JuniorNFTMetadata = env.account(&pool.junior_nft_metadata(1));
+    assert_eq!(carol_nft.owner, investors.carol.pubkey());
+    assert_eq!(carol_nft.principal, CAROL_JUNIOR);
+    let subscription: Subscription =
+        env.account(&pool.subscription(&investors.alice.pubkey(), true));
+    assert_eq!(subscription.status, subscription_status::CONFIRMED);
+
+    // 按期还款
+    let distribution = per_period_distribution(&state);
+    let per_period_total = distribution.platform_fee
+        + distribution.senior_from_repayment
+        + distribution.junior_interest;
+    let borrower = env.new_user(2_000 * ONE_TOKEN);
+    for period in 1..=state.repayment_count {
+        env.warp_to(pool.period_due_time(period));
+        pool.repay(&mut env, &borrower, per_period_total, period)
+            .unwrap();
+
+        let record: RepaymentRecord = env.account(&pool.repayment_record(period));
+        assert_eq!(record.period, period);
+        assert_eq!(record.amount, per_period_total);
+    }
+
+    let state = pool.state(&mut env);
+    assert_eq!(state.status, asset_pool_status::COMPLETED);
+    assert_eq!(
+        state.repaid_amount,
+        per_period_total * state.repayment_count
+    );
+    assert_eq!(
+        env.token_balance(&pool.treasury_ata),
+        distribution.platform_fee * state.repayment_count
+    );
+    assert_eq!(
+        env.token_balance(&pool.junior_interest_pool_vault),
+        distribution.junior_interest * state.repayment_count
+    );
+    assert_eq!(
+        env.token_balance(&pool.senior_pool_vault),
+        ALICE_SENIOR + BOB_SENIOR + distribution.senior_from_repayment * state.repayment_count
+    );
+    pool.check_invariants(&mut env).unwrap();
+
+    // Junior 按本金比例领取利息
+    let total_interest = distribution.junior_interest * state.repayment_count;
+    let junior_total = CAROL_JUNIOR + DAVE_JUNIOR;
+    let carol_ata = env.asset_ata(&investors.carol.pubkey());
+    let before = env.token_balance(&carol_ata);
+    pool.claim_junior_interest(&mut env, &investors.carol, 1)
+        .unwrap();
+    let carol_interest =
+        waterfall::calculate_junior_claimable(total_interest, CAROL_JUNIOR, junior_total, 0)
+            .unwrap();
+    assert_eq!(env.token_balance(&carol_ata) - before, carol_interest);
+
+    // 没有新利息时不能重复领取
+    let error = pool
+        .claim_junior_interest(&mut env, &investors.carol, 1)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::NoInterestToClaim)
+    );
+
+    // Junior 提取本金（首损池未被动用，全额返还）
+    let before = env.token_balance(&carol_ata);
+    pool.withdraw_principal(&mut env, &investors.carol, 1)
+        .unwrap();
+    assert_eq!(env.token_balance(&carol_ata) - before, CAROL_JUNIOR);
+    let carol_nft: JuniorNFTMetadata = env.account(&pool.junior_nft_metadata(1));
+    assert!(carol_nft.principal_withdrawn);
+
+    let dave_ata = env.asset_ata(&investors.dave.pubkey());
+    let before = env.token_balance(&dave_ata);
+    pool.claim_junior_interest(&mut env, &investors.dave, 2)
+        .unwrap();
+    pool.withdraw_principal(&mut env, &investors.dave, 2)
+        .unwrap();
+    let dave_interest =
+        waterfall::calculate_junior_claimable(total_interest, DAVE_JUNIOR, junior_total, 0)
+            .unwrap();
+    assert_eq!(
+        env.token_balance(&dave_ata) - before,
+        dave_interest + DAVE_JUNIOR
+    );
+
+    // Senior 在池完成后按 GROW 比例提取优先池 Vault
+    let senior_vault = env.token_balance(&pool.senior_pool_vault);
+    let alice_ata = env.asset_ata(&investors.alice.pubkey());
+    let before = env.token_balance(&alice_ata);
+    pool.early_exit_senior(&mut env, &investors.alice, ALICE_SENIOR)
+        .unwrap();
+    let expected =
+        (senior_vault as u128 * ALICE_SENIOR as u128 / (ALICE_SENIOR + BOB_SENIOR) as u128) as u64;
+    assert_eq!(env.token_balance(&alice_ata) - before, expected);
+    assert!(expected > ALICE_SENIOR);
+    assert_eq!(env.token_balance(&alice_grow), 0);
+}
+
+#[test]
+fn test_repay_guards() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, _investors) = funded_pool(&mut env);
+    let state = pool.state(&mut env);
+    let distribution = per_period_distribution(&state);
+    let per_period_total = distribution.platform_fee
+        + distribution.senior_from_repayment
+        + distribution.junior_interest;
+    let borrower = env.new_user(2_000 * ONE_TOKEN);
+
+    // 第 2 期尚未到期
+    let error = pool
+        .repay(&mut env, &borrower, per_period_total, 2)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::RepaymentNotDue)
+    );
+
+    // 还款金额不足每期应还
+    let error = pool
+        .repay(&mut env, &borrower, per_period_total - 1, 1)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidRepaymentAmount)
+    );
+
+    // 同一期不能重复还款
+    pool.repay(&mut env, &borrower, per_period_total, 1)
+        .unwrap();
+    assert!(pool
+        .repay(&mut env, &borrower, per_period_total, 1)
+        .is_err());
+
+    // 超出还款期数
+    env.warp_to(pool.period_due_time(state.repayment_count + 1));
+    let error = pool
+        .repay(
+            &mut env,
+            &borrower,
+            per_period_total,
+            state.repayment_count + 1,
+        )
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidRepaymentPeriod)
+    );
+
+    // 池未完成前不能提取本金
+    let state = pool.state(&mut env);
+    assert_eq!(state.status, asset_pool_status::REPAYING);
+}
+
+#[test]
+fn test_subscription_window() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder {
+        funding_start_offset: 100,
+        ..PoolBuilder::default()
+    }
+    .open(&mut env);
+    let alice = env.new_user(INITIAL_BALANCE);
+
+    let error = pool
+        .subscribe(&mut env, &alice, true, ONE_TOKEN)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::FundingNotStarted)
+    );
+
+    env.warp_to(pool.funding_end_time + 1);
+    let error = pool
+        .subscribe(&mut env, &alice, true, ONE_TOKEN)
+        .unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::FundingEnded));
+
+    // 募资期结束前不能完成募资
+    env.warp_to(pool.funding_end_time);
+    let error = pool.complete_funding(&mut env).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::FundingNotCompleted)
+    );
+}
+
+#[test]
+fn test_failed_funding_refund_and_cancel() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().open(&mut env);
+
+    // 募资总额 350 < 最低 500
+    let alice = env.new_user(INITIAL_BALANCE);
+    let carol = env.new_user(INITIAL_BALANCE);
+    pool.subscribe(&mut env, &alice, true, 300 * ONE_TOKEN)
+        .unwrap();
+    pool.subscribe(&mut env, &carol, false, 50 * ONE_TOKEN)
+        .unwrap();
+
+    // 募资期内不能退款
+    let error = pool.process_refund(&mut env, &alice, true).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::FundingNotCompleted)
+    );
+
+    env.warp_to(pool.funding_end_time + 1);
+    let error = pool.complete_funding(&mut env).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::FundingMinimumNotMet)
+    );
+
+    // 还有认购资金未退时不能取消
+    let error = pool.cancel(&mut env).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InsufficientVaultBalance)
+    );
+
+    pool.process_refund(&mut env, &alice, true).unwrap();
+    pool.process_refund(&mut env, &carol, false).unwrap();
+    let alice_ata = env.asset_ata(&alice.pubkey());
+    let carol_ata = env.asset_ata(&carol.pubkey());
+    assert_eq!(env.token_balance(&alice_ata), INITIAL_BALANCE);
+    assert_eq!(env.token_balance(&carol_ata), INITIAL_BALANCE);
+
+    // 重复退款被拒绝
+    let error = pool.process_refund(&mut env, &alice, true).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::RefundAlreadyProcessed)
+    );
+
+    pool.cancel(&mut env).unwrap();
+    let state = pool.state(&mut env);
+    assert_eq!(state.status, asset_pool_status::CANCELLED);
+    assert_eq!(state.senior_amount, 0);
+    assert_eq!(state.junior_amount, 0);
+}
+
+#[test]
+fn test_junior_ratio_not_met() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().open(&mut env);
+
+    // Junior 占比 100 / 900 ≈ 11% < 20%
+    let alice = env.new_user(INITIAL_BALANCE);
+    let carol = env.new_user(INITIAL_BALANCE);
+    pool.subscribe(&mut env, &alice, true, 800 * ONE_TOKEN)
+        .unwrap();
+    pool.subscribe(&mut env, &carol, false, 100 * ONE_TOKEN)
+        .unwrap();
+
+    env.warp_to(pool.funding_end_time + 1);
+    let error = pool.complete_funding(&mut env).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidJuniorRatio)
+    );
+
+    // 占比不足同样视为募资失败，可以退款
+    pool.process_refund(&mut env, &carol, false).unwrap();
+    let carol_ata = env.asset_ata(&carol.pubkey());
+    assert_eq!(env.token_balance(&carol_ata), INITIAL_BALANCE);
+}
@@ -0,0 +1,163 @@
+//! 二级市场集成测试
+//! 卖方将 GROW 或 Junior NFT 托管挂单，买方按单价整单或部分成交，
+//! 协议费转入金库，Junior NFT 成交后元数据所有者改为买方。
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use pencil_solana::constants::listing_kind;
+use pencil_solana::errors::PencilError;
+use pencil_solana::instructions::calculate_listing_settlement;
+use pencil_solana::state::{JuniorNFTMetadata, Listing};
+use pencil_solana::{ListingCancelled, ListingFilled};
+use solana_sdk::signature::{Keypair, Signer};
+
+const MARKETPLACE_FEE: u16 = 100;
+
+/// 募资达标并分发 GROW 与 Junior NFT 1，返回资产池、Alice (Senior)、Carol (Junior)
+fn funded_pool(env: &mut TestEnv) -> (Pool, Keypair, Keypair) {
+    SystemConfigBuilder::default()
+        .marketplace_fee_rate(MARKETPLACE_FEE)
+        .build(env);
+    let pool = PoolBuilder::default().open(env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &alice, true, 800 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &carol, false, 200 * ONE_TOKEN).unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+    pool.distribute_senior_token(env, &alice.pubkey()).unwrap();
+    pool.distribute_junior_nft(env, &carol.pubkey(), 1).unwrap();
+    (pool, alice, carol)
+}
+
+#[test]
+fn test_grow_listing_partial_fills() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, _carol) = funded_pool(&mut env);
+    let bob = env.new_user(1_000 * ONE_TOKEN);
+    let alice_grow = get_associated_token_address(&alice.pubkey(), &pool.grow_token_mint);
+    let bob_grow = get_associated_token_address(&bob.pubkey(), &pool.grow_token_mint);
+    let unit_price = 1_020_000;
+
+    pool.create_grow_listing(&mut env, &alice, 0, 300 * ONE_TOKEN, unit_price)
+        .unwrap();
+    assert_eq!(env.token_balance(&alice_grow), 500 * ONE_TOKEN);
+    assert_eq!(
+        env.token_balance(&pool.listing_escrow(&alice.pubkey(), 0)),
+        300 * ONE_TOKEN
+    );
+
+    // 卖方不能成交自己的挂单
+    let error = pool
+        .fill_listing(&mut env, &alice, &alice.pubkey(), 0, None, ONE_TOKEN)
+        .unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::InvalidListing));
+
+    // Bob 先成交 100 个 GROW
+    let alice_before = env.token_balance(&env.asset_ata(&alice.pubkey()));
+    let treasury_before = env.token_balance(&pool.treasury_ata);
+    let expected =
+        calculate_listing_settlement(100 * ONE_TOKEN, unit_price, ONE_TOKEN, MARKETPLACE_FEE)
+            .unwrap();
+    pool.fill_listing(&mut env, &bob, &alice.pubkey(), 0, None, 100 * ONE_TOKEN)
+        .unwrap();
+    let [filled] = env.events::<ListingFilled>().try_into().ok().unwrap();
+    assert_eq!(filled.kind, listing_kind::GROW);
+    assert_eq!(filled.cost, 102 * ONE_TOKEN);
+    assert_eq!(filled.fee, expected.fee);
+    assert_eq!(filled.remaining, 200 * ONE_TOKEN);
+    assert_eq!(env.token_balance(&bob_grow), 100 * ONE_TOKEN);
+    assert_eq!(
+        env.token_balance(&env.asset_ata(&bob.pubkey())),
+        1_000 * ONE_TOKEN - expected.cost
+    );
+    assert_eq!(
+        env.token_balance(&env.asset_ata(&alice.pubkey())) - alice_before,
+        expected.net().unwrap()
+    );
+    assert_eq!(
+        env.token_balance(&pool.treasury_ata) - treasury_before,
+        expected.fee
+    );
+    let listing: Listing = env.account(&pool.listing(&alice.pubkey(), 0));
+    assert_eq!(
+        (listing.amount, listing.filled_amount),
+        (200 * ONE_TOKEN, 100 * ONE_TOKEN)
+    );
+
+    // 超出剩余数量
+    let error = pool
+        .fill_listing(&mut env, &bob, &alice.pubkey(), 0, None, 250 * ONE_TOKEN)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::ListingAmountExceeded)
+    );
+
+    // 剩余部分全部成交后关闭挂单与托管账户
+    pool.fill_listing(&mut env, &bob, &alice.pubkey(), 0, None, 200 * ONE_TOKEN)
+        .unwrap();
+    assert_eq!(env.token_balance(&bob_grow), 300 * ONE_TOKEN);
+    assert!(!env.account_exists(&pool.listing(&alice.pubkey(), 0)));
+    assert!(!env.account_exists(&pool.listing_escrow(&alice.pubkey(), 0)));
+
+    // 撤单退还未成交的 GROW
+    pool.create_grow_listing(&mut env, &alice, 1, 100 * ONE_TOKEN, unit_price)
+        .unwrap();
+    pool.cancel_listing(&mut env, &alice, 1, None).unwrap();
+    let [cancelled] = env.events::<ListingCancelled>().try_into().ok().unwrap();
+    assert_eq!(cancelled.returned_amount, 100 * ONE_TOKEN);
+    assert_eq!(env.token_balance(&alice_grow), 500 * ONE_TOKEN);
+    assert!(!env.account_exists(&pool.listing(&alice.pubkey(), 1)));
+    pool.check_invariants(&mut env).unwrap();
+}
+
+#[test]
+fn test_junior_nft_listing_rebinds_owner() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, carol) = funded_pool(&mut env);
+    let bob = env.new_user(1_000 * ONE_TOKEN);
+    let price = 210 * ONE_TOKEN;
+
+    // 只有 NFT 所有者可以挂单
+    assert!(pool
+        .create_junior_nft_listing(&mut env, &alice, 0, 1, price)
+        .is_err());
+    pool.create_junior_nft_listing(&mut env, &carol, 0, 1, price)
+        .unwrap();
+
+    // 托管期间卖方不能领取利息或早退
+    assert!(pool.claim_junior_interest(&mut env, &carol, 1).is_err());
+
+    // NFT 只能整单成交
+    assert!(pool
+        .fill_listing(&mut env, &bob, &carol.pubkey(), 0, Some(1), 2)
+        .is_err());
+    pool.fill_listing(&mut env, &bob, &carol.pubkey(), 0, Some(1), 1)
+        .unwrap();
+    let [filled] = env.events::<ListingFilled>().try_into().ok().unwrap();
+    assert_eq!(filled.kind, listing_kind::JUNIOR_NFT);
+    assert_eq!(filled.cost, price);
+    assert_eq!(filled.fee, price * MARKETPLACE_FEE as u64 / 10_000);
+    assert_eq!(filled.remaining, 0);
+
+    let nft: JuniorNFTMetadata = env.account(&pool.junior_nft_metadata(1));
+    assert_eq!(nft.owner, bob.pubkey());
+    let bob_nft = get_associated_token_address(&bob.pubkey(), &pool.junior_nft_mint(1));
+    assert_eq!(env.token_balance(&bob_nft), 1);
+    assert!(!env.account_exists(&pool.listing(&carol.pubkey(), 0)));
+
+    // 买方持有到期并提取本金
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    pool.prepay_remaining(&mut env, &borrower, u64::MAX)
+        .unwrap();
+    pool.claim_junior_interest(&mut env, &bob, 1).unwrap();
+    pool.withdraw_principal(&mut env, &bob, 1).unwrap();
+    pool.check_invariants(&mut env).unwrap();
+}
@@ -0,0 +1,226 @@
+//! 发起方注册与敞口上限集成测试
+
+mod common;
+
+use common::*;
+use pencil_solana::constants::seeds;
+use pencil_solana::errors::PencilError;
+use pencil_solana::state::{AssetPool, Originator};
+use pencil_solana::waterfall;
+use pencil_solana::OriginatorExposureUpdated;
+use solana_sdk::account::Account;
+use solana_sdk::signature::Signer;
+
+fn originator(env: &TestEnv) -> Originator {
+    env.account(&pda(&[seeds::ORIGINATOR, env.admin.pubkey().as_ref()]))
+}
+
+fn per_period_total(state: &AssetPool) -> u64 {
+    waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap()
+}
+
+/// 募资达标（Senior 700 / Junior 200，共 3 期）的资产池
+fn funded_pool(env: &mut TestEnv) -> Pool {
+    let pool = PoolBuilder::default().open(env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &alice, true, 700 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &carol, false, 200 * ONE_TOKEN).unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+    pool
+}
+
+#[test]
+fn test_exposure_limit_across_pools() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default()
+        .originator_max_outstanding(1_500 * ONE_TOKEN)
+        .build(&mut env);
+
+    let first = PoolBuilder::default().name("first").create(&mut env);
+    assert_eq!(originator(&env).outstanding_principal, 1_000 * ONE_TOKEN);
+    assert_eq!(originator(&env).pool_count, 1);
+    assert_eq!(
+        first.state(&mut env).repayment_authority,
+        env.admin.pubkey()
+    );
+
+    // 两个资产池合计 2,000 超过 1,500 上限
+    let second = PoolBuilder::default().name("second");
+    let error = second.clone().try_create(&mut env).err().unwrap();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::OriginatorExposureExceeded)
+    );
+
+    // 撤回资产池释放敞口
+    let creator = env.admin.insecure_clone();
+    first.withdraw_pool(&mut env, &creator).unwrap();
+    assert_eq!(originator(&env).outstanding_principal, 0);
+    second.create(&mut env);
+    assert_eq!(originator(&env).outstanding_principal, 1_000 * ONE_TOKEN);
+}
+
+#[test]
+fn test_inactive_originator_cannot_create() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let admin = env.admin.insecure_clone();
+    let ix = instruction(
+        pencil_solana::accounts::UpdateOriginator {
+            operation_admin: admin.pubkey(),
+            system_config: pda(&[seeds::SYSTEM_CONFIG]),
+            originator: pda(&[seeds::ORIGINATOR, admin.pubkey().as_ref()]),
+        },
+        pencil_solana::instruction::UpdateOriginator {
+            max_outstanding: 0,
+            allowed_assets: vec![],
+            active: false,
+        },
+    );
+    env.process(&[ix], &[&admin]).unwrap();
+
+    let error = PoolBuilder::default().try_create(&mut env).err().unwrap();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::OriginatorNotActive)
+    );
+}
+
+#[test]
+fn test_sync_exposure_after_funding_and_record_default() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = funded_pool(&mut env);
+
+    // 募资完成时即以实际募资额计入
+    assert_eq!(originator(&env).outstanding_principal, 900 * ONE_TOKEN);
+    assert_eq!(pool.state(&mut env).originator_exposure, 900 * ONE_TOKEN);
+
+    // 无变化时同步同样发出事件
+    pool.sync_originator_exposure(&mut env).unwrap();
+    let [synced] = env
+        .events::<OriginatorExposureUpdated>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(synced.pool_exposure, 900 * ONE_TOKEN);
+    assert_eq!(synced.outstanding_principal, 900 * ONE_TOKEN);
+
+    pool.record_originator_default(&mut env).unwrap();
+    assert_eq!(originator(&env).defaulted_pools, 1);
+    let error = pool.record_originator_default(&mut env).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::OriginatorDefaultAlreadyRecorded)
+    );
+}
+
+#[test]
+fn test_failed_funding_releases_exposure() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().open(&mut env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(&mut env, &alice, true, 100 * ONE_TOKEN)
+        .unwrap();
+    assert_eq!(originator(&env).outstanding_principal, 1_000 * ONE_TOKEN);
+
+    // 募资失败后首笔退款即释放敞口
+    env.warp_to(pool.funding_end_time + 1);
+    pool.process_refund(&mut env, &alice, true).unwrap();
+    assert_eq!(originator(&env).outstanding_principal, 0);
+    assert_eq!(pool.state(&mut env).originator_exposure, 0);
+
+    // 取消时记录一次，之后同步不重复计数
+    pool.cancel(&mut env).unwrap();
+    assert_eq!(originator(&env).cancelled_pools, 1);
+    pool.sync_originator_exposure(&mut env).unwrap();
+    assert_eq!(originator(&env).cancelled_pools, 1);
+    let error = pool.cancel(&mut env).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidAssetPoolStatus)
+    );
+}
+
+#[test]
+fn test_repayments_release_exposure() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+
+    // 按期还款后以剩余本金计入
+    let amount = per_period_total(&pool.state(&mut env));
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, amount, 1).unwrap();
+    assert_eq!(pool.state(&mut env).originator_exposure, 600 * ONE_TOKEN);
+    assert_eq!(originator(&env).outstanding_principal, 600 * ONE_TOKEN);
+
+    // 提前还清后全部释放
+    pool.prepay_remaining(&mut env, &borrower, u64::MAX)
+        .unwrap();
+    assert_eq!(pool.state(&mut env).originator_exposure, 0);
+    assert_eq!(originator(&env).outstanding_principal, 0);
+    assert_eq!(originator(&env).completed_pools, 1);
+}
+
+#[test]
+fn test_pool_without_originator_account_can_repay() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+
+    // 发起方登记前创建的资产池，其创建者没有发起方账户
+    env.svm
+        .set_account(pool.originator(), Account::default())
+        .unwrap();
+    let amount = per_period_total(&pool.state(&mut env));
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, amount, 1).unwrap();
+    pool.prepay_remaining(&mut env, &borrower, u64::MAX)
+        .unwrap();
+    assert!(!env.account_exists(&pool.originator()));
+}
+
+#[test]
+fn test_pool_without_originator_account_can_be_amended_and_closed() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let amended = PoolBuilder::default().name("amended").create(&mut env);
+    let rejected = PoolBuilder::default().name("rejected").create(&mut env);
+    env.svm
+        .set_account(amended.originator(), Account::default())
+        .unwrap();
+
+    let mut terms = amended.terms(&mut env);
+    terms.total_amount = 2_000 * ONE_TOKEN;
+    amended.amend(&mut env, terms).unwrap();
+    rejected.reject(&mut env, 1).unwrap();
+    let creator = env.admin.insecure_clone();
+    amended.withdraw_pool(&mut env, &creator).unwrap();
+    assert!(!env.account_exists(&amended.asset_pool));
+    assert!(!env.account_exists(&amended.originator()));
+}
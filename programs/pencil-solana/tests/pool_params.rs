@@ -0,0 +1,176 @@
+//! 资产池参数继承集成测试
+//! 费率与最低 Junior 占比可继承 SystemConfig 默认值或由创建者覆盖；覆盖值须在系统
+//! 设置的范围内，继承值在募资结束前可通过 sync_pool_params 刷新。
+
+mod common;
+
+use common::*;
+use pencil_solana::constants::{pool_param, AMENDMENT_WITHDRAW_WINDOW};
+use pencil_solana::errors::PencilError;
+use pencil_solana::instructions::FeeType;
+use pencil_solana::state::PoolParamBounds;
+use pencil_solana::{PoolParamBoundsUpdated, PoolParamsSynced};
+
+#[test]
+fn test_inherited_params_follow_system_defaults() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder {
+        platform_fee: Some(400),
+        funding_duration: AMENDMENT_WITHDRAW_WINDOW + 3_600,
+        ..PoolBuilder::default().inherit_params()
+    }
+    .open(&mut env);
+
+    // 覆盖值保留，其余参数取系统默认值
+    let state = pool.state(&mut env);
+    assert_eq!(
+        (
+            state.platform_fee,
+            state.senior_early_before_exit_fee,
+            state.min_junior_ratio
+        ),
+        (400, 100, 1000)
+    );
+    assert_eq!(state.inherited_params, 0b11110);
+    let terms = pool.terms(&mut env);
+    assert_eq!(terms.platform_fee, Some(400));
+    assert_eq!(terms.senior_early_before_exit_fee, None);
+
+    let senior = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(&mut env, &senior, true, 500 * ONE_TOKEN)
+        .unwrap();
+
+    // 系统默认值变化后刷新继承参数，已有认购时开放免手续费撤资窗口
+    update_fee_rate(&mut env, FeeType::PlatformFee, 700).unwrap();
+    update_fee_rate(&mut env, FeeType::SeniorEarlyBeforeExitFee, 150).unwrap();
+    pool.sync_params(&mut env).unwrap();
+    let [synced] = env.events::<PoolParamsSynced>().try_into().ok().unwrap();
+    assert_eq!(synced.old_values, [400, 100, 200, 300, 1000]);
+    assert_eq!(synced.new_values, [400, 150, 200, 300, 1000]);
+    assert_eq!(
+        synced.withdraw_deadline,
+        env.now() + AMENDMENT_WITHDRAW_WINDOW
+    );
+
+    // 撤资手续费按资产池的生效费率而非系统当前费率计算
+    update_fee_rate(&mut env, FeeType::SeniorEarlyBeforeExitFee, 50).unwrap();
+    env.warp_to(synced.withdraw_deadline + 1);
+    let treasury_before = env.token_balance(&pool.treasury_ata);
+    pool.withdraw_subscription(&mut env, &senior, true, 100 * ONE_TOKEN)
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&pool.treasury_ata) - treasury_before,
+        ONE_TOKEN * 3 / 2
+    );
+
+    // 募资结束后参数固定
+    env.warp_to(pool.funding_end_time + 1);
+    let error = pool.sync_params(&mut env).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::AmendmentNotAllowed)
+    );
+}
+
+#[test]
+fn test_overrides_must_respect_system_bounds() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+
+    // 范围须在协议上限内且包含当前默认值 (500)
+    let error = set_pool_param_bounds(
+        &mut env,
+        pool_param::PLATFORM_FEE,
+        Some(PoolParamBounds { min: 0, max: 6000 }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidPoolParam)
+    );
+    let error = set_pool_param_bounds(
+        &mut env,
+        pool_param::PLATFORM_FEE,
+        Some(PoolParamBounds { min: 600, max: 800 }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::PoolParamOutOfBounds)
+    );
+    let bounds = PoolParamBounds { min: 300, max: 800 };
+    set_pool_param_bounds(&mut env, pool_param::PLATFORM_FEE, Some(bounds)).unwrap();
+    let [updated] = env
+        .events::<PoolParamBoundsUpdated>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(
+        (updated.old_bounds, updated.new_bounds),
+        (None, Some(bounds))
+    );
+
+    // 默认值同样受范围约束
+    let error = update_fee_rate(&mut env, FeeType::PlatformFee, 900).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::PoolParamOutOfBounds)
+    );
+
+    // 覆盖值先按协议上限校验，再按系统范围校验
+    let builder = |platform_fee| PoolBuilder {
+        platform_fee: Some(platform_fee),
+        ..PoolBuilder::default()
+    };
+    let error = builder(6000).try_create(&mut env).err().unwrap();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidPlatformFee)
+    );
+    let error = builder(900).try_create(&mut env).err().unwrap();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::PoolParamOutOfBounds)
+    );
+    let pool = builder(800).create(&mut env);
+    assert_eq!(pool.state(&mut env).platform_fee, 800);
+
+    // 修订为继承后取系统默认值
+    let mut terms = pool.terms(&mut env);
+    terms.platform_fee = Some(900);
+    let error = pool.amend(&mut env, terms.clone()).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::PoolParamOutOfBounds)
+    );
+    terms.platform_fee = None;
+    pool.amend(&mut env, terms).unwrap();
+    let state = pool.state(&mut env);
+    assert_eq!(state.platform_fee, 500);
+    assert_eq!(state.inherited_params, 1 << pool_param::PLATFORM_FEE);
+
+    // 市场手续费不是资产池参数，不受最低 Junior 占比范围约束
+    set_pool_param_bounds(
+        &mut env,
+        pool_param::MIN_JUNIOR_RATIO,
+        Some(PoolParamBounds {
+            min: 1000,
+            max: 2000,
+        }),
+    )
+    .unwrap();
+    update_fee_rate(&mut env, FeeType::MarketplaceFee, 50).unwrap();
+
+    // 清除范围后仅受协议上限约束
+    set_pool_param_bounds(&mut env, pool_param::PLATFORM_FEE, None).unwrap();
+    builder(900)
+        .name("Unbounded Pool")
+        .try_create(&mut env)
+        .ok()
+        .unwrap();
+}
@@ -0,0 +1,80 @@
+//! 资产池驳回与撤回集成测试
+//! 无认购且关联账户未初始化时管理员驳回或创建者撤回，关闭 AssetPool 并将租金退还创建者。
+
+mod common;
+
+use common::*;
+use pencil_solana::constants::asset_pool_status;
+use pencil_solana::errors::PencilError;
+use pencil_solana::{AssetPoolRejected, AssetPoolWithdrawn};
+use solana_sdk::signature::Signer;
+
+#[test]
+fn test_admin_rejects_pool_and_refunds_rent() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().create(&mut env);
+    let rent = env.lamports(&pool.asset_pool);
+    let creator_balance = env.lamports(&pool.creator);
+
+    pool.reject(&mut env, 3).unwrap();
+
+    let [rejected] = env.events::<AssetPoolRejected>().try_into().ok().unwrap();
+    assert_eq!(rejected.status, asset_pool_status::REJECTED);
+    assert_eq!(rejected.reason_code, 3);
+    assert_eq!(rejected.rent_refunded, rent);
+    assert!(!env.account_exists(&pool.asset_pool));
+    assert_eq!(env.lamports(&pool.creator), creator_balance + rent);
+}
+
+#[test]
+fn test_creator_withdraws_approved_pool() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().create(&mut env);
+    pool.approve(&mut env).unwrap();
+
+    // 只有创建者可以撤回
+    let mallory = env.new_user(0);
+    let error = pool.withdraw_pool(&mut env, &mallory).unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::Unauthorized));
+
+    let creator = env.admin.insecure_clone();
+    pool.withdraw_pool(&mut env, &creator).unwrap();
+    let [withdrawn] = env.events::<AssetPoolWithdrawn>().try_into().ok().unwrap();
+    assert_eq!(withdrawn.creator, creator.pubkey());
+    assert_eq!(withdrawn.status, asset_pool_status::WITHDRAWN);
+    assert!(!env.account_exists(&pool.asset_pool));
+}
+
+#[test]
+fn test_pool_with_related_accounts_cannot_be_closed() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+
+    // 关联账户已初始化后不可驳回或撤回，即使没有认购
+    let pool = PoolBuilder::default().open(&mut env);
+    let error = pool.reject(&mut env, 1).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::RelatedAccountsAlreadyInitialized)
+    );
+    let creator = env.admin.insecure_clone();
+    let error = pool.withdraw_pool(&mut env, &creator).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::RelatedAccountsAlreadyInitialized)
+    );
+    assert_eq!(pool.state(&mut env).status, asset_pool_status::APPROVED);
+
+    // 募资期结束后走取消流程
+    env.warp_to(pool.funding_end_time + 1);
+    pool.cancel(&mut env).unwrap();
+    assert_eq!(pool.state(&mut env).status, asset_pool_status::CANCELLED);
+}
@@ -0,0 +1,199 @@
+//! 储备金集成测试
+//! 启用储备金后，还款按比例截留平台费与 Junior 利息；Senior 缺口先由储备金补足再动用
+//! 首损池；资产池完成时先返还首损池，剩余按截留来源拆分至 Junior 利息池与金库。
+
+mod common;
+
+use common::*;
+use pencil_solana::constants::asset_pool_status;
+use pencil_solana::errors::PencilError;
+use pencil_solana::instructions::calculate_reserve_release;
+use pencil_solana::state::{AssetPool, FirstLossPool, JuniorInterestPool, PoolReserve};
+use pencil_solana::waterfall::{self, RepaymentDistribution, ReserveFlow};
+use pencil_solana::{PoolReserveReleased, PoolReserveUpdated};
+use solana_sdk::signature::Keypair;
+
+/// 开启储备金并募资达标（Senior 800 / Junior 200），返回资产池与还款人
+fn funded_pool(
+    env: &mut TestEnv,
+    builder: PoolBuilder,
+    fee_share: u16,
+    interest_share: u16,
+) -> (Pool, Keypair) {
+    SystemConfigBuilder::default().build(env);
+    let pool = builder.open(env);
+    pool.initialize_pool_reserve(env, fee_share, interest_share)
+        .unwrap();
+    let senior = env.new_user(1_000 * ONE_TOKEN);
+    let junior = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &senior, true, 800 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &junior, false, 200 * ONE_TOKEN)
+        .unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+
+    (pool, env.new_user(10_000 * ONE_TOKEN))
+}
+
+fn per_period_total(state: &AssetPool) -> u64 {
+    waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap()
+}
+
+/// 按链上顺序计算本期分配（先还款瀑布，再叠加储备金）
+fn expected_distribution(env: &mut TestEnv, pool: &Pool) -> (RepaymentDistribution, ReserveFlow) {
+    let state = pool.state(env);
+    let reserve: PoolReserve = env.account(&pool.pool_reserve());
+    let first_loss_pool: FirstLossPool = env.account(&pool.first_loss_pool);
+    let senior_due = waterfall::calculate_senior_amount(
+        state.senior_amount,
+        state.repayment_count,
+        state.senior_fixed_rate,
+    )
+    .unwrap();
+    let distribution = waterfall::distribute_repayment(
+        per_period_total(&state),
+        per_period_total(&state),
+        state.platform_fee,
+        senior_due,
+        first_loss_pool.total_deposits - first_loss_pool.repaid_amount,
+    )
+    .unwrap();
+    waterfall::apply_pool_reserve(
+        distribution,
+        env.token_balance(&pool.reserve_vault()),
+        reserve.fee_share,
+        reserve.interest_share,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_reserve_captures_fees_and_interest_then_releases_on_completion() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, borrower) = funded_pool(&mut env, PoolBuilder::default(), 5000, 2000);
+    let amount = per_period_total(&pool.state(&mut env));
+
+    // 未传入储备金账户的还款被拒绝
+    env.warp_to(pool.period_due_time(1));
+    let mut ix = pool.repay_instruction(&env, &borrower, amount, 1);
+    ix.accounts.truncate(ix.accounts.len() - 2);
+    let error = env.process(&[ix], &[&borrower]).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::PoolReserveRequired)
+    );
+
+    for period in 1..=3 {
+        env.warp_to(pool.period_due_time(period));
+        let (distribution, flow) = expected_distribution(&mut env, &pool);
+        let reserve_before = env.token_balance(&pool.reserve_vault());
+        let treasury_before = env.token_balance(&pool.treasury_ata);
+        let interest_before = env.token_balance(&pool.junior_interest_pool_vault);
+        pool.repay(&mut env, &borrower, amount, period).unwrap();
+
+        let [updated] = env.events::<PoolReserveUpdated>().try_into().ok().unwrap();
+        assert_eq!(
+            (updated.from_fees, updated.from_interest, updated.used),
+            (flow.from_fees, flow.from_interest, 0)
+        );
+        assert_eq!(updated.balance, reserve_before + flow.deposit().unwrap());
+        if period < 3 {
+            assert_eq!(env.token_balance(&pool.reserve_vault()), updated.balance);
+            assert_eq!(
+                env.token_balance(&pool.treasury_ata) - treasury_before,
+                distribution.platform_fee
+            );
+            assert_eq!(
+                env.token_balance(&pool.junior_interest_pool_vault) - interest_before,
+                distribution.junior_interest
+            );
+            assert!(env.events::<PoolReserveReleased>().is_empty());
+            continue;
+        }
+
+        // 最后一期：首损池未动用，储备金按截留来源拆分至利息池与金库
+        let reserve: PoolReserve = env.account(&pool.pool_reserve());
+        let release = calculate_reserve_release(
+            updated.balance,
+            0,
+            reserve.funded_from_fees,
+            reserve.funded_from_interest,
+        )
+        .unwrap();
+        let [released] = env.events::<PoolReserveReleased>().try_into().ok().unwrap();
+        assert_eq!(released.to_first_loss, 0);
+        assert_eq!(released.to_junior_interest, release.to_junior_interest);
+        assert_eq!(released.to_treasury, release.to_treasury);
+        assert!(reserve.released);
+        assert_eq!(env.token_balance(&pool.reserve_vault()), 0);
+        assert_eq!(
+            env.token_balance(&pool.treasury_ata) - treasury_before,
+            distribution.platform_fee + release.to_treasury
+        );
+        assert_eq!(
+            env.token_balance(&pool.junior_interest_pool_vault) - interest_before,
+            distribution.junior_interest + release.to_junior_interest
+        );
+    }
+
+    assert_eq!(pool.state(&mut env).status, asset_pool_status::COMPLETED);
+    let interest_pool: JuniorInterestPool = env.account(&pool.junior_interest_pool);
+    assert_eq!(
+        interest_pool.total_interest,
+        env.token_balance(&pool.junior_interest_pool_vault)
+    );
+    pool.check_invariants(&mut env).unwrap();
+}
+
+#[test]
+fn test_reserve_covers_shortfall_before_first_loss() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    // Senior 固定利率高于资产池还款能力，每期都有缺口
+    let builder = PoolBuilder {
+        senior_fixed_rate: 2000,
+        ..PoolBuilder::default()
+    };
+    let (pool, borrower) = funded_pool(&mut env, builder, 10_000, 0);
+    let amount = per_period_total(&pool.state(&mut env));
+
+    let mut first_loss_used = 0;
+    for period in 1..=3 {
+        env.warp_to(pool.period_due_time(period));
+        let (distribution, flow) = expected_distribution(&mut env, &pool);
+        assert!(flow.used > 0 && distribution.first_loss_used > 0);
+        let senior_before = env.token_balance(&pool.senior_pool_vault);
+        let first_loss_before = env.token_balance(&pool.first_loss_pool_vault);
+        let treasury_before = env.token_balance(&pool.treasury_ata);
+        pool.repay(&mut env, &borrower, amount, period).unwrap();
+
+        // 平台费全部进入储备金并立即用于补足，首损池只承担剩余缺口
+        assert_eq!(flow.used, flow.from_fees);
+        assert_eq!(env.token_balance(&pool.treasury_ata), treasury_before);
+        assert_eq!(env.token_balance(&pool.reserve_vault()), 0);
+        assert_eq!(
+            first_loss_before - env.token_balance(&pool.first_loss_pool_vault),
+            distribution.first_loss_used
+        );
+        assert_eq!(
+            env.token_balance(&pool.senior_pool_vault) - senior_before,
+            distribution.senior_total().unwrap()
+        );
+        first_loss_used += distribution.first_loss_used;
+    }
+
+    let reserve: PoolReserve = env.account(&pool.pool_reserve());
+    assert_eq!(reserve.used_amount, reserve.funded_from_fees);
+    assert!(reserve.released);
+    let first_loss_pool: FirstLossPool = env.account(&pool.first_loss_pool);
+    assert_eq!(first_loss_pool.repaid_amount, first_loss_used);
+    pool.check_invariants(&mut env).unwrap();
+}
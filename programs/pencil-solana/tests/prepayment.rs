@@ -0,0 +1,153 @@
+//! 提前还款集成测试
+//! prepay_remaining 一次性偿还剩余本金、利息（按资产池计息方式）与违约金，
+//! 资产池直接进入 COMPLETED。
+
+mod common;
+
+use common::*;
+use pencil_solana::constants::{asset_pool_status, prepayment_policy, MAX_PREPAYMENT_PENALTY};
+use pencil_solana::errors::PencilError;
+use pencil_solana::waterfall;
+use pencil_solana::PrepaymentProcessed;
+
+/// 创建资产池并在审批前设置提前还款条款，募资达标后返回
+fn funded_pool(env: &mut TestEnv, policy: u8, penalty_rate: u16) -> Pool {
+    SystemConfigBuilder::default().build(env);
+    let pool = PoolBuilder::default().repayment_count(4).create(env);
+    pool.set_prepayment_terms(env, policy, penalty_rate)
+        .unwrap();
+    pool.approve(env).unwrap();
+    pool.initialize_related_accounts(env).unwrap();
+
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &alice, true, 800 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &carol, false, 200 * ONE_TOKEN).unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+    pool
+}
+
+#[test]
+fn test_prepay_full_interest_with_penalty() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let pool = funded_pool(&mut env, prepayment_policy::FULL_INTEREST, 200);
+    let state = pool.state(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+
+    env.warp_to(pool.period_due_time(1));
+    let per_period_total = waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap();
+    pool.repay(&mut env, &borrower, per_period_total, 1)
+        .unwrap();
+
+    // 剩余 3 期本金 750、利息 3 × 30、违约金 750 × 2%
+    let principal = 750 * ONE_TOKEN;
+    let interest = 90 * ONE_TOKEN;
+    let penalty = 15 * ONE_TOKEN;
+    let amount = principal + interest + penalty;
+    let error = pool
+        .prepay_remaining(&mut env, &borrower, amount - 1)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::PrepaymentAmountExceeded)
+    );
+
+    let treasury_before = env.token_balance(&pool.treasury_ata);
+    pool.prepay_remaining(&mut env, &borrower, amount).unwrap();
+    let [prepaid] = env.events::<PrepaymentProcessed>().try_into().ok().unwrap();
+    assert_eq!(prepaid.remaining_periods, 3);
+    assert_eq!(prepaid.principal, principal);
+    assert_eq!(prepaid.interest, interest);
+    assert_eq!(prepaid.penalty, penalty);
+    assert!(!prepaid.third_party);
+    // 平台费只按本息计算
+    assert_eq!(prepaid.platform_fee, (principal + interest) * 5 / 100);
+    assert_eq!(
+        env.token_balance(&pool.treasury_ata) - treasury_before,
+        prepaid.platform_fee
+    );
+    assert_eq!(
+        prepaid.platform_fee + prepaid.senior_amount + prepaid.junior_interest,
+        amount
+    );
+
+    let state = pool.state(&mut env);
+    assert_eq!(state.status, asset_pool_status::COMPLETED);
+    assert_eq!(state.repaid_periods, 4);
+    assert_eq!(state.repaid_amount, per_period_total + amount);
+
+    // 完成后不能再还款或重复提前还款
+    env.warp_to(pool.period_due_time(2));
+    let error = pool
+        .repay(&mut env, &borrower, per_period_total, 2)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidAssetPoolStatus)
+    );
+    assert!(pool.prepay_remaining(&mut env, &borrower, amount).is_err());
+}
+
+#[test]
+fn test_prepay_accrued_interest() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let pool = funded_pool(&mut env, prepayment_policy::ACCRUED_INTEREST, 0);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+
+    // 募资结束后半个周期提前还清：只付半期利息
+    env.warp_to(pool.funding_end_time + pool.repayment_period as i64 / 2);
+    pool.prepay_remaining(&mut env, &borrower, u64::MAX)
+        .unwrap();
+    let [prepaid] = env.events::<PrepaymentProcessed>().try_into().ok().unwrap();
+    assert_eq!(prepaid.remaining_periods, 4);
+    assert_eq!(prepaid.principal, 1_000 * ONE_TOKEN);
+    assert_eq!(prepaid.interest, 15 * ONE_TOKEN);
+    assert_eq!(prepaid.penalty, 0);
+    assert_eq!(pool.state(&mut env).status, asset_pool_status::COMPLETED);
+}
+
+#[test]
+fn test_prepayment_terms_validation() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let pool = PoolBuilder::default().create(&mut env);
+
+    let error = pool.set_prepayment_terms(&mut env, 2, 0).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidPrepaymentTerms)
+    );
+    let error = pool
+        .set_prepayment_terms(
+            &mut env,
+            prepayment_policy::FULL_INTEREST,
+            MAX_PREPAYMENT_PENALTY + 1,
+        )
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidPrepaymentTerms)
+    );
+
+    // 审批后条款固定
+    pool.approve(&mut env).unwrap();
+    let error = pool
+        .set_prepayment_terms(&mut env, prepayment_policy::FULL_INTEREST, 0)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidAssetPoolStatus)
+    );
+}
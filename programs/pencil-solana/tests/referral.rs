@@ -0,0 +1,220 @@
+//! 推荐归因集成测试
+//! 认购时可指定推荐人，订阅记录推荐人并按资产池累计推荐额；募资完成后按预付比例或
+//! 平台费分成结算奖励，从手续费归集账户支付，distribute_fees 为未付奖励保留余额。
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use pencil_solana::constants::referral_reward_mode;
+use pencil_solana::errors::PencilError;
+use pencil_solana::instructions::calculate_referral_reward;
+use pencil_solana::state::{ReferralProgram, ReferralRecord, Subscription};
+use pencil_solana::waterfall;
+use pencil_solana::{FeesDistributed, ReferralRecorded, ReferralRewardPaid};
+use solana_sdk::signature::Signer;
+
+/// 开启手续费归集并设置推荐奖励条款，返回资产池与推荐人
+fn referral_pool(env: &mut TestEnv, reward_mode: u8, reward_rate: u16) -> (Pool, Pubkey) {
+    SystemConfigBuilder::default().build(env);
+    let mut pool = PoolBuilder::default().open(env);
+
+    // 奖励从归集账户支付，须先开启手续费归集
+    let error = pool
+        .set_referral_terms(env, reward_mode, reward_rate)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidReferral)
+    );
+    pool.initialize_fee_collector(env).unwrap();
+
+    // 设置条款前不能记录推荐，否则推荐额不会计入奖励预留
+    let partner = env.new_user(0).pubkey();
+    let user = env.new_user(100 * ONE_TOKEN);
+    let error = pool
+        .subscribe_with_referrer(env, &user, true, 100 * ONE_TOKEN, Some(&partner))
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::ReferralTermsNotSet)
+    );
+
+    pool.set_referral_terms(env, reward_mode, reward_rate)
+        .unwrap();
+    (pool, partner)
+}
+
+fn per_period_total(env: &mut TestEnv, pool: &Pool) -> u64 {
+    let state = pool.state(env);
+    waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_upfront_referral_tracks_subscriptions_and_pays_from_collector() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, partner) = referral_pool(&mut env, referral_reward_mode::UPFRONT, 100);
+    let senior = env.new_user(1_000 * ONE_TOKEN);
+    let junior = env.new_user(1_000 * ONE_TOKEN);
+
+    // 不能推荐自己
+    let error = pool
+        .subscribe_with_referrer(
+            &mut env,
+            &senior,
+            true,
+            500 * ONE_TOKEN,
+            Some(&senior.pubkey()),
+        )
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidReferral)
+    );
+
+    pool.subscribe_with_referrer(&mut env, &senior, true, 500 * ONE_TOKEN, Some(&partner))
+        .unwrap();
+    let [recorded] = env.events::<ReferralRecorded>().try_into().ok().unwrap();
+    assert_eq!(recorded.referrer, partner);
+    assert_eq!(recorded.user, senior.pubkey());
+    let subscription: Subscription = env.account(&pool.subscription(&senior.pubkey(), true));
+    assert_eq!(subscription.referrer, partner);
+
+    // 经推荐的订阅追加认购须附带同一推荐人
+    let error = pool
+        .subscribe(&mut env, &senior, true, 300 * ONE_TOKEN)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::ReferralRequired)
+    );
+    pool.subscribe_with_referrer(&mut env, &senior, true, 300 * ONE_TOKEN, Some(&partner))
+        .unwrap();
+    pool.subscribe(&mut env, &junior, false, 200 * ONE_TOKEN)
+        .unwrap();
+
+    // 撤回认购扣减推荐额，提前退出费进入归集账户
+    pool.withdraw_referred_subscription(&mut env, &senior, true, 100 * ONE_TOKEN, Some(&partner))
+        .unwrap();
+    let record: ReferralRecord = env.account(&pool.referral(&partner));
+    assert_eq!(
+        (
+            record.senior_amount,
+            record.junior_amount,
+            record.subscription_count
+        ),
+        (700 * ONE_TOKEN, 0, 1)
+    );
+    let referral_program: ReferralProgram = env.account(&pool.referral_program());
+    assert_eq!(referral_program.referred_amount, 700 * ONE_TOKEN);
+
+    // 募资完成前不能结算
+    let error = pool.claim_referral_reward(&mut env, &partner).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidAssetPoolStatus)
+    );
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(&mut env).unwrap();
+
+    // 归集账户余额不足时先支付可用部分
+    let reward = 7 * ONE_TOKEN;
+    let collected = env.token_balance(&pool.fee_collector());
+    assert!(collected > 0 && collected < reward);
+    pool.claim_referral_reward(&mut env, &partner).unwrap();
+    let [paid] = env.events::<ReferralRewardPaid>().try_into().ok().unwrap();
+    assert_eq!(
+        (paid.amount, paid.reward_amount, paid.paid_amount),
+        (collected, reward, collected)
+    );
+
+    // 还款后补付剩余奖励
+    let amount = per_period_total(&mut env, &pool);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, amount, 1).unwrap();
+    pool.claim_referral_reward(&mut env, &partner).unwrap();
+    assert_eq!(env.token_balance(&env.asset_ata(&partner)), reward);
+
+    let record: ReferralRecord = env.account(&pool.referral(&partner));
+    assert!(record.settled);
+    assert_eq!(record.paid_amount, reward);
+    let referral_program: ReferralProgram = env.account(&pool.referral_program());
+    assert_eq!(
+        (
+            referral_program.referred_amount,
+            referral_program.reward_owed,
+            referral_program.total_paid
+        ),
+        (0, 0, reward)
+    );
+    pool.check_invariants(&mut env).unwrap();
+}
+
+#[test]
+fn test_fee_share_referral_is_held_back_from_fee_distribution() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, partner) = referral_pool(&mut env, referral_reward_mode::FEE_SHARE, 2000);
+    let senior = env.new_user(1_000 * ONE_TOKEN);
+    let junior = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe_with_referrer(&mut env, &senior, true, 800 * ONE_TOKEN, Some(&partner))
+        .unwrap();
+    pool.subscribe(&mut env, &junior, false, 200 * ONE_TOKEN)
+        .unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(&mut env).unwrap();
+
+    // 募资完成后条款不可修改
+    let error = pool
+        .set_referral_terms(&mut env, referral_reward_mode::UPFRONT, 100)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidAssetPoolStatus)
+    );
+
+    let state = pool.state(&mut env);
+    let reward = calculate_referral_reward(
+        800 * ONE_TOKEN,
+        referral_reward_mode::FEE_SHARE,
+        2000,
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+        state.platform_fee,
+    )
+    .unwrap();
+    assert!(reward > 0);
+
+    let protocol = env.new_user(0).pubkey();
+    set_system_fee_split(&mut env, &[(protocol, 10_000)]).unwrap();
+    let amount = per_period_total(&mut env, &pool);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, amount, 1).unwrap();
+    let collected = env.token_balance(&pool.fee_collector());
+    assert!(collected > reward);
+
+    // 分账时保留尚未结算的推荐奖励
+    pool.distribute_fees(&mut env, &[protocol]).unwrap();
+    let [distributed] = env.events::<FeesDistributed>().try_into().ok().unwrap();
+    assert_eq!(distributed.amount, collected - reward);
+    assert_eq!(env.token_balance(&pool.fee_collector()), reward);
+
+    pool.claim_referral_reward(&mut env, &partner).unwrap();
+    assert_eq!(env.token_balance(&env.asset_ata(&partner)), reward);
+    assert_eq!(env.token_balance(&pool.fee_collector()), 0);
+
+    // 重复领取不会多付
+    pool.claim_referral_reward(&mut env, &partner).unwrap();
+    assert_eq!(env.token_balance(&env.asset_ata(&partner)), reward);
+}
@@ -0,0 +1,112 @@
+//! 还款义务人与受托服务方集成测试
+//! 创建者可变更还款义务人，发起方可委托服务方代为还款；任何人都能还款，
+//! 但非义务人、非服务方的付款会在 RepaymentRecord 中标记为第三方代偿。
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use pencil_solana::constants::seeds;
+use pencil_solana::errors::PencilError;
+use pencil_solana::state::{Originator, RepaymentRecord};
+use pencil_solana::waterfall;
+use pencil_solana::{RepaymentAuthorityUpdated, RepaymentDistributed, ServicerDelegated};
+use solana_sdk::signature::Signer;
+
+/// 募资达标后返回资产池与每期应还总额
+fn funded_pool(env: &mut TestEnv) -> (Pool, u64) {
+    SystemConfigBuilder::default().build(env);
+    let pool = PoolBuilder::default().repayment_count(4).open(env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &alice, true, 800 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &carol, false, 200 * ONE_TOKEN).unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+
+    let state = pool.state(env);
+    let per_period_total = waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap();
+    (pool, per_period_total)
+}
+
+#[test]
+fn test_repayment_payer_tracking() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, per_period_total) = funded_pool(&mut env);
+    let admin = env.admin.insecure_clone();
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    let servicer = env.new_user(10_000 * ONE_TOKEN);
+    let stranger = env.new_user(10_000 * ONE_TOKEN);
+
+    // 只有创建者可以变更还款义务人
+    let error = pool
+        .set_repayment_authority(&mut env, &stranger, &stranger.pubkey())
+        .unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::Unauthorized));
+    pool.set_repayment_authority(&mut env, &admin, &borrower.pubkey())
+        .unwrap();
+    let [updated] = env
+        .events::<RepaymentAuthorityUpdated>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(updated.old_authority, admin.pubkey());
+    assert_eq!(updated.new_authority, borrower.pubkey());
+    assert_eq!(pool.state(&mut env).repayment_authority, borrower.pubkey());
+
+    delegate_servicer(&mut env, &admin, &servicer.pubkey()).unwrap();
+    let [delegated] = env.events::<ServicerDelegated>().try_into().ok().unwrap();
+    assert_eq!(delegated.old_servicer, Pubkey::default());
+    assert_eq!(delegated.new_servicer, servicer.pubkey());
+    let originator: Originator = env.account(&pool.originator());
+    assert_eq!(originator.servicer, servicer.pubkey());
+
+    // 义务人、服务方与第三方依次还款
+    for (period, payer, third_party) in [
+        (1, &borrower, false),
+        (2, &servicer, false),
+        (3, &stranger, true),
+    ] {
+        env.warp_to(pool.period_due_time(period));
+        pool.repay(&mut env, payer, per_period_total, period)
+            .unwrap();
+        let [repaid] = env
+            .events::<RepaymentDistributed>()
+            .try_into()
+            .ok()
+            .unwrap();
+        assert_eq!(repaid.payer, payer.pubkey());
+        assert_eq!(repaid.third_party, third_party);
+        let record: RepaymentRecord = env.account(&pool.repayment_record(period));
+        assert_eq!(record.payer, payer.pubkey());
+        assert_eq!(record.third_party, third_party);
+    }
+
+    // 撤销委托后服务方的还款也视为第三方代偿
+    delegate_servicer(&mut env, &admin, &Pubkey::default()).unwrap();
+    env.warp_to(pool.period_due_time(4));
+    pool.repay(&mut env, &servicer, per_period_total, 4)
+        .unwrap();
+    let record: RepaymentRecord = env.account(&pool.repayment_record(4));
+    assert!(record.third_party);
+}
+
+#[test]
+fn test_only_originator_authority_delegates() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let stranger = env.new_user(0);
+
+    // 未注册为发起方的账户没有 Originator PDA
+    assert!(delegate_servicer(&mut env, &stranger, &stranger.pubkey()).is_err());
+    assert!(!env.account_exists(&pda(&[seeds::ORIGINATOR, stranger.pubkey().as_ref()])));
+}
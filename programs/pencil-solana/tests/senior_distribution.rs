@@ -0,0 +1,258 @@
+//! Senior 分配集成测试
+//! 锁定的 GROW 按每个 GROW 的累计指数在每期还款后领取 Senior 还款（免手续费），
+//! 资产池完成后赎回锁定部分；未锁定的 GROW 仍按完成后按比例提取，两者金额一致。
+//! 完成前可解锁 GROW，解锁部分对应的已领取分配从结算额中扣回。
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use pencil_solana::constants::{asset_pool_status, DISTRIBUTION_INDEX_SCALE};
+use pencil_solana::errors::PencilError;
+use pencil_solana::state::{SeniorCheckpoint, SeniorPool};
+use pencil_solana::waterfall;
+use pencil_solana::{
+    RepaymentDistributed, SeniorDistributionClaimed, SeniorDistributionUnlocked, SeniorWithdrawn,
+};
+use solana_sdk::signature::{Keypair, Signer};
+
+const ALICE_SENIOR: u64 = 500 * ONE_TOKEN;
+const BOB_SENIOR: u64 = 300 * ONE_TOKEN;
+
+/// 募资达标并分发 GROW，返回资产池、Alice、Bob 与每期应还总额
+fn funded_pool(env: &mut TestEnv) -> (Pool, Keypair, Keypair, u64) {
+    SystemConfigBuilder::default().build(env);
+    let pool = PoolBuilder::default().repayment_count(2).open(env);
+    let alice = env.new_user(1_000 * ONE_TOKEN);
+    let bob = env.new_user(1_000 * ONE_TOKEN);
+    let carol = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &alice, true, ALICE_SENIOR).unwrap();
+    pool.subscribe(env, &bob, true, BOB_SENIOR).unwrap();
+    pool.subscribe(env, &carol, false, 200 * ONE_TOKEN).unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+    pool.distribute_senior_token(env, &alice.pubkey()).unwrap();
+    pool.distribute_senior_token(env, &bob.pubkey()).unwrap();
+
+    let state = pool.state(env);
+    let per_period_total = waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap();
+    (pool, alice, bob, per_period_total)
+}
+
+#[test]
+fn test_locked_grow_claims_each_installment() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, bob, per_period_total) = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    let alice_grow = get_associated_token_address(&alice.pubkey(), &pool.grow_token_mint);
+
+    // 没有锁定的 GROW 时无可领取
+    let error = pool
+        .claim_senior_distribution(&mut env, &bob, 0)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::NoSeniorDistribution)
+    );
+
+    // Alice 锁定全部 GROW，托管后不可转让
+    pool.claim_senior_distribution(&mut env, &alice, ALICE_SENIOR)
+        .unwrap();
+    assert_eq!(env.token_balance(&alice_grow), 0);
+    assert_eq!(env.token_balance(&pool.grow_escrow()), ALICE_SENIOR);
+    let checkpoint: SeniorCheckpoint = env.account(&pool.senior_checkpoint(&alice.pubkey()));
+    assert_eq!(checkpoint.locked_amount, ALICE_SENIOR);
+    assert_eq!(checkpoint.index, 0);
+
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, per_period_total, 1)
+        .unwrap();
+    let [repaid] = env
+        .events::<RepaymentDistributed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    let senior_pool: SeniorPool = env.account(&pool.senior_pool);
+    assert_eq!(
+        senior_pool.distribution_index,
+        repaid.senior_amount as u128 * DISTRIBUTION_INDEX_SCALE
+            / (ALICE_SENIOR + BOB_SENIOR) as u128
+    );
+
+    // 领取 5/8 的 Senior 还款，不收手续费
+    let alice_before = env.token_balance(&env.asset_ata(&alice.pubkey()));
+    let treasury_before = env.token_balance(&pool.treasury_ata);
+    pool.claim_senior_distribution(&mut env, &alice, 0).unwrap();
+    let [claimed] = env
+        .events::<SeniorDistributionClaimed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    let first_claim = repaid.senior_amount * 5 / 8;
+    assert_eq!(claimed.amount, first_claim);
+    assert_eq!(claimed.locked_amount, ALICE_SENIOR);
+    assert_eq!(claimed.grow_redeemed, 0);
+    assert_eq!(
+        env.token_balance(&env.asset_ata(&alice.pubkey())) - alice_before,
+        first_claim
+    );
+    assert_eq!(env.token_balance(&pool.treasury_ata), treasury_before);
+    pool.check_invariants(&mut env).unwrap();
+
+    // 同一指数不能重复领取
+    let error = pool
+        .claim_senior_distribution(&mut env, &alice, 0)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::NoSeniorDistribution)
+    );
+
+    env.warp_to(pool.period_due_time(2));
+    pool.repay(&mut env, &borrower, per_period_total, 2)
+        .unwrap();
+    assert_eq!(pool.state(&mut env).status, asset_pool_status::COMPLETED);
+    pool.check_invariants(&mut env).unwrap();
+
+    // 完成后不能再追加锁定
+    assert!(pool.claim_senior_distribution(&mut env, &alice, 1).is_err());
+
+    // Bob 按比例提取，Alice 赎回锁定部分：两人按 GROW 持有量获得相同比例
+    pool.early_exit_senior(&mut env, &bob, BOB_SENIOR).unwrap();
+    let [withdrawn] = env.events::<SeniorWithdrawn>().try_into().ok().unwrap();
+    pool.claim_senior_distribution(&mut env, &alice, 0).unwrap();
+    let [redeemed] = env
+        .events::<SeniorDistributionClaimed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(redeemed.grow_redeemed, ALICE_SENIOR);
+    assert_eq!(redeemed.locked_amount, 0);
+    let alice_total = first_claim + redeemed.amount;
+    assert!(alice_total.abs_diff(withdrawn.amount * 5 / 3) <= 1);
+
+    assert_eq!(env.token_balance(&pool.grow_escrow()), 0);
+    let senior_pool: SeniorPool = env.account(&pool.senior_pool);
+    assert_eq!(senior_pool.distributed_amount, 0);
+    assert!(env.token_balance(&pool.senior_pool_vault) <= 1);
+}
+
+#[test]
+fn test_lock_after_installment_starts_at_current_index() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, bob, per_period_total) = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+
+    pool.claim_senior_distribution(&mut env, &alice, ALICE_SENIOR)
+        .unwrap();
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, per_period_total, 1)
+        .unwrap();
+
+    // Bob 在第 1 期之后才锁定，不能领取之前的分配
+    pool.claim_senior_distribution(&mut env, &bob, BOB_SENIOR)
+        .unwrap();
+    let [locked] = env
+        .events::<SeniorDistributionClaimed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(locked.amount, 0);
+    let error = pool
+        .claim_senior_distribution(&mut env, &bob, 0)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::NoSeniorDistribution)
+    );
+
+    // 锁定的 GROW 不能早退
+    assert!(pool.early_exit_senior(&mut env, &bob, 1).is_err());
+    pool.check_invariants(&mut env).unwrap();
+}
+
+#[test]
+fn test_unlock_before_completion_nets_claimed_distribution() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    let (pool, alice, bob, per_period_total) = funded_pool(&mut env);
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    let alice_grow = get_associated_token_address(&alice.pubkey(), &pool.grow_token_mint);
+
+    pool.claim_senior_distribution(&mut env, &alice, ALICE_SENIOR)
+        .unwrap();
+    env.warp_to(pool.period_due_time(1));
+    pool.repay(&mut env, &borrower, per_period_total, 1)
+        .unwrap();
+    let [repaid] = env
+        .events::<RepaymentDistributed>()
+        .try_into()
+        .ok()
+        .unwrap();
+
+    // 不能解锁超过锁定数量
+    let error = pool
+        .unlock_senior_distribution(&mut env, &alice, ALICE_SENIOR + 1)
+        .unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InsufficientBalance)
+    );
+
+    // 解锁一半：结算第 1 期分配，扣回解锁一半对应的部分
+    let alice_before = env.token_balance(&env.asset_ata(&alice.pubkey()));
+    pool.unlock_senior_distribution(&mut env, &alice, ALICE_SENIOR / 2)
+        .unwrap();
+    let [unlocked] = env
+        .events::<SeniorDistributionUnlocked>()
+        .try_into()
+        .ok()
+        .unwrap();
+    let pending = repaid.senior_amount * 5 / 8;
+    assert_eq!(unlocked.grow_unlocked, ALICE_SENIOR / 2);
+    assert_eq!(unlocked.payout, pending - pending.div_ceil(2));
+    assert_eq!(unlocked.repaid, 0);
+    assert_eq!(unlocked.locked_amount, ALICE_SENIOR / 2);
+    assert_eq!(
+        env.token_balance(&env.asset_ata(&alice.pubkey())) - alice_before,
+        unlocked.payout
+    );
+    assert_eq!(env.token_balance(&alice_grow), ALICE_SENIOR / 2);
+    assert_eq!(env.token_balance(&pool.grow_escrow()), ALICE_SENIOR / 2);
+    pool.check_invariants(&mut env).unwrap();
+
+    // 完成后 Alice 赎回锁定部分并提取解锁部分，合计与 Bob 按持有比例一致
+    env.warp_to(pool.period_due_time(2));
+    pool.repay(&mut env, &borrower, per_period_total, 2)
+        .unwrap();
+    assert!(pool
+        .unlock_senior_distribution(&mut env, &alice, 1)
+        .is_err());
+    pool.early_exit_senior(&mut env, &bob, BOB_SENIOR).unwrap();
+    let [bob_withdrawn] = env.events::<SeniorWithdrawn>().try_into().ok().unwrap();
+    pool.claim_senior_distribution(&mut env, &alice, 0).unwrap();
+    let [redeemed] = env
+        .events::<SeniorDistributionClaimed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    pool.early_exit_senior(&mut env, &alice, ALICE_SENIOR / 2)
+        .unwrap();
+    let [alice_withdrawn] = env.events::<SeniorWithdrawn>().try_into().ok().unwrap();
+    let alice_total = unlocked.payout + redeemed.amount + alice_withdrawn.amount;
+    assert!(alice_total.abs_diff(bob_withdrawn.amount * 5 / 3) <= 2);
+
+    let senior_pool: SeniorPool = env.account(&pool.senior_pool);
+    assert_eq!(senior_pool.distributed_amount, 0);
+    assert!(env.token_balance(&pool.senior_pool_vault) <= 2);
+}
@@ -0,0 +1,130 @@
+//! 金库迁移集成测试
+//! 手续费去向按当前 SystemConfig.treasury 校验，set_treasury 后旧金库不再收款；
+//! migrate_pool_treasury 为新金库创建 ATA 并更新资产池记录，已开启归集的资产池保留归集账户。
+
+mod common;
+
+use common::*;
+use pencil_solana::errors::PencilError;
+use pencil_solana::waterfall;
+use pencil_solana::{PoolTreasuryMigrated, RepaymentDistributed};
+use solana_sdk::signature::{Keypair, Signer};
+
+/// 募资达标（Senior 800 / Junior 200）
+fn fund(env: &mut TestEnv, pool: &Pool) {
+    let senior = env.new_user(1_000 * ONE_TOKEN);
+    let junior = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(env, &senior, true, 800 * ONE_TOKEN).unwrap();
+    pool.subscribe(env, &junior, false, 200 * ONE_TOKEN)
+        .unwrap();
+    env.warp_to(pool.funding_end_time + 1);
+    pool.complete_funding(env).unwrap();
+}
+
+#[test]
+fn test_fee_routing_follows_treasury_after_migration() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let mut pool = PoolBuilder::default().open(&mut env);
+    let mut collector_pool = PoolBuilder::default().name("Collector Pool").open(&mut env);
+    collector_pool.initialize_fee_collector(&mut env).unwrap();
+    fund(&mut env, &pool);
+    let old_treasury_ata = pool.treasury_ata;
+
+    let new_treasury = Keypair::new().pubkey();
+    set_treasury(&mut env, &new_treasury).unwrap();
+    let new_treasury_ata = env.asset_ata(&new_treasury);
+
+    // 旧金库 ATA 不再是有效的手续费去向
+    let state = pool.state(&mut env);
+    let amount = waterfall::calculate_per_period_amount(
+        state.total_amount,
+        state.repayment_count,
+        state.repayment_rate,
+    )
+    .unwrap();
+    let borrower = env.new_user(10_000 * ONE_TOKEN);
+    env.warp_to(pool.period_due_time(1));
+    let error = pool.repay(&mut env, &borrower, amount, 1).unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::InvalidAccount));
+
+    // 迁移的金库须与当前配置一致
+    let error = migrate_pool_treasury(&mut env, &pool.treasury, &[pool.asset_pool]).unwrap_err();
+    assert_eq!(
+        error_code(error),
+        pencil_error(PencilError::InvalidTreasuryAddress)
+    );
+
+    migrate_pool_treasury(
+        &mut env,
+        &new_treasury,
+        &[pool.asset_pool, collector_pool.asset_pool],
+    )
+    .unwrap();
+    let migrated = env.events::<PoolTreasuryMigrated>();
+    assert_eq!(migrated.len(), 2);
+    assert_eq!(migrated[0].old_treasury, pool.treasury);
+    assert_eq!(migrated[0].new_treasury, new_treasury);
+    assert_eq!(migrated[0].treasury_ata, new_treasury_ata);
+    assert_eq!(migrated[1].treasury_ata, collector_pool.fee_collector());
+
+    let state = pool.state(&mut env);
+    assert_eq!(
+        (state.treasury, state.treasury_ata),
+        (new_treasury, new_treasury_ata)
+    );
+    let state = collector_pool.state(&mut env);
+    assert_eq!(
+        (state.treasury, state.treasury_ata),
+        (new_treasury, collector_pool.fee_collector())
+    );
+
+    // 平台费转入新金库
+    let old_balance = env.token_balance(&old_treasury_ata);
+    pool.treasury_ata = new_treasury_ata;
+    pool.repay(&mut env, &borrower, amount, 1).unwrap();
+    let [distributed] = env
+        .events::<RepaymentDistributed>()
+        .try_into()
+        .ok()
+        .unwrap();
+    assert!(distributed.platform_fee > 0);
+    assert_eq!(
+        env.token_balance(&new_treasury_ata),
+        distributed.platform_fee
+    );
+    assert_eq!(env.token_balance(&old_treasury_ata), old_balance);
+    pool.check_invariants(&mut env).unwrap();
+}
+
+#[test]
+fn test_withdraw_subscription_fee_goes_to_current_treasury() {
+    let Some(mut env) = TestEnv::start() else {
+        return;
+    };
+    SystemConfigBuilder::default().build(&mut env);
+    let mut pool = PoolBuilder::default().open(&mut env);
+    let senior = env.new_user(1_000 * ONE_TOKEN);
+    pool.subscribe(&mut env, &senior, true, 500 * ONE_TOKEN)
+        .unwrap();
+    let treasury_ata = pool.treasury_ata;
+
+    // 不能把手续费转入任意账户
+    let attacker = env.new_user(0);
+    pool.treasury_ata = env.asset_ata(&attacker.pubkey());
+    let error = pool
+        .withdraw_subscription(&mut env, &senior, true, 100 * ONE_TOKEN)
+        .unwrap_err();
+    assert_eq!(error_code(error), pencil_error(PencilError::InvalidAccount));
+
+    pool.treasury_ata = treasury_ata;
+    let treasury_before = env.token_balance(&treasury_ata);
+    pool.withdraw_subscription(&mut env, &senior, true, 100 * ONE_TOKEN)
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&treasury_ata) - treasury_before,
+        ONE_TOKEN
+    );
+}
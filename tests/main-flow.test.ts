@@ -437,6 +437,37 @@ describe("Pencil Solana - Main Flow Integration Tests", () => {
 
       logSuccess(`USDC added to whitelist: ${env.usdcMint.toString()}`);
     });
+
+    it("should register the provider wallet as originator", async () => {
+      logTestPhase("Registering originator", "🏢");
+
+      const authority = (provider.wallet as any).publicKey as PublicKey;
+      const [originatorPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("originator"), authority.toBuffer()],
+        program.programId
+      );
+
+      const tx = await program.methods
+        .registerOriginator(
+          authority,
+          Array(32).fill(1),
+          new anchor.BN("18446744073709551615"),
+          [env.usdtMint, env.usdcMint]
+        )
+        .accounts({
+          operationAdmin: env.operationAdmin.publicKey,
+          systemConfig: deriveSystemConfigPda(program),
+          originator: originatorPda,
+        } as any)
+        .signers([env.operationAdmin])
+        .rpc();
+
+      logTransaction("Originator registered", tx);
+
+      const originator = await program.account.originator.fetch(originatorPda);
+      assert.isTrue(originator.active, "Originator should be active");
+      logSuccess(`Originator registered at ${originatorPda.toString()}`);
+    });
   });
 
   describe("Asset Pool Creation and Approval", () => {
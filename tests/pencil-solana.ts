@@ -243,6 +243,28 @@ describe("pencil-solana", () => {
       const isInWhitelist = assetWhitelist.assets.some(asset => asset.toString() === assetAddress.toString());
       assert.isTrue(isInWhitelist, "Asset should be in whitelist again");
     });
+
+    it("Registers the payer as originator", async () => {
+      const [originatorPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("originator"), payer.publicKey.toBuffer()],
+        program.programId
+      );
+
+      const tx = await program.methods
+        .registerOriginator(payer.publicKey, Array(32).fill(1), new BN("18446744073709551615"), [assetAddress])
+        .accounts({
+          operationAdmin: operationAdmin.publicKey,
+          systemConfig: systemConfigPda,
+          originator: originatorPda,
+        } as any)
+        .signers([operationAdmin])
+        .rpc();
+
+      console.log("✅ Originator registered:", tx);
+
+      const originator = await program.account.originator.fetch(originatorPda);
+      assert.isTrue(originator.active, "Originator should be active");
+    });
   });
 
   describe("Asset Pool", () => {